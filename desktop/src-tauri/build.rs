@@ -1,3 +1,125 @@
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Manifest of the bundled Python runtime, written by tools/packaging/build-python.sh
+const PYTHON_MANIFEST: &str = "../../tools/packaging/dist/current/runtime-manifest.json";
+
+/// Bundled app sources, as mapped in tauri.conf.json `bundle.resources`
+const APP_SOURCES: &[(&str, &str)] = &[
+    ("../../kiroku_memory", "app/kiroku_memory"),
+    ("../../tools/packaging/entrypoints", "app/entrypoints"),
+];
+
 fn main() {
+    write_runtime_manifest();
+    emit_build_info();
     tauri_build::build()
 }
+
+/// Commit, build time and target for `get_app_info`
+fn emit_build_info() {
+    // HEAD only changes on checkout; commits update the branch ref it points
+    // to, or packed-refs once that ref is packed
+    let git_dir = Path::new("../../.git");
+    let mut watched = vec![git_dir.join("HEAD"), git_dir.join("packed-refs")];
+    if let Ok(head) = std::fs::read_to_string(git_dir.join("HEAD")) {
+        if let Some(branch_ref) = head.trim().strip_prefix("ref: ") {
+            let branch_ref = git_dir.join(branch_ref);
+            // A packed ref is written out loose by the next commit
+            match branch_ref.parent().filter(|_| !branch_ref.exists()) {
+                Some(dir) => watched.push(dir.to_path_buf()),
+                None => watched.push(branch_ref),
+            }
+        }
+    }
+    // A missing path would make Cargo rerun this script on every build
+    for path in watched.iter().filter(|path| path.exists()) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    let commit = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    // Reproducible builds pin the timestamp
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH").unwrap_or_else(|_| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs().to_string())
+            .unwrap_or_default()
+    });
+    println!("cargo:rustc-env=KIROKU_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=KIROKU_BUILD_TIMESTAMP={}", timestamp);
+    println!(
+        "cargo:rustc-env=KIROKU_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+}
+
+/// Combine the Python runtime manifest with hashes of the app sources into
+/// $OUT_DIR/runtime-manifest.json, which the app embeds for `verify_runtime`
+fn write_runtime_manifest() {
+    println!("cargo:rerun-if-changed={}", PYTHON_MANIFEST);
+
+    let mut files: Vec<serde_json::Value> = std::fs::read_to_string(PYTHON_MANIFEST)
+        .ok()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        .and_then(|manifest| manifest.get("files").and_then(|f| f.as_array()).cloned())
+        .unwrap_or_default();
+
+    for (source, target) in APP_SOURCES {
+        println!("cargo:rerun-if-changed={}", source);
+        let root = Path::new(source);
+        for entry in walkdir::WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+        {
+            let path = entry.path();
+            // Bytecode is regenerated at runtime and never shipped
+            if path.components().any(|c| c.as_os_str() == "__pycache__") {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let Some((sha256, size)) = hash_file(path) else {
+                continue;
+            };
+            let manifest_path: PathBuf = Path::new(target).join(relative);
+            files.push(serde_json::json!({
+                "path": manifest_path.to_string_lossy().replace('\\', "/"),
+                "sha256": sha256,
+                "size": size,
+            }));
+        }
+    }
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("runtime-manifest.json");
+    let manifest = serde_json::json!({ "files": files });
+    std::fs::write(out, manifest.to_string()).expect("write runtime manifest");
+}
+
+fn hash_file(path: &Path) -> Option<(String, u64)> {
+    let mut file = File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    let digest = hasher.finalize();
+    Some((digest.iter().map(|b| format!("{:02x}", b)).collect(), size))
+}
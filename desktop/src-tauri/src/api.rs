@@ -0,0 +1,587 @@
+// Kiroku Memory Desktop - API Client
+// Shared HTTP client for talking to the Python FastAPI service
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default base URL of the locally spawned service
+pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:8000";
+
+/// Wait before each retry of a transient failure; its length is the retry limit
+const RETRY_BACKOFF: [Duration; 3] = [
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+    Duration::from_millis(800),
+];
+
+/// Lets the service drop a replayed POST
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Until settings are applied with `set_limits`
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+
+/// Longest timeout any request may use, including maintenance calls
+pub const MAX_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long a request waits for a free slot before failing with `ServiceBusy`
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Errors returned by API calls, distinguishing "busy" from "down"
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// Service answered 503 (typically while it is restarting)
+    ServiceBusy,
+    /// Service could not be reached at all
+    Unavailable(String),
+    /// Service accepted the request but did not answer in time
+    TimedOut(Duration),
+    /// Service answered with a non-success status
+    Status(u16, String),
+    /// Response body did not match the expected shape
+    Decode(String),
+}
+
+impl ApiError {
+    /// Map a non-success HTTP status to an error
+    pub fn from_status(status: u16, body: String) -> Self {
+        if status == 503 {
+            ApiError::ServiceBusy
+        } else {
+            ApiError::Status(status, body)
+        }
+    }
+
+    /// Endpoint (or resource) does not exist on this service version
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ApiError::Status(404 | 405, _))
+    }
+
+    /// Worth retrying: connection errors and 502/503, which are routine right
+    /// after a restart or during a GC pause
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ApiError::Unavailable(_) | ApiError::ServiceBusy | ApiError::Status(502, _)
+        )
+    }
+}
+
+/// How a response was obtained, for the UI's "(retried)"
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// 1 when the first try succeeded
+    pub attempts: u32,
+    pub retried: bool,
+}
+
+impl ResponseMeta {
+    fn new(attempts: u32) -> Self {
+        Self {
+            attempts,
+            retried: attempts > 1,
+        }
+    }
+}
+
+/// A response body together with its `ResponseMeta`
+#[derive(Clone, Debug, Serialize)]
+pub struct WithMeta<T> {
+    pub data: T,
+    pub meta: ResponseMeta,
+}
+
+/// Per-request behaviour beyond the method and body
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestOptions<'a> {
+    /// Allows retrying a POST; the service ignores a write it already applied
+    pub idempotency_key: Option<&'a str>,
+    /// Instead of the default timeout, capped at `MAX_REQUEST_TIMEOUT`
+    pub timeout: Option<Duration>,
+    /// Skip the concurrency limit
+    pub bypass_limit: bool,
+}
+
+impl<'a> RequestOptions<'a> {
+    pub fn idempotent(key: &'a str) -> Self {
+        Self {
+            idempotency_key: Some(key),
+            ..Self::default()
+        }
+    }
+
+    /// Tray and monitor requests, which UI load must not starve
+    pub fn background() -> Self {
+        Self {
+            bypass_limit: true,
+            ..Self::default()
+        }
+    }
+
+    /// Slow maintenance calls such as a bulk delete
+    pub fn maintenance() -> Self {
+        Self {
+            timeout: Some(MAX_REQUEST_TIMEOUT),
+            ..Self::default()
+        }
+    }
+}
+
+/// Current load on the client, for `get_api_client_stats`
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ApiClientStats {
+    pub in_flight: usize,
+    pub queued: usize,
+    pub max_in_flight: usize,
+    pub timeout_secs: u64,
+}
+
+/// Counts itself in a counter while alive
+struct Counted<'a>(&'a AtomicUsize);
+
+impl<'a> Counted<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Caps concurrent requests and counts those running or waiting
+struct Limiter {
+    /// The cap, and the semaphore enforcing it. Replaced when the cap changes;
+    /// requests already running keep their old permits.
+    semaphore: RwLock<(usize, Arc<Semaphore>)>,
+    in_flight: AtomicUsize,
+    queued: AtomicUsize,
+}
+
+impl Limiter {
+    fn new(max_in_flight: usize) -> Self {
+        Self {
+            semaphore: RwLock::new((max_in_flight, Arc::new(Semaphore::new(max_in_flight)))),
+            in_flight: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    fn max_in_flight(&self) -> usize {
+        self.semaphore.read().unwrap().0
+    }
+
+    fn set_max_in_flight(&self, max_in_flight: usize) {
+        let mut semaphore = self.semaphore.write().unwrap();
+        if semaphore.0 != max_in_flight {
+            *semaphore = (max_in_flight, Arc::new(Semaphore::new(max_in_flight)));
+        }
+    }
+
+    /// Wait for a free slot, or fail with `ServiceBusy` after `QUEUE_TIMEOUT`
+    async fn acquire(&self) -> Result<(Counted<'_>, OwnedSemaphorePermit), ApiError> {
+        let semaphore = self.semaphore.read().unwrap().1.clone();
+        let waiting = Counted::new(&self.queued);
+        let permit = tokio::time::timeout(QUEUE_TIMEOUT, semaphore.acquire_owned()).await;
+        drop(waiting);
+        match permit {
+            Ok(Ok(permit)) => Ok((Counted::new(&self.in_flight), permit)),
+            _ => Err(ApiError::ServiceBusy),
+        }
+    }
+}
+
+/// Backoff before retry number `attempt` (1-based); None once retries are used up
+fn retry_delay(attempt: u32, error: &ApiError) -> Option<Duration> {
+    if !error.is_transient() {
+        return None;
+    }
+    let index = usize::try_from(attempt).ok()?.checked_sub(1)?;
+    RETRY_BACKOFF.get(index).copied()
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::ServiceBusy => write!(f, "Service busy"),
+            ApiError::Unavailable(e) => write!(f, "Service not available: {}", e),
+            ApiError::TimedOut(timeout) => {
+                write!(f, "Service did not respond within {}s", timeout.as_secs())
+            }
+            ApiError::Status(code, body) => write!(f, "Service returned {}: {}", code, body),
+            ApiError::Decode(e) => write!(f, "Invalid response from service: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Shared client used by all commands that proxy to the service
+pub struct ApiClient {
+    http: RwLock<reqwest::Client>,
+    base_url: RwLock<String>,
+    /// Set when the local service listens on a Unix socket; requests go there
+    /// whatever the base URL's host
+    unix_socket: RwLock<Option<PathBuf>>,
+    /// Sent as `Authorization: Bearer` when the service requires auth
+    token: RwLock<Option<String>>,
+    /// Last response per path, for `get_json_cached`
+    cache: Mutex<HashMap<String, (Instant, serde_json::Value)>>,
+    timeout: RwLock<Duration>,
+    limiter: Limiter,
+}
+
+impl ApiClient {
+    pub fn new() -> Self {
+        Self {
+            http: RwLock::new(reqwest::Client::new()),
+            base_url: RwLock::new(DEFAULT_BASE_URL.to_string()),
+            unix_socket: RwLock::new(None),
+            token: RwLock::new(None),
+            cache: Mutex::new(HashMap::new()),
+            timeout: RwLock::new(DEFAULT_REQUEST_TIMEOUT),
+            limiter: Limiter::new(DEFAULT_MAX_IN_FLIGHT),
+        }
+    }
+
+    /// Apply the default timeout and concurrency cap from settings
+    pub fn set_limits(&self, timeout: Duration, max_in_flight: usize) {
+        *self.timeout.write().unwrap() = timeout.min(MAX_REQUEST_TIMEOUT);
+        self.limiter.set_max_in_flight(max_in_flight);
+    }
+
+    pub fn stats(&self) -> ApiClientStats {
+        ApiClientStats {
+            in_flight: self.limiter.in_flight.load(Ordering::SeqCst),
+            queued: self.limiter.queued.load(Ordering::SeqCst),
+            max_in_flight: self.limiter.max_in_flight(),
+            timeout_secs: self.timeout.read().unwrap().as_secs(),
+        }
+    }
+
+    /// Set or clear the bearer token attached to every request
+    pub fn set_token(&self, token: Option<String>) {
+        *self.token.write().unwrap() = token;
+    }
+
+    /// Add the bearer token, if any, to a request built with another client
+    pub fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.token.read().unwrap().as_deref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Current base URL (without trailing slash)
+    pub fn base_url(&self) -> String {
+        self.base_url.read().unwrap().clone()
+    }
+
+    /// Point the client at a different base URL
+    pub fn set_base_url(&self, url: &str) {
+        *self.base_url.write().unwrap() = url.trim_end_matches('/').to_string();
+        self.clear_cache();
+    }
+
+    /// Socket the client connects through, if any
+    pub fn unix_socket(&self) -> Option<PathBuf> {
+        self.unix_socket.read().unwrap().clone()
+    }
+
+    /// Connect through a Unix socket, or over TCP again with None
+    pub fn set_unix_socket(&self, path: Option<PathBuf>) {
+        if *self.unix_socket.read().unwrap() == path {
+            return;
+        }
+        *self.unix_socket.write().unwrap() = path;
+        match self.client_builder().build() {
+            Ok(client) => *self.http.write().unwrap() = client,
+            Err(e) => println!("[API] Could not rebuild HTTP client: {}", e),
+        }
+        self.clear_cache();
+    }
+
+    /// Builder for a separate client (e.g. health checks with their own
+    /// timeout) that reaches the service the same way this one does
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let builder = reqwest::Client::builder();
+        #[cfg(unix)]
+        if let Some(path) = self.unix_socket() {
+            return builder.unix_socket(path);
+        }
+        builder
+    }
+
+    fn http(&self) -> reqwest::Client {
+        self.http.read().unwrap().clone()
+    }
+
+    /// Forget cached responses so the next `get_json_cached` fetches again
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Build a full URL for an API path
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url(), path)
+    }
+
+    /// Send a request and fail on non-success status.
+    ///
+    /// GETs, and POSTs carrying an idempotency key, are retried with backoff
+    /// on transient failures. One request slot is held across the retries.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<serde_json::Value>,
+        options: RequestOptions<'_>,
+    ) -> Result<(reqwest::Response, ResponseMeta), ApiError> {
+        let _slot = if options.bypass_limit {
+            None
+        } else {
+            Some(self.limiter.acquire().await?)
+        };
+        let timeout = options
+            .timeout
+            .unwrap_or_else(|| *self.timeout.read().unwrap())
+            .min(MAX_REQUEST_TIMEOUT);
+        let retry = method == reqwest::Method::GET || options.idempotency_key.is_some();
+        let mut attempts = 1;
+        loop {
+            match self
+                .send_once(method.clone(), path, query, body.as_ref(), options.idempotency_key, timeout)
+                .await
+            {
+                Ok(resp) => return Ok((resp, ResponseMeta::new(attempts))),
+                Err(e) => match retry_delay(attempts, &e).filter(|_| retry) {
+                    Some(delay) => {
+                        println!("[API] {} {} failed ({}), retrying in {:?}", method, path, e, delay);
+                        tokio::time::sleep(delay).await;
+                        attempts += 1;
+                    }
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+
+    async fn send_once(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&serde_json::Value>,
+        idempotency_key: Option<&str>,
+        timeout: Duration,
+    ) -> Result<reqwest::Response, ApiError> {
+        let mut request = self.authorize(
+            self.http()
+                .request(method, self.url(path))
+                .query(query)
+                .timeout(timeout),
+        );
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        if let Some(key) = idempotency_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        let resp = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ApiError::TimedOut(timeout)
+            } else {
+                ApiError::Unavailable(e.to_string())
+            }
+        })?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ApiError::from_status(status.as_u16(), body));
+        }
+        Ok(resp)
+    }
+
+    /// Open a long-lived streaming GET, with no timeout and outside the
+    /// concurrency cap
+    pub async fn open_stream(&self, path: &str) -> Result<reqwest::Response, ApiError> {
+        let resp = self
+            .authorize(self.http().get(self.url(path)))
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| ApiError::Unavailable(e.to_string()))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ApiError::from_status(status.as_u16(), body));
+        }
+        Ok(resp)
+    }
+
+    /// GET a path and deserialize the JSON body
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ApiError> {
+        self.get_json_with_meta(path, query)
+            .await
+            .map(|reply| reply.data)
+    }
+
+    /// `get_json`, also reporting how many attempts it took
+    pub async fn get_json_with_meta<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<WithMeta<T>, ApiError> {
+        let (resp, meta) = self
+            .send(reqwest::Method::GET, path, query, None, RequestOptions::default())
+            .await?;
+        let data = resp
+            .json::<T>()
+            .await
+            .map_err(|e| ApiError::Decode(e.to_string()))?;
+        Ok(WithMeta { data, meta })
+    }
+
+    /// GET a path (no query), reusing the last response while it is younger
+    /// than `max_age`
+    pub async fn get_json_cached<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        max_age: Duration,
+        options: RequestOptions<'_>,
+    ) -> Result<T, ApiError> {
+        let cached = self
+            .cache
+            .lock()
+            .unwrap()
+            .get(path)
+            .filter(|(fetched, _)| fetched.elapsed() < max_age)
+            .map(|(_, value)| value.clone());
+        let value = match cached {
+            Some(value) => value,
+            None => {
+                let value = self
+                    .send(reqwest::Method::GET, path, &[], None, options)
+                    .await?
+                    .0
+                    .json::<serde_json::Value>()
+                    .await
+                    .map_err(|e| ApiError::Decode(e.to_string()))?;
+                self.cache
+                    .lock()
+                    .unwrap()
+                    .insert(path.to_string(), (Instant::now(), value.clone()));
+                value
+            }
+        };
+        serde_json::from_value(value).map_err(|e| ApiError::Decode(e.to_string()))
+    }
+
+    /// DELETE a path and deserialize the JSON body
+    pub async fn delete_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        options: RequestOptions<'_>,
+    ) -> Result<T, ApiError> {
+        self.send(reqwest::Method::DELETE, path, &[], None, options)
+            .await?
+            .0
+            .json::<T>()
+            .await
+            .map_err(|e| ApiError::Decode(e.to_string()))
+    }
+
+    /// POST a JSON body and deserialize the JSON response.
+    ///
+    /// Retried on transient failures only with `options.idempotency_key`.
+    pub async fn post_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+        options: RequestOptions<'_>,
+    ) -> Result<T, ApiError> {
+        let body = serde_json::to_value(body).map_err(|e| ApiError::Decode(e.to_string()))?;
+        self.send(reqwest::Method::POST, path, &[], Some(body), options)
+            .await?
+            .0
+            .json::<T>()
+            .await
+            .map_err(|e| ApiError::Decode(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_then_stops() {
+        let down = ApiError::Unavailable("connection refused".into());
+        assert_eq!(retry_delay(1, &down), Some(Duration::from_millis(200)));
+        assert_eq!(retry_delay(2, &down), Some(Duration::from_millis(400)));
+        assert_eq!(retry_delay(3, &down), Some(Duration::from_millis(800)));
+        assert_eq!(retry_delay(4, &down), None);
+    }
+
+    #[test]
+    fn test_retry_delay_only_for_transient_errors() {
+        assert!(retry_delay(1, &ApiError::ServiceBusy).is_some());
+        assert!(retry_delay(1, &ApiError::Status(502, String::new())).is_some());
+        assert!(retry_delay(1, &ApiError::Status(500, String::new())).is_none());
+        assert!(retry_delay(1, &ApiError::Status(404, String::new())).is_none());
+        assert!(retry_delay(1, &ApiError::Status(422, String::new())).is_none());
+        assert!(retry_delay(1, &ApiError::Decode("eof".into())).is_none());
+    }
+
+    #[test]
+    fn test_timeout_is_not_retried() {
+        assert!(retry_delay(1, &ApiError::TimedOut(DEFAULT_REQUEST_TIMEOUT)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_reports_busy() {
+        let limiter = Limiter::new(1);
+        let held = limiter.acquire().await.unwrap();
+        assert_eq!(limiter.in_flight.load(Ordering::SeqCst), 1);
+
+        let start = Instant::now();
+        assert_eq!(limiter.acquire().await.err(), Some(ApiError::ServiceBusy));
+        assert!(start.elapsed() >= QUEUE_TIMEOUT);
+        assert_eq!(limiter.queued.load(Ordering::SeqCst), 0);
+
+        drop(held);
+        assert_eq!(limiter.in_flight.load(Ordering::SeqCst), 0);
+        assert!(limiter.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_raising_the_cap_frees_a_slot() {
+        let limiter = Limiter::new(1);
+        let _held = limiter.acquire().await.unwrap();
+        limiter.set_max_in_flight(2);
+        assert_eq!(limiter.max_in_flight(), 2);
+        assert!(limiter.acquire().await.is_ok());
+    }
+
+    #[test]
+    fn test_response_meta_retried() {
+        assert!(!ResponseMeta::new(1).retried);
+        assert!(ResponseMeta::new(3).retried);
+    }
+}
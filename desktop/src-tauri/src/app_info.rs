@@ -0,0 +1,97 @@
+// Kiroku Memory Desktop - App Info
+// Build metadata embedded by build.rs, with the app and service versions
+
+use crate::service::RuntimeSource;
+use serde::Serialize;
+
+pub const GIT_COMMIT: &str = env!("KIROKU_GIT_COMMIT");
+/// Unix timestamp (seconds), or SOURCE_DATE_EPOCH when set
+const BUILD_TIMESTAMP: &str = env!("KIROKU_BUILD_TIMESTAMP");
+const TARGET: &str = env!("KIROKU_TARGET");
+
+/// Returned by `get_app_info`
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AppInfo {
+    pub app_version: String,
+    pub git_commit: String,
+    /// RFC 3339; None if build.rs couldn't read the clock
+    pub built_at: Option<String>,
+    pub target: String,
+    pub tauri_version: String,
+    /// None when no Python runtime could be found
+    pub python_runtime: Option<RuntimeSource>,
+    /// From the last health response; None until the service has answered
+    pub service_version: Option<String>,
+}
+
+impl AppInfo {
+    pub fn new(python_runtime: Option<RuntimeSource>, service_version: Option<String>) -> Self {
+        AppInfo {
+            app_version: crate::updates::CURRENT_VERSION.to_string(),
+            git_commit: GIT_COMMIT.to_string(),
+            built_at: format_build_time(BUILD_TIMESTAMP),
+            target: TARGET.to_string(),
+            tauri_version: tauri::VERSION.to_string(),
+            python_runtime,
+            service_version,
+        }
+    }
+}
+
+/// Tray footer, e.g. "Kiroku Memory v0.3.1 (abc1234)"
+pub fn menu_label() -> String {
+    format!("Kiroku Memory v{} ({})", crate::updates::CURRENT_VERSION, GIT_COMMIT)
+}
+
+fn format_build_time(timestamp: &str) -> Option<String> {
+    let secs = timestamp.parse().ok()?;
+    chrono::DateTime::from_timestamp(secs, 0).map(|time| time.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_build_time() {
+        assert_eq!(
+            format_build_time("1700000000").as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+        assert_eq!(format_build_time(""), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let info = AppInfo {
+            app_version: "0.3.1".into(),
+            git_commit: "abc1234".into(),
+            built_at: None,
+            target: "aarch64-apple-darwin".into(),
+            tauri_version: "2.1.0".into(),
+            python_runtime: Some(RuntimeSource::Bundled),
+            service_version: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            serde_json::json!({
+                "app_version": "0.3.1",
+                "git_commit": "abc1234",
+                "built_at": null,
+                "target": "aarch64-apple-darwin",
+                "tauri_version": "2.1.0",
+                "python_runtime": "bundled",
+                "service_version": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_embedded_metadata() {
+        let info = AppInfo::new(None, None);
+        assert!(!info.git_commit.is_empty());
+        assert!(!info.target.is_empty());
+        assert!(info.built_at.is_some());
+        assert!(menu_label().ends_with(&format!("({})", GIT_COMMIT)));
+    }
+}
@@ -0,0 +1,104 @@
+// Kiroku Memory Desktop - Linux Autostart
+// Launch at login through an XDG autostart desktop entry
+
+use std::path::{Path, PathBuf};
+
+const ENTRY_FILE_NAME: &str = "kiroku-memory.desktop";
+
+/// `$XDG_CONFIG_HOME/autostart`, or `~/.config/autostart`
+fn autostart_dir(config_home: Option<&str>, home: &Path) -> PathBuf {
+    match config_home.filter(|dir| Path::new(dir).is_absolute()) {
+        Some(dir) => PathBuf::from(dir).join("autostart"),
+        None => home.join(".config").join("autostart"),
+    }
+}
+
+/// Quote an Exec argument per the Desktop Entry spec
+fn exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if !arg.contains(|c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c)) {
+        return arg;
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn desktop_entry(exe: &Path) -> Result<String, String> {
+    let exe = exe
+        .to_str()
+        .ok_or_else(|| "Executable path is not valid UTF-8".to_string())?;
+    Ok(format!(
+        "[Desktop Entry]
+Type=Application
+Name=Kiroku Memory
+Exec={} --tray
+Terminal=false
+X-GNOME-Autostart-enabled=true
+",
+        exec_arg(exe)
+    ))
+}
+
+/// Write or remove the autostart entry. Both are no-ops when already done.
+pub fn set(home: &Path, enabled: bool) -> Result<(), String> {
+    let config_home = std::env::var("XDG_CONFIG_HOME").ok();
+    let entry_path = autostart_dir(config_home.as_deref(), home).join(ENTRY_FILE_NAME);
+
+    if !enabled {
+        return match std::fs::remove_file(&entry_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove autostart entry: {}", e))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let exe_path = std::env::current_exe()
+        .map_err(|e| format!("Failed to resolve current exe: {}", e))?;
+    let dir = entry_path.parent().unwrap_or(home);
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create autostart dir: {}", e))?;
+    let tmp_path = entry_path.with_extension("tmp");
+    std::fs::write(&tmp_path, desktop_entry(&exe_path)?)
+        .and_then(|()| std::fs::rename(&tmp_path, &entry_path))
+        .map_err(|e| format!("Failed to write autostart entry: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autostart_dir() {
+        let home = Path::new("/home/kiroku");
+        assert_eq!(
+            autostart_dir(None, home),
+            Path::new("/home/kiroku/.config/autostart")
+        );
+        assert_eq!(
+            autostart_dir(Some("/tmp/config"), home),
+            Path::new("/tmp/config/autostart")
+        );
+        // The spec says relative values are invalid and ignored
+        assert_eq!(
+            autostart_dir(Some("config"), home),
+            Path::new("/home/kiroku/.config/autostart")
+        );
+    }
+
+    #[test]
+    fn test_desktop_entry_quotes_exec() {
+        let entry = desktop_entry(Path::new("/opt/kiroku-memory/kiroku")).unwrap();
+        assert!(entry.contains("\nExec=/opt/kiroku-memory/kiroku --tray\n"));
+        assert!(entry.contains("\nX-GNOME-Autostart-enabled=true\n"));
+
+        let entry = desktop_entry(Path::new("/home/me/My Apps/100%/kiroku")).unwrap();
+        assert!(entry.contains("\nExec=\"/home/me/My Apps/100%%/kiroku\" --tray\n"));
+    }
+}
@@ -0,0 +1,341 @@
+// Kiroku Memory Desktop - Backups
+// Point-in-time copies of the SurrealDB data directory
+
+use crate::backup_crypto::{self, KdfParams, ENCRYPTED_FILE};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Prefix shared by every backup folder we create
+pub const BACKUP_PREFIX: &str = "kiroku-backup-";
+
+/// Name of the database folder inside the data dir and inside each backup
+pub const DB_DIR_NAME: &str = "surrealdb";
+
+/// Description of a completed backup
+#[derive(Clone, Debug, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+    /// Restoring needs the passphrase it was made with
+    pub encrypted: bool,
+}
+
+/// Default backup location under the data dir
+pub fn default_backup_root(data_dir: &Path) -> PathBuf {
+    data_dir.join("backups")
+}
+
+/// Folder name for a backup taken at `time`
+pub fn backup_name(time: DateTime<Local>) -> String {
+    format!("{}{}", BACKUP_PREFIX, time.format("%Y%m%d-%H%M%S"))
+}
+
+/// When the backup named `name` was taken, if it is one `backup_name` created
+fn backup_time(name: &str) -> Option<NaiveDateTime> {
+    let ts = name.strip_prefix(BACKUP_PREFIX)?;
+    NaiveDateTime::parse_from_str(ts, "%Y%m%d-%H%M%S").ok()
+}
+
+/// Whether `name` is a completed backup folder created by `backup_name`
+pub fn is_backup_name(name: &str) -> bool {
+    backup_time(name).is_some()
+}
+
+/// Whether the backup at `backup_path` holds an encrypted database
+pub fn is_encrypted(backup_path: &Path) -> bool {
+    backup_crypto::is_encrypted_file(&backup_path.join(ENCRYPTED_FILE))
+}
+
+/// Completed backups in `backup_root`, newest first
+pub fn list_backups(backup_root: &Path) -> std::io::Result<Vec<BackupInfo>> {
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(backup_root)?.flatten() {
+        if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let Some(time) = backup_time(&entry.file_name().to_string_lossy()) else {
+            continue;
+        };
+        let path = entry.path();
+        let created_at = Local
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_else(|| time.to_string());
+        backups.push(BackupInfo {
+            path: path.to_string_lossy().to_string(),
+            created_at,
+            size_bytes: dir_size(&path)?,
+            encrypted: is_encrypted(&path),
+        });
+    }
+    // Timestamps in the names sort chronologically
+    backups.sort_by(|a, b| b.path.cmp(&a.path));
+    Ok(backups)
+}
+
+/// Delete the oldest backups in `backup_root` so that at most `keep` remain.
+/// Only folders named like our backups are considered. Returns the deleted paths.
+pub fn prune_backups(backup_root: &Path, keep: usize) -> std::io::Result<Vec<PathBuf>> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(backup_root)?
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter(|entry| is_backup_name(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    // Timestamps in the names sort chronologically
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    let mut deleted = Vec::with_capacity(excess);
+    for path in backups.into_iter().take(excess) {
+        std::fs::remove_dir_all(&path)?;
+        deleted.push(path);
+    }
+    Ok(deleted)
+}
+
+/// Copy `data_dir/surrealdb` into a new timestamped folder under `backup_root`,
+/// or with a passphrase, into one encrypted file in that folder.
+///
+/// The copy is written to a `.partial` folder first and renamed on success,
+/// so an interrupted backup never looks complete.
+/// The service must not be writing to the database while this runs.
+pub fn create_backup(
+    data_dir: &Path,
+    backup_root: &Path,
+    passphrase: Option<&str>,
+) -> anyhow::Result<BackupInfo> {
+    let db_dir = data_dir.join(DB_DIR_NAME);
+    if !db_dir.exists() {
+        anyhow::bail!("No database found at {}", db_dir.display());
+    }
+
+    crate::permissions::ensure_private_dir(backup_root)?;
+    let now = Local::now();
+    let target = backup_root.join(backup_name(now));
+    let partial = target.with_extension("partial");
+    if partial.exists() {
+        std::fs::remove_dir_all(&partial)?;
+    }
+
+    let copied = match passphrase {
+        Some(passphrase) => std::fs::create_dir_all(&partial)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| {
+                let file = partial.join(ENCRYPTED_FILE);
+                backup_crypto::encrypt_dir(&db_dir, &file, passphrase, KdfParams::default())?;
+                Ok(std::fs::metadata(&file)?.len())
+            }),
+        None => copy_dir_recursive(&db_dir, &partial.join(DB_DIR_NAME)).map_err(anyhow::Error::from),
+    };
+    let size_bytes = match copied {
+        Ok(size) => size,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&partial);
+            return Err(e);
+        }
+    };
+    std::fs::rename(&partial, &target)?;
+
+    Ok(BackupInfo {
+        path: target.to_string_lossy().to_string(),
+        created_at: now.to_rfc3339(),
+        size_bytes,
+        encrypted: passphrase.is_some(),
+    })
+}
+
+/// Replace `data_dir/surrealdb` with the database from `backup_path`.
+///
+/// The current database is renamed aside first and its path returned, so the
+/// caller can `rollback_restore` if the restored data turns out to be unusable.
+/// An encrypted backup is decrypted in full before the current database is
+/// touched, so a wrong passphrase or damaged file changes nothing.
+/// The service must be stopped while this runs.
+pub fn restore_backup(
+    data_dir: &Path,
+    backup_path: &Path,
+    passphrase: Option<&str>,
+) -> anyhow::Result<Option<PathBuf>> {
+    if is_encrypted(backup_path) {
+        let Some(passphrase) = passphrase else {
+            anyhow::bail!("This backup is encrypted; its passphrase is needed to restore it");
+        };
+        return restore_encrypted(data_dir, backup_path, passphrase);
+    }
+    let source = backup_path.join(DB_DIR_NAME);
+    if !source.is_dir() {
+        anyhow::bail!("{} is not a backup (no {} folder)", backup_path.display(), DB_DIR_NAME);
+    }
+
+    let db_dir = data_dir.join(DB_DIR_NAME);
+    let aside = if db_dir.exists() {
+        let aside = data_dir.join(format!(
+            "{}.pre-restore-{}",
+            DB_DIR_NAME,
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        std::fs::rename(&db_dir, &aside)?;
+        Some(aside)
+    } else {
+        None
+    };
+
+    if let Err(e) = copy_dir_recursive(&source, &db_dir) {
+        let _ = std::fs::remove_dir_all(&db_dir);
+        if let Some(aside) = &aside {
+            std::fs::rename(aside, &db_dir)?;
+        }
+        return Err(e.into());
+    }
+    Ok(aside)
+}
+
+fn restore_encrypted(
+    data_dir: &Path,
+    backup_path: &Path,
+    passphrase: &str,
+) -> anyhow::Result<Option<PathBuf>> {
+    let db_dir = data_dir.join(DB_DIR_NAME);
+    let staging = data_dir.join(format!("{}.restoring", DB_DIR_NAME));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    if let Err(e) = backup_crypto::decrypt_dir(&backup_path.join(ENCRYPTED_FILE), &staging, passphrase) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let aside = if db_dir.exists() {
+        let aside = data_dir.join(format!(
+            "{}.pre-restore-{}",
+            DB_DIR_NAME,
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        std::fs::rename(&db_dir, &aside)?;
+        Some(aside)
+    } else {
+        None
+    };
+    if let Err(e) = std::fs::rename(&staging, &db_dir) {
+        let _ = std::fs::remove_dir_all(&staging);
+        if let Some(aside) = &aside {
+            std::fs::rename(aside, &db_dir)?;
+        }
+        return Err(e.into());
+    }
+    Ok(aside)
+}
+
+/// Put the database set aside by `restore_backup` back in place
+pub fn rollback_restore(data_dir: &Path, aside: Option<&Path>) -> std::io::Result<()> {
+    let db_dir = data_dir.join(DB_DIR_NAME);
+    if db_dir.exists() {
+        std::fs::remove_dir_all(&db_dir)?;
+    }
+    if let Some(aside) = aside {
+        std::fs::rename(aside, &db_dir)?;
+    }
+    Ok(())
+}
+
+/// Total size of the files under `path`
+pub fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            total += dir_size(&entry.path())?;
+        } else {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
+}
+
+/// Recursively copy a directory, returning the number of bytes copied
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<u64> {
+    std::fs::create_dir_all(dst)?;
+    let mut total = 0;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            total += copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            total += std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_prune_keeps_newest_and_ignores_other_files() {
+        let root = TempDir::new("prune");
+        for name in [
+            "kiroku-backup-20250101-000000",
+            "kiroku-backup-20250102-000000",
+            "kiroku-backup-20250103-000000",
+            "kiroku-backup-20250104-000000.partial",
+            "kiroku-backup-notes",
+            "photos",
+        ] {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+        }
+
+        let deleted = prune_backups(root.path(), 2).unwrap();
+        assert_eq!(deleted, vec![root.join("kiroku-backup-20250101-000000")]);
+        for kept in [
+            "kiroku-backup-20250102-000000",
+            "kiroku-backup-20250103-000000",
+            "kiroku-backup-20250104-000000.partial",
+            "kiroku-backup-notes",
+            "photos",
+        ] {
+            assert!(root.join(kept).exists(), "{} was removed", kept);
+        }
+    }
+
+    #[test]
+    fn test_encrypted_restore_leaves_database_on_wrong_passphrase() {
+        let root = TempDir::new("enc-restore");
+        let data_dir = root.join("data");
+        std::fs::create_dir_all(data_dir.join(DB_DIR_NAME)).unwrap();
+        std::fs::write(data_dir.join(DB_DIR_NAME).join("db.bin"), "current").unwrap();
+
+        let source = root.join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("db.bin"), "backed up").unwrap();
+        let backup_path = root.join("backups/kiroku-backup-20250101-000000");
+        std::fs::create_dir_all(&backup_path).unwrap();
+        let cheap = KdfParams {
+            m_cost_kib: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        backup_crypto::encrypt_dir(&source, &backup_path.join(ENCRYPTED_FILE), "secret", cheap).unwrap();
+
+        let listed = list_backups(&root.join("backups")).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].encrypted);
+
+        assert!(restore_backup(&data_dir, &backup_path, None).is_err());
+        let error = restore_backup(&data_dir, &backup_path, Some("wrong")).unwrap_err();
+        assert_eq!(error.to_string(), backup_crypto::WRONG_PASSPHRASE);
+        let live = data_dir.join(DB_DIR_NAME).join("db.bin");
+        assert_eq!(std::fs::read_to_string(&live).unwrap(), "current");
+
+        let aside = restore_backup(&data_dir, &backup_path, Some("secret")).unwrap();
+        assert_eq!(std::fs::read_to_string(&live).unwrap(), "backed up");
+        rollback_restore(&data_dir, aside.as_deref()).unwrap();
+        assert_eq!(std::fs::read_to_string(&live).unwrap(), "current");
+    }
+}
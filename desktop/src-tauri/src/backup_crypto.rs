@@ -0,0 +1,494 @@
+// Kiroku Memory Desktop - Backup Encryption
+// Packs a database folder into one passphrase-encrypted file (Argon2id + XChaCha20-Poly1305)
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{KeyInit, OsRng, Payload};
+use chacha20poly1305::XChaCha20Poly1305;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The encrypted database inside an encrypted backup folder
+pub const ENCRYPTED_FILE: &str = "surrealdb.kbak";
+
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Returned when the passphrase doesn't open a backup
+pub const WRONG_PASSPHRASE: &str = "Wrong passphrase for this backup";
+
+const MAGIC: &[u8; 8] = b"KRKBAK01";
+const FORMAT_VERSION: u32 = 1;
+const KDF: &str = "argon2id";
+const CIPHER: &str = "xchacha20poly1305-stream-be32";
+/// Plaintext bytes per encrypted chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+const TAG_SIZE: usize = 16;
+const SALT_SIZE: usize = 16;
+/// XChaCha20's 24-byte nonce less the STREAM counter and last-chunk flag
+const NONCE_SIZE: usize = 19;
+const MAX_HEADER_SIZE: u32 = 4096;
+
+/// Archive entry tags, inside the encrypted stream
+const ENTRY_END: u8 = 0;
+const ENTRY_DIR: u8 = 1;
+const ENTRY_FILE: u8 = 2;
+
+/// Argon2id cost; stored in the header so restore uses what the backup was made with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub m_cost_kib: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // 64 MiB, 3 passes: about a second on a laptop
+        Self {
+            m_cost_kib: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+}
+
+/// Plain-text parameters at the start of an encrypted backup, authenticated
+/// with every chunk
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Header {
+    pub version: u32,
+    pub kdf: String,
+    pub m_cost_kib: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    /// Hex
+    pub salt: String,
+    pub cipher: String,
+    /// Hex
+    pub nonce: String,
+    pub chunk_size: u32,
+}
+
+type Encryptor = EncryptorBE32<XChaCha20Poly1305>;
+type Decryptor = DecryptorBE32<XChaCha20Poly1305>;
+
+fn derive_key(passphrase: &str, salt: &[u8], params: KdfParams) -> anyhow::Result<[u8; 32]> {
+    let params = Params::new(params.m_cost_kib, params.t_cost, params.p_cost, Some(32))
+        .map_err(|e| anyhow::anyhow!("Invalid key derivation parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Buffers plaintext and writes it out as encrypted chunks
+struct ChunkWriter<W: Write> {
+    out: W,
+    encryptor: Option<Encryptor>,
+    aad: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> ChunkWriter<W> {
+    /// Encrypt what is left as the final chunk
+    fn finish(mut self) -> io::Result<W> {
+        let encryptor = self.encryptor.take().expect("finished twice");
+        let chunk = encryptor
+            .encrypt_last(Payload {
+                msg: &self.buffer,
+                aad: &self.aad,
+            })
+            .map_err(|_| io::Error::other("Encryption failed"))?;
+        self.out.write_all(&chunk)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: Write> Write for ChunkWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        // Keep at least one byte back: the last chunk must go through `finish`
+        while self.buffer.len() > CHUNK_SIZE {
+            let encryptor = self.encryptor.as_mut().expect("write after finish");
+            let chunk = encryptor
+                .encrypt_next(Payload {
+                    msg: &self.buffer[..CHUNK_SIZE],
+                    aad: &self.aad,
+                })
+                .map_err(|_| io::Error::other("Encryption failed"))?;
+            self.out.write_all(&chunk)?;
+            self.buffer.drain(..CHUNK_SIZE);
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Decrypts chunks as they are read; a wrong key fails on the first one
+struct ChunkReader<R: Read> {
+    input: R,
+    decryptor: Option<Decryptor>,
+    aad: Vec<u8>,
+    /// Next ciphertext chunk, read ahead to spot the last one
+    pending: Vec<u8>,
+    plain: Vec<u8>,
+    pos: usize,
+    chunks_read: u64,
+}
+
+impl<R: Read> ChunkReader<R> {
+    fn new(mut input: R, decryptor: Decryptor, aad: Vec<u8>) -> io::Result<Self> {
+        let pending = read_chunk(&mut input)?;
+        Ok(Self {
+            input,
+            decryptor: Some(decryptor),
+            aad,
+            pending,
+            plain: Vec::new(),
+            pos: 0,
+            chunks_read: 0,
+        })
+    }
+
+    fn decrypt_error(&self) -> io::Error {
+        let message = if self.chunks_read == 0 {
+            WRONG_PASSPHRASE
+        } else {
+            "Backup is corrupted"
+        };
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    }
+
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        if self.decryptor.is_none() {
+            return Ok(false);
+        }
+        let current = std::mem::take(&mut self.pending);
+        let next = read_chunk(&mut self.input)?;
+        let payload = Payload {
+            msg: &current,
+            aad: &self.aad,
+        };
+        let plain = if next.is_empty() {
+            let decryptor = self.decryptor.take().expect("checked above");
+            decryptor.decrypt_last(payload)
+        } else {
+            self.decryptor
+                .as_mut()
+                .expect("checked above")
+                .decrypt_next(payload)
+        };
+        self.plain = plain.map_err(|_| self.decrypt_error())?;
+        self.pos = 0;
+        self.pending = next;
+        self.chunks_read += 1;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for ChunkReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.plain.len() {
+            if !self.next_chunk()? {
+                return Ok(0);
+            }
+        }
+        let n = out.len().min(self.plain.len() - self.pos);
+        out[..n].copy_from_slice(&self.plain[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// One ciphertext chunk; shorter only at the end of the file
+fn read_chunk(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE + TAG_SIZE);
+    input.take((CHUNK_SIZE + TAG_SIZE) as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// Read the magic and header, leaving `input` at the first chunk. Returns the
+/// header and its raw bytes (the associated data).
+fn read_header_from(input: &mut impl Read) -> anyhow::Result<(Header, Vec<u8>)> {
+    let mut magic = [0u8; 8];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        anyhow::bail!("Not an encrypted Kiroku backup");
+    }
+    let mut len = [0u8; 4];
+    input.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_HEADER_SIZE {
+        anyhow::bail!("Encrypted backup header is too large");
+    }
+    let mut raw = vec![0u8; len as usize];
+    input.read_exact(&mut raw)?;
+    let header: Header = serde_json::from_slice(&raw)?;
+    if header.version != FORMAT_VERSION || header.kdf != KDF || header.cipher != CIPHER {
+        anyhow::bail!(
+            "Unsupported backup encryption ({} v{}, {})",
+            header.kdf,
+            header.version,
+            header.cipher
+        );
+    }
+    if header.chunk_size as usize != CHUNK_SIZE {
+        anyhow::bail!("Unsupported backup chunk size {}", header.chunk_size);
+    }
+    Ok((header, raw))
+}
+
+fn open_reader(path: &Path, passphrase: &str) -> anyhow::Result<ChunkReader<BufReader<File>>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let (header, raw) = read_header_from(&mut file)?;
+    let invalid = || anyhow::anyhow!("Encrypted backup header is damaged");
+    let salt = from_hex(&header.salt).ok_or_else(invalid)?;
+    let nonce = from_hex(&header.nonce).filter(|n| n.len() == NONCE_SIZE).ok_or_else(invalid)?;
+    let params = KdfParams {
+        m_cost_kib: header.m_cost_kib,
+        t_cost: header.t_cost,
+        p_cost: header.p_cost,
+    };
+    let key = derive_key(passphrase, &salt, params)?;
+    let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(&key));
+    let decryptor = Decryptor::from_aead(cipher, GenericArray::from_slice(&nonce));
+    Ok(ChunkReader::new(file, decryptor, raw)?)
+}
+
+/// Check the passphrase against the first chunk without unpacking anything
+pub fn verify_passphrase(path: &Path, passphrase: &str) -> anyhow::Result<()> {
+    let mut reader = open_reader(path, passphrase)?;
+    reader.next_chunk()?;
+    Ok(())
+}
+
+/// Pack and encrypt `src` into `dst`. Returns the plaintext bytes packed.
+pub fn encrypt_dir(src: &Path, dst: &Path, passphrase: &str, params: KdfParams) -> anyhow::Result<u64> {
+    let mut salt = [0u8; SALT_SIZE];
+    let mut nonce = [0u8; NONCE_SIZE];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let header = Header {
+        version: FORMAT_VERSION,
+        kdf: KDF.to_string(),
+        m_cost_kib: params.m_cost_kib,
+        t_cost: params.t_cost,
+        p_cost: params.p_cost,
+        salt: to_hex(&salt),
+        cipher: CIPHER.to_string(),
+        nonce: to_hex(&nonce),
+        chunk_size: CHUNK_SIZE as u32,
+    };
+    let raw = serde_json::to_vec(&header)?;
+    let key = derive_key(passphrase, &salt, params)?;
+    let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(&key));
+
+    let mut out = BufWriter::new(File::create(dst)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&(raw.len() as u32).to_be_bytes())?;
+    out.write_all(&raw)?;
+    let mut writer = ChunkWriter {
+        out,
+        encryptor: Some(Encryptor::from_aead(cipher, GenericArray::from_slice(&nonce))),
+        aad: raw,
+        buffer: Vec::with_capacity(CHUNK_SIZE * 2),
+    };
+    let total = pack_dir(src, "", &mut writer)?;
+    writer.write_all(&[ENTRY_END])?;
+    writer.finish()?.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(total)
+}
+
+/// Decrypt and unpack `src` into `dst`, which must not exist yet. Returns the
+/// bytes unpacked. A wrong passphrase fails before anything is written.
+pub fn decrypt_dir(src: &Path, dst: &Path, passphrase: &str) -> anyhow::Result<u64> {
+    let mut reader = open_reader(src, passphrase)?;
+    reader.next_chunk()?;
+    std::fs::create_dir_all(dst)?;
+    unpack(&mut reader, dst)
+}
+
+fn pack_dir(dir: &Path, prefix: &str, out: &mut impl Write) -> io::Result<u64> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.collect::<io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    let mut total = 0;
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name
+            .to_str()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Non-UTF-8 file name"))?;
+        let path = format!("{}{}", prefix, name);
+        if entry.file_type()?.is_dir() {
+            write_entry(out, ENTRY_DIR, &path)?;
+            total += pack_dir(&entry.path(), &format!("{}/", path), out)?;
+        } else {
+            write_entry(out, ENTRY_FILE, &path)?;
+            let mut file = File::open(entry.path())?;
+            let len = file.metadata()?.len();
+            out.write_all(&len.to_be_bytes())?;
+            let copied = io::copy(&mut (&mut file).take(len), out)?;
+            if copied != len {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("{} changed while it was backed up", path),
+                ));
+            }
+            total += len;
+        }
+    }
+    Ok(total)
+}
+
+fn write_entry(out: &mut impl Write, tag: u8, path: &str) -> io::Result<()> {
+    let len = u16::try_from(path.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Path too long"))?;
+    out.write_all(&[tag])?;
+    out.write_all(&len.to_be_bytes())?;
+    out.write_all(path.as_bytes())
+}
+
+/// A packed path as a relative path that stays inside the target
+fn safe_relative(path: &str) -> Option<&Path> {
+    let normal = path
+        .split('/')
+        .all(|part| !part.is_empty() && part != "." && part != ".." && !part.contains(['\\', ':']));
+    normal.then(|| Path::new(path))
+}
+
+fn unpack(input: &mut impl Read, dst: &Path) -> anyhow::Result<u64> {
+    let mut total = 0;
+    loop {
+        let mut tag = [0u8; 1];
+        input.read_exact(&mut tag)?;
+        if tag[0] == ENTRY_END {
+            return Ok(total);
+        }
+        let mut len = [0u8; 2];
+        input.read_exact(&mut len)?;
+        let mut path = vec![0u8; u16::from_be_bytes(len) as usize];
+        input.read_exact(&mut path)?;
+        let path = String::from_utf8(path)?;
+        let relative =
+            safe_relative(&path).ok_or_else(|| anyhow::anyhow!("Unsafe path in backup: {}", path))?;
+        let target = dst.join(relative);
+        match tag[0] {
+            ENTRY_DIR => std::fs::create_dir_all(&target)?,
+            ENTRY_FILE => {
+                let mut size = [0u8; 8];
+                input.read_exact(&mut size)?;
+                let size = u64::from_be_bytes(size);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut file = File::create(&target)?;
+                let copied = io::copy(&mut (&mut *input).take(size), &mut file)?;
+                if copied != size {
+                    anyhow::bail!("Backup is truncated");
+                }
+                total += size;
+            }
+            other => anyhow::bail!("Unknown entry {} in backup", other),
+        }
+    }
+}
+
+/// Whether `path` starts like an encrypted backup
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut magic = [0u8; 8];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && &magic == MAGIC
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    /// Cheap enough for debug builds
+    const TEST_PARAMS: KdfParams = KdfParams {
+        m_cost_kib: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn test_round_trip_and_wrong_passphrase() {
+        let root = TempDir::new("crypto-round-trip");
+        let src = root.join("db");
+        std::fs::create_dir_all(src.join("nested/empty")).unwrap();
+        std::fs::write(src.join("small.txt"), "記録").unwrap();
+        // Spans several chunks and ends exactly on a chunk boundary
+        let large: Vec<u8> = (0..CHUNK_SIZE * 3).map(|i| (i % 251) as u8).collect();
+        std::fs::write(src.join("nested/large.bin"), &large).unwrap();
+
+        let file = root.join(ENCRYPTED_FILE);
+        let packed = encrypt_dir(&src, &file, "correct horse", TEST_PARAMS).unwrap();
+        assert_eq!(packed, large.len() as u64 + "記録".len() as u64);
+        assert!(is_encrypted_file(&file));
+        let (header, _) = read_header_from(&mut File::open(&file).unwrap()).unwrap();
+        assert_eq!(header.m_cost_kib, 64);
+
+        let wrong = root.join("wrong");
+        let error = decrypt_dir(&file, &wrong, "battery staple").unwrap_err();
+        assert_eq!(error.to_string(), WRONG_PASSPHRASE);
+        assert!(!wrong.exists());
+        assert!(verify_passphrase(&file, "battery staple").is_err());
+        verify_passphrase(&file, "correct horse").unwrap();
+
+        let out = root.join("restored");
+        assert_eq!(decrypt_dir(&file, &out, "correct horse").unwrap(), packed);
+        assert_eq!(std::fs::read_to_string(out.join("small.txt")).unwrap(), "記録");
+        assert_eq!(std::fs::read(out.join("nested/large.bin")).unwrap(), large);
+        assert!(out.join("nested/empty").is_dir());
+    }
+
+    #[test]
+    fn test_truncated_backup_fails() {
+        let root = TempDir::new("crypto-truncated");
+        let src = root.join("db");
+        std::fs::create_dir_all(&src).unwrap();
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2).map(|i| (i % 7) as u8).collect();
+        std::fs::write(src.join("data.bin"), &data).unwrap();
+        let file = root.join(ENCRYPTED_FILE);
+        encrypt_dir(&src, &file, "pass", TEST_PARAMS).unwrap();
+
+        let bytes = std::fs::read(&file).unwrap();
+        std::fs::write(&file, &bytes[..bytes.len() - TAG_SIZE - 10]).unwrap();
+        let error = decrypt_dir(&file, &root.join("out"), "pass").unwrap_err();
+        assert_eq!(error.to_string(), "Backup is corrupted");
+    }
+
+    #[test]
+    fn test_safe_relative() {
+        assert!(safe_relative("a/b.txt").is_some());
+        assert!(safe_relative("../escape").is_none());
+        assert!(safe_relative("/etc/passwd").is_none());
+        assert!(safe_relative("a/./b").is_none());
+        assert!(safe_relative("").is_none());
+    }
+}
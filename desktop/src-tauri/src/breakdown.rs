@@ -0,0 +1,73 @@
+// Kiroku Memory Desktop - Category Breakdown
+// Per-category memory counts from /v2/stats, shared by the tray submenu and the dashboard
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: u64,
+}
+
+/// Active memories per category, largest first
+#[derive(Clone, Debug, Default, Serialize, PartialEq, Eq)]
+pub struct StatsBreakdown {
+    pub total: u64,
+    pub categories: Vec<CategoryCount>,
+}
+
+impl StatsBreakdown {
+    pub fn from_counts(counts: HashMap<String, u64>) -> Self {
+        let mut categories: Vec<CategoryCount> = counts
+            .into_iter()
+            .map(|(category, count)| CategoryCount { category, count })
+            .collect();
+        // Ties by name so the order (and the tray menu) is stable between polls
+        categories.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.category.cmp(&b.category)));
+        Self {
+            total: categories.iter().map(|c| c.count).sum(),
+            categories,
+        }
+    }
+
+    /// The `limit` largest categories, and the summed count of the rest if any
+    pub fn top(&self, limit: usize) -> (&[CategoryCount], Option<u64>) {
+        if self.categories.len() <= limit {
+            return (&self.categories, None);
+        }
+        let (top, rest) = self.categories.split_at(limit);
+        (top, Some(rest.iter().map(|c| c.count).sum()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakdown(counts: &[(&str, u64)]) -> StatsBreakdown {
+        StatsBreakdown::from_counts(
+            counts
+                .iter()
+                .map(|(name, count)| (name.to_string(), *count))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_sorted_by_count_then_name() {
+        let b = breakdown(&[("facts", 3), ("events", 10), ("preferences", 3)]);
+        let names: Vec<&str> = b.categories.iter().map(|c| c.category.as_str()).collect();
+        assert_eq!(names, vec!["events", "facts", "preferences"]);
+        assert_eq!(b.total, 16);
+    }
+
+    #[test]
+    fn test_top_rolls_up_the_rest() {
+        let b = breakdown(&[("a", 5), ("b", 4), ("c", 3), ("d", 2)]);
+        let (top, other) = b.top(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(other, Some(5));
+        assert_eq!(b.top(4).1, None);
+    }
+}
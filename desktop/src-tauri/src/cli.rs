@@ -0,0 +1,200 @@
+// Kiroku Memory Desktop - Command-Line Arguments
+// Session-only overrides parsed at startup; never written back to settings.json
+
+use crate::config::AppSettings;
+use crate::profiles;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Flags that start the app in the tray (see `start_visibility`)
+pub const HIDDEN_FLAGS: &[&str] = &["--tray", "--hidden"];
+
+/// Same as `--headless`
+pub const HEADLESS_ENV: &str = "KIROKU_HEADLESS";
+
+/// Values from `--port`, `--data-dir`, `--profile`, and `--dev-reload`, applied on top of the
+/// saved settings for this run only
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StartupOverrides {
+    pub port: Option<u16>,
+    pub data_dir: Option<PathBuf>,
+    pub profile: Option<String>,
+    /// `--tray` or `--hidden`, as given
+    pub hidden_flag: Option<String>,
+    /// `--dev-reload`: run uvicorn with `--reload` (debug builds only)
+    pub dev_reload: bool,
+    /// `--headless` or HEADLESS_ENV: never create the main window
+    pub headless: bool,
+    /// `--no-tray`: in headless mode, skip the tray icon too
+    pub no_tray: bool,
+    /// Unknown flags and bad values; logged at startup instead of aborting
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+impl StartupOverrides {
+    /// Overrides for this process: its arguments plus HEADLESS_ENV
+    pub fn from_process() -> Self {
+        let mut overrides = Self::parse(std::env::args().skip(1));
+        if std::env::var_os(HEADLESS_ENV).is_some() {
+            overrides.headless = true;
+        }
+        if overrides.no_tray && !overrides.headless {
+            overrides
+                .warnings
+                .push("--no-tray only applies together with --headless".to_string());
+        }
+        overrides
+    }
+
+    /// Headless without a tray: the app can only be stopped with a signal
+    pub fn without_tray(&self) -> bool {
+        self.headless && self.no_tray
+    }
+
+    /// Parse arguments (without the program name). Accepts `--flag value` and
+    /// `--flag=value`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut overrides = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            match flag.as_str() {
+                "--port" | "--data-dir" | "--profile" => {
+                    let Some(value) = inline.or_else(|| args.next()) else {
+                        overrides.warnings.push(format!("{} needs a value", flag));
+                        continue;
+                    };
+                    if let Err(e) = overrides.set(&flag, &value) {
+                        overrides.warnings.push(format!("ignoring {} {}: {}", flag, value, e));
+                    }
+                }
+                "--dev-reload" => overrides.dev_reload = true,
+                "--headless" => overrides.headless = true,
+                "--no-tray" => overrides.no_tray = true,
+                _ if HIDDEN_FLAGS.contains(&flag.as_str()) => {
+                    overrides.hidden_flag = Some(flag);
+                }
+                _ => overrides.warnings.push(format!("unknown argument {}", arg)),
+            }
+        }
+        overrides
+    }
+
+    fn set(&mut self, flag: &str, value: &str) -> Result<(), String> {
+        match flag {
+            "--port" => match value.parse::<u16>() {
+                Ok(port) if port > 0 => self.port = Some(port),
+                _ => return Err("expected a port between 1 and 65535".to_string()),
+            },
+            "--data-dir" => {
+                let dir = PathBuf::from(value);
+                let dir = if dir.is_absolute() {
+                    dir
+                } else {
+                    std::env::current_dir().map_err(|e| e.to_string())?.join(dir)
+                };
+                self.data_dir = Some(dir);
+            }
+            "--profile" => {
+                profiles::validate_name(value)?;
+                self.profile = Some(value.to_string());
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Overlay the overrides on loaded settings. The result must never be saved.
+    pub fn apply(&self, settings: &mut AppSettings) {
+        if let Some(port) = self.port {
+            settings.service_port = port;
+        }
+        if let Some(dir) = &self.data_dir {
+            settings.data_dir_override = Some(dir.to_string_lossy().into_owned());
+        }
+        if let Some(profile) = &self.profile {
+            settings.active_profile = profile.clone();
+        }
+        if self.dev_reload {
+            settings.service_options.dev_reload = true;
+        }
+    }
+
+    /// Active overrides in command-line form, for logs and launch info
+    pub fn describe(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(port) = self.port {
+            out.push(format!("--port {}", port));
+        }
+        if let Some(dir) = &self.data_dir {
+            out.push(format!("--data-dir {}", dir.display()));
+        }
+        if let Some(profile) = &self.profile {
+            out.push(format!("--profile {}", profile));
+        }
+        if self.dev_reload {
+            out.push("--dev-reload".to_string());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> StartupOverrides {
+        StartupOverrides::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        let o = parse(&[
+            "--port",
+            "8123",
+            "--data-dir=/tmp/kiroku-test",
+            "--profile",
+            "work",
+            "--tray",
+            "--dev-reload",
+            "--headless",
+        ]);
+        assert_eq!(o.port, Some(8123));
+        assert_eq!(o.data_dir, Some(PathBuf::from("/tmp/kiroku-test")));
+        assert_eq!(o.profile.as_deref(), Some("work"));
+        assert_eq!(o.hidden_flag.as_deref(), Some("--tray"));
+        assert!(o.headless && !o.without_tray());
+        assert!(o.warnings.is_empty());
+        assert_eq!(
+            o.describe(),
+            vec![
+                "--port 8123",
+                "--data-dir /tmp/kiroku-test",
+                "--profile work",
+                "--dev-reload"
+            ]
+        );
+
+        let mut settings = AppSettings::default();
+        o.apply(&mut settings);
+        assert_eq!(settings.base_url(), "http://127.0.0.1:8123");
+        assert_eq!(settings.active_profile, "work");
+        assert!(settings.service_options.dev_reload);
+    }
+
+    #[test]
+    fn test_bad_and_unknown_flags_warn() {
+        let o = parse(&["--port", "0", "--verbose", "--profile", "../x", "--data-dir"]);
+        assert_eq!(o.port, None);
+        assert_eq!(o.profile, None);
+        assert_eq!(o.data_dir, None);
+        assert_eq!(o.warnings.len(), 4);
+        assert!(o.warnings.iter().any(|w| w.contains("unknown argument --verbose")));
+    }
+}
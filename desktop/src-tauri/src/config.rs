@@ -1,30 +1,429 @@
 // Kiroku Memory Desktop - Configuration Management
 // Handles secure credential storage using macOS Keychain
 
+use crate::cli::StartupOverrides;
+use crate::locale;
+use crate::profiles::{self, DEFAULT_PROFILE};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 /// Configuration keys
 pub mod keys {
     pub const OPENAI_API_KEY: &str = "openai_api_key";
+    /// Bearer token shared with the service when `require_auth` is on
+    pub const SERVICE_API_TOKEN: &str = "service_api_token";
+    /// Salted hash of the PIN that unlocks locked mode
+    pub const LOCK_PIN: &str = "lock_pin";
+    /// Backup passphrase, only when the user chose to remember it
+    pub const BACKUP_PASSPHRASE: &str = "backup_passphrase";
+
+    /// Every key the app may store, for cleanup
+    pub const ALL: &[&str] = &[OPENAI_API_KEY, SERVICE_API_TOKEN, LOCK_PIN, BACKUP_PASSPHRASE];
+}
+
+/// Where the Python service runs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackendMode {
+    /// Spawn and manage the bundled service locally
+    #[default]
+    Local,
+    /// Connect to an already running service at `remote_url`
+    Remote,
+}
+
+/// How the app reaches a local service
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transport {
+    #[default]
+    Tcp,
+    /// A socket file in the app data dir: no port to conflict, nothing on the
+    /// network. Unix only; Windows keeps using TCP.
+    UnixSocket,
+}
+
+/// Host in request URLs over a Unix socket, where it only fills the Host header
+pub const UNIX_SOCKET_BASE_URL: &str = "http://localhost";
+
+/// What to do while running on battery (macOS)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatterySaver {
+    #[default]
+    Off,
+    /// Poll status, health, and stats 5x less often
+    ReducePolling,
+    /// Stop the service until AC power returns
+    StopService,
+}
+
+/// What a left click on the tray icon does (macOS and Windows; Linux always
+/// opens the menu). Right click always opens the menu.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrayClickAction {
+    #[default]
+    OpenMenu,
+    ToggleWindow,
+    /// Show the window and emit `quick-capture` so it opens the capture view
+    QuickCapture,
+}
+
+/// When the service process is started
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ServiceStartMode {
+    #[default]
+    OnAppLaunch,
+    /// Idle until the main window is shown or a capture or search is made
+    OnFirstUse,
+    /// Only from the Start button or tray item
+    Manual,
+}
+
+/// Whether data from an earlier build's identifier was migrated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegacyMigration {
+    /// Not offered yet
+    #[default]
+    Pending,
+    Completed,
+    Declined,
+}
+
+/// How the tray icon is drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrayIconStyle {
+    /// Monochrome matched to the system theme; the colored glyph when the
+    /// theme can't be detected
+    #[default]
+    Auto,
+    /// The colored glyph
+    Color,
+    /// Always monochrome; the dark glyph when the theme can't be detected
+    Monochrome,
+}
+
+/// Storage backends the Python service supports (its `BACKEND` env var)
+pub const BACKENDS: &[&str] = &["surrealdb", "postgres"];
+
+/// Backend that keeps its data in the app data dir
+pub const DEFAULT_BACKEND: &str = "surrealdb";
+
+/// Loopback only: the service has no authentication
+pub const DEFAULT_BIND_HOST: &str = "127.0.0.1";
+
+/// Upper bound for uvicorn worker processes
+pub const MAX_WORKERS: u8 = 4;
+
+/// Environment variables the app sets itself; `extra_env` may not override them
+pub const RESERVED_ENV: &[&str] = &[
+    "PYTHONPATH",
+    "PYTHONUNBUFFERED",
+    "BACKEND",
+    "SURREAL_URL",
+    "SURREAL_NAMESPACE",
+    "SURREAL_DATABASE",
+    "OPENAI_API_KEY",
+    "OPENAI_BASE_URL",
+    "EMBEDDING_MODEL",
+    "CHAT_MODEL",
+    "KIROKU_API_TOKEN",
+    "KIROKU_SECRETS_FILE",
+    "USAGE_FILE",
+];
+
+/// uvicorn `--log-level`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UvicornLogLevel {
+    Critical,
+    Error,
+    #[default]
+    Warning,
+    Info,
+    Debug,
+    Trace,
 }
 
+impl UvicornLogLevel {
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+}
+
+/// Verbosity of app.log; `Trace` also logs every window event unfiltered
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+/// How the Python service process is launched. Changes apply on the next restart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceOptions {
+    /// uvicorn worker processes, 1..=MAX_WORKERS; capped at the CPU count on launch
+    pub workers: u8,
+    pub uvicorn_log_level: UvicornLogLevel,
+    /// `--reload`; only honored in debug builds running a development (not
+    /// bundled) runtime. Also enabled by `--dev-reload` or KIROKU_DEV_RELOAD.
+    pub dev_reload: bool,
+    /// Additional environment for the service; keys in RESERVED_ENV are rejected
+    pub extra_env: HashMap<String, String>,
+}
+
+impl Default for ServiceOptions {
+    fn default() -> Self {
+        Self {
+            workers: 1,
+            uvicorn_log_level: UvicornLogLevel::Warning,
+            dev_reload: false,
+            extra_env: HashMap::new(),
+        }
+    }
+}
+
+impl ServiceOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_WORKERS).contains(&self.workers) {
+            return Err(format!("Workers must be between 1 and {}", MAX_WORKERS));
+        }
+        for key in self.extra_env.keys() {
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("Invalid environment variable name '{}'", key));
+            }
+            if RESERVED_ENV.contains(&key.as_str()) {
+                return Err(format!("{} is set by the app and cannot be overridden", key));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which GitHub releases count as updates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    /// Include prereleases
+    Beta,
+}
+
+/// First-run setup wizard progress
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnboardingState {
+    pub completed: bool,
+    /// App version the wizard was completed with
+    pub completed_version: String,
+    pub steps_done: Vec<String>,
+}
+
+/// Settings passed to the service process at spawn. Saving a change to one of
+/// these needs a service restart; every other setting applies immediately
+/// (`service_port`, `backend_mode`, `remote_url` and `embedding_model` restart the
+/// service themselves).
+pub const RESTART_REQUIRED: &[&str] = &[
+    "bind_host",
+    "require_auth",
+    "data_dir_override",
+    "backend",
+    "surreal_namespace",
+    "surreal_database",
+    "service_options",
+    "openai_base_url",
+    "chat_model",
+    "secrets_via_env",
+];
+
+/// OpenAI chat models `chat_model` is expected to name; others are allowed
+/// with a warning
+pub const KNOWN_CHAT_MODELS: &[&str] = &[
+    "gpt-4o-mini",
+    "gpt-4o",
+    "gpt-4.1",
+    "gpt-4.1-mini",
+    "gpt-4.1-nano",
+    "o4-mini",
+];
+
+/// Bounds of `stats_interval_secs`
+pub const STATS_INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 5..=3600;
+
+/// Bounds of `api_timeout_secs`; maintenance calls may use the maximum regardless
+pub const API_TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 1..=300;
+
+/// Bounds of `api_max_in_flight`
+pub const API_MAX_IN_FLIGHT_RANGE: std::ops::RangeInclusive<usize> = 1..=64;
+
 /// Application settings (non-sensitive, stored in app data)
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AppSettings {
-    pub auto_start_service: bool,
+    /// Replaces `auto_start_service` (false becomes `Manual`)
+    pub service_start_mode: ServiceStartMode,
+    /// Restart the service when it crashes or stops answering health checks
+    pub auto_restart: bool,
     pub service_port: u16,
+    /// Address uvicorn binds to. Anything but loopback exposes the service,
+    /// which has no authentication, to the network.
+    pub bind_host: String,
+    /// Require a bearer token (generated on first use, kept in the keychain)
+    /// on every request to the service
+    pub require_auth: bool,
     pub start_hidden: bool,
     pub launch_at_login: bool,
+    pub backend_mode: BackendMode,
+    pub remote_url: String,
+    /// Path prefix of a remote service behind a reverse proxy (e.g. "/kiroku");
+    /// empty serves from the root. Not used for the local service.
+    pub api_prefix: String,
+    pub active_profile: String,
+    pub data_dir_override: Option<String>,
+    /// Restart the service when its resident memory stays above this many MB
+    pub memory_limit_mb: Option<u64>,
+    /// Notify when the memory count rises to or falls below this value
+    pub memory_count_alert: Option<u64>,
+    /// Show the number of resources waiting for extraction on the dock icon
+    /// (or the tray title while the dock is hidden)
+    pub show_pending_badge: bool,
+    /// macOS: show the memory count as the menu bar title next to the tray icon
+    pub show_count_in_menubar: bool,
+    /// macOS: keep the Dock icon and Cmd+Tab entry while the app is in the tray
+    pub show_in_dock: bool,
+    /// Keep the main window above other apps' windows
+    pub always_on_top: bool,
+    pub battery_saver: BatterySaver,
+    pub tray_left_click_action: TrayClickAction,
+    pub tray_icon_style: TrayIconStyle,
+    pub log_level: LogLevel,
+    /// Back up automatically every this many hours; off when unset
+    pub backup_interval_hours: Option<u64>,
+    /// Where backups are written; defaults to `backups` in the data dir
+    pub backup_dir: Option<String>,
+    /// Number of backups kept in the backup directory; 0 keeps all
+    pub backup_retention: u32,
+    /// Storage backend, one of `BACKENDS`. Takes effect on the next service
+    /// restart; existing data is not migrated between backends.
+    pub backend: String,
+    pub surreal_namespace: String,
+    pub surreal_database: String,
+    pub release_channel: ReleaseChannel,
+    /// Check GitHub for a newer release once a day
+    pub auto_check_updates: bool,
+    pub onboarding: OnboardingState,
+    /// UI language code, or "auto" to follow the OS
+    pub locale: String,
+    pub service_options: ServiceOptions,
+    /// Start the service even when no OpenAI key is stored, for backends
+    /// configured not to need one
+    pub allow_start_without_key: bool,
+    /// OpenAI-compatible endpoint (Azure, corporate gateway) passed to the
+    /// service as OPENAI_BASE_URL
+    pub openai_base_url: Option<String>,
+    /// Embedding model passed to the service as EMBEDDING_MODEL; empty for the
+    /// service's default. Changing it restarts the service.
+    pub embedding_model: Option<String>,
+    /// Chat model for extraction, classification and summaries, passed to the
+    /// service as CHAT_MODEL; empty for the service's default
+    pub chat_model: Option<String>,
+    /// Pass secrets to the service as environment variables instead of a
+    /// private file, for services older than `secrets_file::MIN_SERVICE_VERSION`
+    pub secrets_via_env: bool,
+    /// How often the tray refreshes the memory count and stats
+    pub stats_interval_secs: u64,
+    /// Native notifications (memory count alerts, restarts, backups)
+    pub notifications_enabled: bool,
+    /// Default timeout for requests to the service
+    pub api_timeout_secs: u64,
+    /// Concurrent requests to the service before further ones queue
+    pub api_max_in_flight: usize,
+    /// The service is not started with less free space on the data dir's
+    /// volume (0 disables the check)
+    pub min_free_disk_mb: u64,
+    /// Follow the service's change stream for live dashboard and tray updates
+    pub live_updates_enabled: bool,
+    /// Notify once when free space on the data dir's volume drops below this
+    /// while the service runs (0 disables the warning)
+    pub disk_space_warning_mb: u64,
+    /// Stop the service when the app quits; when off it keeps serving MCP
+    /// clients and the next launch adopts it. Read at each start, since a
+    /// service that may be left running writes its output to service.log.
+    pub stop_service_on_quit: bool,
+    /// TCP on `bind_host`:`service_port`, or a Unix socket (local service only)
+    pub transport: Transport,
+    /// Notify when the estimated OpenAI spend this month reaches 80% and 100%
+    /// of this; off when unset
+    pub monthly_budget_usd: Option<f64>,
+    /// Refuse settings changes and destructive commands until `unlock`; only
+    /// `lock` and `unlock` change it
+    pub locked: bool,
+    /// Set once `migrate_legacy_data` has run so the offer isn't repeated
+    pub legacy_migration: LegacyMigration,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
-            auto_start_service: true,
+            service_start_mode: ServiceStartMode::OnAppLaunch,
+            auto_restart: true,
             service_port: 8000,
+            bind_host: DEFAULT_BIND_HOST.to_string(),
+            require_auth: false,
             start_hidden: false,
             launch_at_login: false,
+            backend_mode: BackendMode::Local,
+            remote_url: String::new(),
+            api_prefix: String::new(),
+            active_profile: DEFAULT_PROFILE.to_string(),
+            data_dir_override: None,
+            memory_limit_mb: None,
+            memory_count_alert: None,
+            show_pending_badge: false,
+            show_count_in_menubar: false,
+            show_in_dock: false,
+            always_on_top: false,
+            battery_saver: BatterySaver::Off,
+            tray_left_click_action: TrayClickAction::OpenMenu,
+            tray_icon_style: TrayIconStyle::Auto,
+            log_level: LogLevel::Info,
+            backup_interval_hours: None,
+            backup_dir: None,
+            backup_retention: 7,
+            backend: DEFAULT_BACKEND.to_string(),
+            surreal_namespace: "kiroku".to_string(),
+            surreal_database: "memory".to_string(),
+            release_channel: ReleaseChannel::Stable,
+            auto_check_updates: true,
+            onboarding: OnboardingState::default(),
+            locale: locale::AUTO.to_string(),
+            service_options: ServiceOptions::default(),
+            allow_start_without_key: false,
+            openai_base_url: None,
+            embedding_model: None,
+            chat_model: None,
+            secrets_via_env: false,
+            stats_interval_secs: 30,
+            notifications_enabled: true,
+            api_timeout_secs: 10,
+            api_max_in_flight: 8,
+            min_free_disk_mb: 100,
+            live_updates_enabled: true,
+            disk_space_warning_mb: 1024,
+            stop_service_on_quit: true,
+            transport: Transport::Tcp,
+            monthly_budget_usd: None,
+            locked: false,
+            legacy_migration: LegacyMigration::Pending,
         }
     }
 }
@@ -33,11 +432,300 @@ impl AppSettings {
     pub fn default_settings() -> Self {
         Self::default()
     }
+
+    pub fn is_remote(&self) -> bool {
+        self.backend_mode == BackendMode::Remote
+    }
+
+    /// Fields in RESTART_REQUIRED that differ from `other`
+    pub fn restart_required_changes(&self, other: &AppSettings) -> Vec<&'static str> {
+        let (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) =
+            (serde_json::to_value(self), serde_json::to_value(other))
+        else {
+            return Vec::new();
+        };
+        RESTART_REQUIRED
+            .iter()
+            .copied()
+            .filter(|key| a.get(*key) != b.get(*key))
+            .collect()
+    }
+
+    /// Whether the local service listens on a Unix socket instead of TCP
+    pub fn uses_unix_socket(&self) -> bool {
+        cfg!(unix) && !self.is_remote() && self.transport == Transport::UnixSocket
+    }
+
+    /// Base URL of the service these settings point at
+    pub fn base_url(&self) -> String {
+        match self.backend_mode {
+            BackendMode::Local if self.uses_unix_socket() => UNIX_SOCKET_BASE_URL.to_string(),
+            BackendMode::Local => format!("http://{}:{}", self.local_host(), self.service_port),
+            BackendMode::Remote => self.remote_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Where the API is: `base_url` plus `api_prefix` for a remote service.
+    /// Every endpoint path is appended to this.
+    pub fn api_url(&self) -> String {
+        match self.backend_mode {
+            BackendMode::Remote => format!("{}{}", self.base_url(), self.api_prefix.trim_end_matches('/')),
+            BackendMode::Local => self.base_url(),
+        }
+    }
+
+    /// Host the app itself uses to reach a local service: loopback unless the
+    /// service is bound to one specific non-loopback address
+    fn local_host(&self) -> String {
+        match self.bind_host.parse::<IpAddr>() {
+            Ok(ip) if !ip.is_loopback() && !ip.is_unspecified() => match ip {
+                IpAddr::V4(v4) => v4.to_string(),
+                IpAddr::V6(v6) => format!("[{}]", v6),
+            },
+            _ => DEFAULT_BIND_HOST.to_string(),
+        }
+    }
+
+    /// Non-fatal problems worth showing the user, e.g. an exposed service
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.is_remote() {
+            return warnings;
+        }
+        // Custom endpoints (Azure deployments, gateways) name models their own way
+        if let Some(model) = self.chat_model() {
+            if self.openai_base_url().is_none() && !KNOWN_CHAT_MODELS.contains(&model) {
+                warnings.push(format!(
+                    "'{}' is not a known OpenAI chat model; memory extraction fails if OpenAI doesn't offer it",
+                    model
+                ));
+            }
+        }
+        if self.transport == Transport::UnixSocket && !self.uses_unix_socket() {
+            warnings.push(format!(
+                "Unix sockets are not supported on this platform; using TCP port {}",
+                self.service_port
+            ));
+        }
+        if self.uses_unix_socket() {
+            return warnings;
+        }
+        match self.bind_host.parse::<IpAddr>() {
+            Ok(ip) if ip.is_unspecified() && !self.require_auth => warnings.push(format!(
+                "The service is bound to {} and reachable from every network interface without authentication",
+                self.bind_host
+            )),
+            Ok(ip) if !ip.is_loopback() && !self.require_auth => warnings.push(format!(
+                "The service is bound to {} and reachable from the network without authentication",
+                self.bind_host
+            )),
+            _ => {}
+        }
+        warnings
+    }
+
+    pub fn uses_surrealdb(&self) -> bool {
+        self.backend == DEFAULT_BACKEND
+    }
+
+    /// Custom data directory, if one is configured
+    pub fn data_dir_override(&self) -> Option<PathBuf> {
+        self.data_dir_override
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Custom backup directory, if one is configured
+    pub fn backup_dir(&self) -> Option<PathBuf> {
+        self.backup_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Custom OpenAI endpoint, if one is configured
+    pub fn openai_base_url(&self) -> Option<&str> {
+        self.openai_base_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    /// Custom embedding model, if one is configured
+    pub fn embedding_model(&self) -> Option<&str> {
+        self.embedding_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+    }
+
+    /// Custom chat model, if one is configured
+    pub fn chat_model(&self) -> Option<&str> {
+        self.chat_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+    }
+
+    /// Validate settings before saving
+    pub fn validate(&self) -> Result<(), String> {
+        profiles::validate_name(&self.active_profile)?;
+        if self.service_port == 0 {
+            return Err("Service port must be between 1 and 65535".to_string());
+        }
+        if self.bind_host.parse::<IpAddr>().is_err() {
+            return Err(format!(
+                "Bind host '{}' must be an IP address such as 127.0.0.1 or 0.0.0.0",
+                self.bind_host
+            ));
+        }
+        self.service_options.validate()?;
+        if !locale::is_supported(&self.locale) {
+            return Err(format!("Unsupported locale '{}'", self.locale));
+        }
+        if !BACKENDS.contains(&self.backend.as_str()) {
+            return Err(format!(
+                "Unknown backend '{}' (expected one of: {})",
+                self.backend,
+                BACKENDS.join(", ")
+            ));
+        }
+        for (label, value) in [
+            ("namespace", &self.surreal_namespace),
+            ("database", &self.surreal_database),
+        ] {
+            if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!(
+                    "SurrealDB {} may only contain letters, digits and '_'",
+                    label
+                ));
+            }
+        }
+        if let Some(dir) = self.data_dir_override() {
+            if !dir.is_absolute() {
+                return Err("Data directory must be an absolute path".to_string());
+            }
+        }
+        if let Some(dir) = self.backup_dir() {
+            if !dir.is_absolute() {
+                return Err("Backup directory must be an absolute path".to_string());
+            }
+        }
+        if !STATS_INTERVAL_RANGE.contains(&self.stats_interval_secs) {
+            return Err(format!(
+                "Stats interval must be between {} and {} seconds",
+                STATS_INTERVAL_RANGE.start(),
+                STATS_INTERVAL_RANGE.end()
+            ));
+        }
+        if !API_TIMEOUT_RANGE.contains(&self.api_timeout_secs) {
+            return Err(format!(
+                "API timeout must be between {} and {} seconds",
+                API_TIMEOUT_RANGE.start(),
+                API_TIMEOUT_RANGE.end()
+            ));
+        }
+        if !API_MAX_IN_FLIGHT_RANGE.contains(&self.api_max_in_flight) {
+            return Err(format!(
+                "Concurrent API requests must be between {} and {}",
+                API_MAX_IN_FLIGHT_RANGE.start(),
+                API_MAX_IN_FLIGHT_RANGE.end()
+            ));
+        }
+        if self
+            .monthly_budget_usd
+            .is_some_and(|budget| !budget.is_finite() || budget <= 0.0)
+        {
+            return Err("Monthly budget must be more than $0".to_string());
+        }
+        if self.backup_interval_hours == Some(0) {
+            return Err("Backup interval must be at least 1 hour".to_string());
+        }
+        if let Some(url) = self.openai_base_url() {
+            let valid = reqwest::Url::parse(url).is_ok_and(|parsed| {
+                matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some()
+            });
+            if !valid {
+                return Err("OpenAI base URL must be an http:// or https:// URL".to_string());
+            }
+        }
+        if let Some(model) = self.embedding_model() {
+            if model.chars().any(|c| c.is_whitespace() || c.is_control()) {
+                return Err(format!("Embedding model '{}' must not contain spaces", model));
+            }
+        }
+        if let Some(model) = self.chat_model() {
+            if model.chars().any(|c| c.is_whitespace() || c.is_control()) {
+                return Err(format!("Chat model '{}' must not contain spaces", model));
+            }
+        }
+        if self.is_remote() {
+            let url = self.remote_url.trim();
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err("Remote URL must start with http:// or https://".to_string());
+            }
+        }
+        if !self.api_prefix.is_empty()
+            && (!self.api_prefix.starts_with('/')
+                || self.api_prefix.contains(['?', '#'])
+                || self.api_prefix.contains(char::is_whitespace))
+        {
+            return Err(format!(
+                "API prefix '{}' must be a path starting with / (e.g. /kiroku)",
+                self.api_prefix
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a secret can be read, as told to the UI by `get_key_status`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum KeychainStatus {
+    Present,
+    Absent,
+    /// The login keychain is locked
+    Locked,
+    /// The user denied (or cancelled) the access prompt
+    AccessDenied,
+    Error(String),
+}
+
+impl KeychainStatus {
+    /// Map a Security framework OSStatus from a failed read
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn from_os_status(code: i32, message: &str) -> Self {
+        match code {
+            // errSecItemNotFound
+            -25300 => KeychainStatus::Absent,
+            // errSecInteractionNotAllowed
+            -25308 => KeychainStatus::Locked,
+            // errSecAuthFailed ("Deny" in the access prompt), errSecUserCanceled
+            -25293 | -128 => KeychainStatus::AccessDenied,
+            _ => KeychainStatus::Error(format!("{} ({})", message, code)),
+        }
+    }
+}
+
+impl std::fmt::Display for KeychainStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeychainStatus::Present => write!(f, "stored"),
+            KeychainStatus::Absent => write!(f, "not stored"),
+            KeychainStatus::Locked => write!(f, "the Keychain is locked"),
+            KeychainStatus::AccessDenied => write!(f, "access to the Keychain item was denied"),
+            KeychainStatus::Error(message) => write!(f, "{}", message),
+        }
+    }
 }
 
 /// Keychain operations for macOS
 #[cfg(target_os = "macos")]
 pub mod keychain {
+    use super::KeychainStatus;
     use security_framework::passwords::{delete_generic_password, get_generic_password, set_generic_password};
 
     /// Service name for Keychain storage
@@ -56,20 +744,26 @@ pub mod keychain {
 
     /// Retrieve a secret from macOS Keychain
     pub fn get_secret(key: &str) -> anyhow::Result<Option<String>> {
-        match get_generic_password(KEYCHAIN_SERVICE, key) {
+        read_secret(KEYCHAIN_SERVICE, key)
+    }
+
+    /// Retrieve a secret stored under an earlier build's service name
+    pub fn get_legacy_secret(service: &str, key: &str) -> anyhow::Result<Option<String>> {
+        read_secret(service, key)
+    }
+
+    fn read_secret(service: &str, key: &str) -> anyhow::Result<Option<String>> {
+        match get_generic_password(service, key) {
             Ok(data) => {
                 let value = String::from_utf8(data.to_vec())
                     .map_err(|e| anyhow::anyhow!("Invalid UTF-8 in Keychain data: {}", e))?;
                 Ok(Some(value))
             }
-            Err(e) => {
+            Err(e) => match KeychainStatus::from_os_status(e.code(), &e.to_string()) {
                 // Item not found is not an error
-                if e.code() == -25300 {
-                    Ok(None)
-                } else {
-                    Err(anyhow::anyhow!("Failed to read from Keychain: {}", e))
-                }
-            }
+                KeychainStatus::Absent => Ok(None),
+                status => Err(anyhow::anyhow!("Failed to read from Keychain: {}", status)),
+            },
         }
     }
 
@@ -88,15 +782,32 @@ pub mod keychain {
         }
     }
 
+    /// Whether a secret can be read, and why not (without revealing the value)
+    pub fn secret_status(key: &str) -> KeychainStatus {
+        lookup(key).map_or_else(|status| status, |_| KeychainStatus::Present)
+    }
+
+    /// Read a secret, or say why it can't be read. Each read may raise its own
+    /// access prompt, so callers needing both use this once.
+    pub fn lookup(key: &str) -> Result<String, KeychainStatus> {
+        match get_generic_password(KEYCHAIN_SERVICE, key) {
+            Ok(data) => String::from_utf8(data.to_vec()).map_err(|e| {
+                KeychainStatus::Error(format!("Invalid UTF-8 in Keychain data: {}", e))
+            }),
+            Err(e) => Err(KeychainStatus::from_os_status(e.code(), &e.to_string())),
+        }
+    }
+
     /// Check if a secret exists in Keychain (without revealing the value)
     pub fn has_secret(key: &str) -> bool {
-        get_generic_password(KEYCHAIN_SERVICE, key).is_ok()
+        secret_status(key) == KeychainStatus::Present
     }
 }
 
 /// Fallback for non-macOS platforms (stores in memory only - NOT secure)
 #[cfg(not(target_os = "macos"))]
 pub mod keychain {
+    use super::KeychainStatus;
     use std::collections::HashMap;
     use std::sync::Mutex;
     use once_cell::sync::Lazy;
@@ -114,12 +825,26 @@ pub mod keychain {
         Ok(secrets.get(key).cloned())
     }
 
+    /// Earlier builds kept nothing that outlives the process here
+    pub fn get_legacy_secret(_service: &str, _key: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
     pub fn delete_secret(key: &str) -> anyhow::Result<()> {
         let mut secrets = SECRETS.lock().unwrap();
         secrets.remove(key);
         Ok(())
     }
 
+    pub fn secret_status(key: &str) -> KeychainStatus {
+        lookup(key).map_or_else(|status| status, |_| KeychainStatus::Present)
+    }
+
+    pub fn lookup(key: &str) -> Result<String, KeychainStatus> {
+        let secrets = SECRETS.lock().unwrap();
+        secrets.get(key).cloned().ok_or(KeychainStatus::Absent)
+    }
+
     pub fn has_secret(key: &str) -> bool {
         let secrets = SECRETS.lock().unwrap();
         secrets.contains_key(key)
@@ -131,13 +856,36 @@ pub mod settings {
     use super::*;
     use std::path::PathBuf;
     use tauri::{AppHandle, Manager};
+    use tokio::sync::watch;
 
-    fn settings_path(app: &AppHandle) -> anyhow::Result<PathBuf> {
+    /// The saved settings, shared with running loops so changes apply without
+    /// a relaunch. `save` publishes every change.
+    pub struct LiveSettings(watch::Sender<AppSettings>);
+
+    impl LiveSettings {
+        pub fn new(settings: AppSettings) -> Self {
+            Self(watch::channel(settings).0)
+        }
+
+        pub fn subscribe(&self) -> watch::Receiver<AppSettings> {
+            self.0.subscribe()
+        }
+    }
+
+    /// Saved settings without touching the disk; `load` until LiveSettings is managed
+    pub fn current(app: &AppHandle) -> AppSettings {
+        match app.try_state::<LiveSettings>() {
+            Some(live) => live.0.borrow().clone(),
+            None => load(app).unwrap_or_default(),
+        }
+    }
+
+    pub fn settings_path(app: &AppHandle) -> anyhow::Result<PathBuf> {
         let data_dir = app
             .path()
             .app_data_dir()
             .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?;
-        std::fs::create_dir_all(&data_dir)?;
+        crate::permissions::ensure_private_dir(&data_dir)?;
         Ok(data_dir.join("settings.json"))
     }
 
@@ -146,25 +894,290 @@ pub mod settings {
         let path = settings_path(app)?;
         if path.exists() {
             let content = std::fs::read_to_string(&path)?;
-            let settings: AppSettings = serde_json::from_str(&content)?;
-            Ok(settings)
+            parse(serde_json::from_str(&content)?)
         } else {
             Ok(AppSettings::default_settings())
         }
     }
 
-    /// Save settings to file
+    /// Settings from a saved file, upgraded from older versions
+    pub fn parse(value: serde_json::Value) -> anyhow::Result<AppSettings> {
+        let predates_onboarding = value.get("onboarding").is_none();
+        let auto_start_off = value.get("service_start_mode").is_none()
+            && value.get("auto_start_service") == Some(&serde_json::Value::Bool(false));
+        let mut settings: AppSettings = serde_json::from_value(value)?;
+        // Installs that were set up before the wizard existed skip it
+        if predates_onboarding {
+            settings.onboarding.completed = true;
+        }
+        if auto_start_off {
+            settings.service_start_mode = ServiceStartMode::Manual;
+        }
+        Ok(settings)
+    }
+
+    /// Settings for this run: the saved file plus any command-line overrides.
+    /// Never pass the result to `save`.
+    pub fn load_effective(app: &AppHandle) -> anyhow::Result<AppSettings> {
+        let mut settings = load(app)?;
+        if let Some(overrides) = app.try_state::<StartupOverrides>() {
+            overrides.apply(&mut settings);
+        }
+        Ok(settings)
+    }
+
+    /// Save settings to file. Written to a temporary file first, so a crash
+    /// or full disk never leaves a half-written settings.json.
     pub fn save(app: &AppHandle, settings: &AppSettings) -> anyhow::Result<()> {
         let path = settings_path(app)?;
         let content = serde_json::to_string_pretty(settings)?;
-        std::fs::write(&path, content)?;
+        crate::settings_watch::record_save();
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, &path)?;
+        publish(app, settings);
         Ok(())
     }
+
+    /// Share settings already on disk (edited outside the app) with running loops
+    pub fn publish(app: &AppHandle, settings: &AppSettings) {
+        if let Some(live) = app.try_state::<LiveSettings>() {
+            live.0.send_replace(settings.clone());
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::DEFAULT_BASE_URL;
+
+    #[test]
+    fn test_remote_settings() {
+        let mut s = AppSettings::default();
+        assert_eq!(s.base_url(), DEFAULT_BASE_URL);
+        assert!(s.validate().is_ok());
+
+        s.backend_mode = BackendMode::Remote;
+        assert!(s.validate().is_err());
+
+        s.remote_url = "http://homeserver:8000/".to_string();
+        assert!(s.validate().is_ok());
+        assert_eq!(s.base_url(), "http://homeserver:8000");
+        assert_eq!(s.api_url(), "http://homeserver:8000");
+    }
+
+    #[test]
+    fn test_api_prefix() {
+        let mut s = AppSettings {
+            api_prefix: "/kiroku/".to_string(),
+            ..Default::default()
+        };
+        assert!(s.validate().is_ok());
+        // The local service always serves from the root
+        assert_eq!(s.api_url(), DEFAULT_BASE_URL);
+
+        s.backend_mode = BackendMode::Remote;
+        s.remote_url = "https://example.com/".to_string();
+        assert_eq!(s.api_url(), "https://example.com/kiroku");
+
+        for bad in ["kiroku", "/kiroku?x=1", "/my prefix"] {
+            s.api_prefix = bad.to_string();
+            assert!(s.validate().is_err(), "{} was accepted", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_migrates_auto_start() {
+        let parse = |json: &str| settings::parse(serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(
+            parse(r#"{"auto_start_service": false}"#).service_start_mode,
+            ServiceStartMode::Manual
+        );
+        assert_eq!(
+            parse(r#"{"auto_start_service": true}"#).service_start_mode,
+            ServiceStartMode::OnAppLaunch
+        );
+        assert_eq!(
+            parse(r#"{"auto_start_service": false, "service_start_mode": "OnFirstUse"}"#)
+                .service_start_mode,
+            ServiceStartMode::OnFirstUse
+        );
+        assert!(parse("{}").onboarding.completed);
+    }
+
+    #[test]
+    fn test_keychain_status_from_os_status() {
+        assert_eq!(KeychainStatus::from_os_status(-25300, "not found"), KeychainStatus::Absent);
+        assert_eq!(KeychainStatus::from_os_status(-25293, "auth failed"), KeychainStatus::AccessDenied);
+        assert_eq!(KeychainStatus::from_os_status(-25308, "no UI"), KeychainStatus::Locked);
+        assert_eq!(KeychainStatus::from_os_status(-128, "canceled"), KeychainStatus::AccessDenied);
+        assert_eq!(
+            KeychainStatus::from_os_status(-25291, "not available"),
+            KeychainStatus::Error("not available (-25291)".to_string())
+        );
+        assert_eq!(KeychainStatus::Locked.to_string(), "the Keychain is locked");
+    }
+
+    #[test]
+    fn test_restart_required_changes() {
+        let a = AppSettings::default();
+        let mut b = a.clone();
+        b.log_level = LogLevel::Debug;
+        b.stats_interval_secs = 60;
+        b.api_timeout_secs = 30;
+        b.api_max_in_flight = 4;
+        assert!(a.restart_required_changes(&b).is_empty());
+
+        b.bind_host = "0.0.0.0".to_string();
+        b.service_options.workers = 2;
+        assert_eq!(a.restart_required_changes(&b), vec!["bind_host", "service_options"]);
+        let fields = serde_json::to_value(&a).unwrap();
+        for key in RESTART_REQUIRED {
+            assert!(fields.get(key).is_some(), "{} is not a setting", key);
+        }
+
+        b.stats_interval_secs = 1;
+        assert!(b.validate().is_err());
+        b.stats_interval_secs = 60;
+        b.api_max_in_flight = 0;
+        assert!(b.validate().is_err());
+    }
+
+    #[test]
+    fn test_bind_host() {
+        let mut s = AppSettings::default();
+        assert!(s.warnings().is_empty());
+
+        s.bind_host = "0.0.0.0".to_string();
+        assert!(s.validate().is_ok());
+        assert_eq!(s.warnings().len(), 1);
+        assert_eq!(s.base_url(), "http://127.0.0.1:8000");
+
+        s.require_auth = true;
+        assert!(s.warnings().is_empty());
+        s.require_auth = false;
+
+        s.bind_host = "192.168.1.5".to_string();
+        assert_eq!(s.base_url(), "http://192.168.1.5:8000");
+
+        s.bind_host = "::1".to_string();
+        assert!(s.warnings().is_empty());
+
+        s.bind_host = "my-laptop".to_string();
+        assert!(s.validate().is_err());
+    }
+
+    #[test]
+    fn test_unix_socket_transport() {
+        let mut s = AppSettings {
+            transport: Transport::UnixSocket,
+            bind_host: "0.0.0.0".to_string(),
+            ..Default::default()
+        };
+        if cfg!(unix) {
+            assert!(s.uses_unix_socket());
+            assert_eq!(s.base_url(), UNIX_SOCKET_BASE_URL);
+            // Nothing is exposed, whatever the bind host
+            assert!(s.warnings().is_empty());
+        } else {
+            assert!(!s.uses_unix_socket());
+            assert_eq!(s.base_url(), "http://127.0.0.1:8000");
+            assert_eq!(s.warnings().len(), 2);
+        }
+
+        s.backend_mode = BackendMode::Remote;
+        s.remote_url = "http://homeserver:8000".to_string();
+        assert!(!s.uses_unix_socket());
+        assert_eq!(s.base_url(), "http://homeserver:8000");
+    }
+
+    #[test]
+    fn test_service_options_validation() {
+        let mut s = AppSettings::default();
+        s.service_options.workers = 5;
+        assert!(s.validate().is_err());
+
+        s.service_options.workers = 2;
+        s.service_options
+            .extra_env
+            .insert("HF_HOME".to_string(), "/tmp/hf".to_string());
+        assert!(s.validate().is_ok());
+
+        s.service_options
+            .extra_env
+            .insert("SURREAL_URL".to_string(), "memory".to_string());
+        assert!(s.validate().is_err());
+    }
+
+    #[test]
+    fn test_backend_validation() {
+        let mut s = AppSettings::default();
+        assert!(s.uses_surrealdb());
+
+        s.backend = "postgres".to_string();
+        assert!(s.validate().is_ok());
+        assert!(!s.uses_surrealdb());
+
+        s.backend = "sqlite".to_string();
+        assert!(s.validate().is_err());
+    }
+
+    #[test]
+    fn test_openai_base_url_validation() {
+        let mut s = AppSettings {
+            openai_base_url: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(s.validate().is_ok());
+        assert_eq!(s.openai_base_url(), None);
+
+        s.openai_base_url = Some("https://example.openai.azure.com/openai/v1".to_string());
+        assert!(s.validate().is_ok());
+
+        for bad in ["example.com/v1", "ftp://example.com", "https://"] {
+            s.openai_base_url = Some(bad.to_string());
+            assert!(s.validate().is_err(), "accepted {}", bad);
+        }
+    }
+
+    #[test]
+    fn test_embedding_model() {
+        let mut s = AppSettings {
+            embedding_model: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert!(s.validate().is_ok());
+        assert_eq!(s.embedding_model(), None);
+
+        s.embedding_model = Some(" text-embedding-3-large ".to_string());
+        assert!(s.validate().is_ok());
+        assert_eq!(s.embedding_model(), Some("text-embedding-3-large"));
+
+        s.embedding_model = Some("text embedding".to_string());
+        assert!(s.validate().is_err());
+    }
+
+    #[test]
+    fn test_chat_model_warning() {
+        let mut s = AppSettings {
+            chat_model: Some("gpt-4.1-mini".to_string()),
+            ..Default::default()
+        };
+        assert!(s.validate().is_ok());
+        assert!(s.warnings().is_empty());
+
+        s.chat_model = Some("gpt-9-turbo".to_string());
+        assert!(s.validate().is_ok());
+        assert_eq!(s.warnings().len(), 1);
+
+        // Deployment names behind a custom endpoint are the user's to choose
+        s.openai_base_url = Some("https://example.openai.azure.com/openai/v1".to_string());
+        assert!(s.warnings().is_empty());
+
+        s.chat_model = Some("gpt 4o".to_string());
+        assert!(s.validate().is_err());
+    }
 
     #[test]
     #[cfg(target_os = "macos")]
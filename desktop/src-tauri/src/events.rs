@@ -0,0 +1,327 @@
+// Kiroku Memory Desktop - App Events
+// Every event sent to the frontend, with its payload. `emit` is the only way to send one.
+
+use crate::backup::BackupInfo;
+use crate::config::{BatterySaver, LogLevel};
+use crate::install_location::RunningFromDmg;
+use crate::legacy::LegacyData;
+use crate::live_updates::MemoryChange;
+use crate::outbox::OutboxStatus;
+use crate::power::PowerSource;
+use crate::runtime::VerifyProgress;
+use crate::service_log::LogLine;
+use crate::storage::DiskSpace;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tauri::{AppHandle, Emitter};
+
+/// Longest payload echoed by debug logging
+const MAX_LOGGED_PAYLOAD: usize = 200;
+
+/// 0: off, 1: debug (all but log lines), 2: trace (everything)
+static LOG_EMISSIONS: AtomicU8 = AtomicU8::new(0);
+
+/// Progress payload for `clear-progress`
+#[derive(Clone, Debug, Serialize)]
+pub struct ClearProgress {
+    pub deleted: u64,
+    pub total: u64,
+}
+
+/// Payload of `prune-complete`
+#[derive(Clone, Debug, Serialize)]
+pub struct PruneComplete {
+    pub older_than_days: u32,
+    pub deleted: u64,
+}
+
+/// Payload of `power-state-changed`
+#[derive(Clone, Debug, Serialize)]
+pub struct PowerStateChanged {
+    pub source: PowerSource,
+    pub battery_saver: BatterySaver,
+}
+
+#[derive(Clone, Debug)]
+pub enum AppEvent {
+    ServiceReady,
+    ServiceRestarting,
+    /// Error status text
+    ServiceError(String),
+    /// New base URL after a settings change
+    ServiceBaseUrlChanged(String),
+    /// The service can't start without an OpenAI key
+    ApiKeyRequired,
+    ServiceLogLine(LogLine),
+    SettingsWarning(Vec<String>),
+    /// Settings changed other than through the settings form (settings.json
+    /// edited by hand, or a factory reset); the changed keys
+    SettingsChanged(Vec<String>),
+    OutboxChanged(OutboxStatus),
+    ClearProgress(ClearProgress),
+    BackupComplete(BackupInfo),
+    BackupFailed(String),
+    /// Open the capture view (tray left click)
+    QuickCapture,
+    /// Why quitting was refused
+    QuitBlocked(String),
+    PowerStateChanged(PowerStateChanged),
+    RuntimeVerifyProgress(VerifyProgress),
+    /// The service was not started for lack of free space
+    LowDiskSpace(DiskSpace),
+    /// A memory was added, changed or removed, by this app or another client
+    MemoryChanged(MemoryChange),
+    /// Old memories were deleted (not sent for a dry run)
+    PruneComplete(PruneComplete),
+    /// An earlier build's memory store was found; the first service start waits
+    /// for `migrate_legacy_data`
+    LegacyDataFound(LegacyData),
+    /// macOS: started from the mounted DMG or a translocated copy
+    AppRunningFromDmg(RunningFromDmg),
+}
+
+/// Event names and payload types for the frontend, in TypeScript
+pub const TYPESCRIPT: &str = r#"export interface AppEvents {
+  "service-ready": null;
+  "service-restarting": null;
+  "service-error": string;
+  "service-base-url-changed": string;
+  "api-key-required": null;
+  "service-log-line": { timestamp: number; stream: "stdout" | "stderr"; line: string };
+  "settings-warning": string[];
+  "settings-changed": string[];
+  "outbox-changed": { pending: number; failed: number; last_error: string | null };
+  "clear-progress": { deleted: number; total: number };
+  "backup-complete": { path: string; created_at: string; size_bytes: number; encrypted: boolean };
+  "backup-failed": string;
+  "quick-capture": null;
+  "quit-blocked": string;
+  "power-state-changed": { source: "Ac" | "Battery" | "Unknown"; battery_saver: "Off" | "ReducePolling" | "StopService" };
+  "runtime-verify-progress": { files_done: number; files_total: number; bytes_done: number; bytes_total: number };
+  "low-disk-space": { path: string; available_bytes: number; human: string; min_free_bytes: number; low: boolean };
+  "memory-changed": { kind: "created" | "updated" | "deleted" | "other"; id: string | null; source: string | null; count: number | null };
+  "prune-complete": { older_than_days: number; deleted: number };
+  "legacy-data-found": { identifier: string; path: string; size_bytes: number };
+  "app-running-from-dmg": { kind: "DiskImage" | "Translocated"; bundle_path: string | null };
+}
+"#;
+
+/// Returned by `get_event_schema`
+#[derive(Clone, Debug, Serialize)]
+pub struct EventSchema {
+    pub names: Vec<&'static str>,
+    pub typescript: &'static str,
+}
+
+pub fn schema() -> EventSchema {
+    EventSchema {
+        names: NAMES.to_vec(),
+        typescript: TYPESCRIPT,
+    }
+}
+
+/// Every event name, in declaration order
+pub const NAMES: &[&str] = &[
+    "service-ready",
+    "service-restarting",
+    "service-error",
+    "service-base-url-changed",
+    "api-key-required",
+    "service-log-line",
+    "settings-warning",
+    "settings-changed",
+    "outbox-changed",
+    "clear-progress",
+    "backup-complete",
+    "backup-failed",
+    "quick-capture",
+    "quit-blocked",
+    "power-state-changed",
+    "runtime-verify-progress",
+    "low-disk-space",
+    "memory-changed",
+    "prune-complete",
+    "legacy-data-found",
+    "app-running-from-dmg",
+];
+
+impl AppEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::ServiceReady => "service-ready",
+            AppEvent::ServiceRestarting => "service-restarting",
+            AppEvent::ServiceError(_) => "service-error",
+            AppEvent::ServiceBaseUrlChanged(_) => "service-base-url-changed",
+            AppEvent::ApiKeyRequired => "api-key-required",
+            AppEvent::ServiceLogLine(_) => "service-log-line",
+            AppEvent::SettingsWarning(_) => "settings-warning",
+            AppEvent::SettingsChanged(_) => "settings-changed",
+            AppEvent::OutboxChanged(_) => "outbox-changed",
+            AppEvent::ClearProgress(_) => "clear-progress",
+            AppEvent::BackupComplete(_) => "backup-complete",
+            AppEvent::BackupFailed(_) => "backup-failed",
+            AppEvent::QuickCapture => "quick-capture",
+            AppEvent::QuitBlocked(_) => "quit-blocked",
+            AppEvent::PowerStateChanged(_) => "power-state-changed",
+            AppEvent::RuntimeVerifyProgress(_) => "runtime-verify-progress",
+            AppEvent::LowDiskSpace(_) => "low-disk-space",
+            AppEvent::MemoryChanged(_) => "memory-changed",
+            AppEvent::PruneComplete(_) => "prune-complete",
+            AppEvent::LegacyDataFound(_) => "legacy-data-found",
+            AppEvent::AppRunningFromDmg(_) => "app-running-from-dmg",
+        }
+    }
+
+    /// The payload as sent; events without one send `null`
+    pub fn payload(&self) -> serde_json::Value {
+        let value = match self {
+            AppEvent::ServiceReady
+            | AppEvent::ServiceRestarting
+            | AppEvent::ApiKeyRequired
+            | AppEvent::QuickCapture => Ok(serde_json::Value::Null),
+            AppEvent::ServiceError(text)
+            | AppEvent::ServiceBaseUrlChanged(text)
+            | AppEvent::BackupFailed(text)
+            | AppEvent::QuitBlocked(text) => serde_json::to_value(text),
+            AppEvent::ServiceLogLine(line) => serde_json::to_value(line),
+            AppEvent::SettingsWarning(list) | AppEvent::SettingsChanged(list) => {
+                serde_json::to_value(list)
+            }
+            AppEvent::OutboxChanged(status) => serde_json::to_value(status),
+            AppEvent::ClearProgress(progress) => serde_json::to_value(progress),
+            AppEvent::BackupComplete(info) => serde_json::to_value(info),
+            AppEvent::PowerStateChanged(state) => serde_json::to_value(state),
+            AppEvent::RuntimeVerifyProgress(progress) => serde_json::to_value(progress),
+            AppEvent::LowDiskSpace(space) => serde_json::to_value(space),
+            AppEvent::MemoryChanged(change) => serde_json::to_value(change),
+            AppEvent::PruneComplete(result) => serde_json::to_value(result),
+            AppEvent::LegacyDataFound(data) => serde_json::to_value(data),
+            AppEvent::AppRunningFromDmg(location) => serde_json::to_value(location),
+        };
+        value.unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// Log emissions from `debug` up; service log lines only at `trace`
+pub fn set_log_level(level: LogLevel) {
+    let verbosity = match level {
+        LogLevel::Trace => 2,
+        LogLevel::Debug => 1,
+        _ => 0,
+    };
+    LOG_EMISSIONS.store(verbosity, Ordering::SeqCst);
+}
+
+pub fn emit(app: &AppHandle, event: AppEvent) {
+    let payload = event.payload();
+    let verbosity = LOG_EMISSIONS.load(Ordering::SeqCst);
+    let is_log_line = matches!(event, AppEvent::ServiceLogLine(_));
+    if verbosity == 2 || (verbosity == 1 && !is_log_line) {
+        let text: String = payload.to_string().chars().take(MAX_LOGGED_PAYLOAD).collect();
+        println!("[Event] {} {}", event.name(), text);
+    }
+    if let Err(e) = app.emit(event.name(), payload) {
+        eprintln!("[Event] Failed to emit {}: {}", event.name(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install_location::LocationKind;
+    use crate::live_updates::ChangeKind;
+    use crate::service_log::LogStream;
+
+    fn samples() -> Vec<AppEvent> {
+        vec![
+            AppEvent::ServiceReady,
+            AppEvent::ServiceRestarting,
+            AppEvent::ServiceError("boom".into()),
+            AppEvent::ServiceBaseUrlChanged("http://127.0.0.1:8123".into()),
+            AppEvent::ApiKeyRequired,
+            AppEvent::ServiceLogLine(LogLine {
+                timestamp: 1,
+                stream: LogStream::Stderr,
+                line: "ERROR: x".into(),
+            }),
+            AppEvent::SettingsWarning(vec!["exposed".into()]),
+            AppEvent::SettingsChanged(vec!["log_level".into()]),
+            AppEvent::OutboxChanged(OutboxStatus::default()),
+            AppEvent::ClearProgress(ClearProgress { deleted: 1, total: 2 }),
+            AppEvent::BackupComplete(BackupInfo {
+                path: "/tmp/b".into(),
+                created_at: "now".into(),
+                size_bytes: 3,
+                encrypted: false,
+            }),
+            AppEvent::BackupFailed("disk full".into()),
+            AppEvent::QuickCapture,
+            AppEvent::QuitBlocked("backup".into()),
+            AppEvent::PowerStateChanged(PowerStateChanged {
+                source: PowerSource::Battery,
+                battery_saver: BatterySaver::Off,
+            }),
+            AppEvent::RuntimeVerifyProgress(VerifyProgress {
+                files_done: 1,
+                files_total: 2,
+                bytes_done: 3,
+                bytes_total: 4,
+            }),
+            AppEvent::LowDiskSpace(DiskSpace {
+                path: "/data".into(),
+                available_bytes: 5,
+                human: "5 B".into(),
+                min_free_bytes: 6,
+                low: true,
+            }),
+            AppEvent::MemoryChanged(MemoryChange {
+                kind: ChangeKind::Created,
+                id: Some("a1".into()),
+                source: None,
+                count: None,
+            }),
+            AppEvent::PruneComplete(PruneComplete {
+                older_than_days: 90,
+                deleted: 12,
+            }),
+            AppEvent::LegacyDataFound(LegacyData {
+                identifier: "com.kiroku-memory.desktop".into(),
+                path: "/tmp/legacy".into(),
+                size_bytes: 4096,
+            }),
+            AppEvent::AppRunningFromDmg(RunningFromDmg {
+                kind: LocationKind::DiskImage,
+                bundle_path: Some("/Volumes/Kiroku/Kiroku Memory.app".into()),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_names_cover_every_event() {
+        let names: Vec<&str> = samples().iter().map(AppEvent::name).collect();
+        assert_eq!(names, NAMES);
+    }
+
+    #[test]
+    fn test_typescript_matches_payloads() {
+        for event in samples() {
+            let line = TYPESCRIPT
+                .lines()
+                .find(|l| l.trim_start().starts_with(&format!("\"{}\":", event.name())))
+                .unwrap_or_else(|| panic!("{} missing from TYPESCRIPT", event.name()));
+            match event.payload() {
+                serde_json::Value::Null => assert!(line.ends_with(": null;"), "{}", line),
+                serde_json::Value::String(_) => assert!(line.ends_with(": string;"), "{}", line),
+                serde_json::Value::Array(_) => assert!(line.ends_with("[];"), "{}", line),
+                serde_json::Value::Object(fields) => {
+                    let declared = line.matches(": ").count() - 1;
+                    assert_eq!(declared, fields.len(), "{}", line);
+                    for key in fields.keys() {
+                        assert!(line.contains(&format!(" {}: ", key)), "{} lacks {}", line, key);
+                    }
+                }
+                other => panic!("unexpected payload {}", other),
+            }
+        }
+    }
+}
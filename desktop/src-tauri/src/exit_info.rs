@@ -0,0 +1,109 @@
+// Kiroku Memory Desktop - Service Exit Info
+// How the service process ended, so "exited with code 3" isn't reported as "unreachable"
+
+use serde::Serialize;
+use std::process::ExitStatus;
+
+/// uvicorn's exit code when the app fails during startup (lifespan, bind, ...)
+const UVICORN_STARTUP_FAILURE: i32 = 3;
+
+/// Exit code or terminating signal of a reaped service process
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq, Eq)]
+pub struct ExitInfo {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+}
+
+impl ExitInfo {
+    pub fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Self {
+            code: status.code(),
+            signal,
+        }
+    }
+
+    /// "code 3", "signal 9 (SIGKILL)"
+    pub fn describe(&self) -> String {
+        match (self.code, self.signal) {
+            (Some(code), _) => format!("code {}", code),
+            (None, Some(signal)) => match signal_name(signal) {
+                Some(name) => format!("signal {} ({})", signal, name),
+                None => format!("signal {}", signal),
+            },
+            (None, None) => "unknown status".to_string(),
+        }
+    }
+
+    /// Explanation for well-known exits, used when the log has nothing better
+    pub fn hint(&self) -> Option<&'static str> {
+        match (self.code, self.signal) {
+            (Some(UVICORN_STARTUP_FAILURE), _) => Some("application startup failed"),
+            (None, Some(9)) => Some("killed, possibly for running out of memory"),
+            (None, Some(15)) => Some("terminated by another process"),
+            _ => None,
+        }
+    }
+
+    /// Error status text, e.g. "Service exited with code 1: Port already in use by another process"
+    pub fn error_message(&self, root_cause: Option<&str>) -> String {
+        let message = format!("Service exited with {}", self.describe());
+        match root_cause.or(self.hint()) {
+            Some(reason) => format!("{}: {}", message, reason),
+            None => message,
+        }
+    }
+}
+
+fn signal_name(signal: i32) -> Option<&'static str> {
+    match signal {
+        1 => Some("SIGHUP"),
+        2 => Some("SIGINT"),
+        6 => Some("SIGABRT"),
+        9 => Some("SIGKILL"),
+        11 => Some("SIGSEGV"),
+        15 => Some("SIGTERM"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit(code: Option<i32>, signal: Option<i32>) -> ExitInfo {
+        ExitInfo { code, signal }
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(exit(Some(3), None).describe(), "code 3");
+        assert_eq!(exit(None, Some(9)).describe(), "signal 9 (SIGKILL)");
+        assert_eq!(exit(None, Some(31)).describe(), "signal 31");
+    }
+
+    #[test]
+    fn test_error_message_prefers_log_cause() {
+        let port = "Port already in use by another process";
+        assert_eq!(
+            exit(Some(1), None).error_message(Some(port)),
+            format!("Service exited with code 1: {}", port)
+        );
+        assert_eq!(
+            exit(Some(3), None).error_message(None),
+            "Service exited with code 3: application startup failed"
+        );
+        assert_eq!(exit(Some(1), None).error_message(None), "Service exited with code 1");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_status() {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(ExitInfo::from_status(ExitStatus::from_raw(3 << 8)), exit(Some(3), None));
+        assert_eq!(ExitInfo::from_status(ExitStatus::from_raw(9)), exit(None, Some(9)));
+    }
+}
@@ -0,0 +1,54 @@
+// Kiroku Memory Desktop - Humanize
+// Short forms of durations for the tray menu and tooltip
+
+use crate::locale::tr;
+use std::time::Duration;
+
+/// "45s", "12m", "3h 12m", "2d 4h"
+pub fn duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Like `duration`, but "just now" under a minute so the text changes at
+/// most once a minute
+pub fn elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(60) {
+        return tr("time.just_now").to_string();
+    }
+    duration(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(duration(Duration::from_secs(45)), "45s");
+        assert_eq!(duration(Duration::from_secs(12 * 60 + 5)), "12m");
+        assert_eq!(duration(Duration::from_secs(3 * 3600 + 12 * 60)), "3h 12m");
+        assert_eq!(duration(Duration::from_secs(2 * 86_400 + 4 * 3600)), "2d 4h");
+    }
+
+    #[test]
+    fn test_elapsed_boundaries() {
+        assert_eq!(elapsed(Duration::ZERO), "just now");
+        assert_eq!(elapsed(Duration::from_secs(59)), "just now");
+        assert_eq!(elapsed(Duration::from_secs(60)), "1m");
+        assert_eq!(elapsed(Duration::from_secs(3599)), "59m");
+        assert_eq!(elapsed(Duration::from_secs(3600)), "1h 0m");
+        assert_eq!(elapsed(Duration::from_secs(86_399)), "23h 59m");
+        assert_eq!(elapsed(Duration::from_secs(86_400)), "1d 0h");
+        assert_eq!(elapsed(Duration::from_secs(3 * 86_400 + 4 * 3600 + 59)), "3d 4h");
+    }
+}
@@ -0,0 +1,104 @@
+// Kiroku Memory Desktop - Install Location
+// Detects a macOS app opened straight from its disk image or from a translocated copy
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const APPLICATIONS_DIR: &str = "/Applications";
+
+/// Returned by `set_launch_at_login` while `check` finds a problem
+#[cfg(target_os = "macos")]
+pub const LAUNCH_AT_LOGIN_ERROR: &str =
+    "Move Kiroku Memory to the Applications folder before turning on launch at login";
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub enum LocationKind {
+    /// Under /Volumes, usually the mounted DMG; gone once it is ejected
+    DiskImage,
+    /// A read-only copy Gatekeeper made under AppTranslocation; its path
+    /// changes on every launch
+    Translocated,
+}
+
+/// Payload of `app-running-from-dmg`
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct RunningFromDmg {
+    pub kind: LocationKind,
+    /// The .app bundle, when the executable is inside one
+    pub bundle_path: Option<String>,
+}
+
+/// Where the running executable `exe` lives, when that breaks a LaunchAgent
+/// or resource paths
+pub fn check(exe: &Path) -> Option<RunningFromDmg> {
+    let kind = if exe
+        .components()
+        .any(|component| component.as_os_str() == "AppTranslocation")
+    {
+        LocationKind::Translocated
+    } else if exe.starts_with("/Volumes") && exe.components().count() > 2 {
+        LocationKind::DiskImage
+    } else {
+        return None;
+    };
+    Some(RunningFromDmg {
+        kind,
+        bundle_path: app_bundle(exe).map(|bundle| bundle.display().to_string()),
+    })
+}
+
+/// The outermost `.app` folder containing `exe`
+pub fn app_bundle(exe: &Path) -> Option<&Path> {
+    exe.ancestors()
+        .filter(|dir| dir.extension().is_some_and(|ext| ext == "app"))
+        .last()
+}
+
+/// Where `bundle` goes when moved to the Applications folder
+pub fn applications_target(bundle: &Path) -> Option<PathBuf> {
+    Some(Path::new(APPLICATIONS_DIR).join(bundle.file_name()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_paths() {
+        let dmg = check(Path::new(
+            "/Volumes/Kiroku Memory 1.2.0/Kiroku Memory.app/Contents/MacOS/kiroku-memory",
+        ))
+        .unwrap();
+        assert_eq!(dmg.kind, LocationKind::DiskImage);
+        assert_eq!(
+            dmg.bundle_path.as_deref(),
+            Some("/Volumes/Kiroku Memory 1.2.0/Kiroku Memory.app")
+        );
+
+        let translocated = check(Path::new(
+            "/private/var/folders/x1/abc/T/AppTranslocation/6F1C0A2E-1B2C/d/Kiroku Memory.app/Contents/MacOS/kiroku-memory",
+        ))
+        .unwrap();
+        assert_eq!(translocated.kind, LocationKind::Translocated);
+
+        for installed in [
+            "/Applications/Kiroku Memory.app/Contents/MacOS/kiroku-memory",
+            "/Users/me/Applications/Kiroku Memory.app/Contents/MacOS/kiroku-memory",
+            "/Users/me/VolumesBackup/Kiroku Memory.app/Contents/MacOS/kiroku-memory",
+            "/home/me/.local/bin/kiroku-memory",
+        ] {
+            assert_eq!(check(Path::new(installed)), None, "{}", installed);
+        }
+    }
+
+    #[test]
+    fn test_applications_target() {
+        let exe = Path::new("/Volumes/Kiroku/Kiroku Memory.app/Contents/MacOS/kiroku-memory");
+        let bundle = app_bundle(exe).unwrap();
+        assert_eq!(
+            applications_target(bundle).unwrap(),
+            Path::new("/Applications/Kiroku Memory.app")
+        );
+        assert_eq!(app_bundle(Path::new("/Volumes/Kiroku/kiroku-memory")), None);
+    }
+}
@@ -0,0 +1,66 @@
+// Kiroku Memory Desktop - JSON Lines Files
+// Append-only files of one JSON value per line, shared by the outbox and stats history
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// Parse a JSONL file, skipping lines that don't parse (e.g. a torn last line)
+pub fn read<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Append values, creating the file if needed, and flush them to disk
+pub fn append<T: Serialize>(path: &Path, items: &[T]) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for item in items {
+        writeln!(file, "{}", serde_json::to_string(item)?)?;
+    }
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Replace the file through a temp file so a crash never leaves it
+/// half-written. An empty list removes the file.
+pub fn replace<T: Serialize>(path: &Path, items: &[T]) -> anyhow::Result<()> {
+    if items.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    append(&tmp, items)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_torn_lines_are_skipped() {
+        let dir = TempDir::new("jsonl");
+        let path = dir.join("torn.jsonl");
+        append(&path, &[serde_json::json!({"content": "kept"})]).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"content\":\"torn").unwrap();
+
+        let items: Vec<serde_json::Value> = read(&path);
+        assert_eq!(items, vec![serde_json::json!({"content": "kept"})]);
+
+        replace::<serde_json::Value>(&path, &[]).unwrap();
+        assert!(!path.exists());
+    }
+}
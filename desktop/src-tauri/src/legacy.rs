@@ -0,0 +1,119 @@
+// Kiroku Memory Desktop - Legacy Data
+// Finds the memory store of early builds, which used a different bundle identifier
+
+use crate::backup::{self, DB_DIR_NAME};
+use serde::Serialize;
+use std::path::Path;
+
+/// Bundle identifiers of earlier shipped builds. Tauri puts app data under
+/// `<platform data dir>/<identifier>` (~/Library/Application Support on macOS,
+/// %APPDATA% on Windows, ~/.local/share on Linux), and the same identifiers
+/// name their Keychain service on macOS. Every release so far has used
+/// `com.kiroku.memory`, so this stays empty until the identifier changes.
+pub const LEGACY_IDENTIFIERS: &[&str] = &[];
+
+/// Staging folder for a copy in progress; renamed into place when complete
+const STAGING_DIR_NAME: &str = "surrealdb.migrating";
+
+/// A store left by an earlier build, offered by `legacy-data-found`
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct LegacyData {
+    pub identifier: String,
+    /// The legacy app data dir (left in place after migration)
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+/// Whether `dir` holds a non-empty database folder
+fn has_store(dir: &Path) -> bool {
+    std::fs::read_dir(dir.join(DB_DIR_NAME))
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false)
+}
+
+/// The first legacy store next to `app_data_dir`, when `data_dir` has none yet
+pub fn find(app_data_dir: &Path, data_dir: &Path) -> Option<LegacyData> {
+    find_among(LEGACY_IDENTIFIERS, app_data_dir, data_dir)
+}
+
+fn find_among(identifiers: &[&str], app_data_dir: &Path, data_dir: &Path) -> Option<LegacyData> {
+    if has_store(data_dir) {
+        return None;
+    }
+    let parent = app_data_dir.parent()?;
+    identifiers.iter().find_map(|identifier| {
+        let dir = parent.join(identifier);
+        if dir == app_data_dir || !has_store(&dir) {
+            return None;
+        }
+        Some(LegacyData {
+            identifier: identifier.to_string(),
+            path: dir.display().to_string(),
+            size_bytes: backup::dir_size(&dir.join(DB_DIR_NAME)).unwrap_or(0),
+        })
+    })
+}
+
+/// Copy the legacy database folder into `data_dir`, returning the bytes
+/// copied. The legacy copy is never touched.
+pub fn copy_store(legacy_dir: &Path, data_dir: &Path) -> std::io::Result<u64> {
+    if has_store(data_dir) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "the current data directory already has a memory store",
+        ));
+    }
+    let staging = data_dir.join(STAGING_DIR_NAME);
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    let copied = match backup::copy_dir_recursive(&legacy_dir.join(DB_DIR_NAME), &staging) {
+        Ok(copied) => copied,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+    let target = data_dir.join(DB_DIR_NAME);
+    if target.exists() {
+        // Empty, or `has_store` would have refused
+        std::fs::remove_dir_all(&target)?;
+    }
+    std::fs::rename(&staging, &target)?;
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_find_and_copy_legacy_store() {
+        let root = TempDir::new("legacy");
+        let current = root.join("com.kiroku.memory");
+        std::fs::create_dir_all(current.join(DB_DIR_NAME)).unwrap();
+        let identifiers = ["com.example.earlier"];
+        assert_eq!(find_among(&identifiers, &current, &current), None);
+
+        let legacy = root.join(identifiers[0]);
+        std::fs::create_dir_all(legacy.join(DB_DIR_NAME).join("data")).unwrap();
+        std::fs::write(legacy.join(DB_DIR_NAME).join("data/000001.sst"), b"memories").unwrap();
+
+        let found = find_among(&identifiers, &current, &current).unwrap();
+        assert_eq!(found.identifier, identifiers[0]);
+        assert_eq!(found.size_bytes, 8);
+
+        assert_eq!(copy_store(&legacy, &current).unwrap(), 8);
+        assert_eq!(
+            std::fs::read(current.join(DB_DIR_NAME).join("data/000001.sst")).unwrap(),
+            b"memories"
+        );
+        assert!(!current.join(STAGING_DIR_NAME).exists());
+        assert!(legacy.join(DB_DIR_NAME).join("data/000001.sst").exists());
+
+        // Once the current dir has data nothing is offered or overwritten
+        assert_eq!(find_among(&identifiers, &current, &current), None);
+        assert!(copy_store(&legacy, &current).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+// Kiroku Memory Desktop - Live Updates
+// Bridges the service's change stream (SSE) to `memory-changed` events and the tray count
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Server-sent change stream; services without it answer 404
+pub const EVENTS_PATH: &str = "/v2/events";
+
+/// SSE event name carrying a `MemoryChange`; unnamed events are accepted too
+const CHANGE_EVENT: &str = "memory-changed";
+
+/// Longest wait between reconnect attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    /// Sent by a newer service; passed on but doesn't change the count
+    #[serde(other)]
+    Other,
+}
+
+/// One change reported by the service
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MemoryChange {
+    pub kind: ChangeKind,
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Who made the change (e.g. "mcp", "watcher")
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Items covered by a bulk change (delete all, prune); None means one
+    #[serde(default)]
+    pub count: Option<u64>,
+}
+
+impl MemoryChange {
+    /// Effect on the memory total
+    pub fn count_delta(&self) -> i64 {
+        let count = i64::try_from(self.count.unwrap_or(1)).unwrap_or(i64::MAX);
+        match self.kind {
+            ChangeKind::Created => count,
+            ChangeKind::Deleted => -count,
+            ChangeKind::Updated | ChangeKind::Other => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeState {
+    /// Turned off in settings
+    Disabled,
+    /// The service has no change stream (404); retried after it restarts
+    Unsupported,
+    #[default]
+    Disconnected,
+    Connected,
+}
+
+/// Returned by `get_live_updates_status`
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LiveUpdatesStatus {
+    pub state: BridgeState,
+    /// Unix timestamp (seconds) of the last change received
+    pub last_event_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Shared bridge status, managed as app state
+#[derive(Default)]
+pub struct LiveUpdates(Mutex<LiveUpdatesStatus>);
+
+impl LiveUpdates {
+    pub fn status(&self) -> LiveUpdatesStatus {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set_state(&self, state: BridgeState, error: Option<String>) {
+        let mut status = self.0.lock().unwrap();
+        status.state = state;
+        status.last_error = error;
+    }
+
+    pub fn record_event(&self) {
+        self.0.lock().unwrap().last_event_at = Some(chrono::Utc::now().timestamp());
+    }
+}
+
+/// Count changes not yet applied by the tray loop
+#[derive(Default)]
+pub struct CountDelta {
+    delta: AtomicI64,
+    notify: Notify,
+}
+
+impl CountDelta {
+    pub fn add(&self, delta: i64) {
+        if delta != 0 {
+            self.delta.fetch_add(delta, Ordering::SeqCst);
+            self.notify.notify_one();
+        }
+    }
+
+    /// Wait for changes and take their sum
+    pub async fn take(&self) -> i64 {
+        self.notify.notified().await;
+        self.delta.swap(0, Ordering::SeqCst)
+    }
+
+    /// Drop pending changes (a full stats fetch supersedes them)
+    pub fn clear(&self) {
+        self.delta.store(0, Ordering::SeqCst);
+    }
+}
+
+/// One dispatched server-sent event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SseMessage {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+impl SseMessage {
+    /// The change this message carries, if it is one
+    pub fn change(&self) -> Option<MemoryChange> {
+        if self.event.as_deref().is_some_and(|event| event != CHANGE_EVENT) {
+            return None;
+        }
+        serde_json::from_str(&self.data).ok()
+    }
+}
+
+/// Incremental `text/event-stream` parser; chunks may split lines (and
+/// characters) anywhere
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk and return the events it completed
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        self.buffer.extend_from_slice(chunk);
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    messages.push(SseMessage {
+                        event: self.event.take(),
+                        data: self.data.join("\n"),
+                    });
+                }
+                self.event = None;
+                self.data.clear();
+                continue;
+            }
+            // Lines starting with ':' are comments (keep-alives)
+            let (field, value) = match line.split_once(':') {
+                Some(("", _)) => continue,
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        messages
+    }
+}
+
+/// Wait before reconnect attempt `failures` (1-based): 1s, 2s, 4s, ... up to a minute
+pub fn reconnect_delay(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(6);
+    (Duration::from_secs(1) * 2u32.pow(exponent)).min(MAX_RECONNECT_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_handles_split_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b": keep-alive\n\nevent: memory-chan").is_empty());
+        assert!(parser.push(b"ged\ndata: {\"kind\":\"created\",").is_empty());
+        // "記" split across chunks
+        assert!(parser.push(b"\r\ndata: \"id\":\"\xe8\xa8").is_empty());
+        let messages = parser.push(b"\x98\"}\r\n\r\n");
+        assert_eq!(
+            messages,
+            vec![SseMessage {
+                event: Some("memory-changed".into()),
+                data: "{\"kind\":\"created\",\n\"id\":\"\u{8a18}\"}".into(),
+            }]
+        );
+        let change = messages[0].change().unwrap();
+        assert_eq!(change.kind, ChangeKind::Created);
+        assert_eq!(change.id.as_deref(), Some("\u{8a18}"));
+        assert_eq!(change.count_delta(), 1);
+    }
+
+    #[test]
+    fn test_change_filters_event_names() {
+        let message = |event: Option<&str>, data: &str| SseMessage {
+            event: event.map(str::to_string),
+            data: data.to_string(),
+        };
+        assert!(message(None, r#"{"kind":"deleted"}"#).change().is_some());
+        assert!(message(Some("ping"), r#"{"kind":"deleted"}"#).change().is_none());
+        assert!(message(None, "not json").change().is_none());
+        let renamed = message(None, r#"{"kind":"merged"}"#).change().unwrap();
+        assert_eq!(renamed.kind, ChangeKind::Other);
+        assert_eq!(renamed.count_delta(), 0);
+        let pruned = message(None, r#"{"kind":"deleted","id":null,"count":250}"#)
+            .change()
+            .unwrap();
+        assert_eq!(pruned.count_delta(), -250);
+    }
+
+    #[test]
+    fn test_reconnect_delay() {
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(3), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(7), Duration::from_secs(60));
+        assert_eq!(reconnect_delay(50), Duration::from_secs(60));
+    }
+}
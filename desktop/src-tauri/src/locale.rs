@@ -0,0 +1,318 @@
+// Kiroku Memory Desktop - Localization
+// Embedded translation tables for native UI (tray menu, notifications)
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Setting value that follows the OS language
+pub const AUTO: &str = "auto";
+
+/// Supported locales as (code, native name)
+pub const LOCALES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("ja", "日本語"),
+    ("zh-TW", "繁體中文"),
+];
+
+/// Every translation key. Keys are stable so the frontend can share the catalog.
+pub const KEYS: &[&str] = &[
+    "tray.status.starting",
+    "tray.status.running",
+    "tray.status.stopped",
+    "tray.status.restarting",
+    "tray.status.error",
+    "tray.status.idle",
+    "tray.status.degraded",
+    "tray.memories",
+    "tray.memories.unknown",
+    "tray.tooltip.starting",
+    "tray.tooltip.running",
+    "tray.tooltip.stopped",
+    "tray.tooltip.restarting",
+    "tray.tooltip.error",
+    "tray.tooltip.idle",
+    "tray.tooltip.degraded",
+    "tray.tooltip.memories",
+    "tray.tooltip.uptime",
+    "tray.breakdown",
+    "tray.breakdown.other",
+    "tray.outbox_pending",
+    "tray.show_window",
+    "tray.bring_to_front",
+    "tray.always_on_top",
+    "tray.restart_service",
+    "tray.start_service",
+    "tray.open_dashboard",
+    "tray.copy_api_url",
+    "tray.copied_api_url",
+    "tray.update_available",
+    "tray.settings",
+    "tray.quit",
+    "tray.quit_leave_running",
+    "tray.usage_month",
+    "tray.uptime",
+    "tray.last_restart",
+    "tray.last_restart.never",
+    "time.just_now",
+    "settings.title",
+];
+
+static CURRENT: RwLock<&str> = RwLock::new("en");
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LocaleInfo {
+    pub code: String,
+    pub name: String,
+}
+
+/// Locales for the settings UI, with the setting and the locale it resolves to
+#[derive(Clone, Debug, Serialize)]
+pub struct LocaleList {
+    pub setting: String,
+    pub current: String,
+    pub locales: Vec<LocaleInfo>,
+    /// Translations of every key in the current locale
+    pub messages: BTreeMap<String, String>,
+}
+
+pub fn is_supported(code: &str) -> bool {
+    code == AUTO || LOCALES.iter().any(|(c, _)| *c == code)
+}
+
+/// Map a `locale` setting to a supported locale, detecting the OS language for "auto"
+pub fn resolve(setting: &str) -> &'static str {
+    if let Some((code, _)) = LOCALES.iter().find(|(c, _)| *c == setting) {
+        return code;
+    }
+    sys_locale::get_locale()
+        .map(|os| match_os_locale(&os))
+        .unwrap_or("en")
+}
+
+fn match_os_locale(os: &str) -> &'static str {
+    let os = os.to_ascii_lowercase();
+    if os.starts_with("ja") {
+        "ja"
+    } else if os.starts_with("zh") {
+        // Traditional Chinese is the only Chinese table
+        "zh-TW"
+    } else {
+        "en"
+    }
+}
+
+pub fn set_current(code: &'static str) {
+    *CURRENT.write().unwrap() = code;
+}
+
+pub fn current() -> &'static str {
+    *CURRENT.read().unwrap()
+}
+
+pub fn list(setting: &str) -> LocaleList {
+    LocaleList {
+        setting: setting.to_string(),
+        current: current().to_string(),
+        locales: LOCALES
+            .iter()
+            .map(|(code, name)| LocaleInfo {
+                code: code.to_string(),
+                name: name.to_string(),
+            })
+            .collect(),
+        messages: KEYS
+            .iter()
+            .map(|key| (key.to_string(), tr(key).to_string()))
+            .collect(),
+    }
+}
+
+/// Translate `key` into the current locale, falling back to English
+pub fn tr(key: &str) -> &'static str {
+    lookup(current(), key)
+        .or_else(|| en(key))
+        .unwrap_or("?")
+}
+
+/// Translate and fill `{name}` placeholders
+pub fn tr_args(key: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(tr(key).to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+fn lookup(locale: &str, key: &str) -> Option<&'static str> {
+    match locale {
+        "ja" => ja(key),
+        "zh-TW" => zh_tw(key),
+        _ => en(key),
+    }
+}
+
+fn en(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "tray.status.starting" => "Status: Starting",
+        "tray.status.running" => "Status: Running",
+        "tray.status.stopped" => "Status: Stopped",
+        "tray.status.restarting" => "Status: Restarting",
+        "tray.status.error" => "Status: Error",
+        "tray.status.idle" => "Status: Idle",
+        "tray.status.degraded" => "Status: Needs Attention",
+        "tray.memories" => "Memories: {count}",
+        "tray.memories.unknown" => "Memories: -",
+        "tray.tooltip.starting" => "Starting",
+        "tray.tooltip.running" => "Running",
+        "tray.tooltip.stopped" => "Stopped",
+        "tray.tooltip.restarting" => "Restarting",
+        "tray.tooltip.error" => "Error",
+        "tray.tooltip.idle" => "Idle",
+        "tray.tooltip.degraded" => "Needs attention",
+        "tray.tooltip.memories" => "{count} memories",
+        "tray.tooltip.uptime" => "up {uptime}",
+        "tray.breakdown" => "Breakdown",
+        "tray.breakdown.other" => "Other: {count}",
+        "tray.outbox_pending" => "{count} pending",
+        "tray.show_window" => "Show Window",
+        "tray.bring_to_front" => "Bring to Front",
+        "tray.always_on_top" => "Keep Window on Top",
+        "tray.restart_service" => "Restart Service",
+        "tray.start_service" => "Start Service",
+        "tray.open_dashboard" => "Open Dashboard in Browser",
+        "tray.copy_api_url" => "Copy API URL",
+        "tray.copied_api_url" => "Copied {url} to the clipboard",
+        "tray.update_available" => "Update available: v{version}\u{2026}",
+        "tray.settings" => "Settings\u{2026}",
+        "tray.quit" => "Quit",
+        "tray.quit_leave_running" => "Quit (leave service running)",
+        "tray.usage_month" => "Usage this month: {cost}",
+        "tray.uptime" => "Uptime: {uptime}",
+        "tray.last_restart" => "Last restart: {time}",
+        "tray.last_restart.never" => "Last restart: never this session",
+        "time.just_now" => "just now",
+        "settings.title" => "Kiroku Memory Settings",
+        _ => return None,
+    })
+}
+
+fn ja(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "tray.status.starting" => "状態: 起動中",
+        "tray.status.running" => "状態: 実行中",
+        "tray.status.stopped" => "状態: 停止",
+        "tray.status.restarting" => "状態: 再起動中",
+        "tray.status.error" => "状態: エラー",
+        "tray.status.idle" => "状態: 待機中",
+        "tray.status.degraded" => "状態: 要確認",
+        "tray.memories" => "記憶: {count}",
+        "tray.memories.unknown" => "記憶: -",
+        "tray.tooltip.starting" => "起動中",
+        "tray.tooltip.running" => "実行中",
+        "tray.tooltip.stopped" => "停止",
+        "tray.tooltip.restarting" => "再起動中",
+        "tray.tooltip.error" => "エラー",
+        "tray.tooltip.idle" => "待機中",
+        "tray.tooltip.degraded" => "要確認",
+        "tray.tooltip.memories" => "記憶 {count} 件",
+        "tray.tooltip.uptime" => "稼働 {uptime}",
+        "tray.breakdown" => "内訳",
+        "tray.breakdown.other" => "その他: {count}",
+        "tray.outbox_pending" => "未送信: {count} 件",
+        "tray.show_window" => "ウィンドウを表示",
+        "tray.bring_to_front" => "前面に表示",
+        "tray.always_on_top" => "常に手前に表示",
+        "tray.restart_service" => "サービスを再起動",
+        "tray.start_service" => "サービスを開始",
+        "tray.open_dashboard" => "ブラウザでダッシュボードを開く",
+        "tray.copy_api_url" => "API URL をコピー",
+        "tray.copied_api_url" => "{url} をクリップボードにコピーしました",
+        "tray.update_available" => "アップデートがあります: v{version}\u{2026}",
+        "tray.settings" => "設定\u{2026}",
+        "tray.quit" => "終了",
+        "tray.quit_leave_running" => "終了（サービスは実行したまま）",
+        "tray.usage_month" => "今月の使用量: {cost}",
+        "tray.uptime" => "稼働時間: {uptime}",
+        "tray.last_restart" => "最終再起動: {time}",
+        "tray.last_restart.never" => "最終再起動: このセッションではなし",
+        "time.just_now" => "たった今",
+        "settings.title" => "Kiroku Memory 設定",
+        _ => return None,
+    })
+}
+
+fn zh_tw(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "tray.status.starting" => "狀態：啟動中",
+        "tray.status.running" => "狀態：執行中",
+        "tray.status.stopped" => "狀態：已停止",
+        "tray.status.restarting" => "狀態：重新啟動中",
+        "tray.status.error" => "狀態：錯誤",
+        "tray.status.idle" => "狀態：閒置",
+        "tray.status.degraded" => "狀態：需要注意",
+        "tray.memories" => "記憶：{count}",
+        "tray.memories.unknown" => "記憶：-",
+        "tray.tooltip.starting" => "啟動中",
+        "tray.tooltip.running" => "執行中",
+        "tray.tooltip.stopped" => "已停止",
+        "tray.tooltip.restarting" => "重新啟動中",
+        "tray.tooltip.error" => "錯誤",
+        "tray.tooltip.idle" => "閒置",
+        "tray.tooltip.degraded" => "需要注意",
+        "tray.tooltip.memories" => "{count} 筆記憶",
+        "tray.tooltip.uptime" => "已執行 {uptime}",
+        "tray.breakdown" => "分類明細",
+        "tray.breakdown.other" => "其他：{count}",
+        "tray.outbox_pending" => "待寫入：{count} 筆",
+        "tray.show_window" => "顯示視窗",
+        "tray.bring_to_front" => "移至最前",
+        "tray.always_on_top" => "視窗保持在最上層",
+        "tray.restart_service" => "重新啟動服務",
+        "tray.start_service" => "啟動服務",
+        "tray.open_dashboard" => "在瀏覽器開啟儀表板",
+        "tray.copy_api_url" => "複製 API URL",
+        "tray.copied_api_url" => "已將 {url} 複製到剪貼簿",
+        "tray.update_available" => "有可用更新：v{version}\u{2026}",
+        "tray.settings" => "設定\u{2026}",
+        "tray.quit" => "結束",
+        "tray.quit_leave_running" => "結束（保留服務執行）",
+        "tray.usage_month" => "本月用量：{cost}",
+        "tray.uptime" => "已執行：{uptime}",
+        "tray.last_restart" => "上次重新啟動：{time}",
+        "tray.last_restart.never" => "上次重新啟動：本次尚未重新啟動",
+        "time.just_now" => "剛剛",
+        "settings.title" => "Kiroku Memory 設定",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_locale_has_every_key() {
+        for (code, _) in LOCALES {
+            for key in KEYS {
+                assert!(lookup(code, key).is_some(), "{} missing {}", code, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_os_locale_matching() {
+        assert_eq!(match_os_locale("ja-JP"), "ja");
+        assert_eq!(match_os_locale("zh-Hant-TW"), "zh-TW");
+        assert_eq!(match_os_locale("en-US"), "en");
+        assert_eq!(match_os_locale("fr-FR"), "en");
+        assert_eq!(resolve("ja"), "ja");
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(tr_args("tray.memories", &[("count", "12")]), "Memories: 12");
+        assert_eq!(
+            tr_args("tray.update_available", &[("version", "0.2.0")]),
+            "Update available: v0.2.0\u{2026}"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+// Kiroku Memory Desktop - Locked Mode
+// Guards settings and destructive commands behind a PIN for shared machines
+
+use sha2::{Digest, Sha256};
+
+/// Returned by every guarded command while `locked` is on
+pub const LOCKED_ERROR: &str = "Kiroku Memory is locked; unlock it to make changes";
+
+const MIN_PIN_LEN: usize = 4;
+const MAX_PIN_LEN: usize = 32;
+
+pub fn validate_pin(pin: &str) -> Result<(), String> {
+    let len = pin.chars().count();
+    if !(MIN_PIN_LEN..=MAX_PIN_LEN).contains(&len) {
+        return Err(format!(
+            "PIN must be {} to {} characters",
+            MIN_PIN_LEN, MAX_PIN_LEN
+        ));
+    }
+    Ok(())
+}
+
+fn digest(salt: &str, pin: &str) -> String {
+    let hash = Sha256::digest(format!("{}:{}", salt, pin).as_bytes());
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// "salt:hash" for the keychain; the PIN itself is never stored
+pub fn hash_pin(pin: &str) -> String {
+    let salt = uuid::Uuid::new_v4().simple().to_string();
+    format!("{}:{}", salt, digest(&salt, pin))
+}
+
+/// Whether `pin` matches a value from `hash_pin`
+pub fn verify_pin(stored: &str, pin: &str) -> bool {
+    match stored.split_once(':') {
+        Some((salt, hash)) => digest(salt, pin) == hash,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_round_trip() {
+        let stored = hash_pin("2468");
+        assert!(!stored.contains("2468"));
+        assert!(verify_pin(&stored, "2468"));
+        assert!(!verify_pin(&stored, "1357"));
+        assert!(!verify_pin("garbage", "2468"));
+        // Salted: the same PIN hashes differently each time
+        assert_ne!(hash_pin("2468"), stored);
+    }
+
+    #[test]
+    fn test_validate_pin() {
+        assert!(validate_pin("123").is_err());
+        assert!(validate_pin("1234").is_ok());
+        assert!(validate_pin(&"9".repeat(33)).is_err());
+    }
+}
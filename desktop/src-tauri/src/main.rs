@@ -3,8 +3,52 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod api;
+mod app_info;
+#[cfg(target_os = "linux")]
+mod autostart;
+mod backup;
+mod backup_crypto;
+mod breakdown;
+mod cli;
 mod config;
+mod events;
+mod exit_info;
+mod humanize;
+mod install_location;
+mod jsonl;
+mod legacy;
+mod live_updates;
+mod locale;
+mod lock;
+mod memories;
+mod outbox;
+mod permissions;
+mod pid_file;
+mod power;
+mod profiles;
+mod redact;
+mod resources;
+mod runtime;
+mod search;
+mod secrets_file;
 mod service;
+mod service_log;
+mod settings_transfer;
+mod settings_watch;
+mod start_visibility;
+mod stats;
+mod stats_history;
+mod tray_icon;
+mod tray_tooltip;
+mod storage;
+mod tasks;
+#[cfg(test)]
+mod test_support;
+mod uninstall;
+mod updates;
+mod usage;
+mod window_log;
 
 #[cfg(target_os = "macos")]
 mod wake_listener {
@@ -50,9 +94,35 @@ mod wake_listener {
     }
 }
 
-use config::{keychain, keys, settings, AppSettings};
-use serde::Deserialize;
-use service::{check_health_once, wait_for_health, PythonService, ServiceStatus};
+use api::{ApiClient, ApiClientStats, ApiError, RequestOptions, ResponseMeta, WithMeta};
+use outbox::{MemoryWrite, Outbox, OutboxStatus};
+use backup::BackupInfo;
+use cli::StartupOverrides;
+use live_updates::{BridgeState, CountDelta, LiveUpdates, LiveUpdatesStatus, SseMessage, SseParser};
+use events::{AppEvent, ClearProgress, PowerStateChanged, PruneComplete};
+use locale::{tr, tr_args, LocaleList};
+use power::PowerSource;
+use profiles::ProfileList;
+use resources::{ResourceSample, ResourceSampler};
+use breakdown::StatsBreakdown;
+use config::{
+    keychain, keys, settings, AppSettings, BatterySaver, KeychainStatus, LegacyMigration, LogLevel,
+    OnboardingState, ServiceStartMode, TrayClickAction,
+};
+use search::{SearchFilters, SearchGenerations, SearchHit, SearchOutcome};
+use service::{
+    check_health_once, HealthSample, HealthState, LastError, LaunchInfo, MaintenanceInfo, PythonService,
+    RuntimeInfo, ServiceInfo, ServiceStatus,
+};
+use service_log::LogLine;
+use start_visibility::{StartVisibility, StartVisibilityDecision, StartVisibilityInputs};
+use stats::ServiceStats;
+use stats_history::{HistoryRange, StatsHistory, StatsSample};
+use storage::{DataDirSize, DiskSpace, LowSpaceLatch};
+use tasks::{TaskInfo, TaskSupervisor};
+use tray_icon::TrayIconVariant;
+use usage::{BudgetAlerts, UsageRange, UsageSummary};
+use window_log::{Verdict, WindowEventFilter, WindowEventKind};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::PathBuf;
@@ -60,9 +130,14 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::image::Image;
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
-use tauri::tray::TrayIconBuilder;
-use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Listener, Manager, State, Window};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
+use uninstall::{UninstallOptions, UninstallReport};
+use updates::{UpdateChecker, UpdateInfo};
 
 /// Tauri command to get service status
 #[tauri::command]
@@ -72,67 +147,405 @@ async fn get_service_status(
     Ok(service.get_status().await)
 }
 
-/// Tauri command to check health
+/// Tauri command to get status, PID, and latest resource usage of the service
 #[tauri::command]
-async fn check_health() -> Result<String, String> {
-    match check_health_once().await {
-        Some(health) => serde_json::to_string(&health).map_err(|e| e.to_string()),
-        None => Err("Service not available".to_string()),
+async fn get_service_info(
+    service: State<'_, Arc<PythonService>>,
+    api: State<'_, Arc<ApiClient>>,
+) -> Result<ServiceInfo, String> {
+    let socket = api.unix_socket().map(|path| path.display().to_string());
+    Ok(service.info(api.base_url(), socket).await)
+}
+
+/// POST paths `proxy_request` still forwards while locked: adding memories,
+/// which `capture_memory` allows too
+const PROXY_WRITES_WHILE_LOCKED: &[&str] = &["/v2/ingest", "/ingest", "/v2/items"];
+
+/// Tauri command to forward a JSON request to the service, for the dashboard
+/// when the service listens on a Unix socket. `path` may include a query.
+#[tauri::command]
+async fn proxy_request(
+    app: AppHandle,
+    api: State<'_, Arc<ApiClient>>,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    if !path.starts_with('/') {
+        return Err(format!("Invalid path '{}'", path));
+    }
+    // Other writes (prune, jobs, ...) are refused while locked like the commands for them
+    let route = path.split(['?', '#']).next().unwrap_or_default();
+    if method != "GET" && !PROXY_WRITES_WHILE_LOCKED.contains(&route) {
+        ensure_unlocked(&app)?;
+    }
+    let reply = match (method.as_str(), body) {
+        ("GET", _) => api.get_json(&path, &[]).await,
+        ("POST", body) => {
+            let body = body.unwrap_or(serde_json::Value::Null);
+            api.post_json(&path, &body, RequestOptions::default()).await
+        }
+        _ => return Err(format!("Unsupported method '{}'", method)),
+    };
+    reply.map_err(redact::error)
+}
+
+/// Tauri command to get CPU / memory samples of the service from the last 10 minutes
+#[tauri::command]
+async fn get_resource_history(
+    service: State<'_, Arc<PythonService>>,
+) -> Result<Vec<ResourceSample>, String> {
+    Ok(service.resource_history().await)
+}
+
+/// Tauri command to get the latest CPU / memory reading of the service
+/// process, or None while it isn't running
+#[tauri::command]
+async fn get_process_metrics(
+    service: State<'_, Arc<PythonService>>,
+) -> Result<Option<ResourceSample>, String> {
+    Ok(service.process_metrics().await)
+}
+
+/// Debug view of supervised background loops (restarts, last panic)
+#[tauri::command]
+async fn get_background_tasks(supervisor: State<'_, Arc<TaskSupervisor>>) -> Result<Vec<TaskInfo>, String> {
+    Ok(supervisor.list())
+}
+
+/// Tauri command to get the most recent service error, even after recovery
+#[tauri::command]
+async fn get_last_error(service: State<'_, Arc<PythonService>>) -> Result<Option<LastError>, String> {
+    Ok(service.last_error().await)
+}
+
+/// Recent health samples from the monitor and manual checks, oldest first:
+/// the last `n` and/or those from the last `minutes`
+#[tauri::command]
+async fn get_health_history(
+    service: State<'_, Arc<PythonService>>,
+    n: Option<usize>,
+    minutes: Option<u32>,
+) -> Result<Vec<HealthSample>, String> {
+    let since = minutes.map(|minutes| chrono::Utc::now().timestamp() - i64::from(minutes) * 60);
+    Ok(service
+        .health_history(n.unwrap_or(service::HEALTH_HISTORY_LEN), since)
+        .await)
+}
+
+/// Tauri command to pause or resume automatic restarts (e.g. while debugging
+/// the backend by hand). Persisted in settings.
+#[tauri::command]
+async fn set_auto_restart(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    enabled: bool,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    app_settings.auto_restart = enabled;
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    service.set_auto_restart(enabled);
+    log_event(&app, &format!("auto-restart {}", if enabled { "enabled" } else { "disabled" }));
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_auto_restart(service: State<'_, Arc<PythonService>>) -> Result<bool, String> {
+    Ok(service.auto_restart_enabled())
+}
+
+/// Desktop and service versions for the About panel
+#[derive(serde::Serialize)]
+struct VersionInfo {
+    desktop: String,
+    /// From the last health response; "unknown" until the service has answered
+    service: String,
+}
+
+/// Tauri command to get app and service versions without a network call
+#[tauri::command]
+async fn get_version(service: State<'_, Arc<PythonService>>) -> Result<VersionInfo, String> {
+    Ok(VersionInfo {
+        desktop: updates::CURRENT_VERSION.to_string(),
+        service: service
+            .last_health()
+            .await
+            .map(|h| h.version)
+            .unwrap_or_else(|| "unknown".to_string()),
+    })
+}
+
+/// Tauri command to get build metadata along with the app and service versions
+#[tauri::command]
+async fn get_app_info(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<app_info::AppInfo, String> {
+    let python_runtime = service::get_python_paths(&app).ok().map(|paths| paths.source);
+    let service_version = service.last_health().await.map(|h| h.version);
+    Ok(app_info::AppInfo::new(python_runtime, service_version))
+}
+
+/// Tauri command to open (or focus) the settings window
+#[tauri::command]
+async fn open_settings_window(app: AppHandle) -> Result<(), String> {
+    show_settings_window(&app).map_err(redact::error)
+}
+
+/// Tauri command to get how the service was last launched (binary, args, env)
+#[tauri::command]
+async fn get_service_launch_info(
+    service: State<'_, Arc<PythonService>>,
+) -> Result<Option<LaunchInfo>, String> {
+    Ok(service.launch_info().await)
+}
+
+/// Tauri command to get what the service will be launched with (paths, port,
+/// data dir); the OpenAI key is reported only as configured or not
+#[tauri::command]
+async fn get_runtime_info(app: AppHandle) -> Result<RuntimeInfo, String> {
+    service::runtime_info(&app).map_err(redact::error)
+}
+
+/// Tauri command to re-hash the bundled runtime and app files against the
+/// build manifest. Emits `runtime-verify-progress` while it runs.
+#[tauri::command]
+async fn verify_runtime(app: AppHandle) -> Result<runtime::RuntimeReport, String> {
+    let resource_dir = app.path().resource_dir().map_err(redact::error)?;
+    let progress_app = app.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        runtime::verify(&resource_dir, |progress| {
+            events::emit(&progress_app, AppEvent::RuntimeVerifyProgress(progress.clone()));
+        })
+    })
+    .await
+    .map_err(redact::error)?;
+    log_event(
+        &app,
+        &format!(
+            "runtime verify: {} checked, {} missing, {} modified",
+            report.checked,
+            report.missing.len(),
+            report.modified.len()
+        ),
+    );
+    Ok(report)
+}
+
+/// Tauri command to re-extract the Python runtime from the bundled archive.
+/// Stops the service first; start it again afterwards.
+#[tauri::command]
+async fn repair_runtime(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    let _maintenance = service
+        .maintenance()
+        .try_acquire("runtime repair")
+        .map_err(|busy| busy.to_string())?;
+    let resource_dir = app.path().resource_dir().map_err(redact::error)?;
+
+    service.stop().await.map_err(redact::error)?;
+    let result = tauri::async_runtime::spawn_blocking(move || runtime::repair(&resource_dir))
+        .await
+        .map_err(redact::error)?;
+    service.forget_verified_python().await;
+    match result {
+        Ok(()) => {
+            log_event(&app, "runtime repaired from bundled archive");
+            Ok(())
+        }
+        Err(e) => {
+            log_event(&app, &format!("runtime repair failed: {}", e));
+            Err(redact::error(e))
+        }
     }
 }
 
-/// Tauri command to get stats
+/// Most recent captured service output lines, oldest first
+#[tauri::command]
+async fn get_recent_service_logs(
+    service: State<'_, Arc<PythonService>>,
+    n: Option<usize>,
+) -> Result<Vec<LogLine>, String> {
+    Ok(service.recent_logs(n.unwrap_or(service_log::MAX_LINES)))
+}
+
+/// Tauri command to check health
 #[tauri::command]
-async fn get_stats() -> Result<String, String> {
-    let client = reqwest::Client::new();
-    match client.get("http://127.0.0.1:8000/v2/stats").send().await {
-        Ok(resp) => {
-            let text = resp.text().await.map_err(|e| e.to_string())?;
-            Ok(text)
+async fn check_health(
+    api: State<'_, Arc<ApiClient>>,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<String, String> {
+    let check = check_health_once(&api).await;
+    service.record_check(check.as_ref()).await;
+    match check {
+        Some(check) => {
+            service.set_last_health(check.response.clone()).await;
+            serde_json::to_string(&check.response).map_err(redact::error)
         }
-        Err(e) => Err(format!("Service not available: {}", e)),
+        None => Err("Service not available".to_string()),
     }
 }
 
+/// Tauri command to get token usage and estimated cost per day (None when the
+/// service doesn't track usage)
+#[tauri::command]
+async fn get_usage_summary(
+    api: State<'_, Arc<ApiClient>>,
+    range: UsageRange,
+) -> Result<Option<UsageSummary>, String> {
+    usage::fetch(&api, range, RequestOptions::default())
+        .await
+        .map_err(redact::error)
+}
+
+/// Tauri command to get the API client's in-flight and queued request counts (debugging)
+#[tauri::command]
+async fn get_api_client_stats(api: State<'_, Arc<ApiClient>>) -> Result<ApiClientStats, String> {
+    Ok(api.stats())
+}
+
+/// Tauri command to get stats, in the current layout whichever the service sent
+#[tauri::command]
+async fn get_stats(
+    api: State<'_, Arc<ApiClient>>,
+) -> Result<WithMeta<serde_json::Value>, String> {
+    let mut reply: WithMeta<serde_json::Value> = api
+        .get_json_with_meta(stats::STATS_PATH, &[])
+        .await
+        .map_err(redact::error)?;
+    stats::normalize(&mut reply.data);
+    Ok(reply)
+}
+
+/// Tauri command to get active memories per category (None when the service
+/// is too old to report them)
+#[tauri::command]
+async fn get_stats_breakdown(
+    api: State<'_, Arc<ApiClient>>,
+) -> Result<Option<StatsBreakdown>, String> {
+    let payload: serde_json::Value = api
+        .get_json_cached(stats::STATS_PATH, STATS_CACHE_MAX_AGE, RequestOptions::default())
+        .await
+        .map_err(redact::error)?;
+    Ok(stats::interpret(&payload)
+        .and_then(|stats| stats.category_counts)
+        .map(StatsBreakdown::from_counts))
+}
+
 /// Tauri command to restart service
 #[tauri::command]
 async fn restart_service(
     app: AppHandle,
     service: State<'_, Arc<PythonService>>,
 ) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    service.set_paused_for_battery(false);
     restart_service_and_wait(app, service.inner().clone()).await
 }
 
+/// Tauri command to start a stopped (or failed) service. Returns the current
+/// status without doing anything if the service is already up or starting.
+#[tauri::command]
+async fn start_service(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<ServiceStatus, String> {
+    ensure_unlocked(&app)?;
+    start_service_and_wait(app, service.inner().clone()).await
+}
+
+/// Start a stopped, failed or idle service and wait for it to become healthy;
+/// unlike a restart there is nothing to stop first
+async fn start_service_and_wait(
+    app: AppHandle,
+    service: Arc<PythonService>,
+) -> Result<ServiceStatus, String> {
+    service.maintenance().check().map_err(|busy| busy.to_string())?;
+    let status = service.get_status().await;
+    if !matches!(
+        status,
+        ServiceStatus::Stopped | ServiceStatus::Error(_) | ServiceStatus::Idle
+    ) {
+        return Ok(status);
+    }
+    if !service.try_start_restart() {
+        return Err("Restart already in progress".to_string());
+    }
+
+    service.set_paused_for_battery(false);
+    start_and_wait(app, service.clone()).await;
+    service.finish_restart();
+
+    match service.get_status().await {
+        ServiceStatus::Error(e) => Err(e),
+        status => Ok(status),
+    }
+}
+
 /// Tauri command to stop service
 #[tauri::command]
-async fn stop_service(service: State<'_, Arc<PythonService>>) -> Result<(), String> {
-    service.stop().await.map_err(|e| e.to_string())
+async fn stop_service(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    service.maintenance().check().map_err(|busy| busy.to_string())?;
+    // An explicit stop wins over the battery saver resuming later
+    service.set_paused_for_battery(false);
+    service.stop().await.map_err(redact::error)
 }
 
+/// Restart the service and wait for it to become healthy. Refused while a
+/// maintenance operation runs; those restart with `restart_and_wait` instead.
+/// Requests made while a restart runs get its result; one made within
+/// `MIN_RESTART_INTERVAL` of the last is refused.
 async fn restart_service_and_wait(
     app: AppHandle,
     service: Arc<PythonService>,
 ) -> Result<(), String> {
+    service.maintenance().check().map_err(|busy| busy.to_string())?;
+    let restarting = service.clone();
+    service
+        .restarts()
+        .run(|| restart_and_wait(app, restarting))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// `restart_service_and_wait` for callers holding the maintenance lock
+async fn restart_and_wait(app: AppHandle, service: Arc<PythonService>) -> Result<(), String> {
     if !service.try_start_restart() {
         return Err("Restart already in progress".to_string());
     }
 
-    app.emit("service-restarting", ()).ok();
+    events::emit(&app, AppEvent::ServiceRestarting);
     let result = async {
-        service.restart(&app).await.map_err(|e| e.to_string())?;
+        let generation = service.restart(&app).await.map_err(redact::error)?;
+        log_service_command(&app, &service).await;
 
-        match wait_for_health("http://127.0.0.1:8000/health", Duration::from_secs(30)).await {
-            Ok(_) => {
-                service.mark_running().await;
-                app.emit("service-ready", ()).ok();
+        let health = service
+            .wait_until_healthy(&api_client(&app), Duration::from_secs(30), generation)
+            .await;
+        match health {
+            Some(Ok(health)) => {
+                service.set_last_health(health).await;
+                if service.mark_running_for(generation).await {
+                    events::emit(&app, AppEvent::ServiceReady);
+                }
                 Ok(())
             }
-            Err(e) => {
-                let error = e.to_string();
-                service.mark_error(error.clone()).await;
-                app.emit("service-error", &error).ok();
+            Some(Err(e)) => {
+                let error = startup_error(&service, e);
+                if service.mark_error_for(generation, error.clone()).await {
+                    events::emit(&app, AppEvent::ServiceError(error.clone()));
+                }
                 Err(error)
             }
+            None => Err("Restart was superseded by a stop or another start".to_string()),
         }
     }
     .await;
@@ -141,162 +554,1784 @@ async fn restart_service_and_wait(
     result
 }
 
-// ============================================================================
-// Config Commands
-// ============================================================================
-
-/// Tauri command to set OpenAI API key (stores in macOS Keychain)
-#[tauri::command]
-async fn set_openai_key(key: String) -> Result<(), String> {
-    keychain::set_secret(keys::OPENAI_API_KEY, &key).map_err(|e| e.to_string())
+/// Shared API client from managed state
+fn api_client(app: &AppHandle) -> Arc<ApiClient> {
+    app.state::<Arc<ApiClient>>().inner().clone()
 }
 
-/// Tauri command to check if OpenAI API key is set (doesn't expose the key)
-#[tauri::command]
-async fn has_openai_key() -> bool {
-    keychain::has_secret(keys::OPENAI_API_KEY)
+/// Point the API client at the service `app_settings` describe, over TCP or
+/// the local socket
+fn connect_api_client(app: &AppHandle, app_settings: &AppSettings) {
+    let api = api_client(app);
+    let socket = if app_settings.uses_unix_socket() {
+        service::socket_path(app).ok()
+    } else {
+        None
+    };
+    api.set_unix_socket(socket);
+    api.set_base_url(&app_settings.api_url());
 }
 
-/// Tauri command to delete OpenAI API key
-#[tauri::command]
-async fn delete_openai_key() -> Result<(), String> {
-    keychain::delete_secret(keys::OPENAI_API_KEY).map_err(|e| e.to_string())
-}
+// ============================================================================
+// Search Commands
+// ============================================================================
 
-/// Tauri command to get app settings
+/// Delay before a search-as-you-type query is sent
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(250);
+const SEARCH_DEFAULT_LIMIT: u32 = 20;
+
+/// Tauri command to search memories
 #[tauri::command]
-async fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
-    settings::load(&app).map_err(|e| e.to_string())
+async fn search_memories(
+    app: AppHandle,
+    api: State<'_, Arc<ApiClient>>,
+    service: State<'_, Arc<PythonService>>,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    filters: Option<SearchFilters>,
+) -> Result<SearchOutcome, String> {
+    ensure_service_started(&app, &service, "search").await;
+    let filters = filters.unwrap_or_default();
+    let result = search::search(
+        &api,
+        &query,
+        limit.unwrap_or(SEARCH_DEFAULT_LIMIT),
+        offset.unwrap_or(0),
+        &filters,
+    )
+    .await;
+    search_outcome(result, &service).await
 }
 
-/// Tauri command to save app settings
+/// Tauri command for search-as-you-type.
+/// Debounced, and cancelled when a newer query arrives from the same window.
 #[tauri::command]
-async fn save_settings(app: AppHandle, new_settings: AppSettings) -> Result<(), String> {
-    let current_settings = settings::load(&app).unwrap_or_default();
-    if current_settings.launch_at_login != new_settings.launch_at_login {
-        set_launch_at_login(&app, new_settings.launch_at_login)?;
+#[allow(clippy::too_many_arguments)]
+async fn search_memories_live(
+    window: Window,
+    api: State<'_, Arc<ApiClient>>,
+    service: State<'_, Arc<PythonService>>,
+    generations: State<'_, SearchGenerations>,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    filters: Option<SearchFilters>,
+) -> Result<SearchOutcome, String> {
+    let filters = filters.unwrap_or_default();
+    let result = generations
+        .run(window.label(), async {
+            tokio::time::sleep(SEARCH_DEBOUNCE).await;
+            ensure_service_started(window.app_handle(), &service, "search").await;
+            search::search(
+                &api,
+                &query,
+                limit.unwrap_or(SEARCH_DEFAULT_LIMIT),
+                offset.unwrap_or(0),
+                &filters,
+            )
+            .await
+        })
+        .await;
+
+    match result {
+        Some(result) => search_outcome(result, &service).await,
+        None => Ok(SearchOutcome::Superseded),
     }
-    settings::save(&app, &new_settings).map_err(|e| e.to_string())
 }
 
-/// Tauri command to get app data directory path
-#[tauri::command]
-async fn get_data_dir(app: AppHandle) -> Result<String, String> {
-    app.path()
-        .app_data_dir()
-        .map(|p| p.to_string_lossy().to_string())
-        .map_err(|e| e.to_string())
+/// Map a search result, reporting "busy" rather than failure while the service restarts
+async fn search_outcome(
+    result: Result<(Vec<SearchHit>, u64, ResponseMeta), ApiError>,
+    service: &PythonService,
+) -> Result<SearchOutcome, String> {
+    match result {
+        Ok((hits, total, meta)) => Ok(SearchOutcome::Results { hits, total, meta }),
+        Err(ApiError::ServiceBusy) => Ok(SearchOutcome::ServiceBusy),
+        Err(ApiError::Unavailable(_))
+            if matches!(
+                service.get_status().await,
+                ServiceStatus::Starting | ServiceStatus::Restarting
+            ) =>
+        {
+            Ok(SearchOutcome::ServiceBusy)
+        }
+        Err(e) => Err(redact::error(e)),
+    }
 }
 
 // ============================================================================
-// Tray Helpers
+// Memory Commands
 // ============================================================================
 
-const TRAY_ID: &str = "main";
-const MENU_ID_STATUS: &str = "status";
-const MENU_ID_TOGGLE_WINDOW: &str = "toggle_window";
-const MENU_ID_RESTART_SERVICE: &str = "restart_service";
-const MENU_ID_MEMORY_COUNT: &str = "memory_count";
-const MENU_ID_QUIT: &str = "quit";
-const TRAY_FALLBACK_TITLE: &str = "Kiroku";
+/// Wakes the tray loop to refresh the memory count immediately
+#[derive(Clone, Default)]
+struct StatsRefresh(Arc<tokio::sync::Notify>);
 
-const TRAY_ICON_PNG: &[u8] = include_bytes!("../icons/tray-icon.png");
+fn request_stats_refresh(app: &AppHandle) {
+    api_client(app).clear_cache();
+    app.state::<StatsRefresh>().0.notify_one();
+}
 
-type AppMenuItem = MenuItem<tauri::Wry>;
+/// Wakes the outbox flusher (service ready, retry requested)
+#[derive(Clone, Default)]
+struct OutboxWake(Arc<tokio::sync::Notify>);
 
-#[derive(Clone)]
-struct TrayItems {
-    status: AppMenuItem,
-    toggle_window: AppMenuItem,
-    restart_service: AppMenuItem,
-    memory_count: AppMenuItem,
+/// Result of `capture_memory`
+#[derive(Clone, serde::Serialize)]
+struct CaptureResult {
+    client_id: String,
+    /// Stored in the outbox to be sent once the service is back
+    queued: bool,
 }
 
-#[derive(Deserialize)]
-struct StatsResponse {
-    items: StatsItems,
+/// Add a write to the outbox and refresh the tray indicator
+async fn queue_memory_write(
+    app: &AppHandle,
+    write: MemoryWrite,
+    reason: String,
+) -> Result<(), String> {
+    let outbox = app.state::<Arc<Outbox>>().inner().clone();
+    log_event(app, &format!("outbox: queued {} ({})", write.client_id, reason));
+    outbox.queue(write, Some(reason)).await.map_err(redact::error)?;
+    outbox_changed(app, &outbox).await;
+    Ok(())
 }
 
-#[derive(Deserialize)]
-struct StatsItems {
-    total: u64,
+async fn outbox_changed(app: &AppHandle, outbox: &Outbox) {
+    let status = outbox.status().await;
+    update_outbox_item(app, &status);
+    events::emit(app, AppEvent::OutboxChanged(status.clone()));
 }
 
-static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
-
-fn build_tray_menu(app: &AppHandle) -> tauri::Result<(Menu<tauri::Wry>, TrayItems)> {
-    let status_item = MenuItem::with_id(app, MENU_ID_STATUS, "Status: Starting", false, None::<&str>)?;
-    let memory_count =
-        MenuItem::with_id(app, MENU_ID_MEMORY_COUNT, "Memories: -", false, None::<&str>)?;
-    let toggle_window = MenuItem::with_id(
-        app,
-        MENU_ID_TOGGLE_WINDOW,
-        "Show Window",
-        true,
-        None::<&str>,
-    )?;
-    let restart_service = MenuItem::with_id(
-        app,
-        MENU_ID_RESTART_SERVICE,
-        "Restart Service",
-        true,
-        None::<&str>,
-    )?;
+/// Tauri command to store a new memory. Writes made while the service is down
+/// (or while earlier writes are still queued) go to the outbox instead of failing.
+#[tauri::command]
+async fn capture_memory(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    api: State<'_, Arc<ApiClient>>,
+    outbox: State<'_, Arc<Outbox>>,
+    content: String,
+    source: Option<String>,
+    metadata: Option<serde_json::Value>,
+) -> Result<CaptureResult, String> {
+    if content.trim().is_empty() {
+        return Err("Nothing to save".to_string());
+    }
+    let write = MemoryWrite::new(
+        content,
+        source.unwrap_or_else(|| "desktop".to_string()),
+        metadata,
+    );
+    let client_id = write.client_id.clone();
 
-    let menu = Menu::with_items(
-        app,
-        &[
-            &status_item,
-            &memory_count,
-            &PredefinedMenuItem::separator(app)?,
-            &toggle_window,
-            &restart_service,
-            &PredefinedMenuItem::separator(app)?,
-            &MenuItem::with_id(app, MENU_ID_QUIT, "Quit", true, None::<&str>)?,
-        ],
-    )?;
+    ensure_service_started(&app, &service, "capture").await;
+    let reason = if !service.get_status().await.is_up() {
+        "Service not running".to_string()
+    } else if outbox.status().await.pending > 0 {
+        // Keep writes in order behind the ones already queued
+        app.state::<OutboxWake>().0.notify_one();
+        "Earlier writes still queued".to_string()
+    } else {
+        match outbox::send(&api, &write).await {
+            Ok(()) => {
+                request_stats_refresh(&app);
+                return Ok(CaptureResult {
+                    client_id,
+                    queued: false,
+                });
+            }
+            Err(e @ (ApiError::Unavailable(_) | ApiError::ServiceBusy | ApiError::TimedOut(_))) => {
+                e.to_string()
+            }
+            Err(e) => return Err(redact::error(e)),
+        }
+    };
 
-    Ok((
-        menu,
-        TrayItems {
-            status: status_item,
-            toggle_window,
-            restart_service,
-            memory_count,
-        },
-    ))
+    queue_memory_write(&app, write, reason).await?;
+    Ok(CaptureResult {
+        client_id,
+        queued: true,
+    })
 }
 
-fn load_tray_icon(app: &AppHandle) -> Option<Image<'static>> {
-    if let Some(icon) = app.default_window_icon() {
-        return Some(icon.clone().to_owned());
-    }
+/// Tauri command to get recorded memory counts for charts, one point per time
+/// bucket. `range` is "24h", "7d", "30d", or "all".
+#[tauri::command]
+async fn get_stats_history(
+    history: State<'_, Arc<StatsHistory>>,
+    range: HistoryRange,
+) -> Result<Vec<StatsSample>, String> {
+    Ok(history.query(range, chrono::Utc::now().timestamp()))
+}
 
-    Image::from_bytes(TRAY_ICON_PNG).ok().map(|img| img.to_owned())
+/// Tauri command to get the number of queued and failed writes
+#[tauri::command]
+async fn get_outbox_status(outbox: State<'_, Arc<Outbox>>) -> Result<OutboxStatus, String> {
+    Ok(outbox.status().await)
 }
 
-fn ensure_log_path(app: &AppHandle) -> Option<&PathBuf> {
-    if LOG_PATH.get().is_none() {
-        if let Ok(dir) = app.path().app_data_dir() {
-            let _ = std::fs::create_dir_all(&dir);
-            let _ = LOG_PATH.set(dir.join("app.log"));
-        }
+/// Tauri command to give failed writes another round of attempts and flush now
+#[tauri::command]
+async fn retry_outbox(
+    app: AppHandle,
+    outbox: State<'_, Arc<Outbox>>,
+) -> Result<OutboxStatus, String> {
+    let moved = outbox.requeue_failed().await.map_err(redact::error)?;
+    if moved > 0 {
+        log_event(&app, &format!("outbox: requeued {} failed writes", moved));
     }
-    LOG_PATH.get()
+    app.state::<OutboxWake>().0.notify_one();
+    outbox_changed(&app, &outbox).await;
+    Ok(outbox.status().await)
 }
 
-fn log_line(path: &PathBuf, line: &str) {
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
-        let _ = writeln!(file, "{}", line);
-    }
+const DELETE_UNSUPPORTED: &str = "This service version cannot delete memories";
+
+/// Tauri command to delete a single memory. Returns how many items were removed.
+#[tauri::command]
+async fn delete_memory(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    api: State<'_, Arc<ApiClient>>,
+    id: String,
+) -> Result<u64, String> {
+    ensure_unlocked(&app)?;
+    ensure_service_started(&app, &service, "delete").await;
+    let removed = memories::delete_item(&api, &id)
+        .await
+        .map_err(redact::error)?
+        .ok_or_else(|| DELETE_UNSUPPORTED.to_string())?;
+    request_stats_refresh(&app);
+    Ok(removed as u64)
 }
 
-fn log_panic(message: &str) {
-    let path = LOG_PATH
-        .get()
-        .cloned()
-        .unwrap_or_else(|| std::env::temp_dir().join("kiroku-tauri.log"));
-    let ts = SystemTime::now()
+/// Tauri command to delete every memory. Returns how many items were removed.
+/// `confirmation` must be the current total count (as reported by get_stats).
+/// A safety backup is taken first unless `skip_backup` is set.
+#[tauri::command]
+async fn clear_all_memories(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    api: State<'_, Arc<ApiClient>>,
+    confirmation: String,
+    skip_backup: Option<bool>,
+) -> Result<u64, String> {
+    ensure_unlocked(&app)?;
+    let total = fetch_memory_count(&api)
+        .await
+        .ok_or_else(|| "Service not available".to_string())?;
+    if confirmation.trim() != total.to_string() {
+        return Err(format!(
+            "Confirmation does not match the current memory count ({})",
+            total
+        ));
+    }
+
+    let _maintenance = service
+        .maintenance()
+        .try_acquire("clear all")
+        .map_err(|busy| busy.to_string())?;
+
+    if !skip_backup.unwrap_or(false) {
+        let backup = run_backup(&app, service.inner(), None).await?;
+        log_event(&app, &format!("clear_all safety backup at {}", backup.path));
+    }
+
+    let result = clear_all_items(&app, &api, total).await;
+    if let Ok(deleted) = &result {
+        log_event(&app, &format!("clear_all removed {} memories", deleted));
+    }
+    request_stats_refresh(&app);
+    result
+}
+
+/// Tauri command to delete memories older than `older_than_days`. With
+/// `dry_run` it returns how many would be deleted and removes nothing, for a
+/// preview before confirming. Emits `prune-complete` after a real prune.
+#[tauri::command]
+async fn prune_memories(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    api: State<'_, Arc<ApiClient>>,
+    older_than_days: u32,
+    dry_run: Option<bool>,
+) -> Result<u64, String> {
+    if older_than_days == 0 {
+        return Err("Age must be at least 1 day".to_string());
+    }
+    if !service.get_status().await.is_up() {
+        return Err("Service not running".to_string());
+    }
+    let dry_run = dry_run.unwrap_or(false);
+    if !dry_run {
+        ensure_unlocked(&app)?;
+    }
+    let _maintenance = if dry_run {
+        None
+    } else {
+        Some(
+            service
+                .maintenance()
+                .try_acquire("prune")
+                .map_err(|busy| busy.to_string())?,
+        )
+    };
+
+    let count = memories::prune(&api, older_than_days, dry_run)
+        .await
+        .map_err(redact::error)?
+        .ok_or_else(|| "This service version cannot prune memories".to_string())?;
+    if !dry_run {
+        log_event(
+            &app,
+            &format!("prune removed {} memories older than {} days", count, older_than_days),
+        );
+        events::emit(
+            &app,
+            AppEvent::PruneComplete(PruneComplete {
+                older_than_days,
+                deleted: count,
+            }),
+        );
+        request_stats_refresh(&app);
+    }
+    Ok(count)
+}
+
+async fn clear_all_items(app: &AppHandle, api: &ApiClient, total: u64) -> Result<u64, String> {
+    let deleted = memories::bulk_delete(api)
+        .await
+        .map_err(redact::error)?
+        .ok_or_else(|| DELETE_UNSUPPORTED.to_string())?;
+    events::emit(app, AppEvent::ClearProgress(ClearProgress { deleted, total }));
+    Ok(deleted)
+}
+
+// ============================================================================
+// Backup Commands
+// ============================================================================
+
+/// Tauri command to report disk usage of the data directory (best effort for large stores)
+#[tauri::command]
+async fn get_data_dir_size(app: AppHandle) -> Result<DataDirSize, String> {
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    tokio::task::spawn_blocking(move || storage::data_dir_size(&data_dir, storage::SCAN_TIME_LIMIT))
+        .await
+        .map_err(redact::error)
+}
+
+/// Tauri command to get free space on the data directory's volume, against the
+/// `min_free_disk_mb` threshold (None when it can't be determined)
+#[tauri::command]
+async fn get_disk_space(app: AppHandle) -> Result<Option<DiskSpace>, String> {
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    let min_free_mb = settings::current(&app).min_free_disk_mb;
+    tokio::task::spawn_blocking(move || storage::disk_space(&data_dir, min_free_mb))
+        .await
+        .map_err(redact::error)
+}
+
+/// Tauri command to back up the memory database. With a passphrase the backup
+/// is encrypted; it is kept in the keychain only with `remember_passphrase`,
+/// and a remembered one also encrypts scheduled and safety backups.
+#[tauri::command]
+async fn create_backup(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    passphrase: Option<String>,
+    remember_passphrase: Option<bool>,
+) -> Result<BackupInfo, String> {
+    if let Some(passphrase) = &passphrase {
+        if passphrase.chars().count() < backup_crypto::MIN_PASSPHRASE_LEN {
+            return Err(format!(
+                "Backup passphrase must be at least {} characters",
+                backup_crypto::MIN_PASSPHRASE_LEN
+            ));
+        }
+        redact::register_secret(passphrase);
+        if remember_passphrase.unwrap_or(false) {
+            keychain::set_secret(keys::BACKUP_PASSPHRASE, passphrase).map_err(redact::error)?;
+        }
+    }
+    let _maintenance = service
+        .maintenance()
+        .try_acquire("backup")
+        .map_err(|busy| busy.to_string())?;
+    run_backup(&app, service.inner(), passphrase).await
+}
+
+/// Tauri command to drop a remembered backup passphrase; later backups are
+/// unencrypted unless one is given
+#[tauri::command]
+async fn forget_backup_passphrase() -> Result<(), String> {
+    keychain::delete_secret(keys::BACKUP_PASSPHRASE).map_err(redact::error)
+}
+
+/// Tauri command to list backups in the backup folder, newest first, marking
+/// the encrypted ones
+#[tauri::command]
+async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    let backup_root = settings::current(&app)
+        .backup_dir()
+        .unwrap_or_else(|| backup::default_backup_root(&data_dir));
+    if !backup_root.exists() {
+        return Ok(Vec::new());
+    }
+    tokio::task::spawn_blocking(move || backup::list_backups(&backup_root))
+        .await
+        .map_err(redact::error)?
+        .map_err(redact::error)
+}
+
+/// The passphrase the user asked to remember, if any
+fn remembered_backup_passphrase() -> Option<String> {
+    match keychain::get_secret(keys::BACKUP_PASSPHRASE) {
+        Ok(passphrase) => passphrase,
+        Err(e) => {
+            eprintln!("[Backup] Failed to read the remembered passphrase: {}", redact::error(e));
+            None
+        }
+    }
+}
+
+/// Stop the local service, copy its database, and bring it back up. Without
+/// a passphrase a remembered one is used, if any. Callers must hold the
+/// maintenance lock.
+async fn run_backup(
+    app: &AppHandle,
+    service: &Arc<PythonService>,
+    passphrase: Option<String>,
+) -> Result<BackupInfo, String> {
+    let passphrase = passphrase.or_else(remembered_backup_passphrase);
+    if service.is_remote() {
+        return Err("Backups are only available for the local service".to_string());
+    }
+    if !service.try_start_restart() {
+        return Err("Restart already in progress".to_string());
+    }
+
+    let result = async {
+        let data_dir = service::get_data_dir(app).map_err(redact::error)?;
+        let was_running = !service.get_status().await.is_stopped();
+        if was_running {
+            service.stop().await.map_err(redact::error)?;
+        }
+
+        let app_settings = settings::load(app).unwrap_or_default();
+        let backup_root = app_settings
+            .backup_dir()
+            .unwrap_or_else(|| backup::default_backup_root(&data_dir));
+        let retention = app_settings.backup_retention as usize;
+        let result = tokio::task::spawn_blocking(move || {
+            let info = backup::create_backup(&data_dir, &backup_root, passphrase.as_deref())?;
+            // A failed prune doesn't fail the backup itself
+            let pruned = if retention > 0 {
+                backup::prune_backups(&backup_root, retention)
+            } else {
+                Ok(Vec::new())
+            };
+            Ok::<_, anyhow::Error>((info, pruned))
+        })
+        .await
+        .map_err(redact::error)
+        .and_then(|r| r.map_err(redact::error));
+
+        if was_running {
+            start_and_wait(app.clone(), service.clone()).await;
+        }
+        let (info, pruned) = result?;
+        match pruned {
+            Ok(paths) => {
+                for path in paths {
+                    log_event(app, &format!("backup pruned {}", path.display()));
+                }
+            }
+            Err(e) => log_event(app, &format!("backup pruning failed: {}", e)),
+        }
+        Ok(info)
+    }
+    .await;
+
+    service.finish_restart();
+    match &result {
+        Ok(info) => log_event(app, &format!("backup created at {}", info.path)),
+        Err(e) => log_event(app, &format!("backup failed: {}", e)),
+    }
+    result
+}
+
+/// Take a backup every `backup_interval_hours`. Skips a tick (and retries on the next)
+/// while the service is unhealthy or another restart/maintenance task is running.
+async fn backup_scheduler(app: AppHandle, service: Arc<PythonService>) {
+    const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+    let mut last_backup = std::time::Instant::now();
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let Some(hours) = settings::current(&app).backup_interval_hours else {
+            continue;
+        };
+        if last_backup.elapsed() < Duration::from_secs(hours * 60 * 60) {
+            continue;
+        }
+        if service.is_remote()
+            // Also skipped when degraded: a backup on a nearly full disk makes it worse
+            || service.get_status().await != ServiceStatus::Running
+            || check_health_once(&api_client(&app)).await.is_none()
+        {
+            log_event(&app, "scheduled backup skipped: service not healthy");
+            continue;
+        }
+        let _maintenance = match service.maintenance().try_acquire("scheduled backup") {
+            Ok(guard) => guard,
+            Err(busy) => {
+                log_event(&app, &format!("scheduled backup skipped: {}", busy));
+                continue;
+            }
+        };
+
+        match run_backup(&app, &service, None).await {
+            Ok(info) => {
+                last_backup = std::time::Instant::now();
+                events::emit(&app, AppEvent::BackupComplete(info));
+            }
+            Err(e) => {
+                // Don't retry every tick; wait for the next interval
+                last_backup = std::time::Instant::now();
+                events::emit(&app, AppEvent::BackupFailed(e));
+            }
+        }
+    }
+}
+
+/// Tauri command to restore the memory database from a backup folder.
+/// Rolls back to the current database if the restored one doesn't come up healthy.
+/// Encrypted backups need `passphrase` (or a remembered one), checked before
+/// the service is stopped.
+#[tauri::command]
+async fn restore_backup(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    backup_dir: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    if service.is_remote() {
+        return Err("Restoring is only available for the local service".to_string());
+    }
+    let backup_path = PathBuf::from(backup_dir);
+    let passphrase = if backup::is_encrypted(&backup_path) {
+        let passphrase = passphrase
+            .or_else(remembered_backup_passphrase)
+            .ok_or_else(|| "This backup is encrypted; enter its passphrase".to_string())?;
+        redact::register_secret(&passphrase);
+        let file = backup_path.join(backup_crypto::ENCRYPTED_FILE);
+        let check = passphrase.clone();
+        tokio::task::spawn_blocking(move || backup_crypto::verify_passphrase(&file, &check))
+            .await
+            .map_err(redact::error)?
+            .map_err(redact::error)?;
+        Some(passphrase)
+    } else {
+        None
+    };
+    let _maintenance = service
+        .maintenance()
+        .try_acquire("restore")
+        .map_err(|busy| busy.to_string())?;
+    if !service.try_start_restart() {
+        return Err("Restart already in progress".to_string());
+    }
+
+    let result = run_restore(&app, service.inner(), backup_path, passphrase).await;
+    service.finish_restart();
+    result
+}
+
+async fn run_restore(
+    app: &AppHandle,
+    service: &Arc<PythonService>,
+    backup_path: PathBuf,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    let data_dir = service::get_data_dir(app).map_err(redact::error)?;
+    service.stop().await.map_err(redact::error)?;
+    log_event(app, &format!("restore from {}", backup_path.display()));
+
+    let restore_dir = data_dir.clone();
+    let restored = tokio::task::spawn_blocking(move || {
+        backup::restore_backup(&restore_dir, &backup_path, passphrase.as_deref())
+    })
+        .await
+        .map_err(redact::error)
+        .and_then(|r| r.map_err(redact::error));
+    let aside = match restored {
+        Ok(aside) => aside,
+        Err(e) => {
+            // Nothing was replaced; bring the original database back up
+            start_and_wait(app.clone(), service.clone()).await;
+            return Err(e);
+        }
+    };
+
+    let healthy = match service.start(app).await {
+        Ok(generation) => {
+            log_service_command(app, service).await;
+            service
+                .wait_until_healthy(&api_client(app), Duration::from_secs(30), generation)
+                .await
+                .unwrap_or_else(|| Err(anyhow::anyhow!("the service was stopped while starting")))
+                .map(|health| (generation, health))
+                .map_err(redact::error)
+        }
+        Err(e) => Err(redact::error(e)),
+    };
+    match healthy {
+        Ok((generation, health)) => {
+            service.set_last_health(health).await;
+            if service.mark_running_for(generation).await {
+                events::emit(app, AppEvent::ServiceReady);
+            }
+            if let Some(aside) = aside {
+                if let Err(e) = std::fs::remove_dir_all(&aside) {
+                    log_event(app, &format!("restore: failed to remove {}: {}", aside.display(), e));
+                }
+            }
+            log_event(app, "restore succeeded");
+            Ok(())
+        }
+        Err(error) => {
+            log_event(app, &format!("restore unhealthy, rolling back: {}", error));
+            let _ = service.stop().await;
+            let rollback_dir = data_dir.clone();
+            tokio::task::spawn_blocking(move || {
+                backup::rollback_restore(&rollback_dir, aside.as_deref())
+            })
+            .await
+            .map_err(redact::error)?
+            .map_err(|e| format!("Restore failed ({}) and rollback failed: {}", error, e))?;
+
+            start_and_wait(app.clone(), service.clone()).await;
+            Err(format!(
+                "Restored data did not start healthy, rolled back: {}",
+                error
+            ))
+        }
+    }
+}
+
+// ============================================================================
+// Profile Commands
+// ============================================================================
+
+/// Tauri command to list memory profiles
+#[tauri::command]
+async fn get_profiles(app: AppHandle) -> Result<ProfileList, String> {
+    let app_settings = settings::load_effective(&app).map_err(redact::error)?;
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    Ok(profiles::list(&data_dir, &app_settings.active_profile))
+}
+
+/// Tauri command to switch to another profile, creating it if needed.
+/// The service is restarted against the profile's store if it was running.
+#[tauri::command]
+async fn switch_profile(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    name: String,
+) -> Result<ProfileList, String> {
+    ensure_unlocked(&app)?;
+    profiles::validate_name(&name)?;
+    if let Some(profile) = &app.state::<StartupOverrides>().profile {
+        return Err(format!(
+            "Profile is set to '{}' by --profile for this session",
+            profile
+        ));
+    }
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    if app_settings.is_remote() {
+        return Err("Profiles are only available for the local service".to_string());
+    }
+
+    let _maintenance = service
+        .maintenance()
+        .try_acquire("profile switch")
+        .map_err(|busy| busy.to_string())?;
+
+    if app_settings.active_profile != name {
+        println!(
+            "[Tauri] Switching profile {} -> {}",
+            app_settings.active_profile, name
+        );
+        log_event(&app, &format!("switch profile to {}", name));
+        app_settings.active_profile = name;
+        settings::save(&app, &app_settings).map_err(redact::error)?;
+
+        if !service.get_status().await.is_stopped() {
+            restart_and_wait(app.clone(), service.inner().clone()).await?;
+        }
+    }
+
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    Ok(profiles::list(&data_dir, &app_settings.active_profile))
+}
+
+// ============================================================================
+// Update Commands
+// ============================================================================
+
+/// Check GitHub releases for a newer version. Cached for 6 hours unless `force`.
+#[tauri::command]
+async fn check_for_updates(
+    app: AppHandle,
+    checker: State<'_, UpdateChecker>,
+    force: Option<bool>,
+) -> Result<UpdateInfo, String> {
+    let channel = settings::load(&app).unwrap_or_default().release_channel;
+    let info = checker
+        .check(channel, force.unwrap_or(false))
+        .await
+        .map_err(redact::error)?;
+    if info.update_available {
+        show_update_item(&app, &info);
+    }
+    Ok(info)
+}
+
+// ============================================================================
+// Onboarding Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_onboarding_state(app: AppHandle) -> Result<OnboardingState, String> {
+    Ok(settings::load(&app).map_err(redact::error)?.onboarding)
+}
+
+/// Record a finished wizard step (e.g. "api_key", "launch_at_login", "data_location")
+#[tauri::command]
+async fn mark_onboarding_step(app: AppHandle, step: String) -> Result<OnboardingState, String> {
+    let step = step.trim();
+    if step.is_empty() {
+        return Err("Step name must not be empty".to_string());
+    }
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    if !app_settings.onboarding.steps_done.iter().any(|s| s == step) {
+        app_settings.onboarding.steps_done.push(step.to_string());
+        settings::save(&app, &app_settings).map_err(redact::error)?;
+    }
+    Ok(app_settings.onboarding)
+}
+
+/// Finish the wizard; starts the service unless it is started manually or has run already
+#[tauri::command]
+async fn complete_onboarding(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<OnboardingState, String> {
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    app_settings.onboarding.completed = true;
+    app_settings.onboarding.completed_version = updates::CURRENT_VERSION.to_string();
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    log_event(&app, "onboarding completed");
+
+    if app_settings.service_start_mode != ServiceStartMode::Manual && !service.has_started() {
+        let app_handle = app.clone();
+        let service = service.inner().clone();
+        tauri::async_runtime::spawn(async move {
+            start_and_wait(app_handle, service).await;
+        });
+    }
+    Ok(app_settings.onboarding)
+}
+
+// ============================================================================
+// Uninstall Commands
+// ============================================================================
+
+/// Remove the app's traces before it is dragged to the Trash.
+/// Data is moved to the Trash, never hard-deleted, and needs a confirmation string.
+#[tauri::command]
+async fn prepare_uninstall(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    options: UninstallOptions,
+) -> Result<UninstallReport, String> {
+    ensure_unlocked(&app)?;
+    if options.remove_data && options.confirmation != uninstall::REMOVE_DATA_CONFIRMATION {
+        return Err(format!(
+            "Type \"{}\" to confirm removing all data",
+            uninstall::REMOVE_DATA_CONFIRMATION
+        ));
+    }
+    let _maintenance = service
+        .maintenance()
+        .try_acquire("uninstall")
+        .map_err(|busy| busy.to_string())?;
+    let home = app.path().home_dir().map_err(redact::error)?;
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+
+    log_event(&app, &format!("prepare uninstall {:?}", options));
+    service.stop().await.map_err(redact::error)?;
+    let mut report = UninstallReport::default();
+
+    if options.remove_launch_agent {
+        #[cfg(target_os = "macos")]
+        report.record("launch agent", remove_launch_agent(&app));
+        #[cfg(any(windows, target_os = "linux"))]
+        report.record("startup entry", set_launch_at_login(&app, false));
+    }
+    if options.remove_keychain_secrets {
+        for key in keys::ALL {
+            report.record(
+                &format!("keychain secret {}", key),
+                keychain::delete_secret(key).map_err(redact::error),
+            );
+        }
+    }
+    if options.remove_logs {
+        let mut logs = vec![std::env::temp_dir().join("kiroku-tauri.log")];
+        logs.extend(ensure_log_path(&app).cloned());
+        for log in logs.into_iter().filter(|path| path.exists()) {
+            report.record(
+                &log.display().to_string(),
+                std::fs::remove_file(&log).map_err(redact::error),
+            );
+        }
+    }
+    if options.remove_data {
+        report.record(
+            &format!("data directory {}", data_dir.display()),
+            uninstall::move_to_trash(&home, &data_dir).map(|_| ()),
+        );
+    }
+
+    // Not written to app.log, which may just have been removed
+    println!(
+        "[Uninstall] removed={:?} failed={:?}",
+        report.removed, report.failed
+    );
+    Ok(report)
+}
+
+/// Delete the LaunchAgent plist and unload it from launchd
+#[cfg(target_os = "macos")]
+fn remove_launch_agent(app: &AppHandle) -> Result<(), String> {
+    use std::os::unix::fs::MetadataExt;
+
+    set_launch_at_login(app, false)?;
+
+    // Booting out the job that launched us would kill the app before it can report back;
+    // with the plist gone it will not be loaded again at the next login anyway.
+    if std::env::var_os(start_visibility::TRAY_ONLY_ENV).is_some() {
+        return Ok(());
+    }
+    let home = app.path().home_dir().map_err(redact::error)?;
+    let uid = std::fs::metadata(&home).map_err(redact::error)?.uid();
+    // Fails harmlessly when the agent is not loaded
+    let _ = std::process::Command::new("launchctl")
+        .args(["bootout", &format!("gui/{}/com.kiroku.memory", uid)])
+        .status();
+    Ok(())
+}
+
+/// Reset settings (including onboarding) to defaults and restart the service.
+/// That means the default profile on the bundled SurrealDB service; memories
+/// are kept, in every profile's store and under the data directory override.
+#[tauri::command]
+async fn reset_to_factory(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    // Checked before saving so a refused restart can't leave the new settings
+    // on disk with the old profile still being served
+    service.maintenance().check().map_err(|busy| busy.to_string())?;
+    let current_settings = settings::load(&app).unwrap_or_default();
+    let defaults = AppSettings {
+        data_dir_override: current_settings.data_dir_override.clone(),
+        ..AppSettings::default()
+    };
+
+    if current_settings.launch_at_login {
+        if let Err(e) = set_launch_at_login(&app, false) {
+            eprintln!("[Tauri] Failed to disable launch at login: {}", e);
+        }
+    }
+    settings::save(&app, &defaults).map_err(redact::error)?;
+    log_event(
+        &app,
+        &format!(
+            "settings reset to factory defaults (profile {} -> {}, backend {} -> {})",
+            current_settings.active_profile,
+            defaults.active_profile,
+            current_settings.backend,
+            defaults.backend
+        ),
+    );
+
+    let overrides = app.state::<StartupOverrides>();
+    let mut current_effective = current_settings.clone();
+    overrides.apply(&mut current_effective);
+    let mut effective = defaults.clone();
+    overrides.apply(&mut effective);
+    connect_api_client(&app, &effective);
+    if current_effective.api_url() != effective.api_url() {
+        events::emit(&app, AppEvent::ServiceBaseUrlChanged(effective.api_url()));
+    }
+    service.set_remote(effective.is_remote());
+    service.set_auto_restart(defaults.auto_restart);
+    service.set_paused_for_battery(false);
+    let changed = settings_watch::changed_keys(&current_settings, &defaults);
+    if !changed.is_empty() {
+        events::emit(&app, AppEvent::SettingsChanged(changed));
+    }
+    restart_service_and_wait(app, service.inner().clone()).await
+}
+
+// ============================================================================
+// Locale Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_available_locales(app: AppHandle) -> Result<LocaleList, String> {
+    let app_settings = settings::load(&app).map_err(redact::error)?;
+    Ok(locale::list(&app_settings.locale))
+}
+
+/// Change the UI language ("auto" follows the OS); the tray updates immediately
+#[tauri::command]
+async fn set_locale(app: AppHandle, locale: String) -> Result<LocaleList, String> {
+    ensure_unlocked(&app)?;
+    if !locale::is_supported(&locale) {
+        return Err(format!("Unsupported locale '{}'", locale));
+    }
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    app_settings.locale = locale;
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    apply_locale(&app, &app_settings.locale).await;
+    Ok(locale::list(&app_settings.locale))
+}
+
+// ============================================================================
+// Config Commands
+// ============================================================================
+
+/// Tauri command to set OpenAI API key (stores in macOS Keychain).
+/// Starts the service if it was held back for lack of a key.
+#[tauri::command]
+async fn set_openai_key(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    key: String,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    redact::register_secret(&key);
+    keychain::set_secret(keys::OPENAI_API_KEY, &key).map_err(redact::error)?;
+
+    let status = service.get_status().await;
+    if matches!(&status, ServiceStatus::Error(e) if e == service::MISSING_KEY_ERROR) {
+        let service = service.inner().clone();
+        tauri::async_runtime::spawn(start_and_wait(app, service));
+    }
+    Ok(())
+}
+
+/// Tauri command to check if OpenAI API key is set (doesn't expose the key)
+#[tauri::command]
+async fn has_openai_key() -> bool {
+    keychain::has_secret(keys::OPENAI_API_KEY)
+}
+
+/// Tauri command to tell a missing OpenAI key from one the Keychain won't
+/// hand out (locked, or access denied)
+#[tauri::command]
+async fn get_key_status() -> KeychainStatus {
+    keychain::secret_status(keys::OPENAI_API_KEY)
+}
+
+/// Tauri command to keep the Dock icon while in the tray (macOS only), saved as
+/// `show_in_dock`
+#[tauri::command]
+async fn set_dock_visibility(app: AppHandle, visible: bool) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    let mut app_settings = settings::load(&app).unwrap_or_default();
+    app_settings.show_in_dock = visible;
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    log_event(&app, &format!("show_in_dock={}", visible));
+    apply_dock_setting(&app, visible);
+    Ok(())
+}
+
+/// Tauri command to keep the main window above other apps, saved as
+/// `always_on_top`
+#[tauri::command]
+async fn set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    let mut app_settings = settings::load(&app).unwrap_or_default();
+    app_settings.always_on_top = enabled;
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    log_event(&app, &format!("always_on_top={}", enabled));
+    apply_always_on_top(&app, enabled);
+    Ok(())
+}
+
+fn apply_always_on_top(app: &AppHandle, enabled: bool) {
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.set_always_on_top(enabled) {
+            eprintln!("[Tauri] Failed to set always on top: {}", e);
+        }
+    }
+    if let Some(tray) = app.try_state::<TrayItems>() {
+        let _ = tray.always_on_top.set_checked(enabled);
+    }
+}
+
+/// Some window managers drop the level while the window is hidden
+fn reapply_always_on_top(app: &AppHandle, window: &tauri::WebviewWindow) {
+    if settings::current(app).always_on_top {
+        let _ = window.set_always_on_top(true);
+    }
+}
+
+/// Tauri command to get the bearer token for direct requests to the service
+/// (None unless `require_auth` is on)
+#[tauri::command]
+async fn get_service_auth_token(app: AppHandle) -> Result<Option<String>, String> {
+    let settings = settings::load_effective(&app).map_err(redact::error)?;
+    if !settings.require_auth {
+        return Ok(None);
+    }
+    service::service_api_token().map(Some).map_err(redact::error)
+}
+
+/// Tauri command to delete OpenAI API key
+#[tauri::command]
+async fn delete_openai_key(app: AppHandle) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    keychain::delete_secret(keys::OPENAI_API_KEY).map_err(redact::error)
+}
+
+/// Refuse a guarded command while locked mode is on
+fn ensure_unlocked(app: &AppHandle) -> Result<(), String> {
+    if settings::current(app).locked {
+        return Err(lock::LOCKED_ERROR.to_string());
+    }
+    Ok(())
+}
+
+/// Tauri command to turn on locked mode; `unlock` needs the same PIN
+#[tauri::command]
+async fn lock(app: AppHandle, pin: String) -> Result<(), String> {
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    if app_settings.locked {
+        return Ok(());
+    }
+    lock::validate_pin(&pin)?;
+    keychain::set_secret(keys::LOCK_PIN, &lock::hash_pin(&pin)).map_err(redact::error)?;
+    app_settings.locked = true;
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    if let Some(tray) = app.try_state::<TrayItems>() {
+        refresh_restart_enabled(&app, &tray);
+    }
+    log_event(&app, "locked");
+    Ok(())
+}
+
+/// Tauri command to turn off locked mode. Without a stored PIN (locked by
+/// editing settings.json) any PIN unlocks.
+#[tauri::command]
+async fn unlock(app: AppHandle, pin: String) -> Result<(), String> {
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    if !app_settings.locked {
+        return Ok(());
+    }
+    if let Some(stored) = keychain::get_secret(keys::LOCK_PIN).map_err(redact::error)? {
+        if !lock::verify_pin(&stored, &pin) {
+            // Slow down guessing
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            return Err("Incorrect PIN".to_string());
+        }
+    }
+    app_settings.locked = false;
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+    if let Some(tray) = app.try_state::<TrayItems>() {
+        refresh_restart_enabled(&app, &tray);
+    }
+    if let Err(e) = keychain::delete_secret(keys::LOCK_PIN) {
+        eprintln!("[Tauri] Failed to remove the lock PIN: {}", e);
+    }
+    log_event(&app, "unlocked");
+    Ok(())
+}
+
+/// A store left under an earlier build's identifier that hasn't been migrated
+/// or declined yet. Remote services don't use the local data dir.
+fn pending_legacy_data(app: &AppHandle, app_settings: &AppSettings) -> Option<legacy::LegacyData> {
+    if app_settings.is_remote() || app_settings.legacy_migration != LegacyMigration::Pending {
+        return None;
+    }
+    let app_data_dir = app.path().app_data_dir().ok()?;
+    let data_dir = service::get_data_dir(app).ok()?;
+    legacy::find(&app_data_dir, &data_dir)
+}
+
+/// Copy the OpenAI key from the earlier build's Keychain service unless one is set
+fn migrate_legacy_key(identifier: &str) {
+    if keychain::has_secret(keys::OPENAI_API_KEY) {
+        return;
+    }
+    match keychain::get_legacy_secret(identifier, keys::OPENAI_API_KEY) {
+        Ok(Some(key)) => {
+            redact::register_secret(&key);
+            if let Err(e) = keychain::set_secret(keys::OPENAI_API_KEY, &key) {
+                eprintln!("[Tauri] Failed to store the migrated OpenAI key: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("[Tauri] Failed to read the OpenAI key of {}: {}", identifier, e),
+    }
+}
+
+/// Tauri command to get the earlier build's data offered by `legacy-data-found`
+/// (None once migrated or declined)
+#[tauri::command]
+async fn get_legacy_data(app: AppHandle) -> Result<Option<legacy::LegacyData>, String> {
+    let app_settings = settings::load_effective(&app).map_err(redact::error)?;
+    Ok(pending_legacy_data(&app, &app_settings))
+}
+
+/// Tauri command to answer `legacy-data-found`: copy the earlier build's store
+/// and OpenAI key when `confirm`, otherwise decline. Either way it isn't offered
+/// again, the legacy copy stays where it is, and the service start held back at
+/// launch goes ahead.
+#[tauri::command]
+async fn migrate_legacy_data(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    confirm: bool,
+) -> Result<(), String> {
+    let mut app_settings = settings::load(&app).map_err(redact::error)?;
+    if app_settings.legacy_migration != LegacyMigration::Pending {
+        return Ok(());
+    }
+    let effective = settings::load_effective(&app).map_err(redact::error)?;
+    if confirm {
+        let status = service.get_status().await;
+        if !(status.is_stopped() || matches!(status, ServiceStatus::Error(_))) {
+            return Err("Stop the service before migrating data".to_string());
+        }
+        let data = pending_legacy_data(&app, &effective)
+            .ok_or_else(|| "No data from an earlier version was found".to_string())?;
+        let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+        let legacy_dir = PathBuf::from(&data.path);
+        let copied = tokio::task::spawn_blocking(move || legacy::copy_store(&legacy_dir, &data_dir))
+            .await
+            .map_err(redact::error)?
+            .map_err(redact::error)?;
+        migrate_legacy_key(&data.identifier);
+        log_event(
+            &app,
+            &format!("legacy data migrated from {} ({} bytes)", data.identifier, copied),
+        );
+        app_settings.legacy_migration = LegacyMigration::Completed;
+    } else {
+        log_event(&app, "legacy data migration declined");
+        app_settings.legacy_migration = LegacyMigration::Declined;
+    }
+    settings::save(&app, &app_settings).map_err(redact::error)?;
+
+    match effective.service_start_mode {
+        ServiceStartMode::OnAppLaunch => {
+            let service = service.inner().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_service_and_wait(app, service).await {
+                    eprintln!("[Tauri] Failed to start after migration: {}", e);
+                }
+            });
+        }
+        ServiceStartMode::OnFirstUse => {
+            if service.get_status().await == ServiceStatus::Stopped {
+                service.mark_idle().await;
+            }
+        }
+        ServiceStartMode::Manual => {}
+    }
+    Ok(())
+}
+
+/// Tauri command to get app settings
+#[tauri::command]
+async fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
+    settings::load(&app).map_err(redact::error)
+}
+
+/// Tauri command to get the names and TypeScript payload types of app events
+#[tauri::command]
+async fn get_event_schema() -> Result<events::EventSchema, String> {
+    Ok(events::schema())
+}
+
+/// Tauri command to save app settings
+#[tauri::command]
+async fn save_settings(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    mut new_settings: AppSettings,
+) -> Result<(), String> {
+    ensure_unlocked(&app)?;
+    new_settings.validate()?;
+    if let Some(dir) = new_settings.data_dir_override() {
+        service::ensure_writable_dir(&dir)?;
+    }
+    let current_settings = settings::load(&app).unwrap_or_default();
+    // Only lock and unlock change this
+    new_settings.locked = current_settings.locked;
+    new_settings.legacy_migration = current_settings.legacy_migration;
+    // Owned by the onboarding commands, so a stale settings form can't reopen the wizard
+    new_settings.onboarding = current_settings.onboarding.clone();
+    if current_settings.launch_at_login != new_settings.launch_at_login {
+        set_launch_at_login(&app, new_settings.launch_at_login)?;
+    }
+    settings::save(&app, &new_settings).map_err(redact::error)?;
+    #[cfg(target_os = "macos")]
+    if let Err(e) = update_launch_agent(&app, &current_settings, &new_settings) {
+        eprintln!("[Settings] Failed to update LaunchAgent: {}", e);
+    }
+    apply_settings_change(&app, service.inner(), &current_settings, &new_settings).await;
+    Ok(())
+}
+
+/// Tauri command to write settings, onboarding state and profile names to
+/// `path` for setting up another machine. Secrets are never included.
+#[tauri::command]
+async fn export_settings(app: AppHandle, path: String) -> Result<(), String> {
+    let path = PathBuf::from(path);
+    settings_transfer::check_export_path(&path)?;
+    let app_settings = settings::load(&app).map_err(redact::error)?;
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    let profiles = profiles::list(&data_dir, &app_settings.active_profile).profiles;
+    let document = settings_transfer::export(&app_settings, profiles, updates::CURRENT_VERSION);
+    let content = serde_json::to_string_pretty(&document).map_err(redact::error)?;
+    std::fs::write(&path, content).map_err(redact::error)?;
+    log_event(&app, &format!("settings exported to {}", path.display()));
+    Ok(())
+}
+
+/// Tauri command to apply a settings export. With `merge`, settings it
+/// doesn't mention are kept; otherwise they reset to defaults. The document
+/// is checked in full before anything is saved.
+#[tauri::command]
+async fn import_settings(
+    app: AppHandle,
+    service: State<'_, Arc<PythonService>>,
+    path: String,
+    merge: bool,
+) -> Result<settings_transfer::ImportReport, String> {
+    ensure_unlocked(&app)?;
+    let content = std::fs::read_to_string(&path).map_err(redact::error)?;
+    let current_settings = settings::load(&app).unwrap_or_default();
+    let data_dir = service::get_data_dir(&app).map_err(redact::error)?;
+    let profiles = profiles::list(&data_dir, &current_settings.active_profile).profiles;
+    let import = settings_transfer::prepare_import(&content, &current_settings, merge, &profiles)?;
+    let (mut new_settings, mut warnings) = (import.settings, import.warnings);
+    if let Some(dir) = new_settings.data_dir_override() {
+        service::ensure_writable_dir(&dir)?;
+    }
+    if current_settings.launch_at_login != new_settings.launch_at_login {
+        if let Err(e) = set_launch_at_login(&app, new_settings.launch_at_login) {
+            warnings.push(format!("Launch at login was not changed: {}", e));
+            new_settings.launch_at_login = current_settings.launch_at_login;
+        }
+    }
+
+    settings::save(&app, &new_settings).map_err(redact::error)?;
+    #[cfg(target_os = "macos")]
+    if let Err(e) = update_launch_agent(&app, &current_settings, &new_settings) {
+        warnings.push(format!("Launch at login still uses the old port and data directory: {}", e));
+    }
+    let report = settings_transfer::ImportReport {
+        changed: settings_watch::changed_keys(&current_settings, &new_settings),
+        restart_required: !current_settings
+            .restart_required_changes(&new_settings)
+            .is_empty(),
+        warnings,
+    };
+    log_event(
+        &app,
+        &format!("settings imported from {}: {}", path, report.changed.join(", ")),
+    );
+    apply_settings_change(&app, service.inner(), &current_settings, &new_settings).await;
+    Ok(report)
+}
+
+/// Apply saved settings that take effect without a relaunch. `current` is what
+/// was saved before.
+async fn apply_settings_change(
+    app: &AppHandle,
+    service: &Arc<PythonService>,
+    current_settings: &AppSettings,
+    new_settings: &AppSettings,
+) {
+    service.set_auto_restart(new_settings.auto_restart);
+    let restart_required = current_settings.restart_required_changes(new_settings);
+    if !restart_required.is_empty() {
+        log_event(
+            app,
+            &format!("settings applied after a service restart: {}", restart_required.join(", ")),
+        );
+    }
+    let warnings = new_settings.warnings();
+    if !warnings.is_empty() && warnings != current_settings.warnings() {
+        for warning in &warnings {
+            log_event(app, &format!("settings warning: {}", warning));
+        }
+        events::emit(app, AppEvent::SettingsWarning(warnings));
+    }
+    if current_settings.locale != new_settings.locale {
+        apply_locale(app, &new_settings.locale).await;
+    }
+    if current_settings.show_pending_badge != new_settings.show_pending_badge
+        || current_settings.show_count_in_menubar != new_settings.show_count_in_menubar
+    {
+        request_stats_refresh(app);
+    }
+    if current_settings.tray_left_click_action != new_settings.tray_left_click_action {
+        if let Some(tray) = app.tray_by_id(TRAY_ID) {
+            let _ = tray.set_show_menu_on_left_click(
+                new_settings.tray_left_click_action == TrayClickAction::OpenMenu,
+            );
+        }
+    }
+    if current_settings.tray_icon_style != new_settings.tray_icon_style {
+        refresh_tray_icon(app);
+    }
+    if current_settings.stop_service_on_quit != new_settings.stop_service_on_quit {
+        if let Some(tray) = app.try_state::<TrayItems>() {
+            update_quit_label(&tray, new_settings.stop_service_on_quit);
+        }
+    }
+    if current_settings.log_level != new_settings.log_level {
+        apply_log_level(app, new_settings.log_level);
+    }
+    if current_settings.show_in_dock != new_settings.show_in_dock {
+        apply_dock_setting(app, new_settings.show_in_dock);
+    }
+    if current_settings.always_on_top != new_settings.always_on_top {
+        apply_always_on_top(app, new_settings.always_on_top);
+    }
+
+    // Switching between local and remote service: reconnect if the service was in use
+    // Compare what the session actually uses, so --port keeps winning
+    let overrides = app.state::<StartupOverrides>();
+    let (mut current_effective, mut new_effective) = (current_settings.clone(), new_settings.clone());
+    overrides.apply(&mut current_effective);
+    overrides.apply(&mut new_effective);
+    let model_change = !new_effective.is_remote()
+        && current_settings.embedding_model() != new_settings.embedding_model();
+    if model_change {
+        let model = new_settings.embedding_model().unwrap_or("the service default");
+        log_event(app, &format!("embedding model changed to {}", model));
+        events::emit(
+            app,
+            AppEvent::SettingsWarning(vec![format!(
+                "The embedding model is now {}; existing memories may need re-embedding before search finds them reliably",
+                model
+            )]),
+        );
+    }
+    if current_effective.api_url() != new_effective.api_url() {
+        let base_url = new_effective.api_url();
+        connect_api_client(app, &new_effective);
+        events::emit(app, AppEvent::ServiceBaseUrlChanged(base_url.clone()));
+        service.set_remote(new_settings.is_remote());
+        // A new port only matters to a process that is actually listening
+        let port_change = !current_effective.is_remote()
+            && !new_effective.is_remote()
+            && current_effective.service_port != new_effective.service_port;
+        let restart = if port_change {
+            service.is_running().await
+        } else {
+            !service.get_status().await.is_stopped()
+        };
+        if port_change && restart {
+            log_event(
+                app,
+                &format!(
+                    "service port changed {} -> {}, restarting",
+                    current_effective.service_port, new_effective.service_port
+                ),
+            );
+        }
+        if restart {
+            let app_handle = app.clone();
+            let service = service.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = restart_service_and_wait(app_handle, service).await {
+                    eprintln!("[Tauri] Failed to switch backend: {}", e);
+                }
+            });
+        }
+    } else if model_change && service.is_running().await {
+        // Embeddings from different models aren't comparable, so don't keep
+        // the old model running until the next launch
+        let app_handle = app.clone();
+        let service = service.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = restart_service_and_wait(app_handle, service).await {
+                eprintln!("[Tauri] Failed to restart for the new embedding model: {}", e);
+            }
+        });
+    }
+}
+
+/// The app data, memory data and backup directories
+fn private_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = app.path().app_data_dir().into_iter().collect();
+    if let Ok(data_dir) = service::get_data_dir(app) {
+        let backup_root = settings::current(app)
+            .backup_dir()
+            .unwrap_or_else(|| backup::default_backup_root(&data_dir));
+        dirs.extend([data_dir, backup_root]);
+    }
+    dirs.dedup();
+    dirs
+}
+
+/// Tauri command to list app directories other users can read
+#[tauri::command]
+async fn get_permission_issues(app: AppHandle) -> Result<Vec<permissions::PermissionIssue>, String> {
+    Ok(permissions::check(&private_dirs(&app)))
+}
+
+/// Tauri command to restrict the app's directories to the current user.
+/// Returns the ones that were open to others.
+#[tauri::command]
+async fn fix_permissions(app: AppHandle) -> Result<Vec<permissions::PermissionIssue>, String> {
+    let fixed = permissions::fix(&private_dirs(&app)).map_err(redact::error)?;
+    for issue in &fixed {
+        log_event(&app, &format!("permissions: restricted {} (was {})", issue.path, issue.mode));
+    }
+    Ok(fixed)
+}
+
+/// Tauri command to get the effective data directory path
+#[tauri::command]
+async fn get_data_dir(app: AppHandle) -> Result<String, String> {
+    service::get_data_dir(&app)
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(redact::error)
+}
+
+// ============================================================================
+// Tray Helpers
+// ============================================================================
+
+const TRAY_ID: &str = "main";
+/// Label of the secondary settings window
+const SETTINGS_WINDOW_LABEL: &str = "settings";
+
+const MENU_ID_STATUS: &str = "status";
+const MENU_ID_TOGGLE_WINDOW: &str = "toggle_window";
+const MENU_ID_RESTART_SERVICE: &str = "restart_service";
+const MENU_ID_ALWAYS_ON_TOP: &str = "always_on_top";
+const MENU_ID_MEMORY_COUNT: &str = "memory_count";
+const MENU_ID_UPTIME: &str = "uptime";
+const MENU_ID_LAST_RESTART: &str = "last_restart";
+const MENU_ID_UPDATE: &str = "update_available";
+const MENU_ID_BREAKDOWN: &str = "breakdown";
+const MENU_ID_OUTBOX: &str = "outbox_pending";
+const MENU_ID_USAGE: &str = "usage_month";
+const MENU_ID_OPEN_DASHBOARD: &str = "open_dashboard";
+const MENU_ID_COPY_API_URL: &str = "copy_api_url";
+const MENU_ID_SETTINGS: &str = "settings";
+const MENU_ID_QUIT: &str = "quit";
+const MENU_ID_VERSION: &str = "version";
+const TRAY_FALLBACK_TITLE: &str = "Kiroku";
+
+type AppMenuItem = MenuItem<tauri::Wry>;
+
+#[derive(Clone)]
+struct TrayItems {
+    status: AppMenuItem,
+    toggle_window: AppMenuItem,
+    always_on_top: CheckMenuItem<tauri::Wry>,
+    restart_service: AppMenuItem,
+    memory_count: AppMenuItem,
+    open_dashboard: AppMenuItem,
+    copy_api_url: AppMenuItem,
+    settings: AppMenuItem,
+    quit: AppMenuItem,
+    /// Only inserted into `menu` once an update is found
+    update: AppMenuItem,
+    /// Only in `menu` while writes are waiting in the outbox
+    outbox: AppMenuItem,
+    /// Only in `menu` while the service reports API usage
+    usage: AppMenuItem,
+    /// Only in `menu` while the service reports per-category counts
+    breakdown: Submenu<tauri::Wry>,
+    /// Only in `menu` while the service is up
+    uptime: AppMenuItem,
+    last_restart: AppMenuItem,
+    menu: Menu<tauri::Wry>,
+}
+
+/// `/v2/stats` responses are shared by the tray and the dashboard for this long
+const STATS_CACHE_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Categories listed in the tray before the rest are rolled up into "Other"
+const BREAKDOWN_TRAY_LIMIT: usize = 8;
+
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Filters window events before they reach app.log (see `window_log`)
+#[derive(Default)]
+struct WindowLog(std::sync::Mutex<WindowEventFilter>);
+
+fn apply_log_level(app: &AppHandle, level: LogLevel) {
+    events::set_log_level(level);
+    if let Some(window_log) = app.try_state::<WindowLog>() {
+        window_log.0.lock().unwrap().set_firehose(level == LogLevel::Trace);
+    }
+}
+
+fn log_window_event(window: &Window, event: &tauri::WindowEvent) {
+    let app = window.app_handle();
+    let Some(window_log) = app.try_state::<WindowLog>() else {
+        return;
+    };
+    let kind = match event {
+        tauri::WindowEvent::CloseRequested { .. }
+        | tauri::WindowEvent::Destroyed
+        | tauri::WindowEvent::ThemeChanged(_)
+        | tauri::WindowEvent::ScaleFactorChanged { .. } => WindowEventKind::Always,
+        tauri::WindowEvent::Focused(focused) => WindowEventKind::Focused(*focused),
+        tauri::WindowEvent::Moved(position) => WindowEventKind::Moved(position.x, position.y),
+        tauri::WindowEvent::Resized(size) => WindowEventKind::Resized(size.width, size.height),
+        _ => WindowEventKind::Noise,
+    };
+    let label = window.label().to_string();
+    let verdict = window_log
+        .0
+        .lock()
+        .unwrap()
+        .record(&label, kind, std::time::Instant::now());
+    match verdict {
+        Verdict::Log => log_event(app, &format!("window event {} {:?}", label, event)),
+        Verdict::Summary(line) => log_event(app, &line),
+        Verdict::FlushAfter(delay) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let line = app
+                    .state::<WindowLog>()
+                    .0
+                    .lock()
+                    .unwrap()
+                    .flush(&label, std::time::Instant::now());
+                if let Some(line) = line {
+                    log_event(&app, &line);
+                }
+            });
+        }
+        Verdict::Drop => {}
+    }
+}
+
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<(Menu<tauri::Wry>, TrayItems)> {
+    let status_item = MenuItem::with_id(
+        app,
+        MENU_ID_STATUS,
+        tr("tray.status.starting"),
+        false,
+        None::<&str>,
+    )?;
+    let memory_count = MenuItem::with_id(
+        app,
+        MENU_ID_MEMORY_COUNT,
+        tr("tray.memories.unknown"),
+        false,
+        None::<&str>,
+    )?;
+    let toggle_window = MenuItem::with_id(
+        app,
+        MENU_ID_TOGGLE_WINDOW,
+        tr("tray.show_window"),
+        true,
+        None::<&str>,
+    )?;
+    let always_on_top = CheckMenuItem::with_id(
+        app,
+        MENU_ID_ALWAYS_ON_TOP,
+        tr("tray.always_on_top"),
+        true,
+        settings::current(app).always_on_top,
+        None::<&str>,
+    )?;
+    let restart_service = MenuItem::with_id(
+        app,
+        MENU_ID_RESTART_SERVICE,
+        tr("tray.restart_service"),
+        true,
+        None::<&str>,
+    )?;
+    let open_dashboard = MenuItem::with_id(
+        app,
+        MENU_ID_OPEN_DASHBOARD,
+        tr("tray.open_dashboard"),
+        false,
+        None::<&str>,
+    )?;
+    let copy_api_url = MenuItem::with_id(
+        app,
+        MENU_ID_COPY_API_URL,
+        tr("tray.copy_api_url"),
+        true,
+        None::<&str>,
+    )?;
+    let update = MenuItem::with_id(
+        app,
+        MENU_ID_UPDATE,
+        tr_args("tray.update_available", &[("version", "")]),
+        true,
+        None::<&str>,
+    )?;
+    let outbox = MenuItem::with_id(
+        app,
+        MENU_ID_OUTBOX,
+        tr_args("tray.outbox_pending", &[("count", "0")]),
+        false,
+        None::<&str>,
+    )?;
+    let usage = MenuItem::with_id(
+        app,
+        MENU_ID_USAGE,
+        tr_args("tray.usage_month", &[("cost", "")]),
+        false,
+        None::<&str>,
+    )?;
+    let settings = MenuItem::with_id(
+        app,
+        MENU_ID_SETTINGS,
+        tr("tray.settings"),
+        true,
+        None::<&str>,
+    )?;
+    let quit = MenuItem::with_id(app, MENU_ID_QUIT, tr("tray.quit"), true, None::<&str>)?;
+    let version = MenuItem::with_id(
+        app,
+        MENU_ID_VERSION,
+        app_info::menu_label(),
+        false,
+        None::<&str>,
+    )?;
+    let breakdown = Submenu::with_id(app, MENU_ID_BREAKDOWN, tr("tray.breakdown"), true)?;
+    let uptime = MenuItem::with_id(app, MENU_ID_UPTIME, "", false, None::<&str>)?;
+    let last_restart = MenuItem::with_id(app, MENU_ID_LAST_RESTART, "", false, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status_item,
+            &memory_count,
+            &PredefinedMenuItem::separator(app)?,
+            &toggle_window,
+            &always_on_top,
+            &restart_service,
+            &PredefinedMenuItem::separator(app)?,
+            &open_dashboard,
+            &copy_api_url,
+            &PredefinedMenuItem::separator(app)?,
+            &settings,
+            &quit,
+            &PredefinedMenuItem::separator(app)?,
+            &version,
+        ],
+    )?;
+
+    Ok((
+        menu.clone(),
+        TrayItems {
+            status: status_item,
+            toggle_window,
+            always_on_top,
+            restart_service,
+            memory_count,
+            open_dashboard,
+            copy_api_url,
+            settings,
+            quit,
+            update,
+            outbox,
+            usage,
+            breakdown,
+            uptime,
+            last_restart,
+            menu,
+        },
+    ))
+}
+
+fn load_tray_icon(app: &AppHandle) -> Option<Image<'static>> {
+    tray_icon_image(app).map(|(icon, _)| icon)
+}
+
+/// The tray icon for the `tray_icon_style` setting and the system theme, and
+/// whether it is a template image
+fn tray_icon_image(app: &AppHandle) -> Option<(Image<'static>, bool)> {
+    let style = settings::current(app).tray_icon_style;
+    // The main window follows the system theme, so it stands in for it
+    let theme = app
+        .get_webview_window("main")
+        .and_then(|window| window.theme().ok());
+    let variant = tray_icon::select(style, theme);
+
+    // The app icon is colored and never a template, so only Color may use it
+    if variant == TrayIconVariant::Color {
+        if let Some(icon) = app.default_window_icon() {
+            return Some((icon.clone().to_owned(), false));
+        }
+    }
+    Image::from_bytes(variant.png())
+        .ok()
+        .map(|img| (img.to_owned(), variant.is_template()))
+}
+
+/// Re-pick the tray icon after the style setting or the system theme changed
+fn refresh_tray_icon(app: &AppHandle) {
+    let (Some(tray), Some((icon, template))) = (app.tray_by_id(TRAY_ID), tray_icon_image(app))
+    else {
+        return;
+    };
+    let _ = tray.set_icon(Some(icon));
+    let _ = tray.set_icon_as_template(template);
+}
+
+fn ensure_log_path(app: &AppHandle) -> Option<&PathBuf> {
+    if LOG_PATH.get().is_none() {
+        if let Ok(dir) = app.path().app_data_dir() {
+            let _ = permissions::ensure_private_dir(&dir);
+            let _ = LOG_PATH.set(dir.join("app.log"));
+        }
+    }
+    LOG_PATH.get()
+}
+
+fn log_line(path: &PathBuf, line: &str) {
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{}", redact::redact(line));
+    }
+}
+
+fn log_panic(message: &str) {
+    let path = LOG_PATH
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("kiroku-tauri.log"));
+    let ts = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs_f64())
         .unwrap_or(0.0);
@@ -304,193 +2339,1161 @@ fn log_panic(message: &str) {
     log_line(&path, &line);
 }
 
-fn log_event(app: &AppHandle, message: &str) {
-    let Some(path) = ensure_log_path(app) else {
+fn log_event(app: &AppHandle, message: &str) {
+    let Some(path) = ensure_log_path(app) else {
+        return;
+    };
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    let line = format!("{:.3} {}", ts, message);
+    log_line(path, &line);
+}
+
+/// Show a native notification unless they are turned off; failures are only logged
+fn notify(app: &AppHandle, body: &str) {
+    if settings::current(app).notifications_enabled {
+        show_notification(app, body);
+    }
+}
+
+/// Show a native notification even when they are turned off, for replies to
+/// something the user just did
+fn show_notification(app: &AppHandle, body: &str) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("Kiroku Memory")
+        .body(body)
+        .show()
+    {
+        eprintln!("[Tauri] Failed to show notification: {}", e);
+    }
+}
+
+fn update_tray_status(tray: &TrayItems, status: &ServiceStatus) {
+    let key = match status {
+        ServiceStatus::Starting => "tray.status.starting",
+        ServiceStatus::Running => "tray.status.running",
+        ServiceStatus::Stopped => "tray.status.stopped",
+        ServiceStatus::Restarting => "tray.status.restarting",
+        ServiceStatus::Error(_) => "tray.status.error",
+        ServiceStatus::Idle => "tray.status.idle",
+        ServiceStatus::Degraded(_) => "tray.status.degraded",
+    };
+    let _ = tray.status.set_text(tr(key));
+}
+
+fn update_restart_label(tray: &TrayItems, status: &ServiceStatus) {
+    let key = match status {
+        ServiceStatus::Running
+        | ServiceStatus::Starting
+        | ServiceStatus::Restarting
+        | ServiceStatus::Degraded(_) => "tray.restart_service",
+        ServiceStatus::Stopped | ServiceStatus::Error(_) | ServiceStatus::Idle => {
+            "tray.start_service"
+        }
+    };
+    let _ = tray.restart_service.set_text(tr(key));
+}
+
+/// A remote service is not ours to restart, and locked mode refuses restarts
+fn refresh_restart_enabled(app: &AppHandle, tray: &TrayItems) {
+    let enabled =
+        !app.state::<Arc<PythonService>>().is_remote() && !settings::current(app).locked;
+    let _ = tray.restart_service.set_enabled(enabled);
+}
+
+fn update_memory_count(tray: &TrayItems, count: Option<u64>) {
+    let label = match count {
+        Some(v) => tr_args("tray.memories", &[("count", &v.to_string())]),
+        None => tr("tray.memories.unknown").to_string(),
+    };
+    let _ = tray.memory_count.set_text(label);
+}
+
+/// Add the "Update available" item below the memory count, once
+fn show_update_item(app: &AppHandle, info: &UpdateInfo) {
+    let Some(tray) = app.try_state::<TrayItems>() else {
+        return;
+    };
+    let _ = tray
+        .update
+        .set_text(tr_args("tray.update_available", &[("version", &info.latest)]));
+    if tray.menu.get(MENU_ID_UPDATE).is_none() {
+        let _ = tray.menu.insert(&tray.update, 2);
+        log_event(app, &format!("update available: v{}", info.latest));
+    }
+}
+
+/// Show "N pending" under the memory count while the outbox is non-empty
+fn update_outbox_item(app: &AppHandle, status: &OutboxStatus) {
+    let Some(tray) = app.try_state::<TrayItems>() else {
+        return;
+    };
+    let listed = tray.menu.get(MENU_ID_OUTBOX).is_some();
+    if status.pending > 0 {
+        let _ = tray.outbox.set_text(tr_args(
+            "tray.outbox_pending",
+            &[("count", &status.pending.to_string())],
+        ));
+        if !listed {
+            let _ = tray.menu.insert(&tray.outbox, 2);
+        }
+    } else if listed {
+        let _ = tray.menu.remove(&tray.outbox);
+    }
+}
+
+/// Uptime and last restart texts, or None to take both out of the menu
+fn service_timing_texts(service: &PythonService, status: &ServiceStatus) -> Option<(String, String)> {
+    if !status.is_up() {
+        return None;
+    }
+    let uptime = service.uptime()?;
+    let last_restart = match service.last_restart_at() {
+        Some(at) => {
+            let time = chrono::DateTime::from_timestamp(at, 0)?
+                .with_timezone(&chrono::Local)
+                .format("%H:%M")
+                .to_string();
+            tr_args("tray.last_restart", &[("time", &time)])
+        }
+        None => tr("tray.last_restart.never").to_string(),
+    };
+    Some((
+        tr_args("tray.uptime", &[("uptime", &humanize::elapsed(uptime))]),
+        last_restart,
+    ))
+}
+
+/// List the uptime and last restart under the memory count, or take them out of
+/// the menu
+fn update_service_timing(tray: &TrayItems, texts: Option<&(String, String)>) {
+    let listed = tray.menu.get(MENU_ID_UPTIME).is_some();
+    match texts {
+        Some((uptime, last_restart)) => {
+            let _ = tray.uptime.set_text(uptime);
+            let _ = tray.last_restart.set_text(last_restart);
+            if !listed {
+                let _ = tray.menu.insert(&tray.uptime, 2);
+                let _ = tray.menu.insert(&tray.last_restart, 3);
+            }
+        }
+        None if listed => {
+            let _ = tray.menu.remove(&tray.uptime);
+            let _ = tray.menu.remove(&tray.last_restart);
+        }
+        None => {}
+    }
+}
+
+/// Show this month's estimated spend, or take the item out of the menu when
+/// the service doesn't track usage
+fn update_usage_item(tray: &TrayItems, cost_usd: Option<f64>) {
+    let listed = tray.menu.get(MENU_ID_USAGE).is_some();
+    match cost_usd {
+        Some(cost) => {
+            let _ = tray
+                .usage
+                .set_text(tr_args("tray.usage_month", &[("cost", &usage::format_cost(cost))]));
+            if !listed {
+                let _ = tray.menu.insert(&tray.usage, 2);
+            }
+        }
+        None if listed => {
+            let _ = tray.menu.remove(&tray.usage);
+        }
+        None => {}
+    }
+}
+
+/// List the per-category counts in the Breakdown submenu, or take it out of
+/// the menu when there are none to show
+fn update_breakdown(app: &AppHandle, tray: &TrayItems, breakdown: Option<&StatsBreakdown>) {
+    let listed = tray.menu.get(MENU_ID_BREAKDOWN).is_some();
+    let Some(breakdown) = breakdown.filter(|b| !b.categories.is_empty()) else {
+        if listed {
+            let _ = tray.menu.remove(&tray.breakdown);
+        }
+        return;
+    };
+
+    while let Ok(Some(_)) = tray.breakdown.remove_at(0) {}
+    let (top, other) = breakdown.top(BREAKDOWN_TRAY_LIMIT);
+    let mut lines: Vec<String> = top
+        .iter()
+        .map(|c| format!("{}: {}", c.category, memories::format_count(c.count)))
+        .collect();
+    if let Some(other) = other {
+        lines.push(tr_args(
+            "tray.breakdown.other",
+            &[("count", &memories::format_count(other))],
+        ));
+    }
+    for line in lines {
+        if let Ok(item) = MenuItem::new(app, line, false, None::<&str>) {
+            let _ = tray.breakdown.append(&item);
+        }
+    }
+    if !listed {
+        let _ = tray.menu.insert(&tray.breakdown, 2);
+    }
+}
+
+fn update_toggle_label(tray: &TrayItems, is_visible: bool) {
+    let key = if is_visible { "tray.bring_to_front" } else { "tray.show_window" };
+    let _ = tray.toggle_window.set_text(tr(key));
+}
+
+/// Quit says when it will leave the service running
+fn update_quit_label(tray: &TrayItems, stop_service_on_quit: bool) {
+    let key = if stop_service_on_quit {
+        "tray.quit"
+    } else {
+        "tray.quit_leave_running"
+    };
+    let _ = tray.quit.set_text(tr(key));
+}
+
+/// Re-apply every tray label in the current locale
+async fn refresh_tray_texts(app: &AppHandle) {
+    let Some(tray) = app.try_state::<TrayItems>().map(|t| t.inner().clone()) else {
+        return;
+    };
+    let status = app.state::<Arc<PythonService>>().get_status().await;
+    update_tray_status(&tray, &status);
+    update_restart_label(&tray, &status);
+    // The next stats poll fills in the count
+    update_memory_count(&tray, None);
+    request_stats_refresh(app);
+    if let Some(window) = app.get_webview_window("main") {
+        update_toggle_label(&tray, window.is_visible().unwrap_or(false));
+    }
+    let _ = tray.always_on_top.set_text(tr("tray.always_on_top"));
+    let _ = tray.open_dashboard.set_text(tr("tray.open_dashboard"));
+    let _ = tray.copy_api_url.set_text(tr("tray.copy_api_url"));
+    let _ = tray.settings.set_text(tr("tray.settings"));
+    update_quit_label(&tray, settings::current(app).stop_service_on_quit);
+    let _ = tray.breakdown.set_text(tr("tray.breakdown"));
+    if let Some(info) = app.state::<UpdateChecker>().last().await {
+        let _ = tray
+            .update
+            .set_text(tr_args("tray.update_available", &[("version", &info.latest)]));
+    }
+    if let Some(outbox) = app.try_state::<Arc<Outbox>>() {
+        update_outbox_item(app, &outbox.status().await);
+    }
+}
+
+/// Switch the native UI language and refresh the tray
+async fn apply_locale(app: &AppHandle, setting: &str) {
+    locale::set_current(locale::resolve(setting));
+    log_event(app, &format!("locale set to {}", locale::current()));
+    refresh_tray_texts(app).await;
+}
+
+fn refresh_toggle_label(app: &AppHandle, tray: &TrayItems, close_guard: &Arc<AtomicBool>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let visible = window.is_visible().unwrap_or(false);
+        close_guard.store(!visible, Ordering::SeqCst);
+        update_toggle_label(tray, visible);
+    }
+}
+
+fn toggle_main_window(app: &AppHandle, tray: &TrayItems, close_guard: &Arc<AtomicBool>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let visible = window.is_visible().unwrap_or(false);
+        if visible {
+            // 視窗可見但可能被遮住，帶到前景
+            let _ = window.set_focus();
+        } else {
+            // 視窗隱藏，恢復 Dock 可見性並顯示視窗
+            #[cfg(target_os = "macos")]
+            show_dock_icon(app);
+            let _ = window.show();
+            reapply_always_on_top(app, &window);
+            let _ = window.set_focus();
+            close_guard.store(false, Ordering::SeqCst);
+            update_toggle_label(tray, true);
+        }
+    }
+}
+
+/// Focus the settings window, creating it on first use. Unlike main it is
+/// closed for real, so it never lingers hidden.
+fn show_settings_window(app: &AppHandle) -> tauri::Result<()> {
+    // Accessory apps can't take focus, so surface the Dock icon first
+    #[cfg(target_os = "macos")]
+    show_dock_icon(app);
+
+    if let Some(window) = app.get_webview_window(SETTINGS_WINDOW_LABEL) {
+        let _ = window.unminimize();
+        window.show()?;
+        return window.set_focus();
+    }
+
+    let window = tauri::WebviewWindowBuilder::new(
+        app,
+        SETTINGS_WINDOW_LABEL,
+        tauri::WebviewUrl::App("settings".into()),
+    )
+    .title(tr("settings.title"))
+    .inner_size(480.0, 560.0)
+    .resizable(false)
+    .maximizable(false)
+    .center()
+    .build()?;
+    window.set_focus()
+}
+
+/// Go back to tray-only mode once no window is left on screen
+#[cfg(target_os = "macos")]
+fn restore_accessory_policy(app: &AppHandle) {
+    let main_visible = app
+        .get_webview_window("main")
+        .is_some_and(|w| w.is_visible().unwrap_or(false));
+    if !main_visible {
+        hide_dock_icon(app);
+    }
+}
+
+/// Debug builds only: keep the Dock icon regardless of `show_in_dock`
+const DOCK_VISIBLE_ENV: &str = "KIROKU_DOCK_VISIBLE";
+
+/// Whether the Dock icon stays while no window is on screen: the `show_in_dock`
+/// setting, or DOCK_VISIBLE_ENV in debug builds
+fn keep_dock_icon(app: &AppHandle) -> bool {
+    settings::current(app).show_in_dock
+        || (cfg!(debug_assertions) && std::env::var_os(DOCK_VISIBLE_ENV).is_some())
+}
+
+#[cfg(target_os = "macos")]
+fn show_dock_icon(app: &AppHandle) {
+    let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
+    let _ = app.set_dock_visibility(true);
+}
+
+/// Go tray-only (Accessory) unless the Dock icon is kept
+#[cfg(target_os = "macos")]
+fn hide_dock_icon(app: &AppHandle) {
+    if keep_dock_icon(app) {
         return;
+    }
+    let _ = app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+    let _ = app.set_dock_visibility(false);
+}
+
+/// Switching to Regular while no window is on screen leaves the app inactive,
+/// so it doesn't come forward or show its menu bar until activated
+#[cfg(target_os = "macos")]
+fn activate_app() {
+    use objc::runtime::{Class, Object, YES};
+    use objc::{msg_send, sel, sel_impl};
+
+    unsafe {
+        if let Some(class) = Class::get("NSApplication") {
+            let ns_app: *mut Object = msg_send![class, sharedApplication];
+            let _: () = msg_send![ns_app, activateIgnoringOtherApps: YES];
+        }
+    }
+}
+
+/// Apply a changed `show_in_dock`: turning it on brings the main window
+/// forward, turning it off hides the Dock icon unless a window is on screen
+fn apply_dock_setting(app: &AppHandle, visible: bool) {
+    #[cfg(target_os = "macos")]
+    {
+        let handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            if !visible {
+                restore_accessory_policy(&handle);
+                return;
+            }
+            show_dock_icon(&handle);
+            activate_app();
+            if let Some(window) = handle.get_webview_window("main") {
+                let _ = window.show();
+                reapply_always_on_top(&handle, &window);
+                let _ = window.set_focus();
+                if let Some(tray) = handle.try_state::<TrayItems>() {
+                    update_toggle_label(&tray, true);
+                }
+            }
+        });
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, visible);
+}
+
+/// Animate window shrinking to tray icon position then hide
+#[cfg(target_os = "macos")]
+async fn animate_minimize_to_tray(window: Window, app: &AppHandle) {
+    use tauri::PhysicalPosition;
+
+    // Get current window position and size
+    let Ok(current_pos) = window.outer_position() else { return };
+    let Ok(current_size) = window.outer_size() else { return };
+
+    // Target position: tray icon's actual position
+    let (target_x, target_y) = if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Ok(Some(rect)) = tray.rect() {
+            let pos = rect.position.to_physical::<i32>(1.0);
+            let size = rect.size.to_physical::<u32>(1.0);
+            // Target: center of tray icon
+            (pos.x + (size.width as i32 / 2), pos.y + (size.height as i32 / 2))
+        } else {
+            // Fallback: top-right corner
+            let screen_width = window.current_monitor().ok().flatten()
+                .map(|m| m.size().width as i32 - 100)
+                .unwrap_or(1000);
+            (screen_width, 25)
+        }
+    } else {
+        (1000, 25)
     };
-    let ts = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs_f64())
-        .unwrap_or(0.0);
-    let line = format!("{:.3} {}", ts, message);
-    log_line(path, &line);
+
+    // Animation parameters
+    let steps = 12;
+    let step_duration = Duration::from_millis(16);  // ~60fps
+
+    for i in 1..=steps {
+        let progress = i as f64 / steps as f64;
+        // Ease-out curve for smoother animation
+        let eased = 1.0 - (1.0 - progress).powi(3);
+
+        // Interpolate position
+        let new_x = current_pos.x + ((target_x - current_pos.x) as f64 * eased) as i32;
+        let new_y = current_pos.y + ((target_y - current_pos.y) as f64 * eased) as i32;
+
+        // Interpolate size (shrink to 10% of original)
+        let scale = 1.0 - (eased * 0.9);
+        let new_width = (current_size.width as f64 * scale) as u32;
+        let new_height = (current_size.height as f64 * scale) as u32;
+
+        let _ = window.set_position(PhysicalPosition::new(new_x, new_y));
+        let _ = window.set_size(tauri::PhysicalSize::new(new_width.max(50), new_height.max(50)));
+
+        tokio::time::sleep(step_duration).await;
+    }
+
+    // Hide window and restore original size for next show
+    let _ = window.hide();
+    let _ = window.set_position(current_pos);
+    let _ = window.set_size(current_size);
+
+    // Hide Dock icon when window is minimized to tray
+    hide_dock_icon(app);
+}
+
+#[cfg(not(target_os = "macos"))]
+async fn animate_minimize_to_tray(window: Window, _app: &AppHandle) {
+    let _ = window.hide();
+}
+
+fn handle_tray_icon_event(
+    app: &AppHandle,
+    event: TrayIconEvent,
+    tray: &TrayItems,
+    close_guard: &Arc<AtomicBool>,
+) {
+    match event {
+        TrayIconEvent::Click {
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            ..
+        } => match settings::current(app).tray_left_click_action {
+            // The menu itself is shown by the tray (show_menu_on_left_click)
+            TrayClickAction::OpenMenu => {}
+            TrayClickAction::ToggleWindow => {
+                let focused = app
+                    .get_webview_window("main")
+                    .is_some_and(|w| {
+                        w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false)
+                    });
+                if focused {
+                    hide_main_window(app, tray, close_guard);
+                } else {
+                    toggle_main_window(app, tray, close_guard);
+                }
+            }
+            TrayClickAction::QuickCapture => {
+                toggle_main_window(app, tray, close_guard);
+                events::emit(app, AppEvent::QuickCapture);
+            }
+        },
+        TrayIconEvent::DoubleClick {
+            button: MouseButton::Left,
+            ..
+        } => {
+            toggle_main_window(app, tray, close_guard);
+        }
+        _ => {}
+    }
+}
+
+/// Send the main window to the tray, as closing it does
+fn hide_main_window(app: &AppHandle, tray: &TrayItems, close_guard: &Arc<AtomicBool>) {
+    let Some(window) = app.get_webview_window("main").map(|w| w.as_ref().window()) else {
+        return;
+    };
+    close_guard.store(true, Ordering::SeqCst);
+    update_toggle_label(tray, false);
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        animate_minimize_to_tray(window, &app_handle).await;
+    });
+}
+
+fn handle_tray_menu_event(
+    app: &AppHandle,
+    event: tauri::menu::MenuEvent,
+    tray: &TrayItems,
+    is_quitting: &Arc<AtomicBool>,
+    close_guard: &Arc<AtomicBool>,
+) {
+    log_event(
+        app,
+        &format!("tray menu event id={}", event.id().as_ref()),
+    );
+    match event.id().as_ref() {
+        MENU_ID_TOGGLE_WINDOW => {
+            toggle_main_window(app, tray, close_guard);
+        }
+        MENU_ID_ALWAYS_ON_TOP => {
+            // The item already toggled its own check mark
+            let enabled = tray.always_on_top.is_checked().unwrap_or(false);
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = set_always_on_top(app_handle.clone(), enabled).await {
+                    eprintln!("[Tray] Failed to change always on top: {}", e);
+                    // Refused (e.g. locked): put the check mark back
+                    if let Some(tray) = app_handle.try_state::<TrayItems>() {
+                        let _ = tray.always_on_top.set_checked(!enabled);
+                    }
+                }
+            });
+        }
+        MENU_ID_RESTART_SERVICE => {
+            if app.state::<Arc<PythonService>>().is_remote() {
+                return;
+            }
+            // The item is disabled while locked, but a click may race the lock
+            if let Err(e) = ensure_unlocked(app) {
+                eprintln!("[Tray] Not restarting the service: {}", e);
+                return;
+            }
+            let app_handle = app.clone();
+            let service = app.state::<Arc<PythonService>>().inner().clone();
+            // The item reads "Start Service" while nothing is running
+            tauri::async_runtime::spawn(async move {
+                let status = service.get_status().await;
+                if status.is_stopped() || matches!(status, ServiceStatus::Error(_)) {
+                    if let Err(e) = start_service_and_wait(app_handle, service).await {
+                        eprintln!("[Tray] Failed to start service: {}", e);
+                    }
+                } else if let Err(e) = restart_service_and_wait(app_handle, service).await {
+                    eprintln!("[Tray] Failed to restart service: {}", e);
+                }
+            });
+        }
+        MENU_ID_OPEN_DASHBOARD => {
+            if let Some(socket) = api_client(app).unix_socket() {
+                eprintln!("[Tray] No dashboard in a browser: the service listens on {:?}", socket);
+                return;
+            }
+            let url = format!("{}/docs", api_client(app).base_url());
+            if let Err(e) = app.opener().open_url(url, None::<&str>) {
+                eprintln!("[Tray] Failed to open dashboard: {}", e);
+            }
+        }
+        MENU_ID_COPY_API_URL => {
+            // Over a socket, its path is what clients need (e.g. curl --unix-socket)
+            let api = api_client(app);
+            let url = match api.unix_socket() {
+                Some(socket) => socket.display().to_string(),
+                None => api.base_url(),
+            };
+            match app.clipboard().write_text(url.clone()) {
+                Ok(()) => notify(app, &tr_args("tray.copied_api_url", &[("url", &url)])),
+                Err(e) => eprintln!("[Tray] Failed to copy API URL: {}", e),
+            }
+        }
+        MENU_ID_UPDATE => {
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(info) = app_handle.state::<UpdateChecker>().last().await else {
+                    return;
+                };
+                let url = info.download_url.unwrap_or(info.release_notes_url);
+                if let Err(e) = app_handle.opener().open_url(url, None::<&str>) {
+                    eprintln!("[Tray] Failed to open download URL: {}", e);
+                }
+            });
+        }
+        MENU_ID_SETTINGS => {
+            if let Err(e) = show_settings_window(app) {
+                eprintln!("[Tray] Failed to open settings: {}", e);
+            }
+        }
+        MENU_ID_QUIT => {
+            request_quit(app.clone(), is_quitting.clone());
+        }
+        _ => {}
+    }
+}
+
+async fn fetch_stats(api: &ApiClient) -> Option<ServiceStats> {
+    let payload: serde_json::Value = api
+        .get_json_cached(stats::STATS_PATH, STATS_CACHE_MAX_AGE, RequestOptions::background())
+        .await
+        .ok()?;
+    stats::interpret(&payload)
+}
+
+/// Append a polled stats response to the chart history
+fn record_stats_sample(app: &AppHandle, stats: &ServiceStats) {
+    let Some(history) = app.try_state::<Arc<StatsHistory>>() else {
+        return;
+    };
+    let sample = StatsSample {
+        ts: chrono::Utc::now().timestamp(),
+        total: stats.items.total,
+        active: stats.items.active,
+        archived: stats.items.archived,
+        categories: stats.categories,
+    };
+    if let Err(e) = history.record(sample) {
+        log_event(app, &format!("stats history: {}", e));
+    }
+}
+
+async fn fetch_memory_count(api: &ApiClient) -> Option<u64> {
+    fetch_stats(api).await.map(|stats| stats.items.total)
+}
+
+/// Badge text for the pending count; None clears the badge
+fn pending_badge_label(pending: u64) -> Option<String> {
+    match pending {
+        0 => None,
+        1..=999 => Some(pending.to_string()),
+        _ => Some("999+".to_string()),
+    }
+}
+
+/// Pending count as currently shown, so the dock and tray are only touched on change
+#[derive(Clone, Default, PartialEq)]
+struct PendingBadge {
+    /// Pending count, or the memory total on a kept Dock icon
+    label: Option<String>,
+    on_dock: bool,
+    /// Memory count for the menu bar title (`show_count_in_menubar`)
+    menubar_count: Option<String>,
+}
+
+/// The dock icon is visible while the main window is (Regular activation
+/// policy), or always when it is kept
+fn dock_visible(app: &AppHandle) -> bool {
+    cfg!(target_os = "macos")
+        && (keep_dock_icon(app)
+            || app
+                .get_webview_window("main")
+                .and_then(|w| w.is_visible().ok())
+                .unwrap_or(false))
+}
+
+#[cfg(target_os = "macos")]
+fn set_dock_badge(app: &AppHandle, label: Option<String>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_badge_label(label);
+    }
+}
+
+/// Only macOS has a dock; `dock_visible` is always false elsewhere
+#[cfg(not(target_os = "macos"))]
+fn set_dock_badge(_app: &AppHandle, _label: Option<String>) {}
+
+/// Move the pending count to the dock badge or the tray title ("Kiroku · 12"),
+/// clearing wherever it was shown before
+fn apply_pending_badge(app: &AppHandle, shown: &PendingBadge, next: &PendingBadge) {
+    if shown.on_dock && shown.label.is_some() && (!next.on_dock || next.label.is_none()) {
+        set_dock_badge(app, None);
+    }
+    if next.on_dock {
+        if let Some(label) = next.label.clone() {
+            set_dock_badge(app, Some(label));
+        }
+    }
+
+    let title = tray_title(next);
+    if title != tray_title(shown) {
+        if let Some(tray) = app.tray_by_id(TRAY_ID) {
+            // Keep the text fallback when the tray has no icon
+            let title = title.or_else(|| {
+                load_tray_icon(app)
+                    .is_none()
+                    .then(|| TRAY_FALLBACK_TITLE.to_string())
+            });
+            let _ = tray.set_title(title);
+        }
+    }
+}
+
+/// Tray title: the menu bar memory count, and the pending count while the
+/// dock isn't showing it
+fn tray_title(badge: &PendingBadge) -> Option<String> {
+    let pending = badge.label.as_ref().filter(|_| !badge.on_dock);
+    match (&badge.menubar_count, pending) {
+        (Some(count), Some(pending)) => Some(format!("{} \u{b7} {}", count, pending)),
+        (Some(count), None) => Some(count.clone()),
+        (None, Some(pending)) => Some(format!("{} \u{b7} {}", TRAY_FALLBACK_TITLE, pending)),
+        (None, None) => None,
+    }
+}
+
+fn check_memory_count_alert(app: &AppHandle, previous: u64, count: u64) {
+    let Some(threshold) = settings::current(app).memory_count_alert else {
+        return;
+    };
+    let message = match memories::threshold_crossing(previous, count, threshold) {
+        Some(memories::Crossing::Above) => {
+            format!("Memory count reached {} (alert at {})", count, threshold)
+        }
+        Some(memories::Crossing::Below) => format!(
+            "Memory count dropped from {} to {}, below {}. Check for data loss.",
+            previous, count, threshold
+        ),
+        None => return,
+    };
+    log_event(app, &format!("memory count alert: {}", message));
+    notify(app, &message);
+}
+
+/// Notify once when free space on the data volume drops below
+/// `disk_space_warning_mb`; `latch` re-arms when space recovers
+async fn check_disk_space_warning(app: &AppHandle, latch: &mut LowSpaceLatch) {
+    let threshold_mb = settings::current(app).disk_space_warning_mb;
+    if threshold_mb == 0 {
+        return;
+    }
+    let Ok(data_dir) = service::get_data_dir(app) else {
+        return;
+    };
+    let space = tokio::task::spawn_blocking(move || storage::disk_space(&data_dir, threshold_mb))
+        .await
+        .ok()
+        .flatten();
+    let Some(space) = space else {
+        return;
+    };
+    if latch.check(space.available_bytes, space.min_free_bytes) {
+        let message = format!(
+            "Low disk space: {} free on the volume holding your memories",
+            space.human
+        );
+        log_event(app, &format!("disk space warning: {} free on {}", space.human, space.path));
+        notify(app, &message);
+    }
 }
 
-fn update_tray_status(tray: &TrayItems, status: &ServiceStatus) {
-    let label = match status {
-        ServiceStatus::Starting => "Status: Starting",
-        ServiceStatus::Running => "Status: Running",
-        ServiceStatus::Stopped => "Status: Stopped",
-        ServiceStatus::Restarting => "Status: Restarting",
-        ServiceStatus::Error(_) => "Status: Error",
+/// Refresh the tray's usage item; a failed fetch keeps the last figure
+async fn refresh_usage(app: &AppHandle, tray: &TrayItems) {
+    let Ok(summary) =
+        usage::fetch(&api_client(app), UsageRange::Month, RequestOptions::background()).await
+    else {
+        return;
     };
-    let _ = tray.status.set_text(label);
+    update_usage_item(tray, summary.as_ref().map(|s| s.cost_usd));
+    if let Some(summary) = summary {
+        check_usage_budget(app, &summary);
+    }
 }
 
-fn update_restart_label(tray: &TrayItems, status: &ServiceStatus) {
-    let label = match status {
-        ServiceStatus::Running | ServiceStatus::Starting | ServiceStatus::Restarting => {
-            "Restart Service"
+/// Notify once per month for each budget threshold the estimated spend reaches
+fn check_usage_budget(app: &AppHandle, month: &UsageSummary) {
+    let Some(budget) = settings::current(app).monthly_budget_usd else {
+        return;
+    };
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let path = dir.join(usage::ALERTS_FILE);
+    let mut alerts = BudgetAlerts::load(&path);
+    let saved = alerts.clone();
+    let crossed = alerts.crossed(&month.since[..7], month.cost_usd, budget);
+    if alerts != saved {
+        if let Err(e) = alerts.save(&path) {
+            log_event(app, &format!("usage alerts: {}", e));
         }
-        ServiceStatus::Stopped | ServiceStatus::Error(_) => "Start Service",
+    }
+    let Some(threshold) = crossed.last() else {
+        return;
     };
-    let _ = tray.restart_service.set_text(label);
-}
-
-fn update_memory_count(tray: &TrayItems, count: Option<u64>) {
-    let label = match count {
-        Some(v) => format!("Memories: {}", v),
-        None => "Memories: -".to_string(),
+    let cost = usage::format_cost(month.cost_usd);
+    let message = if *threshold >= 100 {
+        format!("Estimated OpenAI spend this month ({}) has reached your ${:.2} budget", cost, budget)
+    } else {
+        format!(
+            "Estimated OpenAI spend this month ({}) is at {}% of your ${:.2} budget",
+            cost, threshold, budget
+        )
     };
-    let _ = tray.memory_count.set_text(label);
+    log_event(app, &format!("usage budget: {}% of ${:.2} reached ({})", threshold, budget, cost));
+    notify(app, &message);
 }
 
-fn update_toggle_label(tray: &TrayItems, is_visible: bool) {
-    let label = if is_visible { "Bring to Front" } else { "Show Window" };
-    let _ = tray.toggle_window.set_text(label);
+/// Drain the outbox whenever the service becomes ready, and retry every minute
+async fn outbox_flusher(app: AppHandle, service: Arc<PythonService>) {
+    const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+    let wake = app.state::<OutboxWake>().inner().clone();
+    let outbox = app.state::<Arc<Outbox>>().inner().clone();
+    loop {
+        tokio::select! {
+            _ = wake.0.notified() => {}
+            _ = tokio::time::sleep(RETRY_INTERVAL) => {}
+        }
+        if !service.get_status().await.is_up() {
+            continue;
+        }
+        match outbox.flush(&api_client(&app)).await {
+            Ok(report) if report.sent > 0 || report.failed > 0 => {
+                log_event(
+                    &app,
+                    &format!(
+                        "outbox: sent {}, failed {}, {} left",
+                        report.sent, report.failed, report.remaining
+                    ),
+                );
+                request_stats_refresh(&app);
+                outbox_changed(&app, &outbox).await;
+            }
+            Ok(_) => {}
+            Err(e) => log_event(&app, &format!("outbox flush failed: {}", e)),
+        }
+    }
 }
 
-fn refresh_toggle_label(app: &AppHandle, tray: &TrayItems, close_guard: &Arc<AtomicBool>) {
-    if let Some(window) = app.get_webview_window("main") {
-        let visible = window.is_visible().unwrap_or(false);
-        close_guard.store(!visible, Ordering::SeqCst);
-        update_toggle_label(tray, visible);
+/// How often the live updates bridge rechecks the setting and service status
+const LIVE_UPDATES_POLL: Duration = Duration::from_secs(2);
+
+/// Follow the service's change stream while it runs, re-emitting changes as
+/// `memory-changed` and passing count changes to the tray
+async fn live_updates_loop(app: AppHandle, service: Arc<PythonService>) {
+    let bridge = app.state::<Arc<LiveUpdates>>().inner().clone();
+    let mut failures = 0;
+    loop {
+        if !settings::current(&app).live_updates_enabled {
+            bridge.set_state(BridgeState::Disabled, None);
+            tokio::time::sleep(LIVE_UPDATES_POLL).await;
+            continue;
+        }
+        if !service.get_status().await.is_up() {
+            if bridge.status().state != BridgeState::Disconnected {
+                bridge.set_state(BridgeState::Disconnected, None);
+            }
+            failures = 0;
+            tokio::time::sleep(LIVE_UPDATES_POLL).await;
+            continue;
+        }
+
+        let error = match api_client(&app).open_stream(live_updates::EVENTS_PATH).await {
+            Err(e) if e.is_not_found() => {
+                log_event(&app, "live updates: service has no change stream");
+                bridge.set_state(BridgeState::Unsupported, None);
+                // Try again after a restart, which may bring a newer service
+                while service.get_status().await.is_up()
+                    && settings::current(&app).live_updates_enabled
+                {
+                    tokio::time::sleep(LIVE_UPDATES_POLL).await;
+                }
+                continue;
+            }
+            Err(e) => e.to_string(),
+            Ok(resp) => {
+                log_event(&app, "live updates: connected");
+                bridge.set_state(BridgeState::Connected, None);
+                failures = 0;
+                match forward_changes(&app, &service, &bridge, resp).await {
+                    Ok(()) => continue,
+                    Err(e) => e,
+                }
+            }
+        };
+        failures += 1;
+        let delay = live_updates::reconnect_delay(failures);
+        log_event(
+            &app,
+            &format!("live updates: disconnected ({}), retrying in {:?}", error, delay),
+        );
+        bridge.set_state(BridgeState::Disconnected, Some(redact::error(error)));
+        tokio::time::sleep(delay).await;
     }
 }
 
-fn toggle_main_window(app: &AppHandle, tray: &TrayItems, close_guard: &Arc<AtomicBool>) {
-    if let Some(window) = app.get_webview_window("main") {
-        let visible = window.is_visible().unwrap_or(false);
-        if visible {
-            // 視窗可見但可能被遮住，帶到前景
-            let _ = window.set_focus();
-        } else {
-            // 視窗隱藏，恢復 Dock 可見性並顯示視窗
-            #[cfg(target_os = "macos")]
-            {
-                let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
-                let _ = app.set_dock_visibility(true);
+/// Re-emit changes from an open stream. Ok when the service stopped or live
+/// updates were turned off, Err when the stream broke.
+async fn forward_changes(
+    app: &AppHandle,
+    service: &PythonService,
+    bridge: &LiveUpdates,
+    mut resp: reqwest::Response,
+) -> Result<(), String> {
+    let count_delta = app.state::<Arc<CountDelta>>().inner().clone();
+    let mut parser = SseParser::default();
+    let mut check = tokio::time::interval(LIVE_UPDATES_POLL);
+    loop {
+        tokio::select! {
+            chunk = resp.chunk() => {
+                let chunk = match chunk {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => return Err("stream closed".to_string()),
+                    Err(e) => return Err(e.to_string()),
+                };
+                for change in parser.push(&chunk).iter().filter_map(SseMessage::change) {
+                    bridge.record_event();
+                    count_delta.add(change.count_delta());
+                    events::emit(app, AppEvent::MemoryChanged(change));
+                }
+            }
+            _ = check.tick() => {
+                if !settings::current(app).live_updates_enabled
+                    || !service.get_status().await.is_up()
+                {
+                    return Ok(());
+                }
             }
-            let _ = window.show();
-            let _ = window.set_focus();
-            close_guard.store(false, Ordering::SeqCst);
-            update_toggle_label(tray, true);
         }
     }
 }
 
-/// Animate window shrinking to tray icon position then hide
-#[cfg(target_os = "macos")]
-async fn animate_minimize_to_tray(window: Window, app: &AppHandle) {
-    use tauri::PhysicalPosition;
+/// Tauri command to report whether live updates from the service are flowing
+#[tauri::command]
+async fn get_live_updates_status(
+    bridge: State<'_, Arc<LiveUpdates>>,
+) -> Result<LiveUpdatesStatus, String> {
+    Ok(bridge.status())
+}
 
-    // Get current window position and size
-    let Ok(current_pos) = window.outer_position() else { return };
-    let Ok(current_size) = window.outer_size() else { return };
+/// The running bundle's location when it is a disk image or translocated
+/// copy. Only macOS moves apps like that.
+fn running_from_dmg() -> Option<install_location::RunningFromDmg> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    install_location::check(&std::env::current_exe().ok()?)
+}
 
-    // Target position: tray icon's actual position
-    let (target_x, target_y) = if let Some(tray) = app.tray_by_id(TRAY_ID) {
-        if let Ok(Some(rect)) = tray.rect() {
-            let pos = rect.position.to_physical::<i32>(1.0);
-            let size = rect.size.to_physical::<u32>(1.0);
-            // Target: center of tray icon
-            (pos.x + (size.width as i32 / 2), pos.y + (size.height as i32 / 2))
-        } else {
-            // Fallback: top-right corner
-            let screen_width = window.current_monitor().ok().flatten()
-                .map(|m| m.size().width as i32 - 100)
-                .unwrap_or(1000);
-            (screen_width, 25)
-        }
-    } else {
-        (1000, 25)
-    };
+/// Tauri command to get what `app-running-from-dmg` reported, if anything
+#[tauri::command]
+async fn get_install_location() -> Result<Option<install_location::RunningFromDmg>, String> {
+    Ok(running_from_dmg())
+}
 
-    // Animation parameters
-    let steps = 12;
-    let step_duration = Duration::from_millis(16);  // ~60fps
+/// Tauri command to copy the running bundle to /Applications, open the copy
+/// once this instance has exited, and quit
+#[tauri::command]
+async fn move_to_applications(
+    app: AppHandle,
+    quit_guard: State<'_, QuitGuard>,
+) -> Result<(), String> {
+    let location = running_from_dmg()
+        .ok_or_else(|| "Kiroku Memory is not running from a disk image".to_string())?;
+    let bundle = location
+        .bundle_path
+        .map(PathBuf::from)
+        .ok_or_else(|| "Kiroku Memory is not running from an app bundle".to_string())?;
+    let target = install_location::applications_target(&bundle)
+        .ok_or_else(|| "Kiroku Memory is not running from an app bundle".to_string())?;
+    if target.exists() {
+        return Err(format!(
+            "{} already exists; quit and replace it in Finder",
+            target.display()
+        ));
+    }
 
-    for i in 1..=steps {
-        let progress = i as f64 / steps as f64;
-        // Ease-out curve for smoother animation
-        let eased = 1.0 - (1.0 - progress).powi(3);
+    // ditto keeps the signature, extended attributes and symlinks intact
+    let output = tokio::process::Command::new("/usr/bin/ditto")
+        .arg(&bundle)
+        .arg(&target)
+        .output()
+        .await
+        .map_err(redact::error)?;
+    if !output.status.success() {
+        let _ = std::fs::remove_dir_all(&target);
+        return Err(format!(
+            "Copying to Applications failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
 
-        // Interpolate position
-        let new_x = current_pos.x + ((target_x - current_pos.x) as f64 * eased) as i32;
-        let new_y = current_pos.y + ((target_y - current_pos.y) as f64 * eased) as i32;
+    // Two instances would both try to run the service, so wait for this one to exit
+    std::process::Command::new("/bin/sh")
+        .arg("-c")
+        .arg(r#"while kill -0 "$1" 2>/dev/null; do sleep 0.5; done; exec /usr/bin/open "$2""#)
+        .arg("sh")
+        .arg(std::process::id().to_string())
+        .arg(&target)
+        .spawn()
+        .map_err(redact::error)?;
+    log_event(&app, &format!("moved to {}; relaunching", target.display()));
+    shutdown(app.clone(), quit_guard.0.clone());
+    Ok(())
+}
 
-        // Interpolate size (shrink to 10% of original)
-        let scale = 1.0 - (eased * 0.9);
-        let new_width = (current_size.width as f64 * scale) as u32;
-        let new_height = (current_size.height as f64 * scale) as u32;
+#[derive(Clone, Copy)]
+enum StartupCheck {
+    /// Main window visibility; labeled with the wait since the previous check
+    Keepalive(u64),
+    /// Labeled with the time since startup
+    Heartbeat(u64),
+}
 
-        let _ = window.set_position(PhysicalPosition::new(new_x, new_y));
-        let _ = window.set_size(tauri::PhysicalSize::new(new_width.max(50), new_height.max(50)));
+/// One task for the startup diagnostics, each at its offset (seconds) from launch
+async fn startup_diagnostics(app: AppHandle, headless: bool) {
+    use StartupCheck::{Heartbeat, Keepalive};
+    const SCHEDULE: &[(u64, StartupCheck)] = &[
+        (1, Keepalive(1)),
+        (4, Keepalive(3)),
+        (5, Heartbeat(5)),
+        (9, Keepalive(5)),
+        (15, Heartbeat(15)),
+        (19, Keepalive(10)),
+        (39, Keepalive(20)),
+    ];
 
-        tokio::time::sleep(step_duration).await;
+    for issue in permissions::check(&private_dirs(&app)) {
+        log_event(
+            &app,
+            &format!("permissions: {} is open to other users ({})", issue.path, issue.mode),
+        );
     }
 
-    // Hide window and restore original size for next show
-    let _ = window.hide();
-    let _ = window.set_position(current_pos);
-    let _ = window.set_size(current_size);
+    if let Some(location) = running_from_dmg() {
+        log_event(
+            &app,
+            &format!(
+                "install location: running from {:?} ({}); move the app to {}",
+                location.kind,
+                location.bundle_path.as_deref().unwrap_or("no bundle"),
+                install_location::APPLICATIONS_DIR
+            ),
+        );
+    }
 
-    // Hide Dock icon when window is minimized to tray
-    #[cfg(target_os = "macos")]
-    {
-        let _ = app.set_activation_policy(tauri::ActivationPolicy::Accessory);
-        let _ = app.set_dock_visibility(false);
+    let started = tokio::time::Instant::now();
+    for &(offset, check) in SCHEDULE {
+        if headless && matches!(check, Keepalive(_)) {
+            continue;
+        }
+        tokio::time::sleep_until(started + Duration::from_secs(offset)).await;
+        match check {
+            Keepalive(secs) => {
+                if let Some(win) = app.get_webview_window("main") {
+                    let visible = win.is_visible().unwrap_or(false);
+                    log_event(&app, &format!("keepalive {}s visible={}", secs, visible));
+                }
+            }
+            Heartbeat(secs) => log_event(&app, &format!("heartbeat {}s", secs)),
+        }
     }
 }
 
-#[cfg(not(target_os = "macos"))]
-async fn animate_minimize_to_tray(window: Window, _app: &AppHandle) {
-    let _ = window.hide();
+/// Apply the API client's timeout and concurrency cap from settings, now and
+/// after every change
+async fn api_limits_loop(app: AppHandle) {
+    let mut live_settings = app.state::<settings::LiveSettings>().subscribe();
+    loop {
+        let (timeout_secs, max_in_flight) = {
+            let current = live_settings.borrow_and_update();
+            (current.api_timeout_secs, current.api_max_in_flight)
+        };
+        api_client(&app).set_limits(Duration::from_secs(timeout_secs), max_in_flight);
+        if live_settings.changed().await.is_err() {
+            return;
+        }
+    }
 }
 
-fn handle_tray_menu_event(
-    app: &AppHandle,
-    event: tauri::menu::MenuEvent,
-    tray: &TrayItems,
-    is_quitting: &Arc<AtomicBool>,
-    close_guard: &Arc<AtomicBool>,
-) {
-    log_event(
-        app,
-        &format!("tray menu event id={}", event.id().as_ref()),
-    );
-    match event.id().as_ref() {
-        MENU_ID_TOGGLE_WINDOW => {
-            toggle_main_window(app, tray, close_guard);
+/// Reload settings.json whenever it is edited outside the app
+async fn settings_watch_loop(app: AppHandle) {
+    let path = match settings::settings_path(&app) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("[Settings] Not watching settings.json: {}", e);
+            return;
         }
-        MENU_ID_RESTART_SERVICE => {
-            let app_handle = app.clone();
-            let service = app.state::<Arc<PythonService>>().inner().clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = restart_service_and_wait(app_handle, service).await {
-                    eprintln!("[Tray] Failed to restart service: {}", e);
-                }
-            });
+    };
+    let (_watcher, mut changes) = match settings_watch::watch(&path) {
+        Ok(watch) => watch,
+        Err(e) => {
+            eprintln!("[Settings] Not watching {:?}: {}", path, e);
+            return;
         }
-        MENU_ID_QUIT => {
-            request_quit(app.clone(), is_quitting.clone());
+    };
+    while settings_watch::next_change(&mut changes).await {
+        if !settings_watch::is_own_write() {
+            reload_external_settings(&app).await;
         }
-        _ => {}
     }
 }
 
-async fn fetch_memory_count() -> Option<u64> {
-    let client = reqwest::Client::new();
-    let resp = client
-        .get("http://127.0.0.1:8000/v2/stats")
-        .send()
-        .await
-        .ok()?;
-    if !resp.status().is_success() {
-        return None;
+/// Apply settings.json as edited outside the app. An invalid file is reported
+/// and the previous settings stay in effect. `locked` only changes through
+/// `lock`/`unlock`, and while locked the edit is undone instead of applied.
+async fn reload_external_settings(app: &AppHandle) {
+    let loaded = settings::load(app).and_then(|loaded| {
+        loaded.validate().map_err(anyhow::Error::msg)?;
+        Ok(loaded)
+    });
+    let mut new_settings = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            let warning = format!("settings.json was edited but not reloaded: {}", redact::error(e));
+            log_event(app, &warning);
+            events::emit(app, AppEvent::SettingsWarning(vec![warning]));
+            return;
+        }
+    };
+    let current_settings = settings::current(app);
+    if current_settings.locked {
+        if settings_watch::changed_keys(&current_settings, &new_settings).is_empty() {
+            return;
+        }
+        let warning = "settings.json was edited while locked; the edit was undone".to_string();
+        log_event(app, &warning);
+        if let Err(e) = settings::save(app, &current_settings) {
+            eprintln!("[Settings] Failed to restore settings.json: {}", e);
+        }
+        events::emit(app, AppEvent::SettingsWarning(vec![warning]));
+        return;
+    }
+    if new_settings.locked != current_settings.locked {
+        log_event(app, "settings.json edit to locked ignored; use lock/unlock");
+        new_settings.locked = current_settings.locked;
+        if let Err(e) = settings::save(app, &new_settings) {
+            eprintln!("[Settings] Failed to restore locked in settings.json: {}", e);
+        }
+    }
+    let changed = settings_watch::changed_keys(&current_settings, &new_settings);
+    if changed.is_empty() {
+        return;
+    }
+    log_event(app, &format!("settings.json edited outside the app: {}", changed.join(", ")));
+    if current_settings.launch_at_login != new_settings.launch_at_login {
+        if let Err(e) = set_launch_at_login(app, new_settings.launch_at_login) {
+            eprintln!("[Settings] Failed to apply launch_at_login: {}", e);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    if let Err(e) = update_launch_agent(app, &current_settings, &new_settings) {
+        eprintln!("[Settings] Failed to update LaunchAgent: {}", e);
     }
-    let stats: StatsResponse = resp.json().await.ok()?;
-    Some(stats.items.total)
+    settings::publish(app, &new_settings);
+    let service = app.state::<Arc<PythonService>>().inner().clone();
+    apply_settings_change(app, &service, &current_settings, &new_settings).await;
+    events::emit(app, AppEvent::SettingsChanged(changed));
 }
 
 async fn tray_status_loop(
@@ -499,60 +3502,308 @@ async fn tray_status_loop(
     tray: TrayItems,
     close_guard: Arc<AtomicBool>,
 ) {
-    let mut status_interval = tokio::time::interval(Duration::from_secs(2));
-    let mut stats_interval = tokio::time::interval(Duration::from_secs(30));
+    const STATUS_INTERVAL: Duration = Duration::from_secs(2);
+
+    let mut live_settings = app.state::<settings::LiveSettings>().subscribe();
+    let mut stats_every = Duration::from_secs(live_settings.borrow_and_update().stats_interval_secs);
+    let mut polling_scale = service.polling_scale();
+    let mut status_interval = tokio::time::interval(STATUS_INTERVAL * polling_scale);
+    let mut stats_interval = tokio::time::interval(stats_every * polling_scale);
     let mut last_status: Option<ServiceStatus> = None;
+    // Last successfully fetched count, kept across failed polls so alerts don't repeat
+    let mut last_count: Option<u64> = None;
+    let mut pending: Option<u64> = None;
+    // Cleared whenever the service isn't running so the title goes away
+    let mut menubar_count: Option<u64> = None;
+    // Memory total for the dock badge and tooltip, cleared the same way
+    let mut memory_total: Option<u64> = None;
+    let mut shown_badge = PendingBadge::default();
+    let mut shown_tooltip = String::new();
+    // Locale included so a language switch relabels "Other"
+    let mut shown_breakdown: Option<(&str, Option<StatsBreakdown>)> = None;
+    let stats_refresh = app.state::<StatsRefresh>().inner().clone();
+    let count_delta = app.state::<Arc<CountDelta>>().inner().clone();
+    let mut disk_latch = LowSpaceLatch::default();
+    // Uptime is shown in minutes at best, so the items change at most once a minute
+    let mut shown_timing: Option<(String, String)> = None;
+    let update_badge = |app: &AppHandle,
+                        pending: Option<u64>,
+                        menubar_count: Option<u64>,
+                        memory_total: Option<u64>,
+                        shown: &mut PendingBadge| {
+        let on_dock = dock_visible(app);
+        let mut label = pending.and_then(pending_badge_label);
+        // A kept Dock icon shows the memory total while nothing is pending
+        if label.is_none() && on_dock && keep_dock_icon(app) {
+            label = memory_total.map(memories::format_count);
+        }
+        let next = PendingBadge {
+            label,
+            on_dock,
+            menubar_count: menubar_count
+                .map(|count| format!("\u{1f9e0} {}", memories::format_count(count))),
+        };
+        if next != *shown {
+            apply_pending_badge(app, shown, &next);
+            *shown = next;
+        }
+    };
 
     loop {
         tokio::select! {
             _ = status_interval.tick() => {
+                if service.polling_scale() != polling_scale {
+                    polling_scale = service.polling_scale();
+                    status_interval = tokio::time::interval(STATUS_INTERVAL * polling_scale);
+                    stats_interval = tokio::time::interval(stats_every * polling_scale);
+                }
                 let status = service.get_status().await;
                 if last_status.as_ref() != Some(&status) {
                     update_tray_status(&tray, &status);
                     update_restart_label(&tray, &status);
-                    last_status = Some(status);
+                    refresh_restart_enabled(&app, &tray);
+                    let _ = tray
+                        .open_dashboard
+                        .set_enabled(status.is_up());
+                    if !status.is_up() {
+                        pending = None;
+                        menubar_count = None;
+                        memory_total = None;
+                    }
+                    last_status = Some(status.clone());
+                }
+                let timing = service_timing_texts(&service, &status);
+                if timing != shown_timing {
+                    update_service_timing(&tray, timing.as_ref());
+                    shown_timing = timing;
                 }
                 refresh_toggle_label(&app, &tray, &close_guard);
+                // Also follows the dock showing/hiding with the window
+                update_badge(&app, pending, menubar_count, memory_total, &mut shown_badge);
+                let tooltip = tray_tooltip::text(&status, memory_total, service.uptime());
+                if tooltip != shown_tooltip {
+                    if let Some(icon) = app.tray_by_id(TRAY_ID) {
+                        let _ = icon.set_tooltip(Some(&tooltip));
+                    }
+                    shown_tooltip = tooltip;
+                }
             }
             _ = stats_interval.tick() => {
                 let status = service.get_status().await;
-                if matches!(status, ServiceStatus::Running) {
-                    update_memory_count(&tray, fetch_memory_count().await);
+                if status.is_up() {
+                    let stats = fetch_stats(&api_client(&app)).await;
+                    // The fetched total already includes streamed changes
+                    count_delta.clear();
+                    if let Some(stats) = &stats {
+                        record_stats_sample(&app, stats);
+                    }
+                    let count = stats.as_ref().map(|s| s.items.total);
+                    let app_settings = settings::current(&app);
+                    pending = stats
+                        .as_ref()
+                        .filter(|_| app_settings.show_pending_badge)
+                        .map(|s| s.pending);
+                    menubar_count = count
+                        .filter(|_| cfg!(target_os = "macos") && app_settings.show_count_in_menubar);
+                    memory_total = count;
+                    update_memory_count(&tray, count);
+                    let breakdown = stats
+                        .as_ref()
+                        .and_then(|s| s.category_counts.clone())
+                        .map(StatsBreakdown::from_counts);
+                    if shown_breakdown.as_ref() != Some(&(locale::current(), breakdown.clone())) {
+                        update_breakdown(&app, &tray, breakdown.as_ref());
+                        shown_breakdown = Some((locale::current(), breakdown));
+                    }
+                    if let Some(count) = count {
+                        if let Some(previous) = last_count {
+                            check_memory_count_alert(&app, previous, count);
+                        }
+                        last_count = Some(count);
+                    }
+                    check_disk_space_warning(&app, &mut disk_latch).await;
+                    refresh_usage(&app, &tray).await;
                 } else {
                     update_memory_count(&tray, None);
+                    update_usage_item(&tray, None);
+                    pending = None;
+                    menubar_count = None;
+                    memory_total = None;
+                    if shown_breakdown.as_ref().is_none_or(|(_, shown)| shown.is_some()) {
+                        update_breakdown(&app, &tray, None);
+                        shown_breakdown = Some((locale::current(), None));
+                    }
+                }
+                update_badge(&app, pending, menubar_count, memory_total, &mut shown_badge);
+            }
+            _ = stats_refresh.0.notified() => {
+                stats_interval.reset_immediately();
+            }
+            delta = count_delta.take() => {
+                // Live updates: adjust the last fetched total until the next poll
+                if let Some(total) = memory_total {
+                    let total = total.saturating_add_signed(delta);
+                    memory_total = Some(total);
+                    menubar_count = menubar_count.map(|_| total);
+                    update_memory_count(&tray, memory_total);
+                    update_badge(&app, pending, menubar_count, memory_total, &mut shown_badge);
+                }
+            }
+            Ok(()) = live_settings.changed() => {
+                let every = Duration::from_secs(live_settings.borrow_and_update().stats_interval_secs);
+                if every != stats_every {
+                    stats_every = every;
+                    stats_interval = tokio::time::interval(stats_every * polling_scale);
+                }
+            }
+        }
+    }
+}
+
+/// Decide how the app opens at launch; see `start_visibility` for the precedence
+fn start_visibility_decision(app: &AppHandle) -> StartVisibilityDecision {
+    let app_settings = settings::load(app).unwrap_or_default();
+    let overrides = app.state::<StartupOverrides>();
+    start_visibility::resolve(&StartVisibilityInputs::from_env(&app_settings, &overrides))
+}
+
+/// Tauri command to explain whether the app started visible or in the tray
+#[tauri::command]
+async fn get_start_visibility_decision(
+    decision: State<'_, StartVisibilityDecision>,
+) -> Result<StartVisibilityDecision, String> {
+    Ok(decision.inner().clone())
+}
+
+/// Quit cleanly on Ctrl+C / SIGTERM (the service runs in its own process
+/// group and would otherwise outlive the app). A second signal exits at once.
+async fn quit_on_signal(app: AppHandle, is_quitting: Arc<AtomicBool>) {
+    #[cfg(unix)]
+    let mut terminate =
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(signal) => Some(signal),
+            Err(e) => {
+                eprintln!("[Tauri] Failed to listen for SIGTERM: {}", e);
+                None
+            }
+        };
+
+    loop {
+        #[cfg(unix)]
+        let terminated = async {
+            match terminate.as_mut() {
+                Some(signal) => {
+                    signal.recv().await;
                 }
+                None => std::future::pending::<()>().await,
             }
+        };
+        #[cfg(not(unix))]
+        let terminated = std::future::pending::<()>();
+
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => {
+                if result.is_err() {
+                    return;
+                }
+            }
+            _ = terminated => {}
+        }
+
+        if is_quitting.load(Ordering::SeqCst) {
+            log_event(&app, "second quit signal, exiting now");
+            std::process::exit(130);
         }
+        log_event(&app, "quit signal received");
+        request_quit(app.clone(), is_quitting.clone());
     }
 }
 
-fn should_start_hidden(app: &AppHandle) -> bool {
-    if cfg!(debug_assertions) && std::env::var("KIROKU_ALLOW_START_HIDDEN").is_err() {
-        return false;
-    }
-    if std::env::args().any(|arg| arg == "--tray" || arg == "--hidden") {
-        return true;
-    }
-    if std::env::var("KIROKU_TRAY_ONLY").is_ok() {
-        return true;
+/// Tauri command telling a webview whether the app runs headless, e.g. so a
+/// settings window opened from the tray can explain why there is no main window
+#[tauri::command]
+async fn is_headless(overrides: State<'_, StartupOverrides>) -> Result<bool, String> {
+    Ok(overrides.headless)
+}
+
+/// Set once quitting has begun; checked by the run loop to allow the exit
+struct QuitGuard(Arc<AtomicBool>);
+
+fn request_quit(app: AppHandle, is_quitting: Arc<AtomicBool>) {
+    if is_quitting.load(Ordering::SeqCst) {
+        return;
     }
-    if let Ok(app_settings) = settings::load(app) {
-        return app_settings.start_hidden;
+
+    // Quitting mid-backup/restore would leave half-copied files; let the user decide
+    if let Err(busy) = app.state::<Arc<PythonService>>().maintenance().check() {
+        log_event(&app, &format!("request_quit deferred: {}", busy));
+        events::emit(&app, AppEvent::QuitBlocked(busy.to_string()));
+        // Quit usually comes from the tray with no window on screen to show the prompt
+        let main_visible = app
+            .get_webview_window("main")
+            .is_some_and(|w| w.is_visible().unwrap_or(false));
+        if !main_visible {
+            show_notification(
+                &app,
+                &format!("Quit postponed ({}). Open the window to quit anyway.", busy),
+            );
+        }
+        return;
     }
-    false
+    shutdown(app, is_quitting);
 }
 
-fn request_quit(app: AppHandle, is_quitting: Arc<AtomicBool>) {
+/// Tauri command to get the maintenance operation in progress, if any
+#[tauri::command]
+async fn get_current_maintenance(
+    service: State<'_, Arc<PythonService>>,
+) -> Result<Option<MaintenanceInfo>, String> {
+    Ok(service.maintenance().current())
+}
+
+/// Quit even though a maintenance operation is running (after `quit-blocked`)
+#[tauri::command]
+async fn confirm_quit(app: AppHandle, quit_guard: State<'_, QuitGuard>) -> Result<(), String> {
+    log_event(&app, "quit confirmed during maintenance");
+    shutdown(app.clone(), quit_guard.0.clone());
+    Ok(())
+}
+
+/// Cancel background loops, stop the service, then exit
+fn shutdown(app: AppHandle, is_quitting: Arc<AtomicBool>) {
+    const TASK_GRACE: Duration = Duration::from_secs(3);
+    const SERVICE_GRACE: Duration = Duration::from_secs(5);
+
     if is_quitting.swap(true, Ordering::SeqCst) {
         return;
     }
 
     log_event(&app, "request_quit");
     let service = app.state::<Arc<PythonService>>().inner().clone();
+    let supervisor = app.state::<Arc<TaskSupervisor>>().inner().clone();
     tauri::async_runtime::spawn(async move {
-        if let Err(e) = service.stop().await {
-            eprintln!("[Tauri] Error stopping service: {}", e);
+        if !supervisor.shutdown(TASK_GRACE).await {
+            log_event(&app, "shutdown: background tasks did not stop in time");
+        }
+        if settings::current(&app).stop_service_on_quit || service.is_remote() {
+            if let Err(e) = service.stop_gracefully(SERVICE_GRACE).await {
+                eprintln!("[Tauri] Error stopping service: {}", e);
+            }
+        } else {
+            match service.leave_running(&app).await {
+                Ok(Some(pid)) => log_event(
+                    &app,
+                    &format!("shutdown: service left running (PID {}) for the next launch", pid),
+                ),
+                Ok(None) => {}
+                Err(e) => {
+                    log_event(&app, &format!("shutdown: could not record the service, stopping it: {}", e));
+                    let _ = service.stop_gracefully(SERVICE_GRACE).await;
+                }
+            }
         }
+        // app.log is written line by line, so nothing is left to flush
+        log_event(&app, "shutdown complete");
         app.exit(0);
     });
 }
@@ -561,14 +3812,11 @@ fn request_quit(app: AppHandle, is_quitting: Arc<AtomicBool>) {
 fn set_launch_at_login(app: &AppHandle, enabled: bool) -> Result<(), String> {
     use std::fs;
 
-    let home = app
-        .path()
-        .home_dir()
-        .map_err(|e| format!("Failed to resolve home dir: {}", e))?;
-    let agents_dir = home.join("Library/LaunchAgents");
-    fs::create_dir_all(&agents_dir).map_err(|e| format!("Failed to create LaunchAgents dir: {}", e))?;
-
-    let plist_path = agents_dir.join("com.kiroku.memory.plist");
+    let plist_path = launch_agent_path(app)?;
+    if let Some(agents_dir) = plist_path.parent() {
+        fs::create_dir_all(agents_dir)
+            .map_err(|e| format!("Failed to create LaunchAgents dir: {}", e))?;
+    }
 
     if !enabled {
         if plist_path.exists() {
@@ -578,21 +3826,80 @@ fn set_launch_at_login(app: &AppHandle, enabled: bool) -> Result<(), String> {
         return Ok(());
     }
 
+    write_launch_agent(&plist_path, &settings::load(app).unwrap_or_default())
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let home = app
+        .path()
+        .home_dir()
+        .map_err(|e| format!("Failed to resolve home dir: {}", e))?;
+    Ok(home.join("Library/LaunchAgents/com.kiroku.memory.plist"))
+}
+
+#[cfg(target_os = "macos")]
+fn write_launch_agent(plist_path: &std::path::Path, app_settings: &AppSettings) -> Result<(), String> {
+    // The agent would point into the DMG or a translocated copy
+    if running_from_dmg().is_some() {
+        return Err(install_location::LAUNCH_AT_LOGIN_ERROR.to_string());
+    }
     let exe_path = std::env::current_exe()
         .map_err(|e| format!("Failed to resolve current exe: {}", e))?;
 
-    let plist_content = build_launch_agent_plist(&exe_path)?;
-    write_atomic(&plist_path, plist_content.as_bytes())
+    let plist_content = build_launch_agent_plist(&exe_path, &launch_agent_args(app_settings))?;
+    write_atomic(plist_path, plist_content.as_bytes())
         .map_err(|e| format!("Failed to write LaunchAgent: {}", e))?;
 
     Ok(())
 }
 
+/// Rewrite the LaunchAgent when a setting it passes on has changed
+#[cfg(target_os = "macos")]
+fn update_launch_agent(
+    app: &AppHandle,
+    current_settings: &AppSettings,
+    new_settings: &AppSettings,
+) -> Result<(), String> {
+    if !new_settings.launch_at_login
+        || launch_agent_args(current_settings) == launch_agent_args(new_settings)
+    {
+        return Ok(());
+    }
+    write_launch_agent(&launch_agent_path(app)?, new_settings)
+}
+
+/// `--port` and `--data-dir` for a non-default port or data dir, so a login
+/// launch starts where the user left off
+#[cfg(target_os = "macos")]
+fn launch_agent_args(app_settings: &AppSettings) -> Vec<String> {
+    let mut args = Vec::new();
+    if app_settings.service_port != AppSettings::default().service_port {
+        args.push(format!("--port={}", app_settings.service_port));
+    }
+    if let Some(dir) = app_settings.data_dir_override() {
+        args.push(format!("--data-dir={}", dir.display()));
+    }
+    args
+}
+
+#[cfg(target_os = "macos")]
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
 #[cfg(target_os = "macos")]
-fn build_launch_agent_plist(exe_path: &std::path::Path) -> Result<String, String> {
+fn build_launch_agent_plist(exe_path: &std::path::Path, args: &[String]) -> Result<String, String> {
     let exe = exe_path
         .to_str()
         .ok_or_else(|| "Executable path is not valid UTF-8".to_string())?;
+    let extra_args: String = args
+        .iter()
+        .map(|arg| format!("\n    <string>{}</string>", xml_escape(arg)))
+        .collect();
 
     Ok(format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -604,7 +3911,7 @@ fn build_launch_agent_plist(exe_path: &std::path::Path) -> Result<String, String
   <key>ProgramArguments</key>
   <array>
     <string>{}</string>
-    <string>--tray</string>
+    <string>--tray</string>{}
   </array>
   <key>RunAtLoad</key>
   <true/>
@@ -618,7 +3925,8 @@ fn build_launch_agent_plist(exe_path: &std::path::Path) -> Result<String, String
 </dict>
 </plist>
 "#,
-        exe
+        xml_escape(exe),
+        extra_args
     ))
 }
 
@@ -635,33 +3943,206 @@ fn write_atomic(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()>
     Ok(())
 }
 
-#[cfg(not(target_os = "macos"))]
+/// Per-user startup programs; each value is a command line run at login
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(windows)]
+const RUN_VALUE: &str = "Kiroku Memory";
+
+/// Windows has no LaunchAgent environment, so `--tray` alone asks for the tray
+#[cfg(windows)]
+fn set_launch_at_login(_app: &AppHandle, enabled: bool) -> Result<(), String> {
+    if !enabled {
+        // `reg delete` fails on a missing value; disabling twice is fine
+        if run_reg(&["query", RUN_KEY, "/v", RUN_VALUE]).is_err() {
+            return Ok(());
+        }
+        return run_reg(&["delete", RUN_KEY, "/v", RUN_VALUE, "/f"])
+            .map_err(|e| format!("Failed to remove startup entry: {}", e));
+    }
+
+    let exe_path = std::env::current_exe()
+        .map_err(|e| format!("Failed to resolve current exe: {}", e))?;
+    let command = format!("\"{}\" --tray", exe_path.display());
+    run_reg(&["add", RUN_KEY, "/v", RUN_VALUE, "/t", "REG_SZ", "/d", command.as_str(), "/f"])
+        .map_err(|e| format!("Failed to write startup entry: {}", e))
+}
+
+/// Run reg.exe without flashing a console window
+#[cfg(windows)]
+fn run_reg(args: &[&str]) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn set_launch_at_login(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let home = app
+        .path()
+        .home_dir()
+        .map_err(|e| format!("Failed to resolve home dir: {}", e))?;
+    autostart::set(&home, enabled)
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
 fn set_launch_at_login(_app: &AppHandle, _enabled: bool) -> Result<(), String> {
-    Err("Launch at login is only supported on macOS".to_string())
+    Err("Launch at login is not supported on this platform".to_string())
+}
+
+/// Record the spawned service command line in app.log
+async fn log_service_command(app: &AppHandle, service: &PythonService) {
+    if let Some(info) = service.launch_info().await {
+        log_event(app, &format!("service launch: {}", info.summary()));
+    }
 }
 
 /// Start service and wait for health
 async fn start_and_wait(app: AppHandle, service: Arc<PythonService>) {
     // Start service
-    if let Err(e) = service.start(&app).await {
-        eprintln!("[Tauri] Failed to spawn Python service: {}", e);
-        service.mark_error(e.to_string()).await;
-        app.emit("service-error", e.to_string()).ok();
-        return;
-    }
+    let generation = match service.start(&app).await {
+        Ok(generation) => generation,
+        Err(e) => {
+            eprintln!("[Tauri] Failed to spawn Python service: {}", e);
+            let error = redact::error(e);
+            // Otherwise only noticed when memories turn out to be missing
+            if error == service::LOW_DISK_SPACE_ERROR {
+                notify(&app, "Kiroku Memory was not started: the disk is almost full");
+            } else if error == service::NOT_WRITABLE_ERROR {
+                notify(&app, "Kiroku Memory was not started: its data directory is not writable");
+            }
+            service.mark_error(error.clone()).await;
+            events::emit(&app, AppEvent::ServiceError(error));
+            return;
+        }
+    };
+    log_service_command(&app, &service).await;
 
-    // Wait for health
-    match wait_for_health("http://127.0.0.1:8000/health", Duration::from_secs(30)).await {
-        Ok(_) => {
+    // Wait for health; a stop or restart meanwhile ends the wait and owns the status
+    let health = service
+        .wait_until_healthy(&api_client(&app), Duration::from_secs(30), generation)
+        .await;
+    match health {
+        Some(Ok(health)) => {
             println!("[Tauri] Service is ready!");
-            service.mark_running().await;
-            app.emit("service-ready", ()).ok();
+            service.set_last_health(health).await;
+            if service.mark_running_for(generation).await {
+                events::emit(&app, AppEvent::ServiceReady);
+            }
         }
-        Err(e) => {
+        Some(Err(e)) => {
             eprintln!("[Tauri] Service failed to start: {}", e);
-            service.mark_error(e.to_string()).await;
-            app.emit("service-error", e.to_string()).ok();
+            let error = startup_error(&service, e);
+            if service.mark_error_for(generation, error.clone()).await {
+                events::emit(&app, AppEvent::ServiceError(error));
+            }
+        }
+        None => log_event(&app, "service start superseded before it was healthy"),
+    }
+}
+
+/// How long a request that started an idle service waits for it
+const FIRST_USE_START_TIMEOUT: Duration = Duration::from_secs(35);
+
+/// Start an idle (`OnFirstUse`) service in the background. Returns whether
+/// this call started it.
+async fn start_if_idle(app: &AppHandle, service: &Arc<PythonService>, trigger: &str) -> bool {
+    if !service.claim_idle_start().await {
+        return false;
+    }
+    log_event(app, &format!("service start on first use: {}", trigger));
+    let (app, service) = (app.clone(), service.clone());
+    tauri::async_runtime::spawn(async move {
+        start_and_wait(app, service).await;
+    });
+    true
+}
+
+/// In `OnFirstUse` mode, start the service if it is idle and wait (up to
+/// FIRST_USE_START_TIMEOUT) for it to come up, so the triggering request goes through
+async fn ensure_service_started(app: &AppHandle, service: &Arc<PythonService>, trigger: &str) {
+    let mode = settings::current(app).service_start_mode;
+    if mode != ServiceStartMode::OnFirstUse {
+        return;
+    }
+    start_if_idle(app, service, trigger).await;
+    let deadline = tokio::time::Instant::now() + FIRST_USE_START_TIMEOUT;
+    while service.get_status().await == ServiceStatus::Starting
+        && tokio::time::Instant::now() < deadline
+    {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+/// Health check failure message, led by the root cause from the service's output
+/// (e.g. "ModuleNotFoundError: ...") when one can be found
+fn startup_error(service: &PythonService, e: anyhow::Error) -> String {
+    match service.startup_failure() {
+        Some(cause) => format!("{} ({})", cause, redact::error(e)),
+        None => redact::error(e),
+    }
+}
+
+/// Health sample for a monitor tick that won't restart anything. A service
+/// stopped or paused on purpose is recorded as such rather than as failing.
+async fn record_unmonitored_health(app: &AppHandle, service: &PythonService) {
+    if service.is_paused_for_battery() {
+        service.record_health(HealthState::Paused, None).await;
+    } else if service.get_status().await.is_stopped() {
+        service.record_health(HealthState::Stopped, None).await;
+    } else {
+        // Running with auto-restart off
+        let check = check_health_once(&api_client(app)).await;
+        service.record_check(check.as_ref()).await;
+    }
+}
+
+/// Mark a running service degraded while its data volume is below
+/// `min_free_disk_mb`, and running again once space is freed. A disk filling
+/// up mid-session loses writes just like starting on a full one.
+async fn check_disk_degraded(app: &AppHandle, service: &PythonService, generation: u64) {
+    let Ok(data_dir) = service::get_data_dir(app) else {
+        return;
+    };
+    let min_free_mb = settings::current(app).min_free_disk_mb;
+    let space = tokio::task::spawn_blocking(move || storage::disk_space(&data_dir, min_free_mb))
+        .await
+        .ok()
+        .flatten();
+    let Some(space) = space else {
+        return;
+    };
+    match service.get_status().await {
+        ServiceStatus::Running if space.low => {
+            log_event(app, &format!("monitor: disk almost full ({} free on {})", space.human, space.path));
+            notify(
+                app,
+                &format!("Disk almost full: only {} free, new memories may not be saved", space.human),
+            );
+            if service
+                .mark_degraded_for(generation, service::LOW_DISK_SPACE_ERROR.to_string())
+                .await
+            {
+                events::emit(app, AppEvent::LowDiskSpace(space));
+            }
         }
+        ServiceStatus::Degraded(reason) if reason == service::LOW_DISK_SPACE_ERROR && !space.low => {
+            log_event(app, &format!("monitor: disk space recovered ({} free)", space.human));
+            if service.mark_running_for(generation).await {
+                events::emit(app, AppEvent::ServiceReady);
+            }
+        }
+        _ => {}
     }
 }
 
@@ -670,14 +4151,22 @@ async fn monitor_service(app: AppHandle, service: Arc<PythonService>) {
     let mut consecutive_failures: u32 = 0;
     let mut restart_attempts: u32 = 0;
     let mut last_restart_time: Option<std::time::Instant> = None;
+    let mut sampler = ResourceSampler::new();
+    let mut over_memory_limit: u32 = 0;
+    // start() just checked free space
+    let mut last_disk_check = std::time::Instant::now();
 
     const HEALTH_FAIL_THRESHOLD: u32 = 3;
+    /// Consecutive samples above memory_limit_mb before a controlled restart
+    const MEMORY_LIMIT_SAMPLES: u32 = 3;
     const MAX_RESTART_ATTEMPTS: u32 = 3;
     const CHECK_INTERVAL: Duration = Duration::from_secs(5);
     const RESTART_COOLDOWN: Duration = Duration::from_secs(30);
     const SLOW_CHECK_INTERVAL: Duration = Duration::from_secs(60);
     /// How long the service must stay healthy after restart before we reset the attempt counter.
     const STABLE_PERIOD: Duration = Duration::from_secs(60);
+    /// How often free space is re-checked while the service runs
+    const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(180);
 
     loop {
         let interval = if restart_attempts >= MAX_RESTART_ATTEMPTS {
@@ -685,22 +4174,84 @@ async fn monitor_service(app: AppHandle, service: Arc<PythonService>) {
         } else {
             CHECK_INTERVAL
         };
-        tokio::time::sleep(interval).await;
+        tokio::time::sleep(interval * service.polling_scale()).await;
 
-        // Skip monitoring if service is intentionally stopped
+        // Skip monitoring if service is intentionally stopped (or idle until first use)
         if !service.should_auto_restart() {
             consecutive_failures = 0;
             restart_attempts = 0;
             last_restart_time = None;
+            record_unmonitored_health(&app, &service).await;
+            continue;
+        }
+        // A stop or start while the check below is in flight decides the status instead
+        let generation = service.generation();
+
+        // Remote service: only reachability matters, there is no process to restart
+        if service.is_remote() {
+            let health = check_health_once(&api_client(&app)).await;
+            let reachable = health.is_some();
+            service.record_check(health.as_ref()).await;
+            if let Some(health) = health {
+                service.set_last_health(health.response).await;
+            }
+            let status = service.get_status().await;
+            if reachable && !status.is_up() {
+                if service.mark_running_for(generation).await {
+                    events::emit(&app, AppEvent::ServiceReady);
+                }
+            } else if !reachable
+                && !matches!(status, ServiceStatus::Error(_))
+                && service
+                    .mark_error_for(generation, "Remote service unreachable".to_string())
+                    .await
+            {
+                events::emit(&app, AppEvent::ServiceError("Remote service unreachable".to_string()));
+            }
             continue;
         }
 
         let process_alive = service.is_running().await;
-        let health_ok = if process_alive {
-            check_health_once().await.is_some()
+        let health = if process_alive {
+            check_health_once(&api_client(&app)).await
         } else {
-            false
+            None
+        };
+        let health_ok = health.is_some();
+        service.record_check(health.as_ref()).await;
+        if let Some(health) = health {
+            service.set_last_health(health.response).await;
+        }
+
+        // Sample resource usage; the process may exit between ticks
+        let sample = match service.pid().await {
+            Some(pid) if process_alive => sampler.sample(pid),
+            _ => None,
         };
+        if let Some(sample) = sample {
+            service.record_resource_sample(sample).await;
+        }
+
+        let memory_limit_mb = settings::current(&app).memory_limit_mb;
+        match (memory_limit_mb, sample) {
+            (Some(limit), Some(sample)) if sample.rss_mb() > limit => over_memory_limit += 1,
+            _ => over_memory_limit = 0,
+        }
+        if over_memory_limit >= MEMORY_LIMIT_SAMPLES {
+            over_memory_limit = 0;
+            let message = format!(
+                "Service memory above {} MB limit, restarting",
+                memory_limit_mb.unwrap_or_default()
+            );
+            println!("[Monitor] {}", message);
+            log_event(&app, &format!("monitor: {}", message));
+            notify(&app, &message);
+            if let Err(e) = restart_service_and_wait(app.clone(), service.clone()).await {
+                println!("[Monitor] Memory-limit restart failed: {}", e);
+                log_event(&app, &format!("monitor memory-limit restart failed: {}", e));
+            }
+            continue;
+        }
 
         // Health recovered
         if health_ok {
@@ -718,17 +4269,29 @@ async fn monitor_service(app: AppHandle, service: Arc<PythonService>) {
                     restart_attempts = 0;
                 }
             }
+            if last_disk_check.elapsed() >= DISK_CHECK_INTERVAL {
+                last_disk_check = std::time::Instant::now();
+                check_disk_degraded(&app, &service, generation).await;
+            }
             let status = service.get_status().await;
-            if !matches!(status, ServiceStatus::Running) {
-                service.mark_running().await;
-                app.emit("service-ready", ()).ok();
+            if !status.is_up() && service.mark_running_for(generation).await {
+                events::emit(&app, AppEvent::ServiceReady);
             }
             continue;
         }
 
         // Determine if we should restart
         let should_restart = if !process_alive {
-            println!("[Monitor] Service process is not running");
+            match service.take_unreported_exit().await {
+                Some((exit, error)) => {
+                    println!("[Monitor] {}", error);
+                    log_event(&app, &format!("monitor: {}", error));
+                    notify(&app, &format!("Service exited ({}) — see logs", exit.describe()));
+                    events::emit(&app, AppEvent::ServiceError(error.clone()));
+                    service.mark_error_for(generation, error).await;
+                }
+                None => println!("[Monitor] Service process is not running"),
+            }
             true // Process dead → restart immediately
         } else {
             consecutive_failures += 1;
@@ -739,7 +4302,8 @@ async fn monitor_service(app: AppHandle, service: Arc<PythonService>) {
             consecutive_failures >= HEALTH_FAIL_THRESHOLD
         };
 
-        if !should_restart {
+        // Stopped or restarted by hand since the check began
+        if !should_restart || service.generation() != generation {
             continue;
         }
 
@@ -747,12 +4311,18 @@ async fn monitor_service(app: AppHandle, service: Arc<PythonService>) {
         if restart_attempts >= MAX_RESTART_ATTEMPTS {
             // Already exhausted — stay in error state, slow-check continues
             let status = service.get_status().await;
-            if !matches!(status, ServiceStatus::Error(_)) {
-                service
-                    .mark_error("Service unresponsive (restarts exhausted)".to_string())
-                    .await;
-                app.emit("service-error", "Service unresponsive (restarts exhausted)")
-                    .ok();
+            if !matches!(status, ServiceStatus::Error(_))
+                && service
+                    .mark_error_for(
+                        generation,
+                        "Service unresponsive (restarts exhausted)".to_string(),
+                    )
+                    .await
+            {
+                events::emit(
+                    &app,
+                    AppEvent::ServiceError("Service unresponsive (restarts exhausted)".to_string()),
+                );
             }
             continue;
         }
@@ -792,12 +4362,96 @@ async fn monitor_service(app: AppHandle, service: Arc<PythonService>) {
     }
 }
 
+/// Watch AC / battery state and apply the battery saver setting on transitions
+async fn power_monitor(app: AppHandle, service: Arc<PythonService>) {
+    const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+    const BATTERY_POLLING_SCALE: u32 = 5;
+
+    let mut last_source: Option<PowerSource> = None;
+    loop {
+        let source = power::current_power_source();
+        let saver = settings::current(&app).battery_saver;
+        let on_battery = source == PowerSource::Battery;
+
+        let scale = if on_battery && saver == BatterySaver::ReducePolling {
+            BATTERY_POLLING_SCALE
+        } else {
+            1
+        };
+        service.set_polling_scale(scale);
+
+        if last_source != Some(source) {
+            println!("[Power] Power source: {:?} (battery saver: {:?})", source, saver);
+            log_event(
+                &app,
+                &format!("power source {:?} battery_saver={:?}", source, saver),
+            );
+            service.set_power_source(source).await;
+            events::emit(
+                &app,
+                AppEvent::PowerStateChanged(PowerStateChanged {
+                    source,
+                    battery_saver: saver,
+                }),
+            );
+
+            if on_battery && saver == BatterySaver::StopService {
+                // Only pause a service that is meant to be running, and not mid-backup
+                if service.should_auto_restart()
+                    && !service.is_remote()
+                    && service.maintenance().check().is_ok()
+                {
+                    log_event(&app, "power: on battery, pausing service");
+                    service.set_paused_for_battery(true);
+                    let _ = service.stop().await;
+                }
+            } else if !on_battery && service.is_paused_for_battery() {
+                log_event(&app, "power: back on AC, resuming service");
+                service.set_paused_for_battery(false);
+                start_and_wait(app.clone(), service.clone()).await;
+            }
+            last_source = Some(source);
+        }
+
+        tokio::time::sleep(POWER_CHECK_INTERVAL).await;
+    }
+}
+
+/// Daily background update check. Failures are only logged.
+async fn update_monitor(app: AppHandle) {
+    const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+    const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+    tokio::time::sleep(FIRST_CHECK_DELAY).await;
+    loop {
+        let app_settings = settings::current(&app);
+        if app_settings.auto_check_updates {
+            let checker = app.state::<UpdateChecker>();
+            match checker.check(app_settings.release_channel, false).await {
+                Ok(info) if info.update_available => show_update_item(&app, &info),
+                Ok(_) => {}
+                Err(e) => {
+                    println!("[Updates] Update check failed: {}", e);
+                    log_event(&app, &format!("update check failed: {}", e));
+                }
+            }
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
 fn main() {
     std::panic::set_hook(Box::new(|info| {
         log_panic(&format!("panic: {}", info));
     }));
 
+    let overrides = StartupOverrides::from_process();
+    for warning in &overrides.warnings {
+        eprintln!("[Tauri] {}", warning);
+    }
+
     let service = Arc::new(PythonService::new());
+    let api = Arc::new(ApiClient::new());
     let is_quitting = Arc::new(AtomicBool::new(false));
     let close_to_tray = Arc::new(AtomicBool::new(false));
     let quit_guard_setup = is_quitting.clone();
@@ -806,7 +4460,21 @@ fn main() {
 
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .manage(service.clone())
+        .manage(api)
+        .manage(SearchGenerations::new())
+        .manage(StatsRefresh::default())
+        .manage(Arc::new(LiveUpdates::default()))
+        .manage(Arc::new(CountDelta::default()))
+        .manage(WindowLog::default())
+        .manage(OutboxWake::default())
+        .manage(UpdateChecker::new())
+        .manage(Arc::new(TaskSupervisor::new(log_event)))
+        .manage(QuitGuard(is_quitting.clone()))
+        .manage(overrides)
         .setup(move |app| {
             let app_handle = app.handle().clone();
             let service_clone = service.clone();
@@ -814,22 +4482,91 @@ fn main() {
             let close_guard_setup = close_guard_setup.clone();
 
             log_event(&app_handle, "setup start");
+            app.manage(settings::LiveSettings::new(settings::load(&app_handle).unwrap_or_default()));
+            let overrides = app.state::<StartupOverrides>();
+            for warning in &overrides.warnings {
+                log_event(&app_handle, &format!("command line: {}", warning));
+            }
+            if !overrides.describe().is_empty() {
+                log_event(
+                    &app_handle,
+                    &format!("session overrides: {}", overrides.describe().join(" ")),
+                );
+            }
+            let headless = overrides.headless;
+            let with_tray = !overrides.without_tray();
 
-            #[cfg(target_os = "macos")]
+            // The main window is declared with `create: false` so headless runs never build a webview
+            if headless {
+                log_event(
+                    &app_handle,
+                    &format!("headless mode (tray {})", if with_tray { "on" } else { "off" }),
+                );
+                #[cfg(target_os = "macos")]
+                {
+                    let _ = app_handle.set_activation_policy(tauri::ActivationPolicy::Accessory);
+                    let _ = app_handle.set_dock_visibility(false);
+                }
+            } else if let Some(config) = app
+                .config()
+                .app
+                .windows
+                .iter()
+                .find(|w| w.label == "main")
+                .cloned()
             {
-                let _ = app_handle.set_activation_policy(tauri::ActivationPolicy::Regular);
-                let _ = app_handle.set_dock_visibility(true);
-                log_event(&app_handle, "dock policy=regular visible=true");
+                tauri::WebviewWindowBuilder::from_config(&app_handle, &config)?.build()?;
+            }
+
+            // Without a tray or window, signals are the only way to quit
+            let signal_handle = app_handle.clone();
+            let signal_quit = quit_guard.clone();
+            tauri::async_runtime::spawn(quit_on_signal(signal_handle, signal_quit));
+
+            #[cfg(target_os = "macos")]
+            if !headless {
+                show_dock_icon(&app_handle);
+                log_event(
+                    &app_handle,
+                    &format!("dock policy=regular keep={}", keep_dock_icon(&app_handle)),
+                );
             }
 
+            apply_log_level(&app_handle, settings::current(&app_handle).log_level);
+            let startup_locale = settings::current(&app_handle).locale;
+            locale::set_current(locale::resolve(&startup_locale));
+
+            let outbox_dir = app
+                .path()
+                .app_data_dir()
+                .unwrap_or_else(|_| std::env::temp_dir());
+            let _ = std::fs::create_dir_all(&outbox_dir);
+            app.manage(Arc::new(StatsHistory::new(outbox_dir.clone())));
+            app.manage(Arc::new(Outbox::new(outbox_dir)));
+
             let mut tray_items_opt = None;
-            if let Ok((tray_menu, tray_items)) = build_tray_menu(&app_handle) {
+            let tray_menu = if with_tray {
+                build_tray_menu(&app_handle).ok()
+            } else {
+                None
+            };
+            if let Some((tray_menu, tray_items)) = tray_menu {
+                if headless {
+                    let _ = tray_items.toggle_window.set_enabled(false);
+                }
                 let tray_items_for_events = tray_items.clone();
                 let quit_guard_for_events = quit_guard.clone();
                 let close_guard_for_events = close_guard_setup.clone();
+                let tray_items_for_clicks = tray_items.clone();
+                let close_guard_for_clicks = close_guard_setup.clone();
+                let left_click_action = settings::load(&app_handle)
+                    .unwrap_or_default()
+                    .tray_left_click_action;
+                let tray_icon = tray_icon_image(&app_handle);
                 let mut tray_builder = TrayIconBuilder::with_id(TRAY_ID)
                     .menu(&tray_menu)
-                    .icon_as_template(false)
+                    .show_menu_on_left_click(left_click_action == TrayClickAction::OpenMenu)
+                    .icon_as_template(tray_icon.as_ref().is_some_and(|(_, template)| *template))
                     .tooltip("Kiroku Memory")
                     .on_menu_event(move |app, event| {
                         handle_tray_menu_event(
@@ -839,9 +4576,17 @@ fn main() {
                             &quit_guard_for_events,
                             &close_guard_for_events,
                         );
+                    })
+                    .on_tray_icon_event(move |tray, event| {
+                        handle_tray_icon_event(
+                            tray.app_handle(),
+                            event,
+                            &tray_items_for_clicks,
+                            &close_guard_for_clicks,
+                        );
                     });
 
-                if let Some(icon) = load_tray_icon(&app_handle) {
+                if let Some((icon, _)) = tray_icon {
                     tray_builder = tray_builder.icon(icon);
                 } else {
                     tray_builder = tray_builder.title(TRAY_FALLBACK_TITLE);
@@ -850,22 +4595,33 @@ fn main() {
                 if let Ok(tray) = tray_builder.build(app) {
                     let _ = tray.set_tooltip(Some("Kiroku Memory"));
                     log_event(&app_handle, "tray build ok");
+                    update_quit_label(&tray_items, settings::current(&app_handle).stop_service_on_quit);
+                    app.manage(tray_items.clone());
                     tray_items_opt = Some(tray_items);
                 } else {
                     log_event(&app_handle, "tray build failed");
                 }
             }
 
-            if should_start_hidden(&app_handle) {
+            let decision = start_visibility_decision(&app_handle);
+            log_event(
+                &app_handle,
+                &format!(
+                    "start visibility: {:?} ({})",
+                    decision.visibility, decision.reason
+                ),
+            );
+            let visibility = decision.visibility;
+            app.manage(decision);
+            if visibility == StartVisibility::Headless {
+                close_guard_setup.store(true, Ordering::SeqCst);
+            } else if visibility == StartVisibility::HiddenToTray {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.hide();
                     close_guard_setup.store(true, Ordering::SeqCst);
                     #[cfg(target_os = "macos")]
-                    {
-                        let _ = app_handle.set_activation_policy(tauri::ActivationPolicy::Accessory);
-                        let _ = app_handle.set_dock_visibility(false);
-                    }
-                    log_event(&app_handle, "start hidden=true (window hidden, dock hidden)");
+                    hide_dock_icon(&app_handle);
+                    log_event(&app_handle, "start hidden=true (window hidden)");
                 }
             } else {
                 close_guard_setup.store(false, Ordering::SeqCst);
@@ -875,6 +4631,7 @@ fn main() {
                 }
                 log_event(&app_handle, "start hidden=false (focus requested)");
             }
+            apply_always_on_top(&app_handle, settings::current(&app_handle).always_on_top);
             if let Some(window) = app.get_webview_window("main") {
                 let is_visible = window.is_visible().unwrap_or(false);
                 let is_maximized = window.is_maximized().unwrap_or(false);
@@ -886,49 +4643,127 @@ fn main() {
                         is_visible, is_maximized, is_fullscreen
                     ),
                 );
-            } else {
+            } else if !headless {
                 log_event(&app_handle, "window missing");
             }
 
-            let keepalive_handle = app_handle.clone();
-            tauri::async_runtime::spawn(async move {
-                let checks = [1u64, 3, 5, 10, 20];
-                for secs in checks {
-                    tokio::time::sleep(Duration::from_secs(secs)).await;
-                    if let Some(win) = keepalive_handle.get_webview_window("main") {
-                        let visible = win.is_visible().unwrap_or(false);
-                        log_event(
-                            &keepalive_handle,
-                            &format!("keepalive {}s visible={}", secs, visible),
-                        );
-                    }
-                }
-            });
+            tauri::async_runtime::spawn(startup_diagnostics(app_handle.clone(), headless));
             if let Some(tray_items) = tray_items_opt.as_ref() {
                 refresh_toggle_label(&app_handle, tray_items, &close_guard_setup);
             }
 
-            let app_settings = settings::load(&app_handle).unwrap_or_default();
-            if app_settings.auto_start_service {
+            for key in keys::ALL {
+                if let Ok(Some(secret)) = keychain::get_secret(key) {
+                    redact::register_secret(&secret);
+                }
+            }
+
+            let app_settings = settings::load_effective(&app_handle).unwrap_or_default();
+            connect_api_client(&app_handle, &app_settings);
+            service_clone.set_remote(app_settings.is_remote());
+            // Hold the first start until `migrate_legacy_data` answers the offer.
+            // Headless there is no window to ask in, so start as usual.
+            let legacy_data = if headless {
+                None
+            } else {
+                pending_legacy_data(&app_handle, &app_settings)
+            };
+            // A window on screen at launch counts as first use
+            let start_now = match app_settings.service_start_mode {
+                ServiceStartMode::OnAppLaunch => true,
+                ServiceStartMode::OnFirstUse => visibility == StartVisibility::Visible,
+                ServiceStartMode::Manual => false,
+            };
+            let svc = service_clone.clone();
+            if let Some(location) = running_from_dmg() {
+                events::emit(&app_handle, AppEvent::AppRunningFromDmg(location));
+            }
+            if let Some(data) = legacy_data {
+                log_event(
+                    &app_handle,
+                    &format!("legacy data found at {}; start held", data.path),
+                );
+                events::emit(&app_handle, AppEvent::LegacyDataFound(data));
+                // The prompt is in the main window, so bring it up from the tray
+                if let Some(tray_items) = tray_items_opt.as_ref() {
+                    toggle_main_window(&app_handle, tray_items, &close_guard_setup);
+                }
+            } else if start_now {
                 let startup_handle = app_handle.clone();
-                let startup_service = service_clone.clone();
                 tauri::async_runtime::spawn(async move {
-                    start_and_wait(startup_handle, startup_service).await;
+                    start_and_wait(startup_handle, svc).await;
                 });
-            } else {
-                let svc = service_clone.clone();
+            } else if app_settings.service_start_mode == ServiceStartMode::OnFirstUse {
                 tauri::async_runtime::spawn(async move {
-                    let _ = svc.stop().await;
+                    svc.mark_idle().await;
                 });
             }
+            // Manual: the service stays Stopped until started from the tray or UI
+
+            // Long-lived loops run under the supervisor so a panic doesn't freeze them
+            let supervisor = app.state::<Arc<TaskSupervisor>>().inner().clone();
 
             // Spawn service monitor
             let monitor_handle = app_handle.clone();
             let monitor_svc = service_clone.clone();
+            supervisor.spawn(&app_handle, "monitor_service", move || {
+                let monitor_handle = monitor_handle.clone();
+                let monitor_svc = monitor_svc.clone();
+                async move {
+                    // Wait a bit before starting monitor
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    monitor_service(monitor_handle, monitor_svc).await;
+                }
+            });
+
+            let limits_handle = app_handle.clone();
+            supervisor.spawn(&app_handle, "api_limits_loop", move || {
+                api_limits_loop(limits_handle.clone())
+            });
+
+            let settings_handle = app_handle.clone();
+            supervisor.spawn(&app_handle, "settings_watch_loop", move || {
+                settings_watch_loop(settings_handle.clone())
+            });
+
+            let live_handle = app_handle.clone();
+            let live_svc = service_clone.clone();
+            supervisor.spawn(&app_handle, "live_updates_loop", move || {
+                live_updates_loop(live_handle.clone(), live_svc.clone())
+            });
+
+            // Spawn power source watcher for the battery saver
+            let power_handle = app_handle.clone();
+            let power_svc = service_clone.clone();
+            supervisor.spawn(&app_handle, "power_monitor", move || {
+                power_monitor(power_handle.clone(), power_svc.clone())
+            });
+
+            // Spawn scheduled backups (off unless backup_interval_hours is set)
+            let backup_handle = app_handle.clone();
+            let backup_svc = service_clone.clone();
+            supervisor.spawn(&app_handle, "backup_scheduler", move || {
+                backup_scheduler(backup_handle.clone(), backup_svc.clone())
+            });
+
+            // Replay writes queued while the service was down
+            let outbox_wake = app.state::<OutboxWake>().inner().clone();
+            app.listen_any("service-ready", move |_| outbox_wake.0.notify_one());
+            let outbox_handle = app_handle.clone();
+            let outbox_svc = service_clone.clone();
+            supervisor.spawn(&app_handle, "outbox_flusher", move || {
+                outbox_flusher(outbox_handle.clone(), outbox_svc.clone())
+            });
+            let outbox_tray_handle = app_handle.clone();
             tauri::async_runtime::spawn(async move {
-                // Wait a bit before starting monitor
-                tokio::time::sleep(Duration::from_secs(5)).await;
-                monitor_service(monitor_handle, monitor_svc).await;
+                let outbox = outbox_tray_handle.state::<Arc<Outbox>>().inner().clone();
+                update_outbox_item(&outbox_tray_handle, &outbox.status().await);
+            });
+
+            // Spawn daily update check
+            let update_handle = app_handle.clone();
+            supervisor.spawn(&app_handle, "update_monitor", move || {
+                update_monitor(update_handle.clone())
             });
 
             // Spawn wake handler (macOS only)
@@ -937,8 +4772,16 @@ fn main() {
                 let mut wake_rx = wake_listener::register();
                 let wake_handle = app_handle.clone();
                 let wake_svc = service_clone.clone();
+                let wake_cancel = supervisor.cancellation_token();
                 tauri::async_runtime::spawn(async move {
-                    while wake_rx.recv().await.is_some() {
+                    loop {
+                        let woke = tokio::select! {
+                            woke = wake_rx.recv() => woke.is_some(),
+                            _ = wake_cancel.cancelled() => false,
+                        };
+                        if !woke {
+                            break;
+                        }
                         println!("[Wake] System woke from sleep");
                         log_event(&wake_handle, "wake event received");
 
@@ -953,7 +4796,7 @@ fn main() {
                         }
 
                         // Already healthy → nothing to do
-                        if check_health_once().await.is_some() {
+                        if check_health_once(&api_client(&wake_handle)).await.is_some() {
                             println!("[Wake] Service is healthy after wake, no action needed");
                             log_event(&wake_handle, "wake: service healthy, skip");
                             continue;
@@ -973,7 +4816,7 @@ fn main() {
                             }
                         }
                     }
-                    println!("[Wake] Channel closed, stopping wake handler");
+                    println!("[Wake] Channel closed or shutting down, stopping wake handler");
                 });
             }
 
@@ -981,23 +4824,38 @@ fn main() {
                 let tray_handle = app_handle.clone();
                 let tray_service = service_clone.clone();
                 let tray_close_guard = close_guard_setup.clone();
-                tauri::async_runtime::spawn(async move {
-                    tray_status_loop(tray_handle, tray_service, tray_items, tray_close_guard).await;
+                supervisor.spawn(&app_handle, "tray_status_loop", move || {
+                    tray_status_loop(
+                        tray_handle.clone(),
+                        tray_service.clone(),
+                        tray_items.clone(),
+                        tray_close_guard.clone(),
+                    )
                 });
             }
 
-            let heartbeat_handle = app_handle.clone();
-            tauri::async_runtime::spawn(async move {
-                tokio::time::sleep(Duration::from_secs(5)).await;
-                log_event(&heartbeat_handle, "heartbeat 5s");
-                tokio::time::sleep(Duration::from_secs(10)).await;
-                log_event(&heartbeat_handle, "heartbeat 15s");
-            });
-
             Ok(())
         })
         .on_window_event(move |window, event| {
-            log_event(&window.app_handle(), &format!("window event {:?}", event));
+            log_window_event(window, event);
+            // Only main hides to the tray; other windows (settings) close normally
+            if window.label() != "main" {
+                #[cfg(target_os = "macos")]
+                if let tauri::WindowEvent::Destroyed = event {
+                    restore_accessory_policy(window.app_handle());
+                }
+                return;
+            }
+            if let tauri::WindowEvent::ThemeChanged(_) = event {
+                refresh_tray_icon(window.app_handle());
+            }
+            if let tauri::WindowEvent::Focused(true) = event {
+                let app = window.app_handle().clone();
+                let service = app.state::<Arc<PythonService>>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    start_if_idle(&app, &service, "main window shown").await;
+                });
+            }
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 let app_handle = window.app_handle().clone();
                 let is_visible = window.is_visible().unwrap_or(false);
@@ -1029,17 +4887,90 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             get_service_status,
+            get_service_info,
+            proxy_request,
+            get_resource_history,
+            get_process_metrics,
+            get_health_history,
+            get_last_error,
+            get_background_tasks,
+            confirm_quit,
+            get_recent_service_logs,
+            set_auto_restart,
+            get_auto_restart,
+            is_headless,
+            get_start_visibility_decision,
+            get_version,
+            get_app_info,
+            open_settings_window,
+            get_service_launch_info,
+            get_runtime_info,
+            get_api_client_stats,
+            get_usage_summary,
+            verify_runtime,
+            repair_runtime,
+            capture_memory,
+            get_stats_history,
+            get_stats_breakdown,
+            get_outbox_status,
+            retry_outbox,
             check_health,
             get_stats,
+            start_service,
             restart_service,
             stop_service,
+            // Search commands
+            search_memories,
+            search_memories_live,
+            // Memory commands
+            delete_memory,
+            clear_all_memories,
+            prune_memories,
+            create_backup,
+            list_backups,
+            forget_backup_passphrase,
+            restore_backup,
+            // Profile commands
+            get_profiles,
+            switch_profile,
+            // Update commands
+            check_for_updates,
+            // Onboarding commands
+            get_onboarding_state,
+            mark_onboarding_step,
+            complete_onboarding,
+            // Uninstall commands
+            prepare_uninstall,
+            reset_to_factory,
+            // Locale commands
+            get_available_locales,
+            set_locale,
             // Config commands
             set_openai_key,
             has_openai_key,
             delete_openai_key,
+            get_service_auth_token,
+            set_dock_visibility,
             get_settings,
+            get_event_schema,
             save_settings,
+            lock,
+            unlock,
+            get_key_status,
+            set_always_on_top,
+            get_install_location,
+            move_to_applications,
+            export_settings,
+            import_settings,
+            get_legacy_data,
+            migrate_legacy_data,
             get_data_dir,
+            get_permission_issues,
+            fix_permissions,
+            get_data_dir_size,
+            get_disk_space,
+            get_live_updates_status,
+            get_current_maintenance,
         ])
         .build(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1061,9 +4992,6 @@ fn main() {
                 return;
             }
             api.prevent_exit();
-            if close_guard.load(Ordering::SeqCst) {
-                return;
-            }
         }
         tauri::RunEvent::Exit => {
             log_event(app_handle, "run event exit");
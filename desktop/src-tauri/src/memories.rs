@@ -0,0 +1,130 @@
+// Kiroku Memory Desktop - Memory Management
+// Deleting memories through the Python service
+
+use crate::api::{ApiClient, ApiError, RequestOptions};
+use serde::{Deserialize, Serialize};
+
+const PRUNE_PATH: &str = "/v2/items/prune";
+
+/// `detail` of the service's 404 for an unknown item id, as opposed to the
+/// plain "Not Found" of a service without the route
+const ITEM_NOT_FOUND: &str = "Item not found";
+
+#[derive(Deserialize)]
+struct DeleteResponse {
+    #[serde(default)]
+    deleted: Option<u64>,
+}
+
+/// Delete a single memory item. Returns `Some(false)` if it did not exist and
+/// `None` if this service version has no delete endpoint.
+pub async fn delete_item(api: &ApiClient, id: &str) -> Result<Option<bool>, ApiError> {
+    match api
+        .delete_json::<DeleteResponse>(&format!("/v2/items/{}", id), RequestOptions::default())
+        .await
+    {
+        Ok(resp) => Ok(Some(resp.deleted.unwrap_or(1) > 0)),
+        Err(ApiError::Status(404, body)) if body.contains(ITEM_NOT_FOUND) => Ok(Some(false)),
+        Err(e) if e.is_not_found() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Delete all items with the service's bulk endpoint.
+/// Returns `None` if this service version has no bulk endpoint.
+pub async fn bulk_delete(api: &ApiClient) -> Result<Option<u64>, ApiError> {
+    match api
+        .delete_json::<DeleteResponse>("/v2/items", RequestOptions::maintenance())
+        .await
+    {
+        Ok(resp) => Ok(Some(resp.deleted.unwrap_or(0))),
+        Err(e) if e.is_not_found() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Serialize)]
+struct PruneRequest {
+    older_than_days: u32,
+    dry_run: bool,
+}
+
+#[derive(Deserialize)]
+struct PruneResponse {
+    #[serde(alias = "deleted")]
+    count: u64,
+}
+
+/// Delete items older than `older_than_days`, or with `dry_run` only count
+/// them. Returns `None` if this service version has no prune endpoint.
+pub async fn prune(
+    api: &ApiClient,
+    older_than_days: u32,
+    dry_run: bool,
+) -> Result<Option<u64>, ApiError> {
+    let request = PruneRequest {
+        older_than_days,
+        dry_run,
+    };
+    match api
+        .post_json::<_, PruneResponse>(PRUNE_PATH, &request, RequestOptions::maintenance())
+        .await
+    {
+        Ok(resp) => Ok(Some(resp.count)),
+        Err(e) if e.is_not_found() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Direction in which the memory count crossed the alert threshold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crossing {
+    Above,
+    Below,
+}
+
+/// Compare two consecutive counts against `threshold`. Only the transition is
+/// reported, so repeated polls on the same side stay quiet.
+pub fn threshold_crossing(previous: u64, current: u64, threshold: u64) -> Option<Crossing> {
+    if previous < threshold && current >= threshold {
+        Some(Crossing::Above)
+    } else if previous >= threshold && current < threshold {
+        Some(Crossing::Below)
+    } else {
+        None
+    }
+}
+
+/// A count with thousands separators, e.g. "1,204"
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_crossing_fires_once_per_transition() {
+        assert_eq!(threshold_crossing(9_999, 10_000, 10_000), Some(Crossing::Above));
+        assert_eq!(threshold_crossing(10_000, 10_050, 10_000), None);
+        assert_eq!(threshold_crossing(10_050, 120, 10_000), Some(Crossing::Below));
+        assert_eq!(threshold_crossing(120, 130, 10_000), None);
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1_204), "1,204");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+    }
+}
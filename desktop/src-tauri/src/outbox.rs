@@ -0,0 +1,227 @@
+// Kiroku Memory Desktop - Write Outbox
+// Keeps memory writes made while the service is down and replays them in order
+
+use crate::api::{ApiClient, ApiError, RequestOptions};
+use crate::jsonl;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+/// Queued writes, oldest first, one JSON object per line
+pub const QUEUE_FILE: &str = "pending-writes.jsonl";
+
+/// Writes the service rejected MAX_ATTEMPTS times
+pub const FAILED_FILE: &str = "pending-writes.failed.jsonl";
+
+/// A write is moved to FAILED_FILE after the service rejects it this many times
+pub const MAX_ATTEMPTS: u32 = 5;
+
+const INGEST_PATH: &str = "/v2/ingest";
+
+/// Body of POST /v2/ingest
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MemoryWrite {
+    /// Generated on our side so the service can ignore a replayed write
+    pub client_id: String,
+    pub content: String,
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl MemoryWrite {
+    pub fn new(content: String, source: String, metadata: Option<serde_json::Value>) -> Self {
+        Self {
+            client_id: uuid::Uuid::new_v4().to_string(),
+            content,
+            source,
+            metadata,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PendingWrite {
+    pub write: MemoryWrite,
+    /// Unix timestamp (seconds)
+    pub queued_at: i64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct OutboxStatus {
+    pub pending: usize,
+    pub failed: usize,
+    /// Why the oldest pending write has not been sent yet
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlushReport {
+    pub sent: usize,
+    /// Moved to the failed file during this flush
+    pub failed: usize,
+    pub remaining: usize,
+}
+
+/// Persistent queue in the app data dir. All file access goes through `lock`
+/// so capture paths and the flusher never interleave writes.
+pub struct Outbox {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl Outbox {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn queue_path(&self) -> PathBuf {
+        self.dir.join(QUEUE_FILE)
+    }
+
+    fn failed_path(&self) -> PathBuf {
+        self.dir.join(FAILED_FILE)
+    }
+
+    /// Append a write to the end of the queue
+    pub async fn queue(&self, write: MemoryWrite, reason: Option<String>) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().await;
+        let pending = PendingWrite {
+            write,
+            queued_at: chrono::Utc::now().timestamp(),
+            attempts: 0,
+            last_error: reason,
+        };
+        jsonl::append(&self.queue_path(), &[pending])
+    }
+
+    pub async fn status(&self) -> OutboxStatus {
+        let _guard = self.lock.lock().await;
+        let pending: Vec<PendingWrite> = jsonl::read(&self.queue_path());
+        OutboxStatus {
+            pending: pending.len(),
+            failed: jsonl::read::<PendingWrite>(&self.failed_path()).len(),
+            last_error: pending.first().and_then(|p| p.last_error.clone()),
+        }
+    }
+
+    /// Send queued writes in order. Stops at the first write that can't be sent
+    /// so later writes never overtake it; writes rejected MAX_ATTEMPTS times are
+    /// moved to the failed file instead of blocking the queue forever.
+    pub async fn flush(&self, api: &ApiClient) -> anyhow::Result<FlushReport> {
+        let _guard = self.lock.lock().await;
+        let mut queue: Vec<PendingWrite> = jsonl::read(&self.queue_path());
+        if queue.is_empty() {
+            return Ok(FlushReport::default());
+        }
+
+        let mut report = FlushReport::default();
+        let mut dead = Vec::new();
+        let mut sent_until = 0;
+        for pending in queue.iter_mut() {
+            match send(api, &pending.write).await {
+                Ok(_) => {
+                    report.sent += 1;
+                    sent_until += 1;
+                }
+                Err(e @ (ApiError::Unavailable(_) | ApiError::ServiceBusy)) => {
+                    pending.last_error = Some(e.to_string());
+                    break;
+                }
+                Err(e) => {
+                    pending.attempts += 1;
+                    pending.last_error = Some(e.to_string());
+                    if pending.attempts < MAX_ATTEMPTS {
+                        break;
+                    }
+                    dead.push(pending.clone());
+                    report.failed += 1;
+                    sent_until += 1;
+                }
+            }
+        }
+
+        queue.drain(..sent_until);
+        if !dead.is_empty() {
+            jsonl::append(&self.failed_path(), &dead)?;
+        }
+        jsonl::replace(&self.queue_path(), &queue)?;
+        report.remaining = queue.len();
+        Ok(report)
+    }
+
+    /// Put failed writes back at the front of the queue with a fresh retry budget.
+    /// Returns how many were moved.
+    pub async fn requeue_failed(&self) -> anyhow::Result<usize> {
+        let _guard = self.lock.lock().await;
+        let mut failed: Vec<PendingWrite> = jsonl::read(&self.failed_path());
+        if failed.is_empty() {
+            return Ok(0);
+        }
+        for pending in failed.iter_mut() {
+            pending.attempts = 0;
+        }
+        let moved = failed.len();
+        failed.extend(jsonl::read(&self.queue_path()));
+        jsonl::replace(&self.queue_path(), &failed)?;
+        std::fs::remove_file(self.failed_path())?;
+        Ok(moved)
+    }
+}
+
+/// POST one write to the service; `client_id` doubles as the idempotency key
+pub async fn send(api: &ApiClient, write: &MemoryWrite) -> Result<(), ApiError> {
+    api.post_json::<_, serde_json::Value>(
+        INGEST_PATH,
+        write,
+        RequestOptions::idempotent(&write.client_id),
+    )
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn write(content: &str) -> MemoryWrite {
+        MemoryWrite::new(content.to_string(), "test".to_string(), None)
+    }
+
+    #[tokio::test]
+    async fn test_queue_and_requeue_failed() {
+        let dir = TempDir::new("outbox");
+        let outbox = Outbox::new(dir.path().to_path_buf());
+
+        outbox.queue(write("first"), None).await.unwrap();
+        outbox
+            .queue(write("second"), Some("Service not available".to_string()))
+            .await
+            .unwrap();
+        let queued: Vec<PendingWrite> = jsonl::read(&dir.join(QUEUE_FILE));
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[0].write.content, "first");
+        assert_ne!(queued[0].write.client_id, queued[1].write.client_id);
+
+        // Pretend the first write was rejected for good
+        let mut dead = queued[0].clone();
+        dead.attempts = MAX_ATTEMPTS;
+        jsonl::append(&dir.join(FAILED_FILE), &[dead]).unwrap();
+        jsonl::replace(&dir.join(QUEUE_FILE), &queued[1..]).unwrap();
+        let status = outbox.status().await;
+        assert_eq!((status.pending, status.failed), (1, 1));
+
+        assert_eq!(outbox.requeue_failed().await.unwrap(), 1);
+        let queued: Vec<PendingWrite> = jsonl::read(&dir.join(QUEUE_FILE));
+        assert_eq!(queued[0].write.content, "first");
+        assert_eq!(queued[0].attempts, 0);
+        assert_eq!(queued[1].write.content, "second");
+        assert!(!dir.join(FAILED_FILE).exists());
+    }
+}
@@ -0,0 +1,144 @@
+// Kiroku Memory Desktop - Data Permissions
+// Keeps the memory database, settings, logs and backups private to the current user
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A directory other accounts can get into
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct PermissionIssue {
+    pub path: String,
+    /// Octal, e.g. "0755"
+    pub mode: String,
+}
+
+/// Create `dir` if needed and restrict it to the current user. Returns the
+/// looser mode it had (Unix) when it had to be tightened.
+pub fn ensure_private_dir(dir: &Path) -> std::io::Result<Option<u32>> {
+    let created = !dir.exists();
+    std::fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        let _ = created;
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(dir)?.permissions().mode() & 0o777;
+        if mode & 0o077 == 0 {
+            return Ok(None);
+        }
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        println!(
+            "[Permissions] Restricted {} to the current user (was {:04o})",
+            dir.display(),
+            mode
+        );
+        Ok(Some(mode))
+    }
+    #[cfg(windows)]
+    {
+        // Existing folders are only changed by `fix`, to avoid running icacls on every call
+        if created {
+            restrict_acl(dir)?;
+        }
+        Ok(None)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = created;
+        Ok(None)
+    }
+}
+
+/// Replace inherited access with full control for the current user only
+#[cfg(windows)]
+fn restrict_acl(dir: &Path) -> std::io::Result<()> {
+    let user = std::env::var("USERNAME").map_err(std::io::Error::other)?;
+    let status = std::process::Command::new("icacls")
+        .arg(dir)
+        .args(["/inheritance:r", "/grant:r"])
+        .arg(format!("{}:(OI)(CI)F", user))
+        .stdout(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("icacls exited with {}", status)));
+    }
+    Ok(())
+}
+
+/// Existing directories among `dirs` that other users can read or enter
+pub fn check(dirs: &[PathBuf]) -> Vec<PermissionIssue> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        dirs.iter()
+            .filter_map(|dir| {
+                let mode = std::fs::metadata(dir).ok()?.permissions().mode() & 0o777;
+                (mode & 0o077 != 0).then(|| PermissionIssue {
+                    path: dir.display().to_string(),
+                    mode: format!("{:04o}", mode),
+                })
+            })
+            .collect()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = dirs;
+        Vec::new()
+    }
+}
+
+/// Restrict every existing directory among `dirs`. Returns the ones changed.
+pub fn fix(dirs: &[PathBuf]) -> std::io::Result<Vec<PermissionIssue>> {
+    let issues = check(dirs);
+    for dir in dirs.iter().filter(|dir| dir.exists()) {
+        #[cfg(windows)]
+        restrict_acl(dir)?;
+        #[cfg(not(windows))]
+        ensure_private_dir(dir)?;
+    }
+    Ok(issues)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn mode(path: &Path) -> u32 {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn test_loose_dir_is_tightened() {
+        let temp = TempDir::new("perm");
+        let dir = temp.path().to_path_buf();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(
+            check(std::slice::from_ref(&dir)),
+            vec![PermissionIssue {
+                path: dir.display().to_string(),
+                mode: "0755".to_string(),
+            }]
+        );
+        assert_eq!(ensure_private_dir(&dir).unwrap(), Some(0o755));
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(ensure_private_dir(&dir).unwrap(), None);
+        assert!(check(std::slice::from_ref(&dir)).is_empty());
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o750)).unwrap();
+        let missing = dir.join("missing");
+        let fixed = fix(&[dir.clone(), missing.clone()]).unwrap();
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(mode(&dir), 0o700);
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_new_dir_is_private() {
+        let temp = TempDir::new("perm-new");
+        let dir = temp.join("nested");
+        ensure_private_dir(&dir).unwrap();
+        assert_eq!(mode(&dir), 0o700);
+    }
+}
@@ -0,0 +1,127 @@
+// Kiroku Memory Desktop - Service PID File
+// Records the running service so a relaunch after a crash can adopt it or clean up
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const FILE_NAME: &str = "service.pid";
+
+/// Found in the command line of every service process we spawn
+const COMMAND_MARKER: &str = "kiroku_memory.api:app";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PidRecord {
+    pub pid: u32,
+    pub port: u16,
+    /// Unix timestamp (seconds)
+    pub started_at: i64,
+}
+
+/// What the recorded PID turned out to be
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PidCheck {
+    /// No such process
+    Dead,
+    /// The PID was reused by something else
+    NotOurs(String),
+    /// Our service, possibly still serving
+    Ours,
+}
+
+pub fn write(path: &Path, record: &PidRecord) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// The record in `path`; None if there is none or it can't be parsed
+pub fn read(path: &Path) -> Option<PidRecord> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+pub fn remove(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+/// Classify a PID from its command line, None when the process is gone
+pub fn classify(command: Option<&str>) -> PidCheck {
+    match command {
+        None => PidCheck::Dead,
+        Some(command) if command.contains(COMMAND_MARKER) => PidCheck::Ours,
+        Some(command) => PidCheck::NotOurs(command.chars().take(120).collect()),
+    }
+}
+
+/// Look up `pid` on this machine
+pub fn check(pid: u32) -> PidCheck {
+    classify(process_command(pid).as_deref())
+}
+
+#[cfg(unix)]
+fn process_command(pid: u32) -> Option<String> {
+    let pid = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0)?;
+    // Signal 0 only checks existence; EPERM still means the process exists
+    let exists = unsafe { libc::kill(pid, 0) } == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+    if !exists {
+        return None;
+    }
+    let output = std::process::Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // ps prints nothing for a process that exited in the meantime
+    (!command.is_empty()).then_some(command)
+}
+
+/// No cheap command-line lookup on Windows; treat the PID as reused so the
+/// file is cleaned up and a fresh service is spawned
+#[cfg(not(unix))]
+fn process_command(_pid: u32) -> Option<String> {
+    Some(String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(None), PidCheck::Dead);
+        assert_eq!(
+            classify(Some("/app/python/bin/python3 -m uvicorn kiroku_memory.api:app --port 8000")),
+            PidCheck::Ours
+        );
+        assert_eq!(
+            classify(Some("/usr/bin/vim notes.txt")),
+            PidCheck::NotOurs("/usr/bin/vim notes.txt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new("pid");
+        let path = dir.join(FILE_NAME);
+        let record = PidRecord {
+            pid: 4242,
+            port: 8000,
+            started_at: 1_700_000_000,
+        };
+        write(&path, &record).unwrap();
+        assert_eq!(read(&path), Some(record));
+        remove(&path);
+        assert_eq!(read(&path), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_own_process_is_not_ours() {
+        // The test binary is alive but is no service
+        assert!(matches!(check(std::process::id()), PidCheck::NotOurs(_)));
+    }
+}
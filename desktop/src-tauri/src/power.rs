@@ -0,0 +1,77 @@
+// Kiroku Memory Desktop - Power Source Detection
+// AC / battery state via IOKit on macOS; other platforms report Unknown
+
+use serde::Serialize;
+
+/// What the machine is currently running on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum PowerSource {
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    Ac,
+    Battery,
+    Unknown,
+}
+
+/// Read the current power source
+pub fn current_power_source() -> PowerSource {
+    platform::current_power_source()
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::PowerSource;
+    use objc::runtime::Object;
+    use objc::{msg_send, sel, sel_impl};
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::c_char;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPSCopyPowerSourcesInfo() -> *const c_void;
+        fn IOPSGetProvidingPowerSourceType(snapshot: *const c_void) -> *const c_void;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+
+    pub fn current_power_source() -> PowerSource {
+        unsafe {
+            let snapshot = IOPSCopyPowerSourcesInfo();
+            if snapshot.is_null() {
+                return PowerSource::Unknown;
+            }
+
+            // Follows the Get rule: owned by the snapshot, not released here
+            let source_type = IOPSGetProvidingPowerSourceType(snapshot);
+            let source = if source_type.is_null() {
+                PowerSource::Unknown
+            } else {
+                // CFStringRef is toll-free bridged to NSString
+                let utf8: *const c_char = msg_send![source_type as *mut Object, UTF8String];
+                if utf8.is_null() {
+                    PowerSource::Unknown
+                } else {
+                    match CStr::from_ptr(utf8).to_str() {
+                        Ok("Battery Power") => PowerSource::Battery,
+                        Ok("AC Power") | Ok("UPS Power") => PowerSource::Ac,
+                        _ => PowerSource::Unknown,
+                    }
+                }
+            };
+
+            CFRelease(snapshot);
+            source
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::PowerSource;
+
+    pub fn current_power_source() -> PowerSource {
+        PowerSource::Unknown
+    }
+}
@@ -0,0 +1,108 @@
+// Kiroku Memory Desktop - Memory Profiles
+// Named profiles, each with its own SurrealDB store under the data dir
+
+use crate::backup::DB_DIR_NAME;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Profile used when none has been chosen
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Store folder of the default profile. Predates profiles, so existing data stays in place.
+const DEFAULT_PROFILE_DIR: &str = "kiroku";
+
+const MAX_NAME_LEN: usize = 32;
+
+/// Profiles known on disk plus the active one
+#[derive(Clone, Debug, Serialize)]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+/// Profile names become folder names, so keep them to a safe character set
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(format!(
+            "Profile name must be 1-{} characters long",
+            MAX_NAME_LEN
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Profile name may only contain letters, digits, '-' and '_'".to_string());
+    }
+    // Would share the default profile's store; macOS folders ignore case
+    if name.eq_ignore_ascii_case(DEFAULT_PROFILE_DIR) {
+        return Err(format!("'{}' is reserved for the default profile", DEFAULT_PROFILE_DIR));
+    }
+    Ok(())
+}
+
+fn store_dir_name(profile: &str) -> &str {
+    if profile == DEFAULT_PROFILE {
+        DEFAULT_PROFILE_DIR
+    } else {
+        profile
+    }
+}
+
+/// SurrealDB store path of a profile
+pub fn store_path(data_dir: &Path, profile: &str) -> PathBuf {
+    data_dir.join(DB_DIR_NAME).join(store_dir_name(profile))
+}
+
+/// List profiles that have a store on disk, always including the default and active ones
+pub fn list(data_dir: &Path, active: &str) -> ProfileList {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+    if let Ok(entries) = std::fs::read_dir(data_dir.join(DB_DIR_NAME)) {
+        for entry in entries.flatten() {
+            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Skips the default profile's "kiroku" folder too
+            if validate_name(&name).is_ok() {
+                profiles.push(name);
+            }
+        }
+    }
+    if !profiles.iter().any(|p| p == active) {
+        profiles.push(active.to_string());
+    }
+    profiles[1..].sort();
+
+    ProfileList {
+        active: active.to_string(),
+        profiles,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("work").is_ok());
+        assert!(validate_name("side_project-2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name(&"x".repeat(33)).is_err());
+        assert!(validate_name("kiroku").is_err());
+        assert!(validate_name("Kiroku").is_err());
+    }
+
+    #[test]
+    fn test_default_profile_keeps_legacy_store() {
+        let data_dir = Path::new("/data");
+        assert_eq!(
+            store_path(data_dir, DEFAULT_PROFILE),
+            Path::new("/data/surrealdb/kiroku")
+        );
+        assert_eq!(store_path(data_dir, "work"), Path::new("/data/surrealdb/work"));
+    }
+}
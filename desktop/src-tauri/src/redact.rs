@@ -0,0 +1,101 @@
+// Kiroku Memory Desktop - Secret Redaction
+// Scrubs API keys from log lines and error messages before they leave the process
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
+
+/// Replacement for anything that looks like a key
+pub const REDACTED: &str = "sk-\u{2026}[redacted]";
+
+/// Nothing shorter than this can contain a key; such lines skip the regex entirely
+const MIN_KEY_LEN: usize = 16;
+
+/// sk-ant-… (Anthropic), sk-proj-… and plain sk-… (OpenAI)
+fn key_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"sk-(?:ant-[A-Za-z0-9]+-|proj-)?[A-Za-z0-9_\-]{16,}").expect("valid key regex")
+    })
+}
+
+/// Secrets currently stored in the keychain, matched by value
+fn known_secrets() -> &'static RwLock<Vec<String>> {
+    static SECRETS: OnceLock<RwLock<Vec<String>>> = OnceLock::new();
+    SECRETS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Redact this exact value from now on (e.g. after it is stored in the keychain)
+pub fn register_secret(value: &str) {
+    let value = value.trim();
+    if value.len() < MIN_KEY_LEN {
+        return;
+    }
+    let mut secrets = known_secrets().write().unwrap();
+    if !secrets.iter().any(|s| s == value) {
+        secrets.push(value.to_string());
+    }
+}
+
+/// Replace API keys and known secret values in `text`
+pub fn redact(text: &str) -> Cow<'_, str> {
+    if text.len() < MIN_KEY_LEN {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = Cow::Borrowed(text);
+    for secret in known_secrets().read().unwrap().iter() {
+        if out.contains(secret.as_str()) {
+            out = Cow::Owned(out.replace(secret.as_str(), REDACTED));
+        }
+    }
+    let replaced = match key_pattern().replace_all(&out, REDACTED) {
+        Cow::Owned(replaced) => Some(replaced),
+        Cow::Borrowed(_) => None,
+    };
+    replaced.map(Cow::Owned).unwrap_or(out)
+}
+
+/// Error-to-String conversion for command results, with secrets removed
+pub fn error<E: Display>(e: E) -> String {
+    redact(&e.to_string()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_embedded_keys() {
+        let corpus = [
+            "OPENAI_API_KEY=sk-abcdefghijklmnopqrstuvwxyz012345",
+            "Incorrect API key provided: sk-proj-AbC_dEf-123456789012345678901234.",
+            "auth failed for key \"sk-ant-REDACTED\" (401)",
+            "[Kiroku] env: {'OPENAI_API_KEY': 'sk-0123456789abcdefghij0123'}",
+        ];
+        for line in corpus {
+            let out = redact(line);
+            assert!(out.contains(REDACTED), "not redacted: {}", out);
+            let rest = out.replace(REDACTED, "");
+            assert!(!rest.contains("sk-"), "key left in: {}", out);
+        }
+    }
+
+    #[test]
+    fn test_redacts_registered_values() {
+        register_secret("custom-token-value-0000");
+        let out = redact("Bearer custom-token-value-0000 rejected");
+        assert_eq!(out, format!("Bearer {} rejected", REDACTED));
+    }
+
+    #[test]
+    fn test_leaves_plain_lines_untouched() {
+        assert!(matches!(redact("short"), Cow::Borrowed(_)));
+        assert!(matches!(
+            redact("INFO:     Uvicorn running on http://127.0.0.1:8000"),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(redact("task-runner started"), "task-runner started");
+    }
+}
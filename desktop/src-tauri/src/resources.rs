@@ -0,0 +1,143 @@
+// Kiroku Memory Desktop - Resource Usage
+// CPU / memory sampling of the Python service process
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// How much history to keep for charts
+pub const HISTORY_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// One CPU / memory reading of the service process
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+pub struct ResourceSample {
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+    pub pid: u32,
+    /// Percent of one core, so it can exceed 100 on multi-core machines
+    pub cpu_percent: f32,
+    pub rss_bytes: u64,
+}
+
+impl ResourceSample {
+    pub fn rss_mb(&self) -> u64 {
+        self.rss_bytes / (1024 * 1024)
+    }
+}
+
+/// Samples a single process. Keeps state between calls because CPU usage
+/// is computed from the delta since the previous refresh.
+pub struct ResourceSampler {
+    system: System,
+    /// Process whose CPU time was last read; a new one needs a first read to
+    /// have a delta from
+    baseline: Option<u32>,
+}
+
+impl ResourceSampler {
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+            baseline: None,
+        }
+    }
+
+    /// Sample `pid`. Returns `None` if the process no longer exists, and for
+    /// the first read of a new process, which only sets the CPU baseline.
+    pub fn sample(&mut self, pid: u32) -> Option<ResourceSample> {
+        let first_read = self.baseline != Some(pid);
+        self.baseline = Some(pid);
+        let sys_pid = Pid::from_u32(pid);
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[sys_pid]),
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+        let process = self.system.process(sys_pid)?;
+        if first_read {
+            return None;
+        }
+        Some(ResourceSample {
+            timestamp: unix_now(),
+            pid,
+            cpu_percent: process.cpu_usage(),
+            rss_bytes: process.memory(),
+        })
+    }
+}
+
+/// Rolling window of samples
+#[derive(Default)]
+pub struct ResourceHistory {
+    samples: VecDeque<ResourceSample>,
+}
+
+impl ResourceHistory {
+    pub fn push(&mut self, sample: ResourceSample) {
+        let cutoff = sample.timestamp.saturating_sub(HISTORY_WINDOW.as_secs());
+        while self.samples.front().is_some_and(|s| s.timestamp < cutoff) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn latest(&self) -> Option<ResourceSample> {
+        self.samples.back().copied()
+    }
+
+    pub fn to_vec(&self) -> Vec<ResourceSample> {
+        self.samples.iter().copied().collect()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: u64) -> ResourceSample {
+        ResourceSample {
+            timestamp,
+            pid: 42,
+            cpu_percent: 1.0,
+            rss_bytes: 64 * 1024 * 1024,
+        }
+    }
+
+    #[test]
+    fn test_history_drops_samples_outside_window() {
+        let mut history = ResourceHistory::default();
+        history.push(sample(1_000));
+        history.push(sample(1_300));
+        history.push(sample(1_000 + HISTORY_WINDOW.as_secs() + 1));
+
+        let samples = history.to_vec();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].timestamp, 1_300);
+        assert_eq!(history.latest().unwrap().rss_mb(), 64);
+    }
+
+    #[test]
+    fn test_sampling_missing_pid_returns_none() {
+        let mut sampler = ResourceSampler::new();
+        assert_eq!(sampler.sample(u32::MAX - 1), None);
+        assert_eq!(sampler.sample(u32::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_first_sample_only_sets_baseline() {
+        let mut sampler = ResourceSampler::new();
+        let pid = std::process::id();
+        assert_eq!(sampler.sample(pid), None);
+        let sample = sampler.sample(pid).unwrap();
+        assert_eq!(sample.pid, pid);
+        assert!(sample.rss_bytes > 0);
+    }
+}
@@ -0,0 +1,201 @@
+// Kiroku Memory Desktop - Runtime Integrity
+// Checks the bundled Python runtime and app files against the build-time manifest
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Relative paths + SHA-256 of every bundled file, generated by build.rs
+const MANIFEST_JSON: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/runtime-manifest.json"));
+
+/// Archive `repair` extracts from, if the installer ships one next to the runtime
+pub const REPAIR_ARCHIVE: &str = "python-runtime.tar.gz";
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Debug, Deserialize)]
+struct ManifestEntry {
+    path: String,
+    sha256: String,
+    size: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    files: Vec<ManifestEntry>,
+}
+
+fn manifest() -> Manifest {
+    serde_json::from_slice(MANIFEST_JSON).unwrap_or_default()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyProgress {
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RuntimeReport {
+    /// False for builds made without a Python manifest (e.g. development)
+    pub manifest_available: bool,
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+/// Re-hash every manifest entry under `resource_dir`. Files are streamed, and
+/// `progress` is called at most every PROGRESS_INTERVAL plus once at the end.
+pub fn verify(resource_dir: &Path, mut progress: impl FnMut(&VerifyProgress)) -> RuntimeReport {
+    let manifest = manifest();
+    let mut report = RuntimeReport {
+        manifest_available: manifest.files.iter().any(|f| f.path.starts_with("python/")),
+        ..Default::default()
+    };
+    let mut state = VerifyProgress {
+        files_done: 0,
+        files_total: manifest.files.len(),
+        bytes_done: 0,
+        bytes_total: manifest.files.iter().map(|f| f.size).sum(),
+    };
+    let mut last_report = Instant::now();
+
+    for entry in &manifest.files {
+        let path = resource_dir.join(&entry.path);
+        match sha256_file(&path) {
+            None => report.missing.push(entry.path.clone()),
+            Some(hash) if hash != entry.sha256 => report.modified.push(entry.path.clone()),
+            Some(_) => {}
+        }
+        report.checked += 1;
+        state.files_done += 1;
+        state.bytes_done += entry.size;
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            progress(&state);
+            last_report = Instant::now();
+        }
+    }
+    progress(&state);
+    report
+}
+
+fn sha256_file(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 256 * 1024];
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Fast pre-flight for a bundled runtime: the interpreter exists and is
+/// executable, and a few stdlib modules every start imports are present.
+/// Returns a description of each problem found.
+pub fn quick_check(python_dir: &Path) -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    let (binary, stdlib) = ("python.exe", python_dir.join("Lib"));
+    #[cfg(not(target_os = "windows"))]
+    let (binary, stdlib) = ("bin/python3", python_dir.join("lib/python3.11"));
+
+    let mut problems = Vec::new();
+    let python_bin = python_dir.join(binary);
+    if !python_bin.is_file() {
+        problems.push(format!("{} is missing", python_bin.display()));
+    } else if !is_executable(&python_bin) {
+        problems.push(format!("{} is not executable", python_bin.display()));
+    }
+    for module in ["os.py", "encodings/__init__.py", "asyncio/__init__.py", "site-packages"] {
+        let path = stdlib.join(module);
+        if !path.exists() {
+            problems.push(format!("{} is missing", path.display()));
+        }
+    }
+    problems
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// Re-extract the Python runtime from REPAIR_ARCHIVE, if the installer shipped
+/// one. The archive holds a top-level `python/` directory, like the
+/// python-build-standalone tarballs it is built from.
+pub fn repair(resource_dir: &Path) -> anyhow::Result<()> {
+    let archive = resource_dir.join(REPAIR_ARCHIVE);
+    if !archive.is_file() {
+        anyhow::bail!("This build has no runtime archive to repair from. Please reinstall Kiroku Memory.");
+    }
+
+    let python_dir = resource_dir.join("python");
+    let aside = resource_dir.join("python.damaged");
+    let _ = std::fs::remove_dir_all(&aside);
+    if python_dir.exists() {
+        std::fs::rename(&python_dir, &aside)?;
+    }
+
+    let status = std::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(resource_dir)
+        .status();
+    match status {
+        Ok(status) if status.success() => {
+            let _ = std::fs::remove_dir_all(&aside);
+            Ok(())
+        }
+        other => {
+            // Put the old runtime back rather than leave nothing
+            let _ = std::fs::remove_dir_all(&python_dir);
+            if aside.exists() {
+                let _ = std::fs::rename(&aside, &python_dir);
+            }
+            match other {
+                Ok(status) => anyhow::bail!("Extracting {} failed ({})", archive.display(), status),
+                Err(e) => anyhow::bail!("Could not run tar: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_embedded_manifest_parses() {
+        // The app sources are always hashed, even without a Python manifest
+        let manifest = manifest();
+        assert!(manifest
+            .files
+            .iter()
+            .any(|f| f.path == "app/kiroku_memory/api.py" && f.sha256.len() == 64));
+        assert!(manifest.files.iter().all(|f| !f.path.contains("__pycache__")));
+    }
+
+    #[test]
+    fn test_quick_check_reports_missing_runtime() {
+        let dir = TempDir::new("runtime");
+        let problems = quick_check(&dir.join("missing"));
+        assert!(problems.iter().any(|p| p.contains("is missing")));
+        assert_eq!(problems.len(), 5);
+    }
+}
@@ -0,0 +1,281 @@
+// Kiroku Memory Desktop - Memory Search
+// Typed search results and per-window cancellation of superseded queries
+
+use crate::api::{ApiClient, ApiError, ResponseMeta, WithMeta};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// Maximum length of the text snippet returned to the frontend
+const SNIPPET_MAX_CHARS: usize = 200;
+
+/// Optional filters for a search
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    pub category: Option<String>,
+    pub min_similarity: Option<f64>,
+}
+
+/// Single search hit returned to the frontend
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct SearchHit {
+    pub id: String,
+    pub snippet: String,
+    pub score: f64,
+    pub created_at: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Result of a search command
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchOutcome {
+    Results {
+        hits: Vec<SearchHit>,
+        total: u64,
+        meta: ResponseMeta,
+    },
+    /// Service is restarting; the caller should retry shortly
+    ServiceBusy,
+    /// A newer query from the same window replaced this one
+    Superseded,
+}
+
+#[derive(Deserialize)]
+struct RawSearchResponse {
+    results: Vec<RawSearchResult>,
+    total: u64,
+}
+
+#[derive(Deserialize)]
+struct RawSearchResult {
+    id: String,
+    subject: Option<String>,
+    predicate: Option<String>,
+    object: Option<String>,
+    category: Option<String>,
+    similarity: f64,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+impl From<RawSearchResult> for SearchHit {
+    fn from(raw: RawSearchResult) -> Self {
+        let text = [raw.subject, raw.predicate, raw.object]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            id: raw.id,
+            snippet: snippet(&text),
+            score: raw.similarity,
+            created_at: raw.created_at,
+            tags: raw.category.into_iter().collect(),
+        }
+    }
+}
+
+fn snippet(text: &str) -> String {
+    if text.chars().count() <= SNIPPET_MAX_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(SNIPPET_MAX_CHARS).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Call the service's /search endpoint.
+///
+/// The endpoint has no offset parameter, so `limit + offset` results are
+/// requested and the first `offset` are skipped here.
+pub async fn search(
+    client: &ApiClient,
+    query: &str,
+    limit: u32,
+    offset: u32,
+    filters: &SearchFilters,
+) -> Result<(Vec<SearchHit>, u64, ResponseMeta), ApiError> {
+    let mut params = vec![
+        ("q", query.to_string()),
+        ("limit", limit.saturating_add(offset).to_string()),
+    ];
+    if let Some(category) = &filters.category {
+        params.push(("category", category.clone()));
+    }
+    if let Some(min_similarity) = filters.min_similarity {
+        params.push(("min_similarity", min_similarity.to_string()));
+    }
+
+    let WithMeta { data: resp, meta } = client
+        .get_json_with_meta::<RawSearchResponse>("/search", &params)
+        .await?;
+    let hits = resp
+        .results
+        .into_iter()
+        .skip(offset as usize)
+        .map(SearchHit::from)
+        .collect();
+    Ok((hits, resp.total, meta))
+}
+
+/// Tracks a generation counter per window so that a newer query cancels
+/// the one still in flight for the same window.
+#[derive(Default)]
+pub struct SearchGenerations {
+    windows: Mutex<HashMap<String, watch::Sender<u64>>>,
+}
+
+impl SearchGenerations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new generation for `window`, superseding any in-flight query
+    fn begin(&self, window: &str) -> (u64, watch::Receiver<u64>) {
+        let mut windows = self.windows.lock().unwrap();
+        let tx = windows
+            .entry(window.to_string())
+            .or_insert_with(|| watch::channel(0).0);
+        let generation = *tx.borrow() + 1;
+        tx.send_replace(generation);
+        (generation, tx.subscribe())
+    }
+
+    /// Run `fut` as the latest query for `window`.
+    /// Returns `None` if a newer query arrived before it finished.
+    pub async fn run<T>(&self, window: &str, fut: impl Future<Output = T>) -> Option<T> {
+        let (generation, mut rx) = self.begin(window);
+        tokio::select! {
+            out = fut => (*rx.borrow() == generation).then_some(out),
+            _ = superseded(&mut rx, generation) => None,
+        }
+    }
+}
+
+/// Resolves once the window's generation moves past `generation`
+async fn superseded(rx: &mut watch::Receiver<u64>, generation: u64) {
+    loop {
+        if rx.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        if *rx.borrow() != generation {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Stand-in for the API client with a fixed latency per query
+    struct MockClient {
+        calls: AtomicUsize,
+        completed: AtomicUsize,
+    }
+
+    impl MockClient {
+        fn new() -> Self {
+            Self {
+                calls: AtomicUsize::new(0),
+                completed: AtomicUsize::new(0),
+            }
+        }
+
+        async fn search(&self, query: &str, delay: Duration) -> Result<Vec<SearchHit>, ApiError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(delay).await;
+            self.completed.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![SearchHit {
+                id: query.to_string(),
+                snippet: query.to_string(),
+                score: 1.0,
+                created_at: None,
+                tags: vec![],
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_newer_query_supersedes_slow_one() {
+        let gens = Arc::new(SearchGenerations::new());
+        let client = Arc::new(MockClient::new());
+
+        let (g, c) = (gens.clone(), client.clone());
+        let slow = tokio::spawn(async move {
+            g.run("main", c.search("old", Duration::from_millis(300))).await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let fresh = gens
+            .run("main", client.search("new", Duration::from_millis(10)))
+            .await;
+
+        assert_eq!(slow.await.unwrap(), None);
+        assert_eq!(fresh.unwrap().unwrap()[0].id, "new");
+        // The slow request was dropped before it completed
+        assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(client.completed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_windows_do_not_cancel_each_other() {
+        let gens = Arc::new(SearchGenerations::new());
+        let client = Arc::new(MockClient::new());
+
+        let (g, c) = (gens.clone(), client.clone());
+        let main = tokio::spawn(async move {
+            g.run("main", c.search("a", Duration::from_millis(100))).await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let other = gens
+            .run("settings", client.search("b", Duration::from_millis(10)))
+            .await;
+
+        assert!(main.await.unwrap().is_some());
+        assert!(other.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sequential_queries_all_complete() {
+        let gens = SearchGenerations::new();
+        let client = MockClient::new();
+
+        for q in ["a", "ab", "abc"] {
+            let out = gens.run("main", client.search(q, Duration::from_millis(1))).await;
+            assert_eq!(out.unwrap().unwrap()[0].id, q);
+        }
+    }
+
+    #[test]
+    fn test_503_maps_to_service_busy() {
+        assert_eq!(ApiError::from_status(503, String::new()), ApiError::ServiceBusy);
+        assert_eq!(
+            ApiError::from_status(500, "boom".into()),
+            ApiError::Status(500, "boom".into())
+        );
+    }
+
+    #[test]
+    fn test_hit_from_raw_result() {
+        let raw: RawSearchResult = serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "subject": "user",
+            "predicate": "prefers",
+            "object": "tea",
+            "category": "preferences",
+            "confidence": 1.0,
+            "similarity": 0.82
+        }))
+        .unwrap();
+        let hit = SearchHit::from(raw);
+        assert_eq!(hit.snippet, "user prefers tea");
+        assert_eq!(hit.tags, vec!["preferences".to_string()]);
+        assert_eq!(hit.created_at, None);
+    }
+}
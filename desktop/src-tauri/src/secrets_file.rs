@@ -0,0 +1,109 @@
+// Kiroku Memory Desktop - Secrets Handoff
+// Hands secrets to the service through a private file instead of its environment,
+// which other local processes can read (/proc/<pid>/environ, ps -E)
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Path of the secrets file, read by the service at import
+pub const SECRETS_FILE_ENV: &str = "KIROKU_SECRETS_FILE";
+
+/// First service version that reads SECRETS_FILE_ENV
+pub const MIN_SERVICE_VERSION: &str = "0.1.26";
+
+const FILE_PREFIX: &str = "service-secrets-";
+
+/// How secrets reach the service process
+#[derive(Debug)]
+pub enum Handoff {
+    /// Plain environment variables (`secrets_via_env`, or an older service)
+    Env(Vec<(&'static str, String)>),
+    /// A JSON file of variable name -> value, named by SECRETS_FILE_ENV
+    File(PathBuf),
+}
+
+/// Write `secrets` to a new 0600 file in `dir`, created 0700 if missing
+pub fn write(dir: &Path, secrets: &[(&'static str, String)]) -> anyhow::Result<PathBuf> {
+    create_private_dir(dir)?;
+    let values: BTreeMap<&str, &str> = secrets.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let path = dir.join(format!("{}{}.json", FILE_PREFIX, uuid::Uuid::new_v4().simple()));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    if let Err(e) = file.write_all(serde_json::to_string(&values)?.as_bytes()) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e.into());
+    }
+    Ok(path)
+}
+
+/// Remove every secrets file in `dir`, including ones left by a crash
+pub fn remove_all(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(FILE_PREFIX) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Whether a service reporting `version` reads SECRETS_FILE_ENV. Versions that
+/// don't parse (development builds) are assumed to.
+pub fn supported_by(version: &str) -> bool {
+    let minimum = semver::Version::parse(MIN_SERVICE_VERSION).expect("valid minimum version");
+    semver::Version::parse(version.trim_start_matches('v')).map_or(true, |v| v >= minimum)
+}
+
+pub fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_write_is_private_and_removable() {
+        let dir = TempDir::new("secrets");
+
+        let path = write(dir.path(), &[("OPENAI_API_KEY", "sk-test".to_string())]).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, r#"{"OPENAI_API_KEY":"sk-test"}"#);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(dir.join("unrelated.txt"), "keep").unwrap();
+        remove_all(dir.path());
+        assert!(!path.exists());
+        assert!(dir.join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn test_supported_by_version() {
+        assert!(supported_by(MIN_SERVICE_VERSION));
+        assert!(supported_by("v0.2.0"));
+        assert!(!supported_by("0.1.25"));
+        assert!(supported_by("dev"));
+    }
+}
@@ -1,10 +1,27 @@
 // Kiroku Memory Desktop - Python Service Management
 // Handles spawning, health checking, and lifecycle of the Python FastAPI service
 
-use crate::config::{keychain, keys};
+use crate::api::ApiClient;
+use crate::cli::StartupOverrides;
+use crate::config::{keychain, keys, settings, AppSettings, KeychainStatus, ServiceOptions};
+use crate::events::{self, AppEvent};
+use crate::exit_info::ExitInfo;
+use crate::permissions;
+use crate::pid_file::{self, PidCheck, PidRecord};
+use crate::power::PowerSource;
+use crate::profiles;
+use crate::redact;
+use crate::resources::{ResourceHistory, ResourceSample};
+use crate::runtime;
+use crate::secrets_file::{self, Handoff};
+use crate::service_log::{self, LogLine, LogStream, ServiceLogBuffer};
+use crate::storage;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
@@ -17,6 +34,389 @@ pub enum ServiceStatus {
     Stopped,
     Error(String),
     Restarting,
+    /// `OnFirstUse` mode before anything needed the service
+    Idle,
+    /// Serving, but with a problem that loses data if ignored (e.g. the disk
+    /// is almost full)
+    Degraded(String),
+}
+
+impl ServiceStatus {
+    /// Stopped, or idle until first use: there is no process to stop or restart
+    pub fn is_stopped(&self) -> bool {
+        matches!(self, ServiceStatus::Stopped | ServiceStatus::Idle)
+    }
+
+    /// Healthy and serving requests, degraded or not
+    pub fn is_up(&self) -> bool {
+        matches!(self, ServiceStatus::Running | ServiceStatus::Degraded(_))
+    }
+}
+
+/// Snapshot of the service for the frontend
+#[derive(Clone, serde::Serialize)]
+pub struct ServiceInfo {
+    pub status: ServiceStatus,
+    pub pid: Option<u32>,
+    pub remote: bool,
+    /// Base URL the app talks to, including the effective port
+    pub base_url: String,
+    /// The service's stats needed fallbacks to read; it's likely newer than
+    /// this app, which should be updated
+    pub stats_schema_warning: bool,
+    /// Set when requests go over a Unix socket, which the webview can't
+    /// reach; use `proxy_request` then
+    pub unix_socket: Option<String>,
+    pub resources: Option<ResourceSample>,
+    pub power_source: PowerSource,
+    /// How the last service process ended, if it has
+    pub last_exit_code: Option<i32>,
+    pub last_exit_signal: Option<i32>,
+    /// Most recent health sample, with its latency when it passed
+    pub last_health_check: Option<HealthSample>,
+    /// Unix timestamp (seconds) the current process was spawned or adopted
+    pub started_at: Option<i64>,
+    /// Service processes spawned after the first one this session
+    pub restart_count: u32,
+    /// Unix timestamp (seconds) of the latest of those
+    pub last_restart_at: Option<i64>,
+}
+
+/// Number of health samples kept for the status timeline; about 40 minutes at
+/// the monitor's 5 s interval
+pub const HEALTH_HISTORY_LEN: usize = 500;
+
+/// How the service process was launched, for debugging. Secret values are "<set>".
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct LaunchInfo {
+    pub python_bin: String,
+    pub pythonpath: String,
+    pub working_dir: String,
+    pub args: Vec<String>,
+    /// Variables set explicitly for the service (the rest is inherited)
+    pub env: BTreeMap<String, String>,
+    pub runtime: RuntimeSource,
+    /// Existence checks that led to `runtime`
+    pub runtime_checks: Vec<String>,
+    /// Session-only command-line overrides in effect (e.g. "--port 8123")
+    pub overrides: Vec<String>,
+    /// Unix timestamp (seconds)
+    pub started_at: i64,
+}
+
+impl LaunchInfo {
+    /// One-line form for app.log
+    pub fn summary(&self) -> String {
+        let env: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        let mut summary = format!(
+            "{} {} [runtime: {:?}; cwd: {}; env: {}; checks: {}]",
+            self.python_bin,
+            self.args.join(" "),
+            self.runtime,
+            self.working_dir,
+            env.join(" "),
+            self.runtime_checks.join(", ")
+        );
+        if !self.overrides.is_empty() {
+            summary.push_str(&format!(" [overrides: {}]", self.overrides.join(" ")));
+        }
+        summary
+    }
+}
+
+/// What the service would be launched with under the current settings.
+/// Secrets are reported only as configured or not.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RuntimeInfo {
+    pub python_bin: String,
+    pub pythonpath: String,
+    pub runtime: RuntimeSource,
+    pub host: String,
+    pub port: u16,
+    pub base_url: String,
+    pub remote: bool,
+    pub data_dir: String,
+    pub backend: String,
+    /// None unless the backend uses SurrealDB
+    pub surreal_url: Option<String>,
+    pub openai_key_configured: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Resolve everything `start` prints, without starting anything
+pub fn runtime_info(app: &AppHandle) -> anyhow::Result<RuntimeInfo> {
+    let app_settings = settings::load_effective(app)?;
+    let paths = get_python_paths(app)?;
+    let data_dir = get_data_dir(app)?;
+    Ok(RuntimeInfo {
+        python_bin: paths.python_bin.display().to_string(),
+        pythonpath: paths.pythonpath.display().to_string(),
+        runtime: paths.source,
+        host: app_settings.bind_host.clone(),
+        port: app_settings.service_port,
+        base_url: app_settings.base_url(),
+        remote: app_settings.is_remote(),
+        surreal_url: app_settings
+            .uses_surrealdb()
+            .then(|| surreal_url(&data_dir, &app_settings.active_profile)),
+        data_dir: data_dir.display().to_string(),
+        backend: app_settings.backend.clone(),
+        openai_key_configured: keychain::has_secret(keys::OPENAI_API_KEY),
+        warnings: app_settings.warnings(),
+    })
+}
+
+/// `file://` URL of a profile's embedded SurrealDB store
+fn surreal_url(data_dir: &Path, profile: &str) -> String {
+    let store_path = profiles::store_path(data_dir, profile);
+    format!("file://{}", store_path.to_string_lossy().replace('\\', "/"))
+}
+
+/// The operation holding the maintenance lock
+#[derive(Clone, Debug, serde::Serialize, PartialEq)]
+pub struct MaintenanceInfo {
+    pub operation: String,
+    /// Unix timestamp (seconds)
+    pub started_at: i64,
+}
+
+/// Returned when the maintenance lock is taken
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaintenanceBusy(pub Option<MaintenanceInfo>);
+
+impl fmt::Display for MaintenanceBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(info) => {
+                let since = chrono::DateTime::from_timestamp(info.started_at, 0)
+                    .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                write!(f, "Busy with {} since {}", info.operation, since)
+            }
+            // Only while a holder is between taking the lock and naming itself
+            None => write!(f, "Another maintenance operation is in progress"),
+        }
+    }
+}
+
+impl std::error::Error for MaintenanceBusy {}
+
+/// Makes backups, restores, profile switches and other operations that stop
+/// or rewrite the store mutually exclusive
+#[derive(Default)]
+pub struct MaintenanceLock {
+    lock: Arc<Mutex<()>>,
+    current: Arc<std::sync::Mutex<Option<MaintenanceInfo>>>,
+}
+
+/// Held for the duration of a maintenance operation; dropping it (also while
+/// unwinding from a panic) releases the lock
+pub struct MaintenanceGuard {
+    current: Arc<std::sync::Mutex<Option<MaintenanceInfo>>>,
+    _lock: tokio::sync::OwnedMutexGuard<()>,
+}
+
+impl Drop for MaintenanceGuard {
+    fn drop(&mut self) {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl MaintenanceLock {
+    /// Take the lock for `operation`, or report who holds it
+    pub fn try_acquire(&self, operation: &str) -> Result<MaintenanceGuard, MaintenanceBusy> {
+        let lock = self
+            .lock
+            .clone()
+            .try_lock_owned()
+            .map_err(|_| MaintenanceBusy(self.current()))?;
+        *self.current.lock().unwrap_or_else(|e| e.into_inner()) = Some(MaintenanceInfo {
+            operation: operation.to_string(),
+            started_at: chrono::Utc::now().timestamp(),
+        });
+        Ok(MaintenanceGuard {
+            current: self.current.clone(),
+            _lock: lock,
+        })
+    }
+
+    /// Fail if an operation holds the lock, without taking it
+    pub fn check(&self) -> Result<(), MaintenanceBusy> {
+        match self.lock.try_lock() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(MaintenanceBusy(self.current())),
+        }
+    }
+
+    pub fn current(&self) -> Option<MaintenanceInfo> {
+        self.current.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Least time between one restart finishing and the next one starting
+pub const MIN_RESTART_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Why a restart request didn't restart the service
+#[derive(Clone, Debug, PartialEq)]
+pub enum RestartError {
+    /// The last restart finished less than `MIN_RESTART_INTERVAL` ago
+    TooSoon { retry_in_secs: u64 },
+    Failed(String),
+}
+
+impl fmt::Display for RestartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestartError::TooSoon { retry_in_secs } => write!(
+                f,
+                "The service was just restarted; try again in {}s",
+                retry_in_secs
+            ),
+            RestartError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for RestartError {}
+
+type RestartResult = Result<(), String>;
+
+#[derive(Default)]
+struct RestartQueue {
+    /// Callers waiting on the restart in progress
+    in_flight: Option<tokio::sync::broadcast::Sender<RestartResult>>,
+    last_finished: Option<std::time::Instant>,
+}
+
+/// Coalesces restart requests from the UI, the tray and the monitor: while one
+/// runs, further requests share its result instead of queueing another
+#[derive(Default)]
+pub struct RestartGate {
+    queue: std::sync::Mutex<RestartQueue>,
+}
+
+/// Publishes the leader's result; dropping it unfinished (cancelled) still
+/// releases the waiters
+struct RestartTurn<'a> {
+    gate: &'a RestartGate,
+    result: Option<RestartResult>,
+}
+
+impl Drop for RestartTurn<'_> {
+    fn drop(&mut self) {
+        let mut queue = self.gate.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.last_finished = Some(std::time::Instant::now());
+        if let Some(waiters) = queue.in_flight.take() {
+            let result = self
+                .result
+                .take()
+                .unwrap_or_else(|| Err("Restart was cancelled".to_string()));
+            let _ = waiters.send(result);
+        }
+    }
+}
+
+impl RestartGate {
+    /// Run `restart`, or wait for the one already running and return its result
+    pub async fn run<F, Fut>(&self, restart: F) -> Result<(), RestartError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = RestartResult>,
+    {
+        let waiting = {
+            let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+            match &queue.in_flight {
+                Some(waiters) => Some(waiters.subscribe()),
+                None => {
+                    let since = queue.last_finished.map(|t| t.elapsed());
+                    if let Some(wait) = since.and_then(|since| MIN_RESTART_INTERVAL.checked_sub(since)) {
+                        return Err(RestartError::TooSoon {
+                            retry_in_secs: wait.as_secs_f64().ceil().max(1.0) as u64,
+                        });
+                    }
+                    queue.in_flight = Some(tokio::sync::broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+        if let Some(mut waiting) = waiting {
+            let result = waiting
+                .recv()
+                .await
+                .unwrap_or_else(|_| Err("Restart was cancelled".to_string()));
+            return result.map_err(RestartError::Failed);
+        }
+
+        let mut turn = RestartTurn {
+            gate: self,
+            result: None,
+        };
+        let result = restart().await;
+        turn.result = Some(result.clone());
+        drop(turn);
+        result.map_err(RestartError::Failed)
+    }
+}
+
+/// Error status when the service is not started for lack of an OpenAI key
+pub const MISSING_KEY_ERROR: &str = "No API key configured";
+
+/// Error status when the data dir's volume is below `min_free_disk_mb`; also
+/// the `Degraded` reason when space runs low while the service runs
+pub const LOW_DISK_SPACE_ERROR: &str = "Low disk space";
+
+/// Error status when nothing can be written to the store directory (e.g. the
+/// app runs from a read-only disk image)
+pub const NOT_WRITABLE_ERROR: &str = "Data directory is not writable";
+
+/// Directory under the app data dir for the PID file, and secrets files while the service starts
+const RUN_DIR: &str = "run";
+
+/// Socket file in RUN_DIR for `Transport::UnixSocket`
+const SOCKET_FILE_NAME: &str = "service.sock";
+
+/// sun_path holds 104 bytes on macOS (108 on Linux), including the NUL
+const MAX_SOCKET_PATH_LEN: usize = 103;
+
+/// Health checks before a leftover service is judged unresponsive and killed
+const ADOPT_HEALTH_ATTEMPTS: u32 = 3;
+
+/// Bearer token the service requires on every request when set
+pub const API_TOKEN_ENV: &str = "KIROKU_API_TOKEN";
+
+/// Set to run the service with uvicorn `--reload` (debug builds, development runtime)
+pub const DEV_RELOAD_ENV: &str = "KIROKU_DEV_RELOAD";
+
+/// Outcome of one health sample
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+pub enum HealthState {
+    Ok,
+    Failed,
+    /// Stopped on purpose (or idle until first use); not checked
+    Stopped,
+    /// Stopped by the battery saver; not checked
+    Paused,
+}
+
+/// One health sample from the monitor or a manual check
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+pub struct HealthSample {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub state: HealthState,
+    /// Round trip of a passed check
+    pub latency_ms: Option<u64>,
+}
+
+/// A passed `check_health_once`
+#[derive(Clone, Debug)]
+pub struct HealthCheck {
+    pub response: HealthResponse,
+    pub latency: Duration,
 }
 
 /// Health check response from the API
@@ -30,8 +430,67 @@ pub struct HealthResponse {
 pub struct PythonService {
     child: Mutex<Option<Child>>,
     status: Mutex<ServiceStatus>,
+    /// Bumped by every start and stop, so a health wait left over from an
+    /// earlier one can tell it no longer decides the status
+    generation: tokio::sync::watch::Sender<u64>,
     should_restart: AtomicBool,
+    /// User preference from `AppSettings::auto_restart`; `should_restart` only
+    /// tracks whether the service was stopped on purpose
+    auto_restart: AtomicBool,
     restart_in_progress: AtomicBool,
+    remote: AtomicBool,
+    maintenance: MaintenanceLock,
+    restarts: RestartGate,
+    resource_history: Mutex<ResourceHistory>,
+    health_history: Mutex<VecDeque<HealthSample>>,
+    power_source: Mutex<PowerSource>,
+    polling_scale: AtomicU32,
+    paused_for_battery: AtomicBool,
+    logs: Arc<ServiceLogBuffer>,
+    /// When the current process was spawned (Unix ms), bounding its startup output
+    spawned_at: AtomicU64,
+    /// Spawns after the first one this session, and when the last happened (Unix ms)
+    restart_count: AtomicU32,
+    last_restart_at: AtomicU64,
+    started: AtomicBool,
+    launch_info: Mutex<Option<LaunchInfo>>,
+    /// Python binary that already passed `verify_python`
+    verified_python: Mutex<Option<PathBuf>>,
+    /// Last successful /health response, kept for version display
+    last_health: Mutex<Option<HealthResponse>>,
+    /// Secrets file of the current process, until the service has read it
+    secrets_file: Mutex<Option<SecretsFile>>,
+    /// The service turned out too old for the secrets file; use env from now on
+    legacy_secrets_env: AtomicBool,
+    /// Exit status of the last reaped process, cleared on spawn
+    last_exit: Mutex<Option<ExitInfo>>,
+    exit_reported: AtomicBool,
+    /// Service left running by an earlier app instance and taken over at start
+    adopted_pid: Mutex<Option<u32>>,
+    /// PID file of the current process, removed when it is stopped
+    pid_path: Mutex<Option<PathBuf>>,
+    /// The current process writes to service.log rather than to pipes read by
+    /// this app, so `leave_running` can let it outlive the app
+    output_to_file: AtomicBool,
+    /// Most recent error status, kept after the service recovers
+    last_error: Mutex<Option<LastError>>,
+}
+
+/// Returned by `get_last_error`
+#[derive(Clone, Debug, serde::Serialize, PartialEq)]
+pub struct LastError {
+    pub message: String,
+    /// Unix timestamp (seconds)
+    pub at: i64,
+    /// When the service was next marked running; None while still failing
+    pub recovered_at: Option<i64>,
+}
+
+struct SecretsFile {
+    path: PathBuf,
+    /// `--reload` re-imports the app on every change, and uvicorn imports it
+    /// again in each worker (including respawned ones), so keep it until stop
+    keep_until_stop: bool,
 }
 
 impl PythonService {
@@ -39,11 +498,267 @@ impl PythonService {
         Self {
             child: Mutex::new(None),
             status: Mutex::new(ServiceStatus::Stopped),
-            should_restart: AtomicBool::new(true),
+            generation: tokio::sync::watch::Sender::new(0),
+            // Set by `start`, so the monitor leaves a never-started service alone
+            should_restart: AtomicBool::new(false),
+            auto_restart: AtomicBool::new(true),
             restart_in_progress: AtomicBool::new(false),
+            remote: AtomicBool::new(false),
+            maintenance: MaintenanceLock::default(),
+            restarts: RestartGate::default(),
+            resource_history: Mutex::new(ResourceHistory::default()),
+            health_history: Mutex::new(VecDeque::with_capacity(HEALTH_HISTORY_LEN)),
+            power_source: Mutex::new(PowerSource::Unknown),
+            polling_scale: AtomicU32::new(1),
+            paused_for_battery: AtomicBool::new(false),
+            logs: Arc::new(ServiceLogBuffer::default()),
+            spawned_at: AtomicU64::new(0),
+            restart_count: AtomicU32::new(0),
+            last_restart_at: AtomicU64::new(0),
+            started: AtomicBool::new(false),
+            launch_info: Mutex::new(None),
+            verified_python: Mutex::new(None),
+            last_health: Mutex::new(None),
+            secrets_file: Mutex::new(None),
+            legacy_secrets_env: AtomicBool::new(false),
+            last_exit: Mutex::new(None),
+            exit_reported: AtomicBool::new(false),
+            adopted_pid: Mutex::new(None),
+            pid_path: Mutex::new(None),
+            output_to_file: AtomicBool::new(false),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    /// Last observed power source
+    pub async fn power_source(&self) -> PowerSource {
+        *self.power_source.lock().await
+    }
+
+    pub async fn set_power_source(&self, source: PowerSource) {
+        *self.power_source.lock().await = source;
+    }
+
+    /// Multiplier applied to all polling intervals (raised on battery)
+    pub fn polling_scale(&self) -> u32 {
+        self.polling_scale.load(Ordering::SeqCst)
+    }
+
+    pub fn set_polling_scale(&self, scale: u32) {
+        self.polling_scale.store(scale.max(1), Ordering::SeqCst);
+    }
+
+    /// Whether the service was stopped by the battery saver (not by the user)
+    pub fn is_paused_for_battery(&self) -> bool {
+        self.paused_for_battery.load(Ordering::SeqCst)
+    }
+
+    pub fn set_paused_for_battery(&self, paused: bool) {
+        self.paused_for_battery.store(paused, Ordering::SeqCst);
+    }
+
+    /// How the last service process was launched
+    pub async fn launch_info(&self) -> Option<LaunchInfo> {
+        self.launch_info.lock().await.clone()
+    }
+
+    /// Last successful health response, if the service has answered yet
+    pub async fn last_health(&self) -> Option<HealthResponse> {
+        self.last_health.lock().await.clone()
+    }
+
+    /// Record a successful health check. A single process has read its secrets
+    /// by now, so the secrets file is removed unless it is kept until stop.
+    pub async fn set_last_health(&self, health: HealthResponse) {
+        let mut secrets_file = self.secrets_file.lock().await;
+        if let Some(file) = secrets_file.as_ref() {
+            if !secrets_file::supported_by(&health.version) {
+                println!(
+                    "[Service] WARNING: service {} predates {} (needs {}); secrets go through the environment from the next start",
+                    health.version,
+                    secrets_file::SECRETS_FILE_ENV,
+                    secrets_file::MIN_SERVICE_VERSION
+                );
+                self.legacy_secrets_env.store(true, Ordering::SeqCst);
+            }
+            if !file.keep_until_stop {
+                let _ = std::fs::remove_file(&file.path);
+                *secrets_file = None;
+            }
+        }
+        drop(secrets_file);
+        *self.last_health.lock().await = Some(health);
+    }
+
+    /// Delete the current secrets file, if any
+    async fn remove_secrets_file(&self) {
+        if let Some(file) = self.secrets_file.lock().await.take() {
+            let _ = std::fs::remove_file(&file.path);
+        }
+    }
+
+    /// Whether `start` has been called since launch
+    pub fn has_started(&self) -> bool {
+        self.started.load(Ordering::SeqCst)
+    }
+
+    /// Last `n` lines of captured service output
+    pub fn recent_logs(&self, n: usize) -> Vec<LogLine> {
+        self.logs.recent(n)
+    }
+
+    /// Root cause found in the output of the last spawned process, if any
+    pub fn startup_failure(&self) -> Option<String> {
+        let lines = self.logs.since(self.spawned_at.load(Ordering::SeqCst));
+        service_log::root_cause(&lines).map(|cause| redact::redact(&cause).into_owned())
+    }
+
+    /// How the last service process ended, if it has
+    pub async fn last_exit(&self) -> Option<ExitInfo> {
+        *self.last_exit.lock().await
+    }
+
+    /// The last exit and its error status, e.g. "Service exited with code 1:
+    /// Port already in use by another process". Returned once per exit.
+    pub async fn take_unreported_exit(&self) -> Option<(ExitInfo, String)> {
+        let exit = self.last_exit().await?;
+        if self.exit_reported.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        Some((exit, exit.error_message(self.startup_failure().as_deref())))
+    }
+
+    async fn record_exit(&self, status: ExitStatus) {
+        let mut last_exit = self.last_exit.lock().await;
+        if last_exit.is_none() {
+            let exit = ExitInfo::from_status(status);
+            println!("[Service] Python process exited with {}", exit.describe());
+            *last_exit = Some(exit);
+            self.exit_reported.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Time since the current process was spawned; None for a remote service
+    pub fn uptime(&self) -> Option<Duration> {
+        let spawned_at = self.spawned_at.load(Ordering::SeqCst);
+        if spawned_at == 0 || self.is_remote() {
+            return None;
+        }
+        Some(Duration::from_millis(service_log::unix_millis().saturating_sub(spawned_at)))
+    }
+
+    /// Unix timestamp (seconds) of the latest restart this session
+    pub fn last_restart_at(&self) -> Option<i64> {
+        match self.last_restart_at.load(Ordering::SeqCst) {
+            0 => None,
+            millis => Some((millis / 1000) as i64),
         }
     }
 
+    /// PID of the spawned (or adopted) Python process, if any
+    pub async fn pid(&self) -> Option<u32> {
+        match self.child.lock().await.as_ref() {
+            Some(child) => Some(child.id()),
+            None => *self.adopted_pid.lock().await,
+        }
+    }
+
+    /// Snapshot of status, process, and latest resource usage
+    pub async fn info(&self, base_url: String, unix_socket: Option<String>) -> ServiceInfo {
+        let pid = self.pid().await;
+        let last_exit = self.last_exit().await;
+        ServiceInfo {
+            status: self.get_status().await,
+            pid,
+            remote: self.is_remote(),
+            base_url,
+            stats_schema_warning: crate::stats::schema_warning(),
+            unix_socket,
+            resources: self.process_metrics().await,
+            power_source: self.power_source().await,
+            last_exit_code: last_exit.and_then(|e| e.code),
+            last_exit_signal: last_exit.and_then(|e| e.signal),
+            last_health_check: self.health_history.lock().await.back().copied(),
+            started_at: self.uptime().map(|_| (self.spawned_at.load(Ordering::SeqCst) / 1000) as i64),
+            restart_count: self.restart_count.load(Ordering::SeqCst),
+            last_restart_at: self.last_restart_at(),
+        }
+    }
+
+    /// Record a resource usage sample of the service process
+    pub async fn record_resource_sample(&self, sample: ResourceSample) {
+        self.resource_history.lock().await.push(sample);
+    }
+
+    /// Latest resource usage of the running process; None when there is none
+    /// or it hasn't been sampled yet
+    pub async fn process_metrics(&self) -> Option<ResourceSample> {
+        let pid = self.pid().await?;
+        self.resource_history
+            .lock()
+            .await
+            .latest()
+            .filter(|sample| sample.pid == pid)
+    }
+
+    /// Resource usage samples from the last few minutes
+    pub async fn resource_history(&self) -> Vec<ResourceSample> {
+        self.resource_history.lock().await.to_vec()
+    }
+
+    /// Record a health sample
+    pub async fn record_health(&self, state: HealthState, latency: Option<Duration>) {
+        let mut history = self.health_history.lock().await;
+        if history.len() == HEALTH_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(HealthSample {
+            timestamp: chrono::Utc::now().timestamp(),
+            state,
+            latency_ms: latency.map(|latency| latency.as_millis() as u64),
+        });
+    }
+
+    /// Record the result of `check_health_once`
+    pub async fn record_check(&self, check: Option<&HealthCheck>) {
+        match check {
+            Some(check) => self.record_health(HealthState::Ok, Some(check.latency)).await,
+            None => self.record_health(HealthState::Failed, None).await,
+        }
+    }
+
+    /// Last `n` health samples no older than `since` (Unix seconds), oldest first
+    pub async fn health_history(&self, n: usize, since: Option<i64>) -> Vec<HealthSample> {
+        let history = self.health_history.lock().await;
+        let recent: Vec<HealthSample> = history
+            .iter()
+            .filter(|sample| since.is_none_or(|since| sample.timestamp >= since))
+            .copied()
+            .collect();
+        let skip = recent.len().saturating_sub(n);
+        recent[skip..].to_vec()
+    }
+
+    /// Lock for exclusive maintenance operations (backup, clear-all, ...)
+    pub fn maintenance(&self) -> &MaintenanceLock {
+        &self.maintenance
+    }
+
+    /// Coalescing and rate limit for user and monitor restarts
+    pub fn restarts(&self) -> &RestartGate {
+        &self.restarts
+    }
+
+    /// Whether the service is a remote instance we don't manage
+    pub fn is_remote(&self) -> bool {
+        self.remote.load(Ordering::SeqCst)
+    }
+
+    /// Switch between managing a local process and using a remote service
+    pub fn set_remote(&self, remote: bool) {
+        self.remote.store(remote, Ordering::SeqCst);
+    }
+
     /// Try to acquire the restart lock. Returns true if acquired, false if already in progress.
     pub fn try_start_restart(&self) -> bool {
         self.restart_in_progress
@@ -63,18 +778,76 @@ impl PythonService {
 
     /// Set service status
     async fn set_status(&self, status: ServiceStatus) {
+        self.record_error(&status).await;
         *self.status.lock().await = status;
     }
 
+    async fn record_error(&self, status: &ServiceStatus) {
+        if let ServiceStatus::Error(message) = status {
+            *self.last_error.lock().await = Some(LastError {
+                message: message.clone(),
+                at: chrono::Utc::now().timestamp(),
+                recovered_at: None,
+            });
+        }
+    }
+
+    /// The current start or stop; see `start`
+    pub fn generation(&self) -> u64 {
+        *self.generation.borrow()
+    }
+
+    fn next_generation(&self) -> u64 {
+        self.generation.send_modify(|generation| *generation += 1);
+        self.generation()
+    }
+
+    /// Run `future` until it completes, or return None as soon as a later
+    /// start or stop supersedes `generation`
+    pub async fn unless_superseded<F: std::future::Future>(
+        &self,
+        generation: u64,
+        future: F,
+    ) -> Option<F::Output> {
+        let mut changes = self.generation.subscribe();
+        tokio::select! {
+            output = future => Some(output),
+            _ = changes.wait_for(|current| *current != generation) => None,
+        }
+    }
+
+    /// Set the status for the start `generation`, unless a later start or stop
+    /// has taken over. Returns whether it was set.
+    async fn set_status_for(&self, generation: u64, status: ServiceStatus) -> bool {
+        let mut current = self.status.lock().await;
+        if self.generation() != generation {
+            return false;
+        }
+        self.record_error(&status).await;
+        *current = status;
+        true
+    }
+
+    /// The most recent error, whatever the current status
+    pub async fn last_error(&self) -> Option<LastError> {
+        self.last_error.lock().await.clone()
+    }
+
     /// Check if service process is still running
     pub async fn is_running(&self) -> bool {
         let mut guard = self.child.lock().await;
         if let Some(child) = guard.as_mut() {
             match child.try_wait() {
                 Ok(None) => true,  // Still running
-                Ok(Some(_)) => false,  // Exited
+                Ok(Some(status)) => {
+                    self.record_exit(status).await;
+                    false
+                }
                 Err(_) => false,  // Error checking
             }
+        } else if let Some(pid) = *self.adopted_pid.lock().await {
+            // Not our child, so there is no exit status to collect
+            pid_file::check(pid) == PidCheck::Ours
         } else {
             false
         }
@@ -82,37 +855,289 @@ impl PythonService {
 
     /// Stop the service
     pub async fn stop(&self) -> anyhow::Result<()> {
+        self.next_generation();
         self.should_restart.store(false, Ordering::SeqCst);
+        self.remove_secrets_file().await;
 
         let mut guard = self.child.lock().await;
         if let Some(mut child) = guard.take() {
             println!("[Service] Stopping Python service (PID: {})...", child.id());
-            let _ = child.kill();
-            let _ = child.wait();
+            kill_process_tree(&mut child);
+            if let Ok(status) = child.wait() {
+                self.record_exit(status).await;
+            }
+            println!("[Service] Python service stopped.");
+        }
+        drop(guard);
+        if let Some(pid) = self.adopted_pid.lock().await.take() {
+            println!("[Service] Stopping adopted Python service (PID: {})...", pid);
+            terminate_adopted(pid, true);
+        }
+        self.remove_pid_file().await;
+        self.set_status(ServiceStatus::Stopped).await;
+        Ok(())
+    }
+
+    /// Stop the service, giving it `grace` to exit on SIGTERM before killing it
+    pub async fn stop_gracefully(&self, grace: Duration) -> anyhow::Result<()> {
+        self.next_generation();
+        self.should_restart.store(false, Ordering::SeqCst);
+        self.remove_secrets_file().await;
+
+        let mut guard = self.child.lock().await;
+        if let Some(mut child) = guard.take() {
+            println!("[Service] Terminating Python service (PID: {})...", child.id());
+            request_termination(&child);
+            let deadline = std::time::Instant::now() + grace;
+            loop {
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        self.record_exit(status).await;
+                        break;
+                    }
+                    Ok(None) if std::time::Instant::now() < deadline => {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                    _ => {
+                        println!("[Service] Service did not exit in time, killing it");
+                        kill_process_tree(&mut child);
+                        if let Ok(status) = child.wait() {
+                            self.record_exit(status).await;
+                        }
+                        break;
+                    }
+                }
+            }
             println!("[Service] Python service stopped.");
         }
+        drop(guard);
+        if let Some(pid) = self.adopted_pid.lock().await.take() {
+            println!("[Service] Terminating adopted Python service (PID: {})...", pid);
+            terminate_adopted(pid, false);
+            let deadline = std::time::Instant::now() + grace;
+            while pid_file::check(pid) == PidCheck::Ours && std::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            terminate_adopted(pid, true);
+        }
+        self.remove_pid_file().await;
         self.set_status(ServiceStatus::Stopped).await;
         Ok(())
     }
 
-    /// Start the service
-    pub async fn start(&self, app: &AppHandle) -> anyhow::Result<()> {
+    /// Let go of the service without stopping it (quit with `stop_service_on_quit`
+    /// off). The PID file is rewritten so the next launch adopts it instead of
+    /// spawning a second one. Returns the PID left running, if any.
+    pub async fn leave_running(&self, app: &AppHandle) -> anyhow::Result<Option<u32>> {
+        let mut child = self.child.lock().await;
+        if child.is_some() && !self.output_to_file.load(Ordering::SeqCst) {
+            // Started with stop_service_on_quit on: its output goes through this app
+            anyhow::bail!("service output is piped to the app");
+        }
+        self.should_restart.store(false, Ordering::SeqCst);
+        // Read at import, so no longer needed
+        self.remove_secrets_file().await;
+
+        // Dropping a std Child neither kills nor waits for it
+        let pid = match child.take() {
+            Some(child) => Some(child.id()),
+            None => self.adopted_pid.lock().await.take(),
+        };
+        let Some(pid) = pid else {
+            return Ok(None);
+        };
+        let path = match self.pid_path.lock().await.take() {
+            Some(path) => path,
+            None => pid_file_path(app)?,
+        };
+        let port = pid_file::read(&path)
+            .filter(|record| record.pid == pid)
+            .map(|record| record.port)
+            .unwrap_or_else(|| settings::load_effective(app).unwrap_or_default().service_port);
+        let record = PidRecord {
+            pid,
+            port,
+            started_at: (self.spawned_at.load(Ordering::SeqCst) / 1000) as i64,
+        };
+        pid_file::write(&path, &record)?;
+        Ok(Some(pid))
+    }
+
+    async fn remove_pid_file(&self) {
+        if let Some(path) = self.pid_path.lock().await.take() {
+            pid_file::remove(&path);
+        }
+    }
+
+    /// Deal with a PID file left by an earlier run: adopt that service if it is
+    /// ours and still answers on our port, otherwise clear it away so a fresh
+    /// one can start. Returns whether a service was adopted.
+    async fn recover_pid_file(&self, app: &AppHandle, app_settings: &AppSettings) -> bool {
+        if self.child.lock().await.is_some() || self.adopted_pid.lock().await.is_some() {
+            return false;
+        }
+        let Ok(path) = pid_file_path(app) else {
+            return false;
+        };
+        if !path.exists() {
+            return false;
+        }
+        let Some(record) = pid_file::read(&path) else {
+            println!("[Service] Removing unreadable PID file {:?}", path);
+            pid_file::remove(&path);
+            return false;
+        };
+
+        match pid_file::check(record.pid) {
+            PidCheck::Dead => {
+                println!("[Service] Removing stale PID file (PID {} is gone)", record.pid);
+            }
+            PidCheck::NotOurs(command) => {
+                println!(
+                    "[Service] Removing stale PID file (PID {} is now {:?})",
+                    record.pid, command
+                );
+            }
+            PidCheck::Ours => {
+                let mut healthy = false;
+                let api = app
+                    .try_state::<Arc<ApiClient>>()
+                    .filter(|_| record.port == app_settings.service_port);
+                if let Some(api) = api {
+                    // A launch at login can find the machine too busy to answer at once
+                    for attempt in 0..ADOPT_HEALTH_ATTEMPTS {
+                        if attempt > 0 {
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                        }
+                        if check_health_once(&api).await.is_some() {
+                            healthy = true;
+                            break;
+                        }
+                    }
+                }
+                if healthy {
+                    println!(
+                        "[Service] Adopting Python service left running (PID {}, port {})",
+                        record.pid, record.port
+                    );
+                    *self.adopted_pid.lock().await = Some(record.pid);
+                    *self.pid_path.lock().await = Some(path);
+                    *self.launch_info.lock().await = None;
+                    self.spawned_at
+                        .store(record.started_at.max(0) as u64 * 1000, Ordering::SeqCst);
+                    return true;
+                }
+                // It would hold the port and the database lock
+                println!(
+                    "[Service] Killing leftover Python service that does not answer (PID {})",
+                    record.pid
+                );
+                terminate_adopted(record.pid, true);
+            }
+        }
+        pid_file::remove(&path);
+        false
+    }
+
+    /// Start the service. Returns its generation, for `wait_until_healthy` and
+    /// the `_for` status setters.
+    pub async fn start(&self, app: &AppHandle) -> anyhow::Result<u64> {
+        let generation = self.next_generation();
         self.should_restart.store(true, Ordering::SeqCst);
+        self.started.store(true, Ordering::SeqCst);
         self.set_status(ServiceStatus::Starting).await;
 
-        let (python_bin, pythonpath) = get_python_paths(app)?;
+        let app_settings = settings::load_effective(app).unwrap_or_default();
+        self.set_auto_restart(app_settings.auto_restart);
+        self.set_remote(app_settings.is_remote());
+        let api_token = if app_settings.require_auth {
+            Some(service_api_token()?)
+        } else {
+            None
+        };
+        if let Some(api) = app.try_state::<Arc<ApiClient>>() {
+            api.set_token(api_token.clone());
+        }
+        if app_settings.is_remote() {
+            // Nothing to spawn; reachability is tracked by health checks
+            println!("[Service] Using remote service at {}", app_settings.api_url());
+            *self.launch_info.lock().await = None;
+            return Ok(generation);
+        }
+
+        // Read once: every Keychain read can raise its own access prompt
+        let openai_key = keychain::lookup(keys::OPENAI_API_KEY);
+        if !app_settings.allow_start_without_key && openai_key == Err(KeychainStatus::Absent) {
+            println!("[Service] Not starting: no OpenAI API key configured");
+            // Nothing for the monitor to retry until a key is added
+            self.should_restart.store(false, Ordering::SeqCst);
+            self.set_status(ServiceStatus::Error(MISSING_KEY_ERROR.to_string()))
+                .await;
+            events::emit(app, AppEvent::ApiKeyRequired);
+            anyhow::bail!(MISSING_KEY_ERROR);
+        }
+
+        if self.recover_pid_file(app, &app_settings).await {
+            return Ok(generation);
+        }
+
+        let paths = get_python_paths(app)?;
+        self.ensure_python_verified(&paths).await?;
         let data_dir = get_data_dir(app)?;
-        let data_path = data_dir.to_string_lossy().replace('\\', "/");
-        let surreal_url = format!("file://{}/surrealdb/kiroku", data_path);
+        // A nearly full disk can corrupt the store; refuse to start instead
+        match storage::disk_space(&data_dir, app_settings.min_free_disk_mb) {
+            Some(space) if space.low => {
+                println!("[Service] Not starting: only {} free on {}", space.human, space.path);
+                self.should_restart.store(false, Ordering::SeqCst);
+                self.set_status(ServiceStatus::Error(LOW_DISK_SPACE_ERROR.to_string()))
+                    .await;
+                events::emit(app, AppEvent::LowDiskSpace(space));
+                anyhow::bail!(LOW_DISK_SPACE_ERROR);
+            }
+            Some(_) => {}
+            None => println!("[Service] Could not determine free space for {:?}", data_dir),
+        }
+        // SurrealDB only fails on its first write, long after startup looks fine
+        if app_settings.uses_surrealdb() {
+            if let Err(e) = ensure_writable_dir(&data_dir.join(crate::backup::DB_DIR_NAME)) {
+                println!("[Service] Not starting: {}", e);
+                self.should_restart.store(false, Ordering::SeqCst);
+                self.set_status(ServiceStatus::Error(NOT_WRITABLE_ERROR.to_string()))
+                    .await;
+                anyhow::bail!(NOT_WRITABLE_ERROR);
+            }
+        }
+        let surreal_url = surreal_url(&data_dir, &app_settings.active_profile);
 
-        // Get OpenAI API key from Keychain
-        let openai_key = keychain::get_secret(keys::OPENAI_API_KEY).unwrap_or(None);
+        // A locked or denied Keychain doesn't stop the start
+        let openai_key = match openai_key {
+            Ok(key) => Some(key),
+            Err(KeychainStatus::Absent) => None,
+            Err(status) => {
+                println!("[Service] Starting without the OpenAI API key: {}", status);
+                None
+            }
+        };
+        if let Some(key) = &openai_key {
+            redact::register_secret(key);
+        }
 
         println!("[Service] Starting Python service...");
-        println!("[Service] Python: {:?}", python_bin);
-        println!("[Service] PYTHONPATH: {:?}", pythonpath);
+        println!("[Service] Python: {:?}", paths.python_bin);
+        println!("[Service] PYTHONPATH: {:?}", paths.pythonpath);
         println!("[Service] Data dir: {:?}", data_dir);
-        println!("[Service] SurrealDB URL: {}", surreal_url);
+        println!("[Service] Backend: {}", app_settings.backend);
+        for warning in app_settings.warnings() {
+            println!("[Service] WARNING: {}", warning);
+        }
+        if app_settings.uses_surrealdb() {
+            println!("[Service] Profile: {}", app_settings.active_profile);
+            println!("[Service] SurrealDB URL: {}", surreal_url);
+            println!(
+                "[Service] SurrealDB NS/DB: {}/{}",
+                app_settings.surreal_namespace, app_settings.surreal_database
+            );
+        }
         println!(
             "[Service] OpenAI API Key: {}",
             if openai_key.is_some() {
@@ -122,15 +1147,175 @@ impl PythonService {
             }
         );
 
-        let child = spawn_python_process(&python_bin, &pythonpath, &surreal_url, openai_key)?;
+        let mut secrets: Vec<(&'static str, String)> = Vec::new();
+        if let Some(key) = openai_key {
+            secrets.push(("OPENAI_API_KEY", key));
+        }
+        if let Some(token) = api_token {
+            secrets.push((API_TOKEN_ENV, token));
+        }
+        let socket = if app_settings.uses_unix_socket() {
+            Some(prepare_socket(app)?)
+        } else {
+            None
+        };
+        // The app's own data dir, never a user-chosen (possibly synced) data dir
+        let secrets_dir = app.path().app_data_dir()?.join(RUN_DIR);
+        let handoff = self
+            .secrets_handoff(&secrets_dir, &app_settings, &paths, secrets)
+            .await?;
+
+        let now = service_log::unix_millis();
+        if self.spawned_at.swap(now, Ordering::SeqCst) != 0 {
+            self.restart_count.fetch_add(1, Ordering::SeqCst);
+            self.last_restart_at.store(now, Ordering::SeqCst);
+        }
+        *self.last_exit.lock().await = None;
+        let log_path = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.join(service_log::LOG_FILE_NAME));
+        // Spend is per OpenAI account, so it isn't kept per profile
+        let usage_file = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.join(crate::usage::USAGE_FILE));
+        let log_file = log_path.as_deref().and_then(service_log::open_log_file);
+        // A service that may be left running at quit must not write to pipes
+        // whose reader goes away with the app
+        let output = match (&log_file, app_settings.stop_service_on_quit) {
+            (Some(file), false) => {
+                let file = file.lock().unwrap();
+                let offset = file.metadata().map(|m| m.len()).unwrap_or(0);
+                file.try_clone().ok().map(|file| (file, offset))
+            }
+            _ => None,
+        };
+        self.output_to_file.store(output.is_some(), Ordering::SeqCst);
+        let follow_from = output.as_ref().map(|(_, offset)| *offset);
+        let spawned = spawn_python_process(
+            &paths,
+            &surreal_url,
+            handoff,
+            &app_settings,
+            socket.as_deref(),
+            usage_file.as_deref(),
+            output.map(|(file, _)| file),
+        );
+        if spawned.is_err() {
+            self.remove_secrets_file().await;
+        }
+        let (mut child, launch_info) = spawned?;
         println!("[Service] Python service started with PID: {}", child.id());
+        let pid_path = pid_file_path(app)?;
+        let record = PidRecord {
+            pid: child.id(),
+            port: app_settings.service_port,
+            started_at: chrono::Utc::now().timestamp(),
+        };
+        match pid_file::write(&pid_path, &record) {
+            Ok(()) => *self.pid_path.lock().await = Some(pid_path),
+            Err(e) => println!("[Service] WARNING: could not write PID file: {}", e),
+        }
+        let overrides = app
+            .try_state::<StartupOverrides>()
+            .map(|o| o.describe())
+            .unwrap_or_default();
+        *self.launch_info.lock().await = Some(LaunchInfo {
+            overrides,
+            ..launch_info
+        });
+
+        if let (Some(path), Some(offset)) = (log_path, follow_from) {
+            let changes = self.generation.subscribe();
+            service_log::follow(app.clone(), self.logs.clone(), path, offset, move || {
+                *changes.borrow() == generation
+            });
+        }
+        if let Some(stdout) = child.stdout.take() {
+            service_log::capture(
+                app.clone(),
+                self.logs.clone(),
+                LogStream::Stdout,
+                stdout,
+                log_file.clone(),
+            );
+        }
+        if let Some(stderr) = child.stderr.take() {
+            service_log::capture(
+                app.clone(),
+                self.logs.clone(),
+                LogStream::Stderr,
+                stderr,
+                log_file,
+            );
+        }
 
         *self.child.lock().await = Some(child);
+        Ok(generation)
+    }
+
+    /// Run the pre-flight check once per binary path
+    async fn ensure_python_verified(&self, paths: &PythonPaths) -> anyhow::Result<()> {
+        let python_bin = paths.python_bin.as_path();
+        let mut verified = self.verified_python.lock().await;
+        if verified.as_deref() == Some(python_bin) {
+            return Ok(());
+        }
+        if paths.source == RuntimeSource::Bundled {
+            // pythonpath is <resources>/app, the runtime sits in <resources>/python
+            if let Some(resource_dir) = paths.pythonpath.parent() {
+                let problems = runtime::quick_check(&resource_dir.join("python"));
+                if !problems.is_empty() {
+                    anyhow::bail!(
+                        "The bundled Python runtime is damaged ({}). \
+                        Use Repair Runtime in Settings or reinstall Kiroku Memory.",
+                        problems.join("; ")
+                    );
+                }
+            }
+        }
+        let version = verify_python(python_bin).await?;
+        println!("[Service] Python pre-flight OK: {}", version);
+        *verified = Some(python_bin.to_path_buf());
         Ok(())
     }
 
-    /// Restart the service
-    pub async fn restart(&self, app: &AppHandle) -> anyhow::Result<()> {
+    /// Write a fresh secrets file for the process about to start, unless secrets
+    /// go through the environment (`secrets_via_env`, or an older service)
+    async fn secrets_handoff(
+        &self,
+        dir: &Path,
+        app_settings: &AppSettings,
+        paths: &PythonPaths,
+        secrets: Vec<(&'static str, String)>,
+    ) -> anyhow::Result<Handoff> {
+        self.remove_secrets_file().await;
+        secrets_file::remove_all(dir);
+        if secrets.is_empty()
+            || app_settings.secrets_via_env
+            || self.legacy_secrets_env.load(Ordering::SeqCst)
+        {
+            return Ok(Handoff::Env(secrets));
+        }
+        let path = secrets_file::write(dir, &secrets)?;
+        *self.secrets_file.lock().await = Some(SecretsFile {
+            path: path.clone(),
+            keep_until_stop: dev_reload_enabled(&app_settings.service_options, paths)
+                || effective_workers(app_settings.service_options.workers) > 1,
+        });
+        Ok(Handoff::File(path))
+    }
+
+    /// Re-run the pre-flight checks on the next start (e.g. after a repair)
+    pub async fn forget_verified_python(&self) {
+        *self.verified_python.lock().await = None;
+    }
+
+    /// Restart the service, returning the new start's generation
+    pub async fn restart(&self, app: &AppHandle) -> anyhow::Result<u64> {
         println!("[Service] Restarting service...");
         self.set_status(ServiceStatus::Restarting).await;
         self.stop().await?;
@@ -139,24 +1324,119 @@ impl PythonService {
         self.start(app).await
     }
 
-    /// Mark service as running (called after health check succeeds)
-    pub async fn mark_running(&self) {
-        self.set_status(ServiceStatus::Running).await;
+    /// Wait for first use instead of starting; the monitor leaves an idle service alone
+    pub async fn mark_idle(&self) {
+        self.should_restart.store(false, Ordering::SeqCst);
+        self.set_status(ServiceStatus::Idle).await;
+    }
+
+    /// Move an idle service to Starting. Returns false if it wasn't idle, so
+    /// only one caller goes on to start it.
+    pub async fn claim_idle_start(&self) -> bool {
+        let mut status = self.status.lock().await;
+        if *status != ServiceStatus::Idle {
+            return false;
+        }
+        *status = ServiceStatus::Starting;
+        true
+    }
+
+    async fn mark_recovered(&self) {
+        if let Some(error) = self.last_error.lock().await.as_mut() {
+            error.recovered_at.get_or_insert_with(|| chrono::Utc::now().timestamp());
+        }
+    }
+
+    /// Mark service as running (called after health check succeeds) for the
+    /// start `generation`; ignored once a later start or stop has taken over
+    pub async fn mark_running_for(&self, generation: u64) -> bool {
+        if self.generation() != generation {
+            return false;
+        }
+        self.mark_recovered().await;
+        self.set_status_for(generation, ServiceStatus::Running).await
+    }
+
+    /// Keep serving but flag a problem; `mark_running_for` clears it. Ignored
+    /// once a later start or stop has taken over `generation`.
+    pub async fn mark_degraded_for(&self, generation: u64, reason: String) -> bool {
+        self.set_status_for(generation, ServiceStatus::Degraded(reason)).await
     }
 
     /// Mark service as error
     pub async fn mark_error(&self, error: String) {
+        let error = redact::redact(&error).into_owned();
         self.set_status(ServiceStatus::Error(error)).await;
     }
 
+    /// `mark_error` for the start `generation`; ignored once a later start or
+    /// stop has taken over
+    pub async fn mark_error_for(&self, generation: u64, error: String) -> bool {
+        let error = redact::redact(&error).into_owned();
+        self.set_status_for(generation, ServiceStatus::Error(error)).await
+    }
+
+    /// `wait_for_health` for the start `generation`. None when a stop or
+    /// another start took over first; the wait is dropped then.
+    pub async fn wait_until_healthy(
+        &self,
+        api: &ApiClient,
+        timeout: Duration,
+        generation: u64,
+    ) -> Option<anyhow::Result<HealthResponse>> {
+        self.unless_superseded(generation, wait_for_health(api, timeout)).await
+    }
+
     /// Check if auto-restart is enabled
     pub fn should_auto_restart(&self) -> bool {
-        self.should_restart.load(Ordering::SeqCst)
+        self.should_restart.load(Ordering::SeqCst) && self.auto_restart_enabled()
     }
+
+    /// Whether the user allows automatic restarts, regardless of service state
+    pub fn auto_restart_enabled(&self) -> bool {
+        self.auto_restart.load(Ordering::SeqCst)
+    }
+
+    pub fn set_auto_restart(&self, enabled: bool) {
+        self.auto_restart.store(enabled, Ordering::SeqCst);
+    }
+}
+
+/// Which Python runtime `get_python_paths` picked
+#[derive(Clone, Copy, Debug, serde::Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeSource {
+    /// Shipped inside the app bundle
+    Bundled,
+    /// tools/packaging/dist in a source checkout
+    Development,
+    /// python3 from PATH (dev fallback)
+    System,
+}
+
+/// Python binary and PYTHONPATH, with how they were chosen
+#[derive(Clone, Debug)]
+pub struct PythonPaths {
+    pub python_bin: PathBuf,
+    pub pythonpath: PathBuf,
+    pub source: RuntimeSource,
+    /// Existence checks made along the way, in order
+    pub checks: Vec<String>,
+}
+
+fn check_exists(checks: &mut Vec<String>, label: &str, path: &Path) -> bool {
+    let exists = path.exists();
+    checks.push(format!(
+        "{} {}: {}",
+        label,
+        path.display(),
+        if exists { "found" } else { "missing" }
+    ));
+    exists
 }
 
 /// Get Python binary and PYTHONPATH based on environment (dev vs production)
-pub fn get_python_paths(app: &AppHandle) -> anyhow::Result<(PathBuf, PathBuf)> {
+pub fn get_python_paths(app: &AppHandle) -> anyhow::Result<PythonPaths> {
     let resource_dir = app
         .path()
         .resource_dir()
@@ -173,13 +1453,21 @@ pub fn get_python_paths(app: &AppHandle) -> anyhow::Result<(PathBuf, PathBuf)> {
     // Check if bundled resources exist (production mode)
     let bundled_python = resource_dir.join("python").join(python_binary_name);
     let bundled_app = resource_dir.join("app/kiroku_memory");
+    let mut checks = Vec::new();
+    let has_bundled_python = check_exists(&mut checks, "bundled python", &bundled_python);
+    let has_bundled_app = check_exists(&mut checks, "bundled app", &bundled_app);
 
-    if bundled_python.exists() && bundled_app.exists() {
+    if has_bundled_python && has_bundled_app {
         // Production: use bundled resources
         println!("[Service] Using bundled Python runtime");
         println!("[Service] Python bin: {:?}", bundled_python);
         let app_dir = resource_dir.join("app");
-        Ok((bundled_python, app_dir))
+        Ok(PythonPaths {
+            python_bin: bundled_python,
+            pythonpath: app_dir,
+            source: RuntimeSource::Bundled,
+            checks,
+        })
     } else {
         // Development: use tools/packaging/dist Python
         println!("[Service] Using development Python runtime");
@@ -217,27 +1505,260 @@ pub fn get_python_paths(app: &AppHandle) -> anyhow::Result<(PathBuf, PathBuf)> {
         println!("[Service] Project root: {:?}", project_root);
         println!("[Service] Python bin: {:?}", python_bin);
 
-        Ok((python_bin, project_root))
+        let has_dev_python = check_exists(&mut checks, "dev python", &python_bin);
+        if !has_dev_python && system_python_allowed() {
+            if let Some(system_python) = find_system_python(&project_root) {
+                println!(
+                    "[Service] Dev runtime missing, using system Python: {:?}",
+                    system_python
+                );
+                checks.push(format!(
+                    "system python {}: imports kiroku_memory",
+                    system_python.display()
+                ));
+                return Ok(PythonPaths {
+                    python_bin: system_python,
+                    pythonpath: project_root,
+                    source: RuntimeSource::System,
+                    checks,
+                });
+            }
+            println!("[Service] No system python3 on PATH can import kiroku_memory");
+            checks.push("system python: none on PATH imports kiroku_memory".to_string());
+        }
+
+        Ok(PythonPaths {
+            python_bin,
+            pythonpath: project_root,
+            source: RuntimeSource::Development,
+            checks,
+        })
     }
 }
 
-/// Get data directory for the app
+/// A system interpreter is only considered in debug builds or when opted in,
+/// so a release install never silently runs on an unrelated Python
+fn system_python_allowed() -> bool {
+    cfg!(debug_assertions) || std::env::var("KIROKU_ALLOW_SYSTEM_PYTHON").is_ok()
+}
+
+/// First `python3` on PATH that can import `kiroku_memory` from `project_root`
+fn find_system_python(project_root: &Path) -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let names = ["python3.exe", "python.exe"];
+    #[cfg(not(target_os = "windows"))]
+    let names = ["python3"];
+
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|candidate| candidate.is_file())
+        .find(|candidate| {
+            Command::new(candidate)
+                .args(["-c", "import kiroku_memory"])
+                .env("PYTHONPATH", project_root)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        })
+}
+
+/// Get data directory for the app.
+/// Prefers `data_dir_override` from settings when it is set and writable.
 pub fn get_data_dir(app: &AppHandle) -> anyhow::Result<PathBuf> {
+    if let Some(dir) = settings::load_effective(app).ok().and_then(|s| s.data_dir_override()) {
+        match ensure_writable_dir(&dir) {
+            Ok(()) => {
+                if let Err(e) = permissions::ensure_private_dir(&dir) {
+                    println!("[Service] Could not restrict {}: {}", dir.display(), e);
+                }
+                return Ok(dir);
+            }
+            Err(e) => println!("[Service] Ignoring data dir override: {}", e),
+        }
+    }
+
     let data_dir = app
         .path()
         .app_data_dir()
         .expect("Failed to get app data dir");
-    std::fs::create_dir_all(&data_dir)?;
+    permissions::ensure_private_dir(&data_dir)?;
     Ok(data_dir)
 }
 
-/// Spawn the Python process
+/// Create `dir` if needed and check that files can be written to it
+pub fn ensure_writable_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Cannot create data directory {}: {}", dir.display(), e))?;
+    let probe = dir.join(".kiroku-write-test");
+    std::fs::write(&probe, b"ok")
+        .map_err(|e| format!("Data directory {} is not writable: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Check that the bundled Python actually runs, so a corrupted install fails
+/// fast with a clear message instead of a health-check timeout
+async fn verify_python(python_bin: &Path) -> anyhow::Result<String> {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    const REINSTALL_HINT: &str = "The bundled Python runtime appears to be damaged. \
+        Please reinstall Kiroku Memory.";
+
+    if !python_bin.exists() {
+        anyhow::bail!(
+            "Python binary not found at: {}. {}",
+            python_bin.display(),
+            REINSTALL_HINT
+        );
+    }
+
+    let output = tokio::time::timeout(
+        TIMEOUT,
+        tokio::process::Command::new(python_bin)
+            .arg("--version")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow::anyhow!("`python --version` timed out. {}", REINSTALL_HINT))?
+    .map_err(|e| anyhow::anyhow!("Failed to run Python ({}). {}", e, REINSTALL_HINT))?;
+
+    // Python 2 printed the version to stderr
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let version = text.trim();
+    if !output.status.success() || !version.starts_with("Python 3.") {
+        anyhow::bail!(
+            "Unexpected output from `python --version`: {:?}. {}",
+            version,
+            REINSTALL_HINT
+        );
+    }
+    Ok(version.to_string())
+}
+
+/// Ask the process group to exit (SIGTERM, so uvicorn shuts down its workers cleanly)
+#[cfg(unix)]
+fn request_termination(child: &Child) {
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGTERM);
+    }
+}
+
+/// No SIGTERM on Windows; the grace period simply runs out and the process is killed
+#[cfg(not(unix))]
+fn request_termination(_child: &Child) {}
+
+/// Kill the service and any uvicorn workers it forked. The service runs in its
+/// own process group, so SIGKILL to the group reaches workers too.
+#[cfg(unix)]
+fn kill_process_tree(child: &mut Child) {
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(not(unix))]
+fn kill_process_tree(child: &mut Child) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+}
+
+fn pid_file_path(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(RUN_DIR).join(pid_file::FILE_NAME))
+}
+
+/// Where a local service listens with `Transport::UnixSocket`
+pub fn socket_path(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(RUN_DIR).join(SOCKET_FILE_NAME))
+}
+
+/// Ready the socket path for a new service: a private directory and no file
+/// left by one that crashed (uvicorn can't bind over it)
+fn prepare_socket(app: &AppHandle) -> anyhow::Result<PathBuf> {
+    let path = socket_path(app)?;
+    if path.as_os_str().len() > MAX_SOCKET_PATH_LEN {
+        anyhow::bail!(
+            "Socket path {} is too long for a Unix socket; switch the transport to TCP",
+            path.display()
+        );
+    }
+    if let Some(dir) = path.parent() {
+        secrets_file::create_private_dir(dir)?;
+    }
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    Ok(path)
+}
+
+/// Stop a service adopted from an earlier run, which is not our child. It
+/// leads its own process group, like the ones we spawn.
+#[cfg(unix)]
+fn terminate_adopted(pid: u32, force: bool) {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate_adopted(pid: u32, force: bool) {
+    if force {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+/// uvicorn `--reload` for backend development. Reload forks a watcher plus a
+/// server process, which the process-group kill cleans up. Never used with the
+/// bundled runtime: its sources live inside the app bundle and are not edited.
+fn dev_reload_enabled(options: &ServiceOptions, paths: &PythonPaths) -> bool {
+    let requested = options.dev_reload || std::env::var_os(DEV_RELOAD_ENV).is_some();
+    if !requested {
+        return false;
+    }
+    if !cfg!(debug_assertions) {
+        println!("[Service] Ignoring dev reload: only available in debug builds");
+        return false;
+    }
+    if paths.source == RuntimeSource::Bundled {
+        println!("[Service] Ignoring dev reload: not supported with the bundled runtime");
+        return false;
+    }
+    true
+}
+
+/// Worker count actually passed to uvicorn: the setting, capped at the CPU count
+fn effective_workers(requested: u8) -> u8 {
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    requested.min(u8::try_from(cpus).unwrap_or(u8::MAX)).max(1)
+}
+
+/// Spawn the Python process. Also returns its redacted command line for diagnostics.
 fn spawn_python_process(
-    python_bin: &PathBuf,
-    pythonpath: &PathBuf,
+    paths: &PythonPaths,
     surreal_url: &str,
-    openai_key: Option<String>,
-) -> anyhow::Result<Child> {
+    secrets: Handoff,
+    app_settings: &AppSettings,
+    socket: Option<&Path>,
+    usage_file: Option<&Path>,
+    log_file: Option<std::fs::File>,
+) -> anyhow::Result<(Child, LaunchInfo)> {
+    let python_bin = &paths.python_bin;
     if !python_bin.exists() {
         anyhow::bail!(
             "Python binary not found at: {}. Ensure the app was installed correctly.",
@@ -245,42 +1766,162 @@ fn spawn_python_process(
         );
     }
 
+    let port = app_settings.service_port.to_string();
     let mut cmd = Command::new(python_bin);
-    cmd.args([
-        "-m",
-        "uvicorn",
-        "kiroku_memory.api:app",
-        "--host",
-        "127.0.0.1",
-        "--port",
-        "8000",
-    ])
-    .env("PYTHONPATH", pythonpath)
-    .env("BACKEND", "surrealdb")
-    .env("SURREAL_URL", surreal_url)
-    .env("SURREAL_NAMESPACE", "kiroku")
-    .env("SURREAL_DATABASE", "memory")
-    .env("PYTHONUNBUFFERED", "1")
-    .stdout(Stdio::inherit())
-    .stderr(Stdio::inherit());
-
-    // Pass OpenAI API key if available
-    if let Some(key) = openai_key {
-        cmd.env("OPENAI_API_KEY", key);
-    }
-
-    Ok(cmd.spawn()?)
+    cmd.args(["-m", "uvicorn", "kiroku_memory.api:app"]);
+    match socket {
+        Some(socket) => {
+            cmd.arg("--uds").arg(socket);
+        }
+        None => {
+            cmd.args(["--host", &app_settings.bind_host, "--port", &port]);
+        }
+    }
+
+    let options = &app_settings.service_options;
+    cmd.args(["--log-level", options.uvicorn_log_level.as_arg()]);
+    // --reload and --workers are mutually exclusive in uvicorn
+    if dev_reload_enabled(options, paths) {
+        // Watch only the package; the project root includes target/ and node_modules/
+        cmd.arg("--reload")
+            .arg("--reload-dir")
+            .arg(paths.pythonpath.join("kiroku_memory"));
+    } else {
+        let workers = effective_workers(options.workers);
+        if workers < options.workers {
+            println!(
+                "[Service] Using {} workers instead of {} (CPU count)",
+                workers, options.workers
+            );
+        }
+        if workers > 1 {
+            cmd.args(["--workers", &workers.to_string()]);
+        }
+    }
+    // Own process group, so stopping the service also stops its workers
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    // Extra env first so the app's own variables always win
+    cmd.envs(&options.extra_env)
+    .env("PYTHONPATH", &paths.pythonpath)
+    .env("BACKEND", &app_settings.backend)
+    .env("PYTHONUNBUFFERED", "1");
+    // Appending to service.log directly lets the service outlive the app
+    match log_file.map(|file| file.try_clone().map(|copy| (file, copy))) {
+        Some(Ok((stdout, stderr))) => {
+            cmd.stdout(stdout).stderr(stderr);
+        }
+        _ => {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+    }
+
+    // Other backends are configured through the inherited environment (e.g. DATABASE_URL)
+    if app_settings.uses_surrealdb() {
+        cmd.env("SURREAL_URL", surreal_url)
+            .env("SURREAL_NAMESPACE", &app_settings.surreal_namespace)
+            .env("SURREAL_DATABASE", &app_settings.surreal_database);
+    }
+
+    // OpenAI API key and service token
+    match secrets {
+        Handoff::Env(secrets) => {
+            cmd.envs(secrets);
+        }
+        Handoff::File(path) => {
+            cmd.env(secrets_file::SECRETS_FILE_ENV, path);
+        }
+    }
+    // Read by the OpenAI SDK when no base_url is passed explicitly
+    if let Some(url) = app_settings.openai_base_url() {
+        cmd.env("OPENAI_BASE_URL", url);
+    }
+    if let Some(model) = app_settings.embedding_model() {
+        cmd.env("EMBEDDING_MODEL", model);
+    }
+    if let Some(model) = app_settings.chat_model() {
+        cmd.env("CHAT_MODEL", model);
+    }
+    if let Some(path) = usage_file {
+        cmd.env("USAGE_FILE", path);
+    }
+
+    let info = launch_info(&cmd, paths);
+    Ok((cmd.spawn()?, info))
+}
+
+/// The service's bearer token from the keychain, generated on first use
+pub fn service_api_token() -> anyhow::Result<String> {
+    if let Some(token) = keychain::get_secret(keys::SERVICE_API_TOKEN)? {
+        redact::register_secret(&token);
+        return Ok(token);
+    }
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    keychain::set_secret(keys::SERVICE_API_TOKEN, &token)?;
+    redact::register_secret(&token);
+    println!("[Service] Generated a new service API token");
+    Ok(token)
+}
+
+/// Environment variables whose values are never shown
+fn is_secret_env(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    ["KEY", "SECRET", "TOKEN", "PASSWORD"]
+        .iter()
+        .any(|marker| key.contains(marker))
+}
+
+/// Program, arguments, and explicitly set environment, with secrets removed
+fn launch_info(cmd: &Command, paths: &PythonPaths) -> LaunchInfo {
+    let env = cmd
+        .get_envs()
+        .map(|(key, value)| {
+            let key = key.to_string_lossy().to_string();
+            let value = if is_secret_env(&key) {
+                "<set>".to_string()
+            } else {
+                let value = value.map(|v| v.to_string_lossy().to_string()).unwrap_or_default();
+                redact::redact(&value).into_owned()
+            };
+            (key, value)
+        })
+        .collect();
+    LaunchInfo {
+        python_bin: cmd.get_program().to_string_lossy().to_string(),
+        pythonpath: paths.pythonpath.to_string_lossy().to_string(),
+        working_dir: std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        args: cmd
+            .get_args()
+            .map(|arg| redact::redact(&arg.to_string_lossy()).into_owned())
+            .collect(),
+        env,
+        runtime: paths.source,
+        runtime_checks: paths.checks.clone(),
+        overrides: Vec::new(),
+        started_at: chrono::Utc::now().timestamp(),
+    }
 }
 
 /// Wait for the API to become healthy
-pub async fn wait_for_health(url: &str, timeout: Duration) -> anyhow::Result<HealthResponse> {
-    let client = reqwest::Client::new();
+pub async fn wait_for_health(api: &ApiClient, timeout: Duration) -> anyhow::Result<HealthResponse> {
+    let client = api.client_builder().build()?;
+    let url = api.url("/health");
     let deadline = std::time::Instant::now() + timeout;
 
     println!("[Service] Waiting for API health at {}...", url);
 
     while std::time::Instant::now() < deadline {
-        match client.get(url).send().await {
+        match api.authorize(client.get(&url)).send().await {
             Ok(resp) if resp.status().is_success() => {
                 if let Ok(health) = resp.json::<HealthResponse>().await {
                     println!(
@@ -303,15 +1944,244 @@ pub async fn wait_for_health(url: &str, timeout: Duration) -> anyhow::Result<Hea
     anyhow::bail!("Health check timed out after {:?}", timeout)
 }
 
-/// Check health once (non-blocking)
-pub async fn check_health_once() -> Option<HealthResponse> {
-    let client = reqwest::Client::builder()
+/// Check health once (non-blocking), timing the round trip
+pub async fn check_health_once(api: &ApiClient) -> Option<HealthCheck> {
+    let client = api
+        .client_builder()
         .timeout(Duration::from_secs(2))
         .build()
         .ok()?;
 
-    match client.get("http://127.0.0.1:8000/health").send().await {
-        Ok(resp) if resp.status().is_success() => resp.json::<HealthResponse>().await.ok(),
+    let started = std::time::Instant::now();
+    match api.authorize(client.get(api.url("/health"))).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            let response = resp.json::<HealthResponse>().await.ok()?;
+            Some(HealthCheck {
+                response,
+                latency: started.elapsed(),
+            })
+        }
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test]
+    async fn test_restart_gate_coalesces_concurrent_requests() {
+        let gate = Arc::new(RestartGate::default());
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(tokio::sync::Barrier::new(5));
+        let mut tasks = Vec::new();
+        for _ in 0..5 {
+            let (gate, spawned, barrier) = (gate.clone(), spawned.clone(), barrier.clone());
+            tasks.push(tokio::spawn(async move {
+                barrier.wait().await;
+                gate.run(|| async {
+                    spawned.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Err("health check timed out".to_string())
+                })
+                .await
+            }));
+        }
+        for task in tasks {
+            assert_eq!(
+                task.await.unwrap(),
+                Err(RestartError::Failed("health check timed out".to_string()))
+            );
+        }
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+
+        // Right after it finished a new request is refused without running
+        let result = gate.run(|| async {
+            spawned.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        assert_eq!(result.await, Err(RestartError::TooSoon { retry_in_secs: 3 }));
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_restart_releases_waiters() {
+        let gate = Arc::new(RestartGate::default());
+        let leader = {
+            let gate = gate.clone();
+            tokio::spawn(async move { gate.run(std::future::pending).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let waiter = {
+            let gate = gate.clone();
+            tokio::spawn(async move { gate.run(|| async { Ok(()) }).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        leader.abort();
+        assert_eq!(
+            waiter.await.unwrap(),
+            Err(RestartError::Failed("Restart was cancelled".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_lock_admits_one_of_overlapping_operations() {
+        let lock = Arc::new(MaintenanceLock::default());
+        let barrier = Arc::new(tokio::sync::Barrier::new(8));
+        let admitted = Arc::new(AtomicUsize::new(0));
+        let mut tasks = Vec::new();
+        for i in 0..8 {
+            let (lock, barrier, admitted) = (lock.clone(), barrier.clone(), admitted.clone());
+            tasks.push(tokio::spawn(async move {
+                barrier.wait().await;
+                match lock.try_acquire(&format!("op {}", i)) {
+                    Ok(_guard) => {
+                        admitted.fetch_add(1, Ordering::SeqCst);
+                        // Hold the lock until every other task has tried
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        None
+                    }
+                    Err(busy) => Some(busy),
+                }
+            }));
+        }
+        let mut refused = 0;
+        for task in tasks {
+            if let Some(busy) = task.await.unwrap() {
+                assert!(busy.to_string().starts_with("Busy with op "), "{}", busy);
+                refused += 1;
+            }
+        }
+        assert_eq!(admitted.load(Ordering::SeqCst), 1);
+        assert_eq!(refused, 7);
+        assert_eq!(lock.current(), None);
+        assert!(lock.check().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_last_error_outlives_recovery() {
+        let service = PythonService::new();
+        assert_eq!(service.last_error().await, None);
+
+        service.mark_error("Service exited with code 1".to_string()).await;
+        let error = service.last_error().await.unwrap();
+        assert_eq!(error.message, "Service exited with code 1");
+        assert_eq!(error.recovered_at, None);
+
+        assert!(service.mark_running_for(service.generation()).await);
+        assert!(service.get_status().await == ServiceStatus::Running);
+        let recovered = service.last_error().await.unwrap();
+        assert_eq!(recovered.message, error.message);
+        assert_eq!(recovered.at, error.at);
+        assert!(recovered.recovered_at.is_some_and(|at| at >= error.at));
+    }
+
+    #[tokio::test]
+    async fn test_health_history_is_bounded_and_filtered() {
+        let service = PythonService::new();
+        for _ in 0..HEALTH_HISTORY_LEN + 10 {
+            service.record_health(HealthState::Failed, None).await;
+        }
+        service.record_health(HealthState::Stopped, None).await;
+        service
+            .record_health(HealthState::Ok, Some(Duration::from_micros(12_400)))
+            .await;
+
+        let all = service.health_history(usize::MAX, None).await;
+        assert_eq!(all.len(), HEALTH_HISTORY_LEN);
+        let last_two = service.health_history(2, None).await;
+        assert_eq!(last_two[0].state, HealthState::Stopped);
+        assert_eq!(last_two[1].state, HealthState::Ok);
+        assert_eq!(last_two[1].latency_ms, Some(12));
+
+        let info = service.info(String::new(), None).await;
+        assert_eq!(info.last_health_check.map(|sample| sample.state), Some(HealthState::Ok));
+
+        let future = chrono::Utc::now().timestamp() + 60;
+        assert!(service.health_history(usize::MAX, Some(future)).await.is_empty());
+    }
+
+    /// A start as far as status goes: `start` without spawning anything
+    async fn begin_start(service: &PythonService) -> u64 {
+        let generation = service.next_generation();
+        service.set_status(ServiceStatus::Starting).await;
+        generation
+    }
+
+    #[tokio::test]
+    async fn test_stop_during_start_ends_the_health_wait() {
+        let service = Arc::new(PythonService::new());
+        let generation = begin_start(&service).await;
+        let waiter = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                service
+                    .unless_superseded(generation, std::future::pending::<()>())
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        service.stop().await.unwrap();
+        assert_eq!(waiter.await.unwrap(), None);
+
+        // A health check that timed out meanwhile can't turn Stopped into Error
+        assert!(!service.mark_error_for(generation, "Health check timed out".to_string()).await);
+        assert!(!service.mark_running_for(generation).await);
+        assert!(service.get_status().await == ServiceStatus::Stopped);
+        assert_eq!(service.last_error().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_restart_during_start_ignores_the_stale_waiter() {
+        let service = PythonService::new();
+        let first = begin_start(&service).await;
+        // restart: stop, then start again
+        service.stop().await.unwrap();
+        let second = begin_start(&service).await;
+
+        // The stale waiter finishing first or last changes nothing
+        assert!(!service.mark_running_for(first).await);
+        assert!(service.get_status().await == ServiceStatus::Starting);
+        assert!(service.mark_running_for(second).await);
+        assert!(!service.mark_error_for(first, "Health check timed out".to_string()).await);
+        assert!(service.get_status().await == ServiceStatus::Running);
+
+        // A wait for the current start is not cut short
+        let health = service.unless_superseded(second, async { "healthy" }).await;
+        assert_eq!(health, Some("healthy"));
+    }
+
+    #[tokio::test]
+    async fn test_monitor_check_in_flight_does_not_undo_stop() {
+        let service = PythonService::new();
+        let started = begin_start(&service).await;
+        assert!(service.mark_running_for(started).await);
+
+        // The monitor captures the generation, then the user stops mid-check
+        let checked = service.generation();
+        service.stop().await.unwrap();
+        assert!(!service.mark_running_for(checked).await);
+        assert!(!service.mark_degraded_for(checked, LOW_DISK_SPACE_ERROR.to_string()).await);
+        assert!(service.get_status().await == ServiceStatus::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_lock_released_on_panic() {
+        let lock = Arc::new(MaintenanceLock::default());
+        let holder = lock.clone();
+        let result = tokio::spawn(async move {
+            let _guard = holder.try_acquire("backup").unwrap();
+            panic!("backup failed");
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(lock.current(), None);
+
+        let guard = lock.try_acquire("restore").unwrap();
+        assert_eq!(lock.current().unwrap().operation, "restore");
+        assert!(lock.check().is_err());
+        drop(guard);
+        assert!(lock.check().is_ok());
+    }
+}
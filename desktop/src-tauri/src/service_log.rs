@@ -0,0 +1,292 @@
+// Kiroku Memory Desktop - Service Log Capture
+// Pipes the Python service's stdout/stderr into a ring buffer and live events
+
+use crate::events::{self, AppEvent};
+use crate::redact;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+/// Lines kept for backfilling the log viewer
+pub const MAX_LINES: usize = 5000;
+
+/// Full service output, next to app.log
+pub const LOG_FILE_NAME: &str = "service.log";
+
+/// service.log is moved to service.log.1 at the next start once it grows past this
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// How often `follow` looks for new output in service.log
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Longest root cause shown in the error status
+const MAX_CAUSE_LEN: usize = 200;
+
+/// Well-known failures, matched case-insensitively, with the message to show instead
+const KNOWN_FAILURES: &[(&str, &str)] = &[
+    ("database is locked", "SurrealDB: database is locked"),
+    (
+        "lock hold by current process",
+        "SurrealDB: database is locked",
+    ),
+    (
+        "address already in use",
+        "Port already in use by another process",
+    ),
+];
+
+/// service.log shared by the stdout and stderr readers
+pub type LogFile = Arc<Mutex<File>>;
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of service output
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct LogLine {
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub stream: LogStream,
+    pub line: String,
+}
+
+/// Bounded buffer of recent service output
+#[derive(Default)]
+pub struct ServiceLogBuffer {
+    lines: Mutex<VecDeque<LogLine>>,
+}
+
+impl ServiceLogBuffer {
+    pub fn push(&self, line: LogLine) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Last `n` lines, oldest first
+    pub fn recent(&self, n: usize) -> Vec<LogLine> {
+        let lines = self.lines.lock().unwrap();
+        let skip = lines.len().saturating_sub(n);
+        lines.iter().skip(skip).cloned().collect()
+    }
+
+    /// Lines captured at or after `timestamp` (Unix ms)
+    pub fn since(&self, timestamp: u64) -> Vec<LogLine> {
+        let lines = self.lines.lock().unwrap();
+        lines
+            .iter()
+            .filter(|line| line.timestamp >= timestamp)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Open service.log for appending, rotating it first if it has grown too large
+pub fn open_log_file(path: &Path) -> Option<LogFile> {
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > MAX_FILE_BYTES) {
+        let _ = std::fs::rename(path, path.with_extension("log.1"));
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .ok()?;
+    Some(Arc::new(Mutex::new(file)))
+}
+
+/// Best guess at why the service failed to start: a known failure, the final
+/// line of the last Python traceback, or the last uvicorn `ERROR:` line
+pub fn root_cause(lines: &[LogLine]) -> Option<String> {
+    for line in lines {
+        let lower = line.line.to_lowercase();
+        if let Some((_, message)) = KNOWN_FAILURES
+            .iter()
+            .find(|(marker, _)| lower.contains(marker))
+        {
+            return Some(message.to_string());
+        }
+    }
+
+    let traceback = lines
+        .iter()
+        .rposition(|l| l.line.starts_with("Traceback (most recent call last)"));
+    let cause = match traceback {
+        // Frames are indented; the exception is the first line that isn't
+        Some(start) => lines[start + 1..]
+            .iter()
+            .map(|l| l.line.trim_end())
+            .find(|l| !l.is_empty() && !l.starts_with(char::is_whitespace)),
+        None => lines
+            .iter()
+            .rev()
+            .find_map(|l| l.line.strip_prefix("ERROR:"))
+            .map(str::trim),
+    }?;
+    Some(cause.chars().take(MAX_CAUSE_LEN).collect())
+}
+
+/// Forward lines from a child pipe until it closes. Lines are still echoed
+/// to our own stdout/stderr so terminal logs keep working in development.
+pub fn capture<R: Read + Send + 'static>(
+    app: AppHandle,
+    buffer: Arc<ServiceLogBuffer>,
+    stream: LogStream,
+    pipe: R,
+    file: Option<LogFile>,
+) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let line = redact::redact(&line).into_owned();
+            if let Some(file) = &file {
+                let _ = writeln!(file.lock().unwrap(), "{}", line);
+            }
+            forward(&app, &buffer, stream, line);
+        }
+    });
+}
+
+/// Like `capture`, for a service writing straight to service.log so it can
+/// outlive the app: lines appended after `offset` are forwarded until
+/// `keep_going` returns false at the end of the file. Both streams share the
+/// file, so lines are reported as stdout, and the file itself is not redacted.
+pub fn follow(
+    app: AppHandle,
+    buffer: Arc<ServiceLogBuffer>,
+    path: PathBuf,
+    offset: u64,
+    keep_going: impl Fn() -> bool + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let Ok(mut file) = File::open(&path) else {
+            return;
+        };
+        if file.seek(SeekFrom::Start(offset)).is_err() {
+            return;
+        }
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            match reader.read_line(&mut line) {
+                // A partial line stays in `line` until the rest is written
+                Ok(_) if line.ends_with('\n') => {
+                    let text = redact::redact(line.trim_end_matches(['\n', '\r'])).into_owned();
+                    forward(&app, &buffer, LogStream::Stdout, text);
+                    line.clear();
+                }
+                Ok(_) if keep_going() => std::thread::sleep(FOLLOW_INTERVAL),
+                _ => break,
+            }
+        }
+    });
+}
+
+fn forward(app: &AppHandle, buffer: &ServiceLogBuffer, stream: LogStream, line: String) {
+    match stream {
+        LogStream::Stdout => println!("{}", line),
+        LogStream::Stderr => eprintln!("{}", line),
+    }
+    let entry = LogLine {
+        timestamp: unix_millis(),
+        stream,
+        line,
+    };
+    events::emit(app, AppEvent::ServiceLogLine(entry.clone()));
+    buffer.push(entry);
+}
+
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stderr(text: &str) -> LogLine {
+        LogLine {
+            timestamp: 0,
+            stream: LogStream::Stderr,
+            line: text.to_string(),
+        }
+    }
+
+    fn line(n: usize) -> LogLine {
+        LogLine {
+            timestamp: n as u64,
+            stream: LogStream::Stdout,
+            line: format!("line {}", n),
+        }
+    }
+
+    #[test]
+    fn test_buffer_is_capped_and_returns_tail() {
+        let buffer = ServiceLogBuffer::default();
+        for n in 0..MAX_LINES + 10 {
+            buffer.push(line(n));
+        }
+
+        let all = buffer.recent(usize::MAX);
+        assert_eq!(all.len(), MAX_LINES);
+        assert_eq!(all[0].line, "line 10");
+
+        let tail = buffer.recent(2);
+        assert_eq!(tail[0].line, format!("line {}", MAX_LINES + 8));
+        assert_eq!(tail[1].line, format!("line {}", MAX_LINES + 9));
+    }
+
+    #[test]
+    fn test_root_cause_from_traceback() {
+        let lines: Vec<LogLine> = [
+            "INFO:     Started server process [4242]",
+            "Traceback (most recent call last):",
+            "  File \"<frozen runpy>\", line 198, in _run_module_as_main",
+            "  File \"/app/kiroku_memory/api.py\", line 3, in <module>",
+            "    import kiroku_memory.db",
+            "ModuleNotFoundError: No module named 'kiroku_memory.db'",
+        ]
+        .into_iter()
+        .map(stderr)
+        .collect();
+        assert_eq!(
+            root_cause(&lines).as_deref(),
+            Some("ModuleNotFoundError: No module named 'kiroku_memory.db'")
+        );
+    }
+
+    #[test]
+    fn test_root_cause_known_failures_and_uvicorn_errors() {
+        let locked = [stderr("RuntimeError: IO error: Database is locked")];
+        assert_eq!(
+            root_cause(&locked).as_deref(),
+            Some("SurrealDB: database is locked")
+        );
+
+        let uvicorn = [
+            stderr("INFO:     Waiting for application startup."),
+            stderr("ERROR:    Application startup failed. Exiting."),
+        ];
+        assert_eq!(
+            root_cause(&uvicorn).as_deref(),
+            Some("Application startup failed. Exiting.")
+        );
+
+        assert_eq!(root_cause(&[stderr("INFO:     Started")]), None);
+    }
+}
@@ -0,0 +1,254 @@
+// Kiroku Memory Desktop - Settings Transfer
+// Exports settings to a single document and imports them on another machine
+
+use crate::config::{settings, AppSettings};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Identifies an export document
+pub const FORMAT: &str = "kiroku-settings";
+
+/// Version of the export document. Settings inside are upgraded like a saved
+/// settings.json, so this only changes when the document layout does.
+pub const VERSION: u32 = 1;
+
+/// Settings that belong to this install and are neither exported nor imported
+const LOCAL_ONLY: &[&str] = &["locked", "legacy_migration"];
+
+/// Everything needed to set up another machine the same way. Secrets stay in
+/// the keychain, and `service_options.extra_env` is left out since it may hold
+/// tokens.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportDocument {
+    pub format: String,
+    pub version: u32,
+    /// RFC 3339
+    pub exported_at: String,
+    pub app_version: String,
+    pub settings: serde_json::Value,
+    /// Profiles with a store on the exporting machine
+    #[serde(default)]
+    pub profiles: Vec<String>,
+}
+
+/// Returned by `import_settings`
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ImportReport {
+    /// Top-level settings that changed
+    pub changed: Vec<String>,
+    /// A changed setting only applies after a service restart
+    pub restart_required: bool,
+    /// Values skipped or kept because they don't fit this machine
+    pub warnings: Vec<String>,
+}
+
+/// Imported settings, ready to save
+pub struct PreparedImport {
+    pub settings: AppSettings,
+    pub warnings: Vec<String>,
+}
+
+pub fn export(settings: &AppSettings, profiles: Vec<String>, app_version: &str) -> ExportDocument {
+    let mut value = serde_json::to_value(settings).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        for key in LOCAL_ONLY {
+            object.remove(*key);
+        }
+        if let Some(options) = object.get_mut("service_options").and_then(|v| v.as_object_mut()) {
+            options.remove("extra_env");
+        }
+    }
+    ExportDocument {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        app_version: app_version.to_string(),
+        settings: value,
+        profiles,
+    }
+}
+
+/// Parse and check an export against `current`. With `merge`, settings the
+/// document doesn't mention keep their current values; otherwise they reset
+/// to defaults. `profiles` are the ones with a store here. Nothing is written.
+pub fn prepare_import(
+    content: &str,
+    current: &AppSettings,
+    merge: bool,
+    profiles: &[String],
+) -> Result<PreparedImport, String> {
+    let document: ExportDocument =
+        serde_json::from_str(content).map_err(|e| format!("Not a settings export: {}", e))?;
+    if document.format != FORMAT {
+        return Err("Not a Kiroku Memory settings export".to_string());
+    }
+    if document.version > VERSION {
+        return Err(format!(
+            "This export was made by a newer version ({}); update the app to import it",
+            document.app_version
+        ));
+    }
+    let serde_json::Value::Object(imported) = document.settings else {
+        return Err("The export has no settings".to_string());
+    };
+
+    let mut value = if merge {
+        serde_json::to_value(current).map_err(|e| e.to_string())?
+    } else {
+        serde_json::Value::Object(Default::default())
+    };
+    let object = value.as_object_mut().ok_or("Current settings are not an object")?;
+    for (key, field) in imported {
+        if !LOCAL_ONLY.contains(&key.as_str()) {
+            object.insert(key, field);
+        }
+    }
+    let mut new = settings::parse(value).map_err(|e| format!("Invalid settings in export: {}", e))?;
+
+    // This install's LOCAL_ONLY values and extra_env are never replaced
+    new.locked = current.locked;
+    new.legacy_migration = current.legacy_migration;
+    new.service_options.extra_env = current.service_options.extra_env.clone();
+
+    let mut warnings = Vec::new();
+    if let Some(dir) = new.data_dir_override() {
+        if !dir.is_dir() {
+            warnings.push(format!(
+                "Data directory {} doesn't exist here; kept the current one",
+                dir.display()
+            ));
+            new.data_dir_override = current.data_dir_override.clone();
+        }
+    }
+    if let Some(dir) = new.backup_dir() {
+        if !dir.is_dir() {
+            warnings.push(format!(
+                "Backup directory {} doesn't exist here; kept the current one",
+                dir.display()
+            ));
+            new.backup_dir = current.backup_dir.clone();
+        }
+    }
+    if !profiles.contains(&new.active_profile) && new.active_profile != current.active_profile {
+        warnings.push(format!(
+            "Profile '{}' has no memories here; kept '{}' active",
+            new.active_profile, current.active_profile
+        ));
+        new.active_profile = current.active_profile.clone();
+    }
+    let missing: Vec<&str> = document
+        .profiles
+        .iter()
+        .filter(|profile| !profiles.contains(profile))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        warnings.push(format!(
+            "Profiles without memories here (created when switched to): {}",
+            missing.join(", ")
+        ));
+    }
+
+    new.validate()?;
+    Ok(PreparedImport {
+        settings: new,
+        warnings,
+    })
+}
+
+/// Whether `path` looks like somewhere an export can be written
+pub fn check_export_path(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            Err(format!("Folder {} doesn't exist", parent.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogLevel;
+
+    fn profiles(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_export_leaves_out_local_values() {
+        let mut settings = AppSettings {
+            locked: true,
+            ..Default::default()
+        };
+        settings
+            .service_options
+            .extra_env
+            .insert("HF_TOKEN".to_string(), "hf_secret".to_string());
+        let document = export(&settings, profiles(&["default"]), "1.2.3");
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(!json.contains("hf_secret"));
+        assert!(document.settings.get("locked").is_none());
+        assert_eq!(document.version, VERSION);
+    }
+
+    #[test]
+    fn test_import_round_trip_and_merge() {
+        let mut exported = AppSettings {
+            log_level: LogLevel::Debug,
+            service_port: 9123,
+            ..Default::default()
+        };
+        exported.onboarding.completed = true;
+        let content = serde_json::to_string(&export(&exported, profiles(&["default"]), "1.0.0")).unwrap();
+
+        let current = AppSettings {
+            locked: true,
+            notifications_enabled: false,
+            ..Default::default()
+        };
+        let import = prepare_import(&content, &current, true, &profiles(&["default"])).unwrap();
+        assert_eq!(import.settings.log_level, LogLevel::Debug);
+        assert_eq!(import.settings.service_port, 9123);
+        assert!(import.settings.locked);
+        assert!(import.warnings.is_empty());
+
+        // Partial documents: merge keeps what they don't mention
+        let partial = r#"{"format":"kiroku-settings","version":1,"exported_at":"","app_version":"1.0.0","settings":{"log_level":"Warn"}}"#;
+        let merged = prepare_import(partial, &current, true, &profiles(&["default"])).unwrap();
+        assert!(!merged.settings.notifications_enabled);
+        let replaced = prepare_import(partial, &current, false, &profiles(&["default"])).unwrap();
+        assert!(replaced.settings.notifications_enabled);
+        // An old export without onboarding is upgraded like an old settings.json
+        assert!(replaced.settings.onboarding.completed);
+    }
+
+    #[test]
+    fn test_import_skips_paths_missing_here() {
+        let exported = AppSettings {
+            data_dir_override: Some("/nonexistent/kiroku-data".to_string()),
+            active_profile: "work".to_string(),
+            ..Default::default()
+        };
+        let content =
+            serde_json::to_string(&export(&exported, profiles(&["default", "work"]), "1.0.0")).unwrap();
+        let current = AppSettings::default();
+        let import = prepare_import(&content, &current, true, &profiles(&["default"])).unwrap();
+        assert_eq!(import.settings.data_dir_override, None);
+        assert_eq!(import.settings.active_profile, current.active_profile);
+        assert_eq!(import.warnings.len(), 3);
+    }
+
+    #[test]
+    fn test_import_rejects_foreign_and_newer_documents() {
+        let current = AppSettings::default();
+        assert!(prepare_import("{}", &current, true, &[]).is_err());
+        let newer = format!(
+            r#"{{"format":"kiroku-settings","version":{},"exported_at":"","app_version":"9.0.0","settings":{{}}}}"#,
+            VERSION + 1
+        );
+        assert!(prepare_import(&newer, &current, true, &[]).is_err());
+        let invalid = r#"{"format":"kiroku-settings","version":1,"exported_at":"","app_version":"1.0.0","settings":{"service_port":0}}"#;
+        assert!(prepare_import(invalid, &current, true, &[]).is_err());
+    }
+}
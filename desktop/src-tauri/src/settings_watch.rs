@@ -0,0 +1,106 @@
+// Kiroku Memory Desktop - Settings Watcher
+// Notices edits to settings.json made outside the app
+
+use crate::config::AppSettings;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Changes this soon after our own save are that save
+const OWN_WRITE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Editors write in bursts (truncate, write, rename); wait for quiet first
+const SETTLE: Duration = Duration::from_millis(300);
+
+static LAST_SAVE: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Called by `settings::save` before it writes
+pub fn record_save() {
+    *LAST_SAVE.lock().unwrap() = Some(Instant::now());
+}
+
+fn within_own_write(last_save: Option<Instant>, now: Instant) -> bool {
+    last_save.is_some_and(|saved| now.saturating_duration_since(saved) < OWN_WRITE_WINDOW)
+}
+
+/// Whether a change seen now is most likely our own save
+pub fn is_own_write() -> bool {
+    within_own_write(*LAST_SAVE.lock().unwrap(), Instant::now())
+}
+
+/// Changes to `path`. The directory is watched rather than the file so
+/// editors that save by renaming a temp file over it are seen too. Keep the
+/// watcher alive for as long as changes are wanted.
+pub fn watch(path: &Path) -> notify::Result<(RecommendedWatcher, mpsc::UnboundedReceiver<()>)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        if event
+            .paths
+            .iter()
+            .any(|changed: &PathBuf| changed.file_name().map(|name| name.to_os_string()) == file_name)
+        {
+            let _ = tx.send(());
+        }
+    })?;
+    let dir = path.parent().unwrap_or(path);
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok((watcher, rx))
+}
+
+/// Wait for the next burst of changes to settle. False once the watcher is gone.
+pub async fn next_change(changes: &mut mpsc::UnboundedReceiver<()>) -> bool {
+    if changes.recv().await.is_none() {
+        return false;
+    }
+    while let Ok(Some(())) = tokio::time::timeout(SETTLE, changes.recv()).await {}
+    true
+}
+
+/// Top-level settings that differ
+pub fn changed_keys(old: &AppSettings, new: &AppSettings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogLevel;
+
+    #[test]
+    fn test_own_write_window() {
+        let now = Instant::now();
+        assert!(!within_own_write(None, now));
+        assert!(within_own_write(Some(now), now + Duration::from_millis(500)));
+        assert!(!within_own_write(Some(now), now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_changed_keys() {
+        let old = AppSettings::default();
+        let mut new = old.clone();
+        assert!(changed_keys(&old, &new).is_empty());
+
+        new.log_level = LogLevel::Debug;
+        new.stats_interval_secs = old.stats_interval_secs + 30;
+        let mut changed = changed_keys(&old, &new);
+        changed.sort();
+        assert_eq!(changed, vec!["log_level", "stats_interval_secs"]);
+    }
+}
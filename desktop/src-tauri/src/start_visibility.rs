@@ -0,0 +1,147 @@
+// Kiroku Memory Desktop - Start Visibility
+// Decides whether the main window is shown at launch or the app starts in the tray
+//
+// Precedence, first match wins:
+//   0. `--headless` / KIROKU_HEADLESS                   -> Headless (no main window)
+//   1. `--tray` / `--hidden` command-line flag          -> HiddenToTray
+//   2. KIROKU_TRAY_ONLY environment variable            -> HiddenToTray
+//   3. Setup wizard not finished                        -> Visible
+//   4. `start_hidden` setting (ignored in debug builds
+//      unless KIROKU_ALLOW_START_HIDDEN is set)         -> HiddenToTray
+//   5. Otherwise                                        -> Visible
+
+use crate::cli::StartupOverrides;
+use crate::config::AppSettings;
+use serde::Serialize;
+
+pub const TRAY_ONLY_ENV: &str = "KIROKU_TRAY_ONLY";
+pub const ALLOW_START_HIDDEN_ENV: &str = "KIROKU_ALLOW_START_HIDDEN";
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StartVisibility {
+    Visible,
+    HiddenToTray,
+    /// The main window is never created
+    Headless,
+}
+
+/// What was decided at launch and which rule decided it
+#[derive(Clone, Debug, Serialize)]
+pub struct StartVisibilityDecision {
+    pub visibility: StartVisibility,
+    pub reason: String,
+}
+
+/// Everything the decision depends on, gathered up front so `resolve` is pure
+#[derive(Clone, Debug, Default)]
+pub struct StartVisibilityInputs {
+    pub headless: bool,
+    /// The hide flag the app was launched with, if any
+    pub cli_flag: Option<String>,
+    pub env_tray_only: bool,
+    pub onboarding_completed: bool,
+    pub start_hidden_setting: bool,
+    /// Debug build without ALLOW_START_HIDDEN_ENV: the setting alone never hides
+    pub ignore_setting: bool,
+}
+
+impl StartVisibilityInputs {
+    pub fn from_env(settings: &AppSettings, overrides: &StartupOverrides) -> Self {
+        Self {
+            headless: overrides.headless,
+            cli_flag: overrides.hidden_flag.clone(),
+            env_tray_only: std::env::var_os(TRAY_ONLY_ENV).is_some(),
+            onboarding_completed: settings.onboarding.completed,
+            start_hidden_setting: settings.start_hidden,
+            ignore_setting: cfg!(debug_assertions)
+                && std::env::var_os(ALLOW_START_HIDDEN_ENV).is_none(),
+        }
+    }
+}
+
+pub fn resolve(inputs: &StartVisibilityInputs) -> StartVisibilityDecision {
+    let (visibility, reason) = if inputs.headless {
+        (StartVisibility::Headless, "headless mode".to_string())
+    } else if let Some(flag) = &inputs.cli_flag {
+        (StartVisibility::HiddenToTray, format!("{} command-line flag", flag))
+    } else if inputs.env_tray_only {
+        (StartVisibility::HiddenToTray, format!("{} is set", TRAY_ONLY_ENV))
+    } else if !inputs.onboarding_completed {
+        (StartVisibility::Visible, "setup wizard not finished".to_string())
+    } else if inputs.start_hidden_setting && inputs.ignore_setting {
+        (
+            StartVisibility::Visible,
+            format!("start_hidden setting ignored in debug builds (set {})", ALLOW_START_HIDDEN_ENV),
+        )
+    } else if inputs.start_hidden_setting {
+        (StartVisibility::HiddenToTray, "start_hidden setting".to_string())
+    } else {
+        (StartVisibility::Visible, "default".to_string())
+    };
+    StartVisibilityDecision { visibility, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use StartVisibility::{HiddenToTray as H, Visible as V};
+
+    #[test]
+    fn test_every_combination() {
+        // (flag, env, onboarded, setting, ignore_setting) -> (visibility, reason contains)
+        #[rustfmt::skip]
+        let table = [
+            ((false, false, false, false, false), V, "wizard"),
+            ((false, false, false, false, true ), V, "wizard"),
+            ((false, false, false, true,  false), V, "wizard"),
+            ((false, false, false, true,  true ), V, "wizard"),
+            ((false, false, true,  false, false), V, "default"),
+            ((false, false, true,  false, true ), V, "default"),
+            ((false, false, true,  true,  false), H, "start_hidden setting"),
+            ((false, false, true,  true,  true ), V, "ignored in debug"),
+            ((false, true,  false, false, false), H, TRAY_ONLY_ENV),
+            ((false, true,  false, false, true ), H, TRAY_ONLY_ENV),
+            ((false, true,  false, true,  false), H, TRAY_ONLY_ENV),
+            ((false, true,  false, true,  true ), H, TRAY_ONLY_ENV),
+            ((false, true,  true,  false, false), H, TRAY_ONLY_ENV),
+            ((false, true,  true,  false, true ), H, TRAY_ONLY_ENV),
+            ((false, true,  true,  true,  false), H, TRAY_ONLY_ENV),
+            ((false, true,  true,  true,  true ), H, TRAY_ONLY_ENV),
+            ((true,  false, false, false, false), H, "--tray"),
+            ((true,  false, false, false, true ), H, "--tray"),
+            ((true,  false, false, true,  false), H, "--tray"),
+            ((true,  false, false, true,  true ), H, "--tray"),
+            ((true,  false, true,  false, false), H, "--tray"),
+            ((true,  false, true,  false, true ), H, "--tray"),
+            ((true,  false, true,  true,  false), H, "--tray"),
+            ((true,  false, true,  true,  true ), H, "--tray"),
+            ((true,  true,  false, false, false), H, "--tray"),
+            ((true,  true,  false, false, true ), H, "--tray"),
+            ((true,  true,  false, true,  false), H, "--tray"),
+            ((true,  true,  false, true,  true ), H, "--tray"),
+            ((true,  true,  true,  false, false), H, "--tray"),
+            ((true,  true,  true,  false, true ), H, "--tray"),
+            ((true,  true,  true,  true,  false), H, "--tray"),
+            ((true,  true,  true,  true,  true ), H, "--tray"),
+        ];
+
+        for ((flag, env, onboarded, setting, ignore), visibility, reason) in table {
+            let mut inputs = StartVisibilityInputs {
+                headless: false,
+                cli_flag: flag.then(|| "--tray".to_string()),
+                env_tray_only: env,
+                onboarding_completed: onboarded,
+                start_hidden_setting: setting,
+                ignore_setting: ignore,
+            };
+            let decision = resolve(&inputs);
+            assert_eq!(decision.visibility, visibility, "{:?}", inputs);
+            assert!(decision.reason.contains(reason), "{:?}: {}", inputs, decision.reason);
+
+            // Headless beats every other rule
+            inputs.headless = true;
+            assert_eq!(resolve(&inputs).visibility, StartVisibility::Headless);
+        }
+    }
+}
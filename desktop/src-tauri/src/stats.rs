@@ -0,0 +1,229 @@
+// Kiroku Memory Desktop - Service Stats
+// Reads `/v2/stats`, guessing at renamed fields a future service might send, and flags layouts it doesn't know
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const STATS_PATH: &str = "/v2/stats";
+
+/// Longest payload echoed in the unrecognized-layout warning
+const MAX_LOGGED_PAYLOAD: usize = 300;
+
+/// Whether the last payload needed a fallback or couldn't be read at all
+static SCHEMA_WARNING: AtomicBool = AtomicBool::new(false);
+
+/// Where a renamed layout might keep each figure, tried in order
+const TOTAL_PATHS: &[&[&str]] = &[
+    &["items", "total"],
+    &["memories", "total"],
+    &["items", "count"],
+    &["memories", "count"],
+    &["total_items"],
+    &["total"],
+];
+const ACTIVE_PATHS: &[&[&str]] = &[
+    &["items", "active"],
+    &["memories", "active"],
+    &["active_items"],
+];
+const ARCHIVED_PATHS: &[&[&str]] = &[
+    &["items", "archived"],
+    &["memories", "archived"],
+    &["archived_items"],
+];
+const CATEGORIES_PATHS: &[&[&str]] = &[&["categories", "count"], &["category_count"]];
+const CATEGORY_COUNTS_PATHS: &[&[&str]] = &[&["categories", "counts"]];
+const PENDING_PATHS: &[&[&str]] = &[&["pending_resources"], &["resources", "pending"]];
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StatsItems {
+    pub total: u64,
+    #[serde(default)]
+    pub active: u64,
+    #[serde(default)]
+    pub archived: u64,
+}
+
+/// `/v2/stats` in the layout the app and dashboard use
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ServiceStats {
+    pub items: StatsItems,
+    #[serde(default)]
+    pub categories: u64,
+    /// Resources waiting for extraction; missing from older services
+    #[serde(default)]
+    pub pending: u64,
+    /// Active memories per category; missing from older services
+    #[serde(default)]
+    pub category_counts: Option<HashMap<String, u64>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Parsed {
+    /// The layout `ServiceStats` mirrors
+    Current(ServiceStats),
+    /// Found the total under a renamed field
+    Fallback(ServiceStats),
+    Unrecognized,
+}
+
+pub fn parse(value: &Value) -> Parsed {
+    if let Ok(stats) = ServiceStats::deserialize(value) {
+        return Parsed::Current(stats);
+    }
+    let Some(total) = probe_u64(value, TOTAL_PATHS) else {
+        return Parsed::Unrecognized;
+    };
+    let category_counts = probe(value, CATEGORY_COUNTS_PATHS)
+        .or_else(|| value.get("category_counts"))
+        .and_then(|counts| serde_json::from_value::<HashMap<String, u64>>(counts.clone()).ok());
+    let categories = value
+        .get("categories")
+        .and_then(Value::as_u64)
+        .or_else(|| probe_u64(value, CATEGORIES_PATHS))
+        .or_else(|| category_counts.as_ref().map(|counts| counts.len() as u64))
+        .unwrap_or_default();
+    Parsed::Fallback(ServiceStats {
+        items: StatsItems {
+            total,
+            active: probe_u64(value, ACTIVE_PATHS).unwrap_or_default(),
+            archived: probe_u64(value, ARCHIVED_PATHS).unwrap_or_default(),
+        },
+        categories,
+        pending: value
+            .get("pending")
+            .and_then(Value::as_u64)
+            .or_else(|| probe_u64(value, PENDING_PATHS))
+            .unwrap_or_default(),
+        category_counts,
+    })
+}
+
+/// `parse`, updating the schema warning and logging when a payload first
+/// stops matching the current layout
+pub fn interpret(value: &Value) -> Option<ServiceStats> {
+    let (stats, warning) = match parse(value) {
+        Parsed::Current(stats) => (Some(stats), false),
+        Parsed::Fallback(stats) => (Some(stats), true),
+        Parsed::Unrecognized => (None, true),
+    };
+    if !warning {
+        SCHEMA_WARNING.store(false, Ordering::SeqCst);
+    } else if !SCHEMA_WARNING.swap(true, Ordering::SeqCst) {
+        let payload: String = value.to_string().chars().take(MAX_LOGGED_PAYLOAD).collect();
+        println!(
+            "[Stats] WARNING: unexpected {} layout ({}): {}",
+            STATS_PATH,
+            if stats.is_some() { "read with fallbacks" } else { "no memory total found" },
+            payload
+        );
+    }
+    stats
+}
+
+/// Rewrite the current-layout fields of a payload for the dashboard, leaving
+/// any others as sent
+pub fn normalize(value: &mut Value) {
+    let Some(stats) = interpret(value) else {
+        return;
+    };
+    if let (Value::Object(fields), Ok(Value::Object(current))) = (value, serde_json::to_value(stats)) {
+        fields.extend(current);
+    }
+}
+
+/// Whether the service's stats needed fallbacks (a newer service, most likely)
+pub fn schema_warning() -> bool {
+    SCHEMA_WARNING.load(Ordering::SeqCst)
+}
+
+fn probe<'a>(value: &'a Value, paths: &[&[&str]]) -> Option<&'a Value> {
+    paths
+        .iter()
+        .find_map(|path| path.iter().try_fold(value, |value, key| value.get(key)))
+}
+
+fn probe_u64(value: &Value, paths: &[&[&str]]) -> Option<u64> {
+    paths.iter().find_map(|path| {
+        path.iter()
+            .try_fold(value, |value, key| value.get(key))
+            .and_then(Value::as_u64)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Synthetic payloads: the shapes follow `stats_v2` in api.py at each
+    // version, the numbers are made up
+
+    /// Service 0.1.25, before `pending` and `category_counts`
+    const STATS_0_1_25: &str = r#"{
+        "backend": "surrealdb",
+        "items": {"total": 12, "active": 10, "archived": 2},
+        "categories": 3
+    }"#;
+
+    /// Service 0.1.26
+    const STATS_0_1_26: &str = r#"{
+        "backend": "surrealdb",
+        "items": {"total": 12, "active": 10, "archived": 2},
+        "categories": 2,
+        "category_counts": {"preferences": 7, "facts": 3},
+        "pending": 4
+    }"#;
+
+    /// Hypothetical layout with renamed and nested keys, to exercise the
+    /// fallback; no service version has sent it
+    const STATS_RENAMED: &str = r#"{
+        "backend": "surrealdb",
+        "memories": {"count": 12, "active": 10, "archived": 2},
+        "categories": {"count": 2, "counts": {"preferences": 7, "facts": 3}},
+        "pending_resources": 4
+    }"#;
+
+    fn json(text: &str) -> Value {
+        serde_json::from_str(text).unwrap()
+    }
+
+    fn expected(categories: u64, pending: u64, with_counts: bool) -> ServiceStats {
+        ServiceStats {
+            items: StatsItems {
+                total: 12,
+                active: 10,
+                archived: 2,
+            },
+            categories,
+            pending,
+            category_counts: with_counts
+                .then(|| HashMap::from([("preferences".to_string(), 7), ("facts".to_string(), 3)])),
+        }
+    }
+
+    #[test]
+    fn test_parse_known_layouts() {
+        assert_eq!(parse(&json(STATS_0_1_25)), Parsed::Current(expected(3, 0, false)));
+        assert_eq!(parse(&json(STATS_0_1_26)), Parsed::Current(expected(2, 4, true)));
+        assert_eq!(parse(&json(STATS_RENAMED)), Parsed::Fallback(expected(2, 4, true)));
+        assert_eq!(parse(&json(r#"{"status": "ok"}"#)), Parsed::Unrecognized);
+    }
+
+    #[test]
+    fn test_normalize_and_schema_warning() {
+        let mut renamed = json(STATS_RENAMED);
+        normalize(&mut renamed);
+        assert!(schema_warning());
+        assert_eq!(renamed["items"]["total"], 12);
+        assert_eq!(renamed["categories"], 2);
+        assert_eq!(renamed["backend"], "surrealdb");
+
+        assert_eq!(interpret(&json(STATS_0_1_26)), Some(expected(2, 4, true)));
+        assert!(!schema_warning());
+
+        assert_eq!(interpret(&json("[]")), None);
+        assert!(schema_warning());
+    }
+}
@@ -0,0 +1,231 @@
+// Kiroku Memory Desktop - Stats History
+// Records the memory counts the tray already polls, downsampled for charts
+
+use crate::jsonl;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// One sample per line, oldest first
+pub const HISTORY_FILE: &str = "stats-history.jsonl";
+
+/// Oldest samples are dropped once a compacted file is still larger than this
+const MAX_FILE_BYTES: usize = 1024 * 1024;
+
+/// Compact after this many appends (about an hour of tray polls)
+const COMPACT_EVERY: u32 = 120;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+/// Every sample is kept for a day, hourly for 30 days, daily beyond
+const RAW_RETENTION: i64 = DAY;
+const HOURLY_RETENTION: i64 = 30 * DAY;
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StatsSample {
+    /// Unix timestamp (seconds)
+    pub ts: i64,
+    pub total: u64,
+    #[serde(default)]
+    pub active: u64,
+    #[serde(default)]
+    pub archived: u64,
+    /// Number of distinct categories
+    #[serde(default)]
+    pub categories: u64,
+}
+
+/// Time span for `get_stats_history`; each picks a bucket width
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum HistoryRange {
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[serde(rename = "all")]
+    All,
+}
+
+impl HistoryRange {
+    /// (span in seconds, bucket width in seconds); `None` span means everything
+    fn window(self) -> (Option<i64>, i64) {
+        match self {
+            HistoryRange::Day => (Some(DAY), 15 * 60),
+            HistoryRange::Week => (Some(7 * DAY), HOUR),
+            HistoryRange::Month => (Some(30 * DAY), 6 * HOUR),
+            HistoryRange::All => (None, DAY),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Timestamp of the newest recorded sample, loaded from the file on first use
+    last_ts: Option<i64>,
+    loaded: bool,
+    appends_since_compact: u32,
+}
+
+pub struct StatsHistory {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+impl StatsHistory {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            path: dir.join(HISTORY_FILE),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Append a sample. Samples older than the last recorded one (clock moved
+    /// backwards) are rejected so the file stays sorted.
+    pub fn record(&self, sample: StatsSample) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.loaded {
+            state.last_ts = jsonl::read::<StatsSample>(&self.path).last().map(|s| s.ts);
+            state.loaded = true;
+            // Catch up on anything that aged while the app was closed
+            state.appends_since_compact = COMPACT_EVERY;
+        }
+        if let Some(last) = state.last_ts {
+            if sample.ts < last {
+                anyhow::bail!(
+                    "dropped sample at {} (earlier than last recorded {})",
+                    sample.ts,
+                    last
+                );
+            }
+        }
+
+        jsonl::append(&self.path, &[sample])?;
+        state.last_ts = Some(sample.ts);
+        state.appends_since_compact += 1;
+        if state.appends_since_compact >= COMPACT_EVERY {
+            let samples = compact(jsonl::read::<StatsSample>(&self.path), sample.ts);
+            jsonl::replace(&self.path, &cap(samples))?;
+            state.appends_since_compact = 0;
+        }
+        Ok(())
+    }
+
+    /// Samples within `range`, one per bucket (the last sample in each)
+    pub fn query(&self, range: HistoryRange, now: i64) -> Vec<StatsSample> {
+        let _state = self.state.lock().unwrap();
+        let (span, width) = range.window();
+        let since = span.map_or(i64::MIN, |span| now - span);
+        bucket(jsonl::read::<StatsSample>(&self.path), since, width)
+    }
+}
+
+/// Thin out samples by age: keep all within RAW_RETENTION, the last of each
+/// hour within HOURLY_RETENTION, and the last of each day beyond. Input must
+/// be sorted; re-compacting a compacted list changes nothing.
+fn compact(samples: Vec<StatsSample>, now: i64) -> Vec<StatsSample> {
+    let key = |s: &StatsSample| {
+        let age = now - s.ts;
+        if age <= RAW_RETENTION {
+            None
+        } else if age <= HOURLY_RETENTION {
+            Some((1, s.ts.div_euclid(HOUR)))
+        } else {
+            Some((2, s.ts.div_euclid(DAY)))
+        }
+    };
+
+    let mut out: Vec<StatsSample> = Vec::with_capacity(samples.len());
+    for sample in samples {
+        match (out.last(), key(&sample)) {
+            (Some(prev), Some(k)) if key(prev) == Some(k) => *out.last_mut().unwrap() = sample,
+            _ => out.push(sample),
+        }
+    }
+    out
+}
+
+/// Drop the oldest samples until the serialized file fits MAX_FILE_BYTES
+fn cap(mut samples: Vec<StatsSample>) -> Vec<StatsSample> {
+    let mut size: usize = samples.iter().map(line_len).sum();
+    let mut drop = 0;
+    while size > MAX_FILE_BYTES && drop < samples.len() {
+        size -= line_len(&samples[drop]);
+        drop += 1;
+    }
+    samples.drain(..drop);
+    samples
+}
+
+fn line_len(sample: &StatsSample) -> usize {
+    serde_json::to_string(sample).map_or(0, |s| s.len() + 1)
+}
+
+/// Last sample of each `width`-second bucket from `since` on, stamped with the
+/// bucket start
+fn bucket(samples: Vec<StatsSample>, since: i64, width: i64) -> Vec<StatsSample> {
+    let mut out: Vec<StatsSample> = Vec::new();
+    for sample in samples.into_iter().filter(|s| s.ts >= since) {
+        let start = sample.ts - sample.ts.rem_euclid(width);
+        let point = StatsSample { ts: start, ..sample };
+        match out.last_mut() {
+            Some(last) if last.ts == start => *last = point,
+            _ => out.push(point),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn at(ts: i64, total: u64) -> StatsSample {
+        StatsSample {
+            ts,
+            total,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compact_keeps_raw_then_hourly_then_daily() {
+        let now = 100 * DAY;
+        let samples = vec![
+            // Two samples on a day 40 days ago -> one
+            at(now - 40 * DAY, 1),
+            at(now - 40 * DAY + 2 * HOUR, 2),
+            // Two samples in one hour 10 days ago -> one
+            at(now - 10 * DAY, 3),
+            at(now - 10 * DAY + 60, 4),
+            // Last day: all kept
+            at(now - 60, 5),
+            at(now - 30, 6),
+        ];
+        let compacted = compact(samples, now);
+        let totals: Vec<u64> = compacted.iter().map(|s| s.total).collect();
+        assert_eq!(totals, vec![2, 4, 5, 6]);
+        assert_eq!(compact(compacted.clone(), now), compacted);
+    }
+
+    #[test]
+    fn test_bucket_takes_last_sample_per_bucket() {
+        let points = bucket(vec![at(10, 1), at(890, 2), at(900, 3)], 0, 900);
+        assert_eq!(points, vec![at(0, 2), at(900, 3)]);
+        assert!(bucket(vec![at(10, 1)], 20, 900).is_empty());
+    }
+
+    #[test]
+    fn test_record_drops_samples_from_the_past() {
+        let dir = TempDir::new("stats-history");
+        let history = StatsHistory::new(dir.path().to_path_buf());
+
+        history.record(at(1_000, 1)).unwrap();
+        assert!(history.record(at(999, 2)).is_err());
+        history.record(at(1_000, 3)).unwrap();
+        assert_eq!(jsonl::read::<StatsSample>(&dir.join(HISTORY_FILE)).len(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+// Kiroku Memory Desktop - Storage Usage
+// Disk usage of the data directory, and free space on its volume
+
+use crate::backup::DB_DIR_NAME;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Stop walking after this long and report what was counted so far
+pub const SCAN_TIME_LIMIT: Duration = Duration::from_secs(3);
+
+/// Space above the warning threshold needed to re-arm `LowSpaceLatch`, so a
+/// volume hovering at the threshold doesn't warn on every poll
+const RECOVERY_MARGIN_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Disk usage of the data directory
+#[derive(Clone, Debug, Serialize)]
+pub struct DataDirSize {
+    pub path: String,
+    pub total_bytes: u64,
+    /// Size of the `surrealdb` folder alone
+    pub database_bytes: u64,
+    pub human: String,
+    /// False if the scan hit the time limit and the figures are a lower bound
+    pub complete: bool,
+}
+
+/// Sum file sizes under `data_dir`, giving up after `limit`
+pub fn data_dir_size(data_dir: &Path, limit: Duration) -> DataDirSize {
+    let deadline = Instant::now() + limit;
+    let mut complete = true;
+    let database_bytes = dir_size(&data_dir.join(DB_DIR_NAME), deadline, &mut complete);
+    let mut total_bytes = database_bytes;
+
+    if let Ok(entries) = std::fs::read_dir(data_dir) {
+        for entry in entries.flatten() {
+            if entry.file_name() == DB_DIR_NAME {
+                continue;
+            }
+            total_bytes += entry_size(&entry, deadline, &mut complete);
+        }
+    }
+
+    DataDirSize {
+        path: data_dir.to_string_lossy().to_string(),
+        total_bytes,
+        database_bytes,
+        human: human_size(total_bytes),
+        complete,
+    }
+}
+
+/// Free space on the data directory's volume
+#[derive(Clone, Debug, Serialize)]
+pub struct DiskSpace {
+    pub path: String,
+    pub available_bytes: u64,
+    pub human: String,
+    /// Below this the service is not started; 0 disables the check
+    pub min_free_bytes: u64,
+    pub low: bool,
+}
+
+/// Free space for `data_dir`; None if no mounted volume contains it
+pub fn disk_space(data_dir: &Path, min_free_mb: u64) -> Option<DiskSpace> {
+    let path = data_dir
+        .canonicalize()
+        .unwrap_or_else(|_| data_dir.to_path_buf());
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let available_bytes = volume_available(
+        &path,
+        disks
+            .list()
+            .iter()
+            .map(|disk| (disk.mount_point(), disk.available_space())),
+    )?;
+    let min_free_bytes = min_free_mb * 1024 * 1024;
+    Some(DiskSpace {
+        path: data_dir.to_string_lossy().to_string(),
+        available_bytes,
+        human: human_size(available_bytes),
+        min_free_bytes,
+        low: available_bytes < min_free_bytes,
+    })
+}
+
+/// Warns once each time free space drops below a threshold
+#[derive(Default)]
+pub struct LowSpaceLatch {
+    warned: bool,
+}
+
+impl LowSpaceLatch {
+    /// True when this reading crossed below `threshold_bytes`
+    pub fn check(&mut self, available_bytes: u64, threshold_bytes: u64) -> bool {
+        if available_bytes < threshold_bytes {
+            let first = !self.warned;
+            self.warned = true;
+            first
+        } else {
+            if available_bytes >= threshold_bytes.saturating_add(RECOVERY_MARGIN_BYTES) {
+                self.warned = false;
+            }
+            false
+        }
+    }
+}
+
+/// Available bytes of the most specific mount point containing `path`
+fn volume_available<'a>(
+    path: &Path,
+    volumes: impl IntoIterator<Item = (&'a Path, u64)>,
+) -> Option<u64> {
+    volumes
+        .into_iter()
+        .filter(|(mount, _)| path.starts_with(mount))
+        .max_by_key(|(mount, _)| mount.components().count())
+        .map(|(_, available)| available)
+}
+
+fn dir_size(dir: &Path, deadline: Instant, complete: &mut bool) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        if Instant::now() >= deadline {
+            *complete = false;
+            break;
+        }
+        total += entry_size(&entry, deadline, complete);
+    }
+    total
+}
+
+fn entry_size(entry: &std::fs::DirEntry, deadline: Instant, complete: &mut bool) -> u64 {
+    // Symlinks are not followed, so a link to a large folder isn't counted twice
+    match entry.file_type() {
+        Ok(t) if t.is_dir() => dir_size(&entry.path(), deadline, complete),
+        Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Format a byte count like "240 MB"
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 100.0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(240 * 1024 * 1024), "240 MB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    #[test]
+    fn test_low_space_latch() {
+        const MB: u64 = 1024 * 1024;
+        let mut latch = LowSpaceLatch::default();
+        assert!(!latch.check(2000 * MB, 1000 * MB));
+        assert!(latch.check(900 * MB, 1000 * MB));
+        assert!(!latch.check(800 * MB, 1000 * MB));
+        // Back just above the threshold: still latched
+        assert!(!latch.check(1010 * MB, 1000 * MB));
+        assert!(!latch.check(990 * MB, 1000 * MB));
+        // Recovered past the margin, then low again
+        assert!(!latch.check(1100 * MB, 1000 * MB));
+        assert!(latch.check(900 * MB, 1000 * MB));
+    }
+
+    #[test]
+    fn test_volume_available_prefers_deepest_mount() {
+        let volumes = [
+            (Path::new("/"), 10),
+            (Path::new("/home"), 20),
+            (Path::new("/home/me/external"), 30),
+        ];
+        let data_dir = Path::new("/home/me/Library/Kiroku");
+        assert_eq!(volume_available(data_dir, volumes), Some(20));
+        assert_eq!(volume_available(Path::new("/var/tmp"), volumes), Some(10));
+        assert_eq!(volume_available(Path::new("relative"), volumes), None);
+    }
+}
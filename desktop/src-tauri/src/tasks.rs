@@ -0,0 +1,187 @@
+// Kiroku Memory Desktop - Background Task Supervisor
+// Respawns long-lived loops (monitor, tray, ...) if they panic and cancels them on quit
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// Give up on a task after this many panics
+pub const MAX_RESTARTS: u32 = 5;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Where the supervisor reports panics and restarts (app.log)
+pub type Logger = fn(&AppHandle, &str);
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Returned normally
+    Finished,
+    /// Panicked more than MAX_RESTARTS times
+    Failed,
+    /// Stopped by `shutdown`
+    Cancelled,
+}
+
+/// Debug view of one supervised task
+#[derive(Clone, Debug, Serialize)]
+pub struct TaskInfo {
+    pub name: String,
+    pub state: TaskState,
+    pub restarts: u32,
+    pub last_panic: Option<String>,
+}
+
+pub struct TaskSupervisor {
+    tasks: Mutex<BTreeMap<&'static str, TaskInfo>>,
+    log: Logger,
+    cancel: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl TaskSupervisor {
+    pub fn new(log: Logger) -> Self {
+        Self {
+            tasks: Mutex::new(BTreeMap::new()),
+            log,
+            cancel: CancellationToken::new(),
+            tracker: TaskTracker::new(),
+        }
+    }
+
+    /// Token cancelled on shutdown, for loops that can't be respawned (e.g. they own a receiver)
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Cancel every supervised task and wait up to `timeout` for them to stop.
+    /// Returns false if some task was still running when the time ran out.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.cancel.cancel();
+        self.tracker.close();
+        tokio::time::timeout(timeout, self.tracker.wait()).await.is_ok()
+    }
+
+    /// Run the future built by `make` and build a fresh one whenever it panics,
+    /// waiting 1s, 2s, 4s, ... (capped) between attempts. The future is dropped
+    /// at its next await point once `shutdown` is called.
+    pub fn spawn<F, Fut>(self: &Arc<Self>, app: &AppHandle, name: &'static str, make: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.update(name, |info| info.state = TaskState::Running);
+
+        let supervisor = self.clone();
+        let app = app.clone();
+        let cancel = self.cancel.clone();
+        let supervised = async move {
+            let mut restarts = 0u32;
+            loop {
+                let task_cancel = cancel.clone();
+                let fut = make();
+                let outcome = tokio::spawn(async move {
+                    tokio::select! {
+                        _ = task_cancel.cancelled() => false,
+                        _ = fut => true,
+                    }
+                })
+                .await;
+                let payload = match outcome {
+                    Ok(finished) => {
+                        let state = if finished {
+                            TaskState::Finished
+                        } else {
+                            TaskState::Cancelled
+                        };
+                        supervisor.update(name, |info| info.state = state);
+                        return;
+                    }
+                    Err(e) if e.is_panic() => panic_message(e.into_panic()),
+                    Err(_) => "task cancelled".to_string(),
+                };
+
+                (supervisor.log)(&app, &format!("task {} panicked: {}", name, payload));
+                if restarts >= MAX_RESTARTS {
+                    (supervisor.log)(&app, &format!("task {} exceeded restart limit", name));
+                    supervisor.update(name, |info| {
+                        info.state = TaskState::Failed;
+                        info.last_panic = Some(payload);
+                    });
+                    return;
+                }
+
+                restarts += 1;
+                supervisor.update(name, |info| {
+                    info.restarts = restarts;
+                    info.last_panic = Some(payload);
+                });
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        supervisor.update(name, |info| info.state = TaskState::Cancelled);
+                        return;
+                    }
+                    _ = tokio::time::sleep(backoff(restarts)) => {}
+                }
+                (supervisor.log)(&app, &format!("task {} restarting (#{})", name, restarts));
+            }
+        };
+        tauri::async_runtime::spawn(self.tracker.track_future(supervised));
+    }
+
+    pub fn list(&self) -> Vec<TaskInfo> {
+        self.tasks.lock().unwrap().values().cloned().collect()
+    }
+
+    fn update(&self, name: &'static str, f: impl FnOnce(&mut TaskInfo)) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let info = tasks.entry(name).or_insert_with(|| TaskInfo {
+            name: name.to_string(),
+            state: TaskState::Running,
+            restarts: 0,
+            last_panic: None,
+        });
+        f(info);
+    }
+}
+
+fn backoff(restarts: u32) -> Duration {
+    Duration::from_secs(1u64 << restarts.saturating_sub(1).min(5)).min(MAX_BACKOFF)
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_panic_payload_is_captured() {
+        let err = tokio::spawn(async { panic!("boom") }).await.unwrap_err();
+        assert_eq!(panic_message(err.into_panic()), "boom");
+    }
+}
@@ -0,0 +1,40 @@
+// Kiroku Memory Desktop - Test Support
+// Fixtures shared by unit tests
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// An empty directory under the system temp dir, removed on drop. Every call
+/// gets its own, so tests running in parallel never share one.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(label: &str) -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "kiroku-{}-{}-{}",
+            label,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::SeqCst)
+        ));
+        // Left over from an earlier run that reused this pid
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // Tests may have made parts read-only (see permissions.rs); best effort
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
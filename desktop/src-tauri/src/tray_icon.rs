@@ -0,0 +1,86 @@
+// Kiroku Memory Desktop - Tray Icon
+// Picks the tray icon variant for the `tray_icon_style` setting and the system theme
+
+use crate::config::TrayIconStyle;
+use tauri::Theme;
+
+/// The colored glyph
+const COLOR_PNG: &[u8] = include_bytes!("../icons/tray-icon.png");
+/// Black glyph; macOS only uses its alpha and recolors it for the menu bar
+const TEMPLATE_PNG: &[u8] = include_bytes!("../icons/tray-icon-template.png");
+/// Dark glyph for light taskbars and panels
+const DARK_PNG: &[u8] = include_bytes!("../icons/tray-icon-dark.png");
+/// Light glyph for dark taskbars and panels
+const LIGHT_PNG: &[u8] = include_bytes!("../icons/tray-icon-light.png");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrayIconVariant {
+    Color,
+    Template,
+    Dark,
+    Light,
+}
+
+impl TrayIconVariant {
+    pub fn png(self) -> &'static [u8] {
+        match self {
+            TrayIconVariant::Color => COLOR_PNG,
+            TrayIconVariant::Template => TEMPLATE_PNG,
+            TrayIconVariant::Dark => DARK_PNG,
+            TrayIconVariant::Light => LIGHT_PNG,
+        }
+    }
+
+    pub fn is_template(self) -> bool {
+        self == TrayIconVariant::Template
+    }
+}
+
+/// `theme` is the system theme, if it could be detected
+pub fn select(style: TrayIconStyle, theme: Option<Theme>) -> TrayIconVariant {
+    select_for(style, theme, cfg!(target_os = "macos"))
+}
+
+fn select_for(style: TrayIconStyle, theme: Option<Theme>, macos: bool) -> TrayIconVariant {
+    match (style, theme) {
+        (TrayIconStyle::Color, _) => TrayIconVariant::Color,
+        // The menu bar recolors template images itself
+        _ if macos => TrayIconVariant::Template,
+        (_, Some(Theme::Dark)) => TrayIconVariant::Light,
+        (_, Some(Theme::Light)) => TrayIconVariant::Dark,
+        (TrayIconStyle::Monochrome, _) => TrayIconVariant::Dark,
+        (TrayIconStyle::Auto, _) => TrayIconVariant::Color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_variant() {
+        use TrayIconStyle::{Auto, Color, Monochrome};
+        use TrayIconVariant as V;
+
+        for theme in [None, Some(Theme::Light), Some(Theme::Dark)] {
+            assert_eq!(select_for(Color, theme, true), V::Color);
+            assert_eq!(select_for(Color, theme, false), V::Color);
+            assert_eq!(select_for(Auto, theme, true), V::Template);
+            assert_eq!(select_for(Monochrome, theme, true), V::Template);
+        }
+        assert_eq!(select_for(Auto, Some(Theme::Dark), false), V::Light);
+        assert_eq!(select_for(Auto, Some(Theme::Light), false), V::Dark);
+        assert_eq!(select_for(Auto, None, false), V::Color);
+        assert_eq!(select_for(Monochrome, Some(Theme::Dark), false), V::Light);
+        assert_eq!(select_for(Monochrome, None, false), V::Dark);
+    }
+
+    #[test]
+    fn test_embedded_variants_decode() {
+        use TrayIconVariant as V;
+
+        for variant in [V::Color, V::Template, V::Dark, V::Light] {
+            assert!(tauri::image::Image::from_bytes(variant.png()).is_ok(), "{:?}", variant);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+// Kiroku Memory Desktop - Tray Tooltip
+// Live hover text for the tray icon: status, memory count, and uptime
+
+use crate::humanize;
+use crate::locale::{tr, tr_args};
+use crate::memories;
+use crate::service::ServiceStatus;
+use std::time::Duration;
+
+/// Error summaries are cut here; Windows truncates tooltips at 127 characters
+const MAX_ERROR_LEN: usize = 80;
+
+pub fn text(status: &ServiceStatus, count: Option<u64>, uptime: Option<Duration>) -> String {
+    let mut parts = vec![match status {
+        ServiceStatus::Starting => tr("tray.tooltip.starting").to_string(),
+        ServiceStatus::Running => tr("tray.tooltip.running").to_string(),
+        ServiceStatus::Stopped => tr("tray.tooltip.stopped").to_string(),
+        ServiceStatus::Restarting => tr("tray.tooltip.restarting").to_string(),
+        ServiceStatus::Idle => tr("tray.tooltip.idle").to_string(),
+        ServiceStatus::Error(error) => format!("{}: {}", tr("tray.tooltip.error"), summarize(error)),
+        ServiceStatus::Degraded(reason) => {
+            format!("{}: {}", tr("tray.tooltip.degraded"), summarize(reason))
+        }
+    }];
+    if status.is_up() {
+        if let Some(count) = count {
+            parts.push(tr_args("tray.tooltip.memories", &[("count", &memories::format_count(count))]));
+        }
+        if let Some(uptime) = uptime {
+            parts.push(tr_args("tray.tooltip.uptime", &[("uptime", &humanize::duration(uptime))]));
+        }
+    }
+    format!("Kiroku \u{2014} {}", parts.join(" \u{b7} "))
+}
+
+fn summarize(error: &str) -> String {
+    let line = error.lines().next().unwrap_or_default();
+    if line.chars().count() <= MAX_ERROR_LEN {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_ERROR_LEN - 1).collect();
+    format!("{}\u{2026}", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text() {
+        let uptime = Some(Duration::from_secs(3 * 3600 + 12 * 60));
+        assert_eq!(
+            text(&ServiceStatus::Running, Some(1204), uptime),
+            "Kiroku \u{2014} Running \u{b7} 1,204 memories \u{b7} up 3h 12m"
+        );
+        assert_eq!(text(&ServiceStatus::Stopped, Some(1204), uptime), "Kiroku \u{2014} Stopped");
+        assert_eq!(
+            text(&ServiceStatus::Degraded("Low disk space".into()), Some(1204), None),
+            "Kiroku \u{2014} Needs attention: Low disk space \u{b7} 1,204 memories"
+        );
+
+        let error = ServiceStatus::Error(format!("Service exited with code 1: {}", "x".repeat(100)));
+        let tooltip = text(&error, None, None);
+        assert!(tooltip.starts_with("Kiroku \u{2014} Error: Service exited with code 1: x"));
+        assert!(tooltip.ends_with('\u{2026}'));
+    }
+}
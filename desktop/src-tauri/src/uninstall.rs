@@ -0,0 +1,136 @@
+// Kiroku Memory Desktop - Uninstall
+// Options, report, and Trash handling for removing the app's traces
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Must be passed as `confirmation` when `remove_data` is set
+pub const REMOVE_DATA_CONFIRMATION: &str = "DELETE MY DATA";
+
+/// What `prepare_uninstall` should remove
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UninstallOptions {
+    pub remove_launch_agent: bool,
+    pub remove_keychain_secrets: bool,
+    pub remove_data: bool,
+    pub remove_logs: bool,
+    pub confirmation: String,
+}
+
+/// Outcome of each cleanup step
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UninstallReport {
+    pub removed: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+impl UninstallReport {
+    pub fn record(&mut self, what: &str, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.removed.push(what.to_string()),
+            Err(e) => self.failed.push(format!("{}: {}", what, e)),
+        }
+    }
+}
+
+/// Move `path` into the user's Trash. Never falls back to deleting.
+pub fn move_to_trash(home: &Path, path: &Path) -> Result<PathBuf, String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Cannot trash {}", path.display()))?
+        .to_string_lossy()
+        .to_string();
+    let trash_dir = trash_files_dir(home)?;
+    std::fs::create_dir_all(&trash_dir).map_err(|e| format!("Cannot open Trash: {}", e))?;
+
+    let mut target = trash_dir.join(&name);
+    if target.exists() {
+        target = trash_dir.join(format!(
+            "{}-{}",
+            name,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+    }
+
+    write_trash_info(home, path, &target)?;
+    std::fs::rename(path, &target)
+        .map_err(|e| format!("Failed to move {} to Trash: {}", path.display(), e))?;
+    Ok(target)
+}
+
+#[cfg(target_os = "macos")]
+fn trash_files_dir(home: &Path) -> Result<PathBuf, String> {
+    Ok(home.join(".Trash"))
+}
+
+#[cfg(target_os = "macos")]
+fn write_trash_info(_home: &Path, _original: &Path, _target: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// freedesktop.org Trash: files/ holds the item, info/ records where it came from
+#[cfg(target_os = "linux")]
+fn trash_files_dir(home: &Path) -> Result<PathBuf, String> {
+    Ok(linux_trash_root(home).join("files"))
+}
+
+#[cfg(target_os = "linux")]
+fn linux_trash_root(home: &Path) -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/share"))
+        .join("Trash")
+}
+
+#[cfg(target_os = "linux")]
+fn write_trash_info(home: &Path, original: &Path, target: &Path) -> Result<(), String> {
+    let info_dir = linux_trash_root(home).join("info");
+    std::fs::create_dir_all(&info_dir).map_err(|e| format!("Cannot open Trash: {}", e))?;
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let info = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        trash_info_path(original),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    std::fs::write(info_dir.join(format!("{}.trashinfo", name)), info)
+        .map_err(|e| format!("Cannot write Trash info: {}", e))
+}
+
+/// `Path=` value of a .trashinfo file: percent-encoded bytes, '/' kept as is
+#[cfg(target_os = "linux")]
+fn trash_info_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn trash_files_dir(_home: &Path) -> Result<PathBuf, String> {
+    Err("Moving to the Trash is not supported on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn write_trash_info(_home: &Path, _original: &Path, _target: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_info_path_is_percent_encoded() {
+        assert_eq!(
+            trash_info_path(Path::new("/home/me/My Data/記録%1")),
+            "/home/me/My%20Data/%E8%A8%98%E9%8C%B2%251"
+        );
+    }
+}
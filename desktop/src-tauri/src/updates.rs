@@ -0,0 +1,203 @@
+// Kiroku Memory Desktop - Update Checks
+// Compares the running version against GitHub releases
+
+use crate::config::ReleaseChannel;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/yelban/kiroku-memory/releases";
+
+/// How long a successful check is reused
+pub const CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Version compiled into this build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Result of an update check
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct UpdateInfo {
+    pub current: String,
+    pub latest: String,
+    pub update_available: bool,
+    pub release_notes_url: String,
+    /// Installer for this platform/arch, if the release has one
+    pub download_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Suffix of the release asset built for this platform (see .github/workflows/release.yml)
+fn platform_asset_suffix() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        if cfg!(target_arch = "aarch64") {
+            Some("_macos_arm64.dmg")
+        } else {
+            Some("_macos_x64.dmg")
+        }
+    } else if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+        Some("_windows_x64.msi")
+    } else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("_linux_x64.AppImage")
+    } else {
+        None
+    }
+}
+
+fn parse_version(tag: &str) -> Option<Version> {
+    Version::parse(tag.trim_start_matches('v')).ok()
+}
+
+/// Newest release on the channel: stable ignores prereleases, beta includes them
+fn pick_release(releases: &[GithubRelease], channel: ReleaseChannel) -> Option<(&GithubRelease, Version)> {
+    releases
+        .iter()
+        .filter(|r| !r.draft)
+        .filter(|r| channel == ReleaseChannel::Beta || !r.prerelease)
+        .filter_map(|r| parse_version(&r.tag_name).map(|v| (r, v)))
+        .max_by(|a, b| a.1.cmp(&b.1))
+}
+
+fn build_info(current: &str, release: &GithubRelease, latest: &Version) -> UpdateInfo {
+    let update_available = parse_version(current)
+        .map(|current| latest > &current)
+        .unwrap_or(false);
+    let download_url = platform_asset_suffix().and_then(|suffix| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name.ends_with(suffix))
+            .map(|a| a.browser_download_url.clone())
+    });
+
+    UpdateInfo {
+        current: current.to_string(),
+        latest: latest.to_string(),
+        update_available,
+        release_notes_url: release.html_url.clone(),
+        download_url,
+    }
+}
+
+async fn fetch_latest(channel: ReleaseChannel) -> anyhow::Result<UpdateInfo> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent(format!("kiroku-memory-desktop/{}", CURRENT_VERSION))
+        .build()?;
+    let releases: Vec<GithubRelease> = client
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let (release, latest) = pick_release(&releases, channel)
+        .ok_or_else(|| anyhow::anyhow!("No {:?} releases found", channel))?;
+    Ok(build_info(CURRENT_VERSION, release, &latest))
+}
+
+/// Caches the last successful check per channel
+#[derive(Default)]
+pub struct UpdateChecker {
+    cache: Mutex<Option<(Instant, ReleaseChannel, UpdateInfo)>>,
+}
+
+impl UpdateChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check for updates, reusing a cached result younger than CACHE_TTL unless `force`
+    pub async fn check(&self, channel: ReleaseChannel, force: bool) -> anyhow::Result<UpdateInfo> {
+        let mut cache = self.cache.lock().await;
+        if let Some((checked_at, cached_channel, info)) = cache.as_ref() {
+            if !force && *cached_channel == channel && checked_at.elapsed() < CACHE_TTL {
+                return Ok(info.clone());
+            }
+        }
+
+        let info = fetch_latest(channel).await?;
+        *cache = Some((Instant::now(), channel, info.clone()));
+        Ok(info)
+    }
+
+    /// Last successful result, if any
+    pub async fn last(&self) -> Option<UpdateInfo> {
+        self.cache.lock().await.as_ref().map(|(_, _, info)| info.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool) -> GithubRelease {
+        GithubRelease {
+            tag_name: tag.to_string(),
+            html_url: format!("https://github.com/yelban/kiroku-memory/releases/tag/{}", tag),
+            draft: false,
+            prerelease,
+            assets: vec![GithubAsset {
+                name: format!(
+                    "Kiroku.Memory_{}{}",
+                    tag.trim_start_matches('v'),
+                    platform_asset_suffix().unwrap_or("_unknown")
+                ),
+                browser_download_url: format!("https://example.com/{}", tag),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_channel_selection() {
+        let releases = vec![
+            release("v0.2.0-beta.1", true),
+            release("v0.1.30", false),
+            release("v0.1.9", false),
+        ];
+
+        let (stable, _) = pick_release(&releases, ReleaseChannel::Stable).unwrap();
+        assert_eq!(stable.tag_name, "v0.1.30");
+        let (beta, _) = pick_release(&releases, ReleaseChannel::Beta).unwrap();
+        assert_eq!(beta.tag_name, "v0.2.0-beta.1");
+    }
+
+    #[test]
+    fn test_semver_comparison() {
+        let newer = release("v0.1.30", false);
+        let info = build_info("0.1.25", &newer, &parse_version("v0.1.30").unwrap());
+        assert!(info.update_available);
+        assert_eq!(info.latest, "0.1.30");
+
+        let same = release("v0.1.25", false);
+        let info = build_info("0.1.25", &same, &parse_version("v0.1.25").unwrap());
+        assert!(!info.update_available);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "macos", all(target_os = "linux", target_arch = "x86_64")))]
+    fn test_download_url_matches_platform() {
+        let newer = release("v0.1.30", false);
+        let info = build_info("0.1.25", &newer, &parse_version("v0.1.30").unwrap());
+        assert_eq!(info.download_url.as_deref(), Some("https://example.com/v0.1.30"));
+    }
+}
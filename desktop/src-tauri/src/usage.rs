@@ -0,0 +1,289 @@
+// Kiroku Memory Desktop - API Usage
+// OpenAI token usage reported by the service, with estimated cost and budget alerts
+
+use crate::api::{ApiClient, ApiError, RequestOptions};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Per-day, per-model token counts; services without usage tracking answer 404
+pub const USAGE_PATH: &str = "/v2/usage";
+
+/// Reports are re-fetched at most this often
+const CACHE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Token counts kept by the service across restarts (its USAGE_FILE); each
+/// worker writes `usage.<pid>.jsonl` beside it
+pub const USAGE_FILE: &str = "usage.json";
+
+/// Which budget thresholds already notified this month
+pub const ALERTS_FILE: &str = "usage-alerts.json";
+
+/// Percent of `monthly_budget_usd` that triggers a notification
+pub const BUDGET_THRESHOLDS: &[u8] = &[80, 100];
+
+/// USD per million (input, output) tokens, by model name prefix. Longer
+/// prefixes first so "gpt-4o-mini" isn't priced as "gpt-4o".
+const PRICES: &[(&str, f64, f64)] = &[
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("text-embedding-ada-002", 0.10, 0.0),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageRange {
+    Today,
+    /// Today and the six days before
+    Week,
+    /// Since the first of the current month
+    Month,
+}
+
+impl UsageRange {
+    /// First and last day (inclusive) of the range ending `today`
+    pub fn dates(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let since = match self {
+            UsageRange::Today => today,
+            UsageRange::Week => today - Duration::days(6),
+            UsageRange::Month => today.with_day(1).unwrap_or(today),
+        };
+        (since, today)
+    }
+}
+
+/// One row of the service's usage report
+#[derive(Clone, Debug, Deserialize)]
+pub struct UsageRecord {
+    /// YYYY-MM-DD
+    pub date: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    /// When the service prices usage itself; preferred over the estimate
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UsageReport {
+    pub records: Vec<UsageRecord>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct DailyUsage {
+    pub date: String,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Returned by `get_usage_summary`
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct UsageSummary {
+    pub range: UsageRange,
+    pub since: String,
+    pub until: String,
+    /// Days with usage, oldest first
+    pub days: Vec<DailyUsage>,
+    pub total_tokens: u64,
+    /// Estimate; excludes `unpriced_models`
+    pub cost_usd: f64,
+    /// Models without a known price, counted in tokens only
+    pub unpriced_models: Vec<String>,
+}
+
+/// Estimated USD for a model's tokens; None for unknown models
+pub fn estimate_cost(model: &str, prompt_tokens: u64, completion_tokens: u64) -> Option<f64> {
+    let (_, input, output) = PRICES.iter().find(|(prefix, _, _)| model.starts_with(prefix))?;
+    Some((prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0)
+}
+
+pub fn summarize(
+    range: UsageRange,
+    since: NaiveDate,
+    until: NaiveDate,
+    records: &[UsageRecord],
+) -> UsageSummary {
+    let mut days: BTreeMap<&str, DailyUsage> = BTreeMap::new();
+    let mut unpriced = BTreeSet::new();
+    for record in records {
+        let tokens = record.prompt_tokens + record.completion_tokens;
+        let cost = record
+            .cost_usd
+            .or_else(|| estimate_cost(&record.model, record.prompt_tokens, record.completion_tokens));
+        if cost.is_none() && tokens > 0 {
+            unpriced.insert(record.model.clone());
+        }
+        let day = days.entry(record.date.as_str()).or_insert_with(|| DailyUsage {
+            date: record.date.clone(),
+            tokens: 0,
+            cost_usd: 0.0,
+        });
+        day.tokens += tokens;
+        day.cost_usd += cost.unwrap_or_default();
+    }
+    let days: Vec<DailyUsage> = days.into_values().collect();
+    UsageSummary {
+        range,
+        since: since.to_string(),
+        until: until.to_string(),
+        total_tokens: days.iter().map(|day| day.tokens).sum(),
+        cost_usd: days.iter().map(|day| day.cost_usd).sum(),
+        days,
+        unpriced_models: unpriced.into_iter().collect(),
+    }
+}
+
+/// Usage for `range` ending today. Returns `None` if this service version
+/// doesn't track usage.
+pub async fn fetch(
+    api: &ApiClient,
+    range: UsageRange,
+    options: RequestOptions<'_>,
+) -> Result<Option<UsageSummary>, ApiError> {
+    let (since, until) = range.dates(chrono::Local::now().date_naive());
+    let path = format!("{}?since={}&until={}", USAGE_PATH, since, until);
+    match api.get_json_cached::<UsageReport>(&path, CACHE_MAX_AGE, options).await {
+        Ok(report) => Ok(Some(summarize(range, since, until, &report.records))),
+        Err(e) if e.is_not_found() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// "~$1.23"; "<$0.01" for anything smaller
+pub fn format_cost(usd: f64) -> String {
+    if usd > 0.0 && usd < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("~${:.2}", usd)
+    }
+}
+
+/// Budget thresholds that fired in `month` (YYYY-MM), persisted in ALERTS_FILE
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BudgetAlerts {
+    pub month: String,
+    pub fired: Vec<u8>,
+}
+
+impl BudgetAlerts {
+    /// The saved state; empty if there is none or it can't be parsed
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Thresholds `spend` newly crosses this month, recorded as fired
+    pub fn crossed(&mut self, month: &str, spend: f64, budget: f64) -> Vec<u8> {
+        if self.month != month {
+            self.month = month.to_string();
+            self.fired.clear();
+        }
+        if budget <= 0.0 {
+            return Vec::new();
+        }
+        let percent = spend / budget * 100.0;
+        let crossed: Vec<u8> = BUDGET_THRESHOLDS
+            .iter()
+            .copied()
+            .filter(|threshold| percent >= f64::from(*threshold) && !self.fired.contains(threshold))
+            .collect();
+        self.fired.extend(&crossed);
+        crossed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn record(date: &str, model: &str, prompt_tokens: u64, completion_tokens: u64) -> UsageRecord {
+        UsageRecord {
+            date: date.to_string(),
+            model: model.to_string(),
+            prompt_tokens,
+            completion_tokens,
+            cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn test_range_dates() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        assert_eq!(UsageRange::Today.dates(today), (today, today));
+        assert_eq!(UsageRange::Week.dates(today), (day(10), today));
+        assert_eq!(UsageRange::Month.dates(today), (day(1), today));
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        assert_eq!(estimate_cost("text-embedding-3-small", 1_000_000, 0), Some(0.02));
+        // Dated snapshot of the mini model, not priced as gpt-4o
+        assert_eq!(estimate_cost("gpt-4o-mini-2024-07-18", 1_000_000, 1_000_000), Some(0.75));
+        assert_eq!(estimate_cost("llama3", 1000, 1000), None);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let since = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let mut priced = record("2026-10-02", "custom", 10, 0);
+        priced.cost_usd = Some(0.5);
+        let records = vec![
+            record("2026-10-03", "gpt-4o-mini", 2_000_000, 0),
+            record("2026-10-02", "text-embedding-3-small", 1_000_000, 0),
+            record("2026-10-03", "llama3", 500, 500),
+            priced,
+        ];
+        let summary = summarize(UsageRange::Month, since, until, &records);
+        assert_eq!(summary.since, "2026-10-01");
+        assert_eq!(
+            summary.days.iter().map(|d| (d.date.as_str(), d.tokens)).collect::<Vec<_>>(),
+            vec![("2026-10-02", 1_000_010), ("2026-10-03", 2_001_000)]
+        );
+        assert!((summary.cost_usd - 0.82).abs() < 1e-9);
+        assert_eq!(summary.total_tokens, 3_001_010);
+        assert_eq!(summary.unpriced_models, vec!["llama3".to_string()]);
+    }
+
+    #[test]
+    fn test_format_cost() {
+        assert_eq!(format_cost(1.234), "~$1.23");
+        assert_eq!(format_cost(0.0), "~$0.00");
+        assert_eq!(format_cost(0.004), "<$0.01");
+    }
+
+    #[test]
+    fn test_budget_thresholds_fire_once_per_month() {
+        let mut alerts = BudgetAlerts::default();
+        assert!(alerts.crossed("2026-10", 7.0, 10.0).is_empty());
+        assert_eq!(alerts.crossed("2026-10", 8.5, 10.0), vec![80]);
+        assert!(alerts.crossed("2026-10", 9.0, 10.0).is_empty());
+        assert_eq!(alerts.crossed("2026-10", 10.0, 10.0), vec![100]);
+        assert!(alerts.crossed("2026-10", 12.0, 10.0).is_empty());
+        // A new month starts over, and a jump past both reports both
+        assert_eq!(alerts.crossed("2026-11", 11.0, 10.0), vec![80, 100]);
+
+        let dir = TempDir::new("usage");
+        let path = dir.join(ALERTS_FILE);
+        alerts.save(&path).unwrap();
+        assert_eq!(BudgetAlerts::load(&path), alerts);
+    }
+}
@@ -0,0 +1,180 @@
+// Kiroku Memory Desktop - Window Event Log Filter
+// Decides which window events reach app.log, so dragging a window doesn't flood it
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Moved/Resized are summarized at most this often per window
+pub const GEOMETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The parts of a window event the filter cares about
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowEventKind {
+    /// Lifecycle events (CloseRequested, Destroyed, ThemeChanged): always logged
+    Always,
+    Focused(bool),
+    Moved(i32, i32),
+    Resized(u32, u32),
+    /// Drag-and-drop and the like: only logged as a firehose
+    Noise,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Log the event as it is
+    Log,
+    /// Log this line instead of the event
+    Summary(String),
+    /// Call `flush` after this long to log the final geometry
+    FlushAfter(Duration),
+    Drop,
+}
+
+#[derive(Default)]
+struct Geometry {
+    position: Option<(i32, i32)>,
+    size: Option<(u32, u32)>,
+    /// Events since the last summary
+    pending: u32,
+    last_logged: Option<Instant>,
+    flush_scheduled: bool,
+}
+
+#[derive(Default)]
+struct WindowState {
+    focused: Option<bool>,
+    geometry: Geometry,
+}
+
+#[derive(Default)]
+pub struct WindowEventFilter {
+    /// Log every event unfiltered (the `trace` log level)
+    firehose: bool,
+    windows: HashMap<String, WindowState>,
+}
+
+impl WindowEventFilter {
+    pub fn set_firehose(&mut self, firehose: bool) {
+        self.firehose = firehose;
+    }
+
+    pub fn record(&mut self, label: &str, kind: WindowEventKind, now: Instant) -> Verdict {
+        if self.firehose {
+            return Verdict::Log;
+        }
+        let state = self.windows.entry(label.to_string()).or_default();
+        match kind {
+            WindowEventKind::Always => Verdict::Log,
+            WindowEventKind::Focused(focused) => {
+                if state.focused.replace(focused) == Some(focused) {
+                    Verdict::Drop
+                } else {
+                    Verdict::Log
+                }
+            }
+            WindowEventKind::Moved(x, y) => {
+                state.geometry.position = Some((x, y));
+                record_geometry(label, &mut state.geometry, now)
+            }
+            WindowEventKind::Resized(width, height) => {
+                state.geometry.size = Some((width, height));
+                record_geometry(label, &mut state.geometry, now)
+            }
+            WindowEventKind::Noise => Verdict::Drop,
+        }
+    }
+
+    /// The summary held back since the last one, once a `FlushAfter` delay is up
+    pub fn flush(&mut self, label: &str, now: Instant) -> Option<String> {
+        let geometry = &mut self.windows.get_mut(label)?.geometry;
+        geometry.flush_scheduled = false;
+        (geometry.pending > 0).then(|| summarize(label, geometry, now))
+    }
+}
+
+fn record_geometry(label: &str, geometry: &mut Geometry, now: Instant) -> Verdict {
+    geometry.pending += 1;
+    match geometry.last_logged {
+        Some(last) if now.duration_since(last) < GEOMETRY_INTERVAL => {
+            if geometry.flush_scheduled {
+                Verdict::Drop
+            } else {
+                geometry.flush_scheduled = true;
+                Verdict::FlushAfter(GEOMETRY_INTERVAL - now.duration_since(last))
+            }
+        }
+        _ => Verdict::Summary(summarize(label, geometry, now)),
+    }
+}
+
+fn summarize(label: &str, geometry: &mut Geometry, now: Instant) -> String {
+    let position = geometry
+        .position
+        .map_or("?".to_string(), |(x, y)| format!("{},{}", x, y));
+    let size = geometry
+        .size
+        .map_or("?".to_string(), |(w, h)| format!("{}x{}", w, h));
+    let line = format!(
+        "window geometry {} position={} size={} ({} events)",
+        label, position, size, geometry.pending
+    );
+    geometry.pending = 0;
+    geometry.last_logged = Some(now);
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WindowEventKind::{Always, Focused, Moved, Noise, Resized};
+
+    #[test]
+    fn test_focus_logged_on_change_only() {
+        let mut filter = WindowEventFilter::default();
+        let now = Instant::now();
+        assert_eq!(filter.record("main", Focused(true), now), Verdict::Log);
+        assert_eq!(filter.record("main", Focused(true), now), Verdict::Drop);
+        assert_eq!(filter.record("settings", Focused(true), now), Verdict::Log);
+        assert_eq!(filter.record("main", Focused(false), now), Verdict::Log);
+        assert_eq!(filter.record("main", Always, now), Verdict::Log);
+        assert_eq!(filter.record("main", Noise, now), Verdict::Drop);
+    }
+
+    #[test]
+    fn test_geometry_rate_limited_with_final_summary() {
+        let mut filter = WindowEventFilter::default();
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        assert_eq!(
+            filter.record("main", Moved(10, 20), at(0)),
+            Verdict::Summary("window geometry main position=10,20 size=? (1 events)".into())
+        );
+        assert_eq!(
+            filter.record("main", Moved(11, 21), at(100)),
+            Verdict::FlushAfter(Duration::from_millis(900))
+        );
+        assert_eq!(filter.record("main", Resized(800, 600), at(200)), Verdict::Drop);
+        assert_eq!(filter.record("main", Moved(15, 25), at(300)), Verdict::Drop);
+        assert_eq!(
+            filter.flush("main", at(1000)).as_deref(),
+            Some("window geometry main position=15,25 size=800x600 (3 events)")
+        );
+        // Nothing new since the summary
+        assert_eq!(filter.flush("main", at(2000)), None);
+        assert!(matches!(
+            filter.record("main", Moved(0, 0), at(2500)),
+            Verdict::Summary(_)
+        ));
+    }
+
+    #[test]
+    fn test_firehose_logs_everything() {
+        let mut filter = WindowEventFilter::default();
+        filter.set_firehose(true);
+        let now = Instant::now();
+        for kind in [Noise, Moved(1, 1), Moved(2, 2), Focused(true), Focused(true)] {
+            assert_eq!(filter.record("main", kind, now), Verdict::Log);
+        }
+    }
+}
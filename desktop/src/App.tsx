@@ -6,13 +6,17 @@ import { StatusPage } from "./pages/Status";
 import { MemoriesPage } from "./pages/Memories";
 import { SettingsPage } from "./pages/Settings";
 import { MaintenancePage } from "./pages/Maintenance";
+import { LegacyDataPrompt } from "./components/LegacyDataPrompt";
+import { QuitBlockedPrompt } from "./components/QuitBlockedPrompt";
 import {
   getServiceStatus,
   checkHealth,
   getStats,
+  getServiceInfo,
   type ServiceStatus,
   type HealthResponse,
   isServiceRunning,
+  isServiceDegraded,
   isServiceError,
 } from "./lib/api";
 
@@ -27,6 +31,10 @@ interface Stats {
     archived: number;
   };
   categories: number;
+  // The last fetch only succeeded after retrying
+  retried?: boolean;
+  // The service sent stats in a layout this app doesn't know
+  schemaWarning?: boolean;
 }
 
 function App() {
@@ -43,7 +51,7 @@ function App() {
     if (tauriStatus === "Running") {
       setStatus("healthy");
       setError(null);
-    } else if (tauriStatus === "Starting") {
+    } else if (tauriStatus === "Starting" || tauriStatus === "Idle") {
       setStatus("loading");
       setError(null);
     } else if (tauriStatus === "Restarting") {
@@ -55,6 +63,10 @@ function App() {
     } else if (isServiceError(tauriStatus)) {
       setStatus("error");
       setError(tauriStatus.Error);
+    } else if (isServiceDegraded(tauriStatus)) {
+      // Still serving; keep the UI usable but show the problem
+      setStatus("healthy");
+      setError(tauriStatus.Degraded);
     }
   }, []);
 
@@ -72,12 +84,14 @@ function App() {
   // Fetch stats from API
   const fetchStats = useCallback(async () => {
     try {
-      const data = await getStats();
+      const [data, info] = await Promise.all([getStats(), getServiceInfo().catch(() => null)]);
       // API response matches UI format directly
       setStats({
         backend: data.backend || "SurrealDB",
         items: data.items || { total: 0, active: 0, archived: 0 },
         categories: data.categories || 0,
+        retried: data.meta.retried,
+        schemaWarning: info?.stats_schema_warning,
       });
     } catch {
       // Ignore stats errors
@@ -137,6 +151,11 @@ function App() {
       setError(null);
     }).then((unlisten) => unlisteners.push(unlisten));
 
+    // Live updates from the service: a memory was added, changed or removed
+    listen("memory-changed", () => {
+      fetchStats();
+    }).then((unlisten) => unlisteners.push(unlisten));
+
     // Tray actions
     listen<void>("tray:open_settings", () => {
       setCurrentPath("/settings");
@@ -199,6 +218,8 @@ function App() {
       onNavigate={setCurrentPath}
       serviceStatus={status === "restarting" ? "loading" : status}
     >
+      <QuitBlockedPrompt />
+      <LegacyDataPrompt />
       {renderPage()}
     </Layout>
   );
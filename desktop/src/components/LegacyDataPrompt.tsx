@@ -0,0 +1,66 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useTranslation } from "react-i18next";
+import { Button } from "./ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "./ui/card";
+import { getLegacyData, migrateLegacyData, type LegacyData } from "../lib/api";
+
+// Asks whether to copy an earlier build's memories. The first service start
+// waits for the answer, so the prompt stays until one is given.
+export function LegacyDataPrompt() {
+  const { t } = useTranslation();
+  const [data, setData] = useState<LegacyData | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    // The event may have fired before this listener was registered
+    getLegacyData().then(setData).catch(() => {});
+    const unlisten = listen<LegacyData>("legacy-data-found", (event) => {
+      setData(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (!data) return null;
+
+  const answer = async (confirm: boolean) => {
+    setBusy(true);
+    setError(null);
+    try {
+      await migrateLegacyData(confirm);
+      setData(null);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Card className="mb-6 border-primary">
+      <CardHeader className="pb-3">
+        <CardTitle>{t("legacy.title")}</CardTitle>
+        <CardDescription>
+          {t("legacy.description", {
+            path: data.path,
+            size: (data.size_bytes / (1024 * 1024)).toFixed(1),
+          })}
+        </CardDescription>
+      </CardHeader>
+      <CardContent>
+        {error && <p className="mb-3 text-sm text-destructive">{error}</p>}
+        <div className="flex gap-2">
+          <Button disabled={busy} onClick={() => answer(true)}>
+            {t("legacy.copy")}
+          </Button>
+          <Button variant="outline" disabled={busy} onClick={() => answer(false)}>
+            {t("legacy.skip")}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,58 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useTranslation } from "react-i18next";
+import { Button } from "./ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "./ui/card";
+import { confirmQuit } from "../lib/api";
+
+// Explains why Quit did nothing while a backup or restore runs, and lets the
+// user quit anyway.
+export function QuitBlockedPrompt() {
+  const { t } = useTranslation();
+  const [reason, setReason] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<string>("quit-blocked", (event) => {
+      setError(null);
+      setReason(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (!reason) return null;
+
+  const quit = async () => {
+    setBusy(true);
+    setError(null);
+    try {
+      await confirmQuit();
+    } catch (e) {
+      setError(String(e));
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Card className="mb-6 border-destructive">
+      <CardHeader className="pb-3">
+        <CardTitle>{t("quitBlocked.title")}</CardTitle>
+        <CardDescription>{t("quitBlocked.description", { reason })}</CardDescription>
+      </CardHeader>
+      <CardContent>
+        {error && <p className="mb-3 text-sm text-destructive">{error}</p>}
+        <div className="flex gap-2">
+          <Button variant="destructive" disabled={busy} onClick={quit}>
+            {t("quitBlocked.confirm")}
+          </Button>
+          <Button variant="outline" disabled={busy} onClick={() => setReason(null)}>
+            {t("quitBlocked.dismiss")}
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -1,6 +1,7 @@
 // Kiroku Memory Desktop - Tauri API Types and Functions
 
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 // ============================================================================
 // Types
@@ -11,7 +12,10 @@ export type ServiceStatus =
   | "Running"
   | "Stopped"
   | "Restarting"
-  | { Error: string };
+  | "Idle"
+  | { Error: string }
+  // Serving, but needs attention (e.g. disk almost full)
+  | { Degraded: string };
 
 export interface HealthResponse {
   status: string;
@@ -28,11 +32,66 @@ export interface StatsResponse {
   categories: number;
 }
 
+// How a proxied response was obtained; `retried` drives the "(retried)" hint
+export interface ResponseMeta {
+  attempts: number;
+  retried: boolean;
+}
+
+export interface WithMeta<T> {
+  data: T;
+  meta: ResponseMeta;
+}
+
+export interface CategoryCount {
+  category: string;
+  count: number;
+}
+
+export interface StatsBreakdown {
+  total: number;
+  categories: CategoryCount[];
+}
+
+export type ServiceStartMode = "OnAppLaunch" | "OnFirstUse" | "Manual";
+
+// First-run wizard progress. Settings saved before the wizard existed load
+// as completed, so upgrades skip it.
+export interface OnboardingState {
+  completed: boolean;
+  completed_version: string;
+  steps_done: string[];
+}
+
 export interface AppSettings {
-  auto_start_service: boolean;
+  service_start_mode: ServiceStartMode;
   service_port: number;
   start_hidden: boolean;
   launch_at_login: boolean;
+  // Keep the main window above other apps
+  always_on_top: boolean;
+  // Empty for the service default; changing it restarts the service
+  embedding_model: string | null;
+  // Empty for the service default; applies after a service restart
+  chat_model: string | null;
+  // Changed only through lockApp / unlockApp
+  locked: boolean;
+  legacy_migration: "Pending" | "Completed" | "Declined";
+  // Changed only through the onboarding commands
+  onboarding: OnboardingState;
+}
+
+// Memory store left by an earlier build under a different app identifier
+export interface LegacyData {
+  identifier: string;
+  path: string;
+  size_bytes: number;
+}
+
+// macOS: the app was opened from its DMG or a translocated copy
+export interface RunningFromDmg {
+  kind: "DiskImage" | "Translocated";
+  bundle_path: string | null;
 }
 
 // Memory Types
@@ -73,6 +132,17 @@ export interface RetrievalResponse {
 // Service Commands
 // ============================================================================
 
+// Most recent service error, kept after the service recovers (times in Unix seconds)
+export interface LastError {
+  message: string;
+  at: number;
+  recovered_at: number | null;
+}
+
+export async function getLastError(): Promise<LastError | null> {
+  return invoke<LastError | null>("get_last_error");
+}
+
 export async function getServiceStatus(): Promise<ServiceStatus> {
   return invoke<ServiceStatus>("get_service_status");
 }
@@ -82,15 +152,83 @@ export async function checkHealth(): Promise<HealthResponse> {
   return JSON.parse(json);
 }
 
-export async function getStats(): Promise<StatsResponse> {
-  const json = await invoke<string>("get_stats");
-  return JSON.parse(json);
+// Subset of the service snapshot the dashboard reads
+export interface ServiceInfo {
+  base_url: string;
+  unix_socket: string | null;
+  // Stats arrived in an unknown layout; the service is likely newer than the app
+  stats_schema_warning: boolean;
+  last_health_check: HealthSample | null;
+  // Unix seconds the current process was spawned or adopted
+  started_at: number | null;
+  // Service processes spawned after the first one this session
+  restart_count: number;
+  last_restart_at: number | null;
+}
+
+export async function getServiceInfo(): Promise<ServiceInfo> {
+  return invoke<ServiceInfo>("get_service_info");
+}
+
+export interface ProcessMetrics {
+  // Unix timestamp (seconds) of the reading
+  timestamp: number;
+  pid: number;
+  // Percent of one core; can exceed 100
+  cpu_percent: number;
+  rss_bytes: number;
+}
+
+// Latest CPU / memory reading of the service process; null while it isn't running
+export async function getProcessMetrics(): Promise<ProcessMetrics | null> {
+  return invoke<ProcessMetrics | null>("get_process_metrics");
+}
+
+// Stopped / Paused samples were taken while the service was off on purpose
+export type HealthState = "Ok" | "Failed" | "Stopped" | "Paused";
+
+export interface HealthSample {
+  // Unix timestamp (seconds)
+  timestamp: number;
+  state: HealthState;
+  // Round trip of a passed check
+  latency_ms: number | null;
+}
+
+// Health samples from the last `minutes`, oldest first
+export async function getHealthHistory(minutes: number): Promise<HealthSample[]> {
+  return invoke<HealthSample[]>("get_health_history", { minutes });
+}
+
+export async function getStats(): Promise<StatsResponse & { meta: ResponseMeta }> {
+  const { data, meta } = await invoke<WithMeta<StatsResponse>>("get_stats");
+  return { ...data, meta };
+}
+
+// Null when the service is too old to report per-category counts
+export async function getStatsBreakdown(): Promise<StatsBreakdown | null> {
+  return invoke<StatsBreakdown | null>("get_stats_breakdown");
+}
+
+export interface EventSchema {
+  names: string[];
+  // `AppEvents` interface mapping each event name to its payload type
+  typescript: string;
+}
+
+export async function getEventSchema(): Promise<EventSchema> {
+  return invoke<EventSchema>("get_event_schema");
 }
 
 export async function restartService(): Promise<void> {
   return invoke<void>("restart_service");
 }
 
+// Starts a stopped, failed or idle service; resolves with the status once healthy
+export async function startService(): Promise<ServiceStatus> {
+  return invoke<ServiceStatus>("start_service");
+}
+
 export async function stopService(): Promise<void> {
   return invoke<void>("stop_service");
 }
@@ -107,6 +245,19 @@ export async function hasOpenAIKey(): Promise<boolean> {
   return invoke<boolean>("has_openai_key");
 }
 
+// Why the OpenAI key can't be read; hasOpenAIKey is false for all but Present.
+// Prefer this over calling both, since each Keychain read may prompt.
+export type KeychainStatus =
+  | "Present"
+  | "Absent"
+  | "Locked"
+  | "AccessDenied"
+  | { Error: string };
+
+export async function getKeyStatus(): Promise<KeychainStatus> {
+  return invoke<KeychainStatus>("get_key_status");
+}
+
 export async function deleteOpenAIKey(): Promise<void> {
   return invoke<void>("delete_openai_key");
 }
@@ -119,15 +270,292 @@ export async function saveSettings(newSettings: AppSettings): Promise<void> {
   return invoke<void>("save_settings", { newSettings });
 }
 
+// Show the setup wizard while `completed` is false
+export async function getOnboardingState(): Promise<OnboardingState> {
+  return invoke<OnboardingState>("get_onboarding_state");
+}
+
+// step: e.g. "api_key", "launch_at_login", "data_location"
+export async function markOnboardingStep(step: string): Promise<OnboardingState> {
+  return invoke<OnboardingState>("mark_onboarding_step", { step });
+}
+
+// Also starts the service unless it is started manually
+export async function completeOnboarding(): Promise<OnboardingState> {
+  return invoke<OnboardingState>("complete_onboarding");
+}
+
+export async function setAlwaysOnTop(enabled: boolean): Promise<void> {
+  return invoke<void>("set_always_on_top", { enabled });
+}
+
+// Locked mode: settings changes and destructive commands fail until unlocked
+export async function lockApp(pin: string): Promise<void> {
+  return invoke<void>("lock", { pin });
+}
+
+export async function unlockApp(pin: string): Promise<void> {
+  return invoke<void>("unlock", { pin });
+}
+
+// Earlier-build data: offered once, then migrated or declined. The service
+// start waits for the answer; the legacy copy is never deleted.
+export async function getLegacyData(): Promise<LegacyData | null> {
+  return invoke<LegacyData | null>("get_legacy_data");
+}
+
+export async function migrateLegacyData(confirm: boolean): Promise<void> {
+  return invoke<void>("migrate_legacy_data", { confirm });
+}
+
+// Settings export for moving to another machine; never contains secrets
+export interface ImportReport {
+  changed: string[];
+  restart_required: boolean;
+  // Paths or profiles that don't exist on this machine and were skipped
+  warnings: string[];
+}
+
+export async function exportSettings(path: string): Promise<void> {
+  return invoke<void>("export_settings", { path });
+}
+
+// merge: keep settings the export doesn't mention instead of resetting them
+export async function importSettings(path: string, merge: boolean): Promise<ImportReport> {
+  return invoke<ImportReport>("import_settings", { path, merge });
+}
+
+// Launch at login stays off until the app runs from /Applications
+export async function getInstallLocation(): Promise<RunningFromDmg | null> {
+  return invoke<RunningFromDmg | null>("get_install_location");
+}
+
+// Copies the app to /Applications, then quits and opens the copy
+export async function moveToApplications(): Promise<void> {
+  return invoke<void>("move_to_applications");
+}
+
 export async function getDataDir(): Promise<string> {
   return invoke<string>("get_data_dir");
 }
 
+// A data, settings or backup folder other accounts on this machine can read
+export interface PermissionIssue {
+  path: string;
+  // Octal, e.g. "0755"
+  mode: string;
+}
+
+export async function getPermissionIssues(): Promise<PermissionIssue[]> {
+  return invoke<PermissionIssue[]>("get_permission_issues");
+}
+
+// Restricts the folders to the current user; returns the ones that were open
+export async function fixPermissions(): Promise<PermissionIssue[]> {
+  return invoke<PermissionIssue[]>("fix_permissions");
+}
+
+// What the service will be launched with under the current settings
+export interface RuntimeInfo {
+  python_bin: string;
+  pythonpath: string;
+  runtime: "bundled" | "development" | "system";
+  host: string;
+  port: number;
+  base_url: string;
+  remote: boolean;
+  data_dir: string;
+  backend: string;
+  surreal_url: string | null;
+  openai_key_configured: boolean;
+  warnings: string[];
+}
+
+export async function getRuntimeInfo(): Promise<RuntimeInfo> {
+  return invoke<RuntimeInfo>("get_runtime_info");
+}
+
+export type UsageRange = "today" | "week" | "month";
+
+export interface DailyUsage {
+  date: string;
+  tokens: number;
+  cost_usd: number;
+}
+
+// OpenAI token usage with estimated cost; cost excludes unpriced models
+export interface UsageSummary {
+  range: UsageRange;
+  since: string;
+  until: string;
+  days: DailyUsage[];
+  total_tokens: number;
+  cost_usd: number;
+  unpriced_models: string[];
+}
+
+// Null when the service doesn't track usage; hide the feature then
+export async function getUsageSummary(range: UsageRange): Promise<UsageSummary | null> {
+  return invoke<UsageSummary | null>("get_usage_summary", { range });
+}
+
+// Build metadata plus the app and service versions
+export interface AppInfo {
+  app_version: string;
+  git_commit: string;
+  built_at: string | null;
+  target: string;
+  tauri_version: string;
+  python_runtime: "bundled" | "development" | "system" | null;
+  service_version: string | null;
+}
+
+export async function getAppInfo(): Promise<AppInfo> {
+  return invoke<AppInfo>("get_app_info");
+}
+
+// Load on the desktop app's connection to the service, for debugging
+export interface ApiClientStats {
+  in_flight: number;
+  queued: number;
+  max_in_flight: number;
+  timeout_secs: number;
+}
+
+export async function getApiClientStats(): Promise<ApiClientStats> {
+  return invoke<ApiClientStats>("get_api_client_stats");
+}
+
+// Free space on the data directory's volume; the service won't start when `low`
+export interface DiskSpace {
+  path: string;
+  available_bytes: number;
+  human: string;
+  min_free_bytes: number;
+  low: boolean;
+}
+
+export async function getDiskSpace(): Promise<DiskSpace | null> {
+  return invoke<DiskSpace | null>("get_disk_space");
+}
+
+// Bridge from the service's change stream to `memory-changed` events
+export interface LiveUpdatesStatus {
+  state: "disabled" | "unsupported" | "disconnected" | "connected";
+  // Unix timestamp (seconds)
+  last_event_at: number | null;
+  last_error: string | null;
+}
+
+export async function getLiveUpdatesStatus(): Promise<LiveUpdatesStatus> {
+  return invoke<LiveUpdatesStatus>("get_live_updates_status");
+}
+
+// Backup, restore or other operation holding the maintenance lock
+export interface MaintenanceInfo {
+  operation: string;
+  // Unix timestamp (seconds)
+  started_at: number;
+}
+
+export async function getCurrentMaintenance(): Promise<MaintenanceInfo | null> {
+  return invoke<MaintenanceInfo | null>("get_current_maintenance");
+}
+
+// Quits even though a backup or restore is running (after "quit-blocked")
+export async function confirmQuit(): Promise<void> {
+  return invoke("confirm_quit");
+}
+
+// Deletes memories older than `olderThanDays`; with `dryRun` only counts them
+export async function pruneMemories(olderThanDays: number, dryRun: boolean): Promise<number> {
+  return invoke<number>("prune_memories", { olderThanDays, dryRun });
+}
+
+export interface BackupInfo {
+  path: string;
+  created_at: string;
+  size_bytes: number;
+  // Restoring asks for the passphrase it was made with
+  encrypted: boolean;
+}
+
+// With a passphrase the backup is encrypted; it is stored in the keychain only
+// when rememberPassphrase is set
+export async function createBackup(passphrase?: string, rememberPassphrase = false): Promise<BackupInfo> {
+  return invoke<BackupInfo>("create_backup", { passphrase: passphrase ?? null, rememberPassphrase });
+}
+
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke<BackupInfo[]>("list_backups");
+}
+
+export async function restoreBackup(backupDir: string, passphrase?: string): Promise<void> {
+  return invoke<void>("restore_backup", { backupDir, passphrase: passphrase ?? null });
+}
+
+export async function forgetBackupPassphrase(): Promise<void> {
+  return invoke<void>("forget_backup_passphrase");
+}
+
 // ============================================================================
-// Memory API (Direct HTTP to Python FastAPI)
+// Memory API (Direct HTTP to Python FastAPI, or proxied over a Unix socket)
 // ============================================================================
 
-const API_BASE = "http://127.0.0.1:8000";
+interface Connection {
+  baseUrl: string;
+  // The service listens on a Unix socket; go through the app's proxy
+  proxied: boolean;
+}
+
+let connection: Promise<Connection> | null = null;
+
+// How to reach the service, following port and transport changes made in settings
+async function serviceConnection(): Promise<Connection> {
+  connection ??= getServiceInfo()
+    .then((info) => ({ baseUrl: info.base_url, proxied: info.unix_socket !== null }))
+    .catch(() => {
+      connection = null;
+      return { baseUrl: "http://127.0.0.1:8000", proxied: false };
+    });
+  return connection;
+}
+
+listen<string>("service-base-url-changed", () => {
+  connection = null;
+});
+
+let authToken: Promise<string | null> | null = null;
+
+// Bearer token the service expects when require_auth is on; cached per page
+async function authHeaders(): Promise<Record<string, string>> {
+  authToken ??= invoke<string | null>("get_service_auth_token").catch(() => null);
+  const token = await authToken;
+  return token ? { Authorization: `Bearer ${token}` } : {};
+}
+
+// GET (or POST `body`) a service path and parse the JSON reply
+async function serviceJson<T>(path: string, action: string, body?: unknown): Promise<T> {
+  const { baseUrl, proxied } = await serviceConnection();
+  const method = body === undefined ? "GET" : "POST";
+  if (proxied) {
+    return invoke<T>("proxy_request", { method, path, body: body ?? null });
+  }
+  const res = await fetch(`${baseUrl}${path}`, {
+    method,
+    headers:
+      body === undefined
+        ? await authHeaders()
+        : { "Content-Type": "application/json", ...(await authHeaders()) },
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  if (!res.ok) throw new Error(`Failed to ${action}: ${res.status}`);
+  return res.json();
+}
+
+function withQuery(path: string, params: URLSearchParams): string {
+  return params.toString() ? `${path}?${params}` : path;
+}
 
 export async function getResources(options?: {
   limit?: number;
@@ -139,10 +567,7 @@ export async function getResources(options?: {
   if (options?.offset) params.set("offset", String(options.offset));
   if (options?.source) params.set("source", options.source);
 
-  const url = `${API_BASE}/v2/resources${params.toString() ? `?${params}` : ""}`;
-  const res = await fetch(url);
-  if (!res.ok) throw new Error(`Failed to fetch resources: ${res.status}`);
-  return res.json();
+  return serviceJson<Resource[]>(withQuery("/v2/resources", params), "fetch resources");
 }
 
 export async function getItems(options?: {
@@ -157,33 +582,20 @@ export async function getItems(options?: {
   if (options?.category) params.set("category", options.category);
   if (options?.status) params.set("status", options.status);
 
-  const url = `${API_BASE}/v2/items${params.toString() ? `?${params}` : ""}`;
-  const res = await fetch(url);
-  if (!res.ok) throw new Error(`Failed to fetch items: ${res.status}`);
-  return res.json();
+  return serviceJson<Item[]>(withQuery("/v2/items", params), "fetch items");
 }
 
 export async function getCategories(): Promise<Category[]> {
-  const res = await fetch(`${API_BASE}/v2/categories`);
-  if (!res.ok) throw new Error(`Failed to fetch categories: ${res.status}`);
-  return res.json();
+  return serviceJson<Category[]>("/v2/categories", "fetch categories");
 }
 
 export async function searchMemories(query: string): Promise<RetrievalResponse> {
   const params = new URLSearchParams({ query });
-  const res = await fetch(`${API_BASE}/retrieve?${params}`);
-  if (!res.ok) throw new Error(`Failed to search: ${res.status}`);
-  return res.json();
+  return serviceJson<RetrievalResponse>(withQuery("/retrieve", params), "search");
 }
 
 export async function ingestMemory(content: string, source: string): Promise<{ resource_id: string }> {
-  const res = await fetch(`${API_BASE}/v2/ingest`, {
-    method: "POST",
-    headers: { "Content-Type": "application/json" },
-    body: JSON.stringify({ content, source }),
-  });
-  if (!res.ok) throw new Error(`Failed to ingest: ${res.status}`);
-  return res.json();
+  return serviceJson<{ resource_id: string }>("/v2/ingest", "ingest", { content, source });
 }
 
 // ============================================================================
@@ -191,7 +603,13 @@ export async function ingestMemory(content: string, source: string): Promise<{ r
 // ============================================================================
 
 export function isServiceRunning(status: ServiceStatus): boolean {
-  return status === "Running";
+  return status === "Running" || isServiceDegraded(status);
+}
+
+export function isServiceDegraded(
+  status: ServiceStatus
+): status is { Degraded: string } {
+  return typeof status === "object" && "Degraded" in status;
 }
 
 export function isServiceError(
@@ -204,5 +622,8 @@ export function getServiceStatusText(status: ServiceStatus): string {
   if (typeof status === "string") {
     return status;
   }
+  if (isServiceDegraded(status)) {
+    return `Running (${status.Degraded})`;
+  }
   return `Error: ${status.Error}`;
 }
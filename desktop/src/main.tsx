@@ -1,11 +1,21 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import { SettingsPage } from "./pages/Settings";
 import "./i18n";
 import "./styles/globals.css";
 
+// The tray's "Settings…" item opens a separate window at /settings
+const isSettingsWindow = window.location.pathname === "/settings";
+
 ReactDOM.createRoot(document.getElementById("root")!).render(
   <React.StrictMode>
-    <App />
+    {isSettingsWindow ? (
+      <div className="p-6">
+        <SettingsPage />
+      </div>
+    ) : (
+      <App />
+    )}
   </React.StrictMode>
 );
@@ -13,7 +13,7 @@ import {
   Check,
   AlertTriangle,
 } from "lucide-react";
-import { restartService, stopService, getDataDir, getServiceStatus, isServiceRunning } from "../lib/api";
+import { restartService, startService, stopService, getDataDir, getServiceStatus, isServiceRunning } from "../lib/api";
 
 interface MaintenancePageProps {
   onRefresh: () => void;
@@ -75,7 +75,7 @@ export function MaintenancePage({ onRefresh }: MaintenancePageProps) {
     setIsStopping(true); // reuse the same loading state
     setMessage(null);
     try {
-      await restartService(); // restart also starts a stopped service
+      await startService();
       setMessage({ type: "success", text: t("maintenance.messages.startSuccess") });
       onRefresh();
     } catch (error) {
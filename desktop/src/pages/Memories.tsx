@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { useTranslation } from "react-i18next";
+import { listen } from "@tauri-apps/api/event";
 import { Card, CardContent, CardHeader, CardTitle } from "../components/ui/card";
 import { Button } from "../components/ui/button";
 import { Input } from "../components/ui/input";
@@ -58,6 +59,18 @@ export function MemoriesPage() {
     loadData();
   }, [loadData]);
 
+  // Live updates (other clients, folder watcher): refresh the list quietly
+  useEffect(() => {
+    const unlisten = listen("memory-changed", () => {
+      getItems({ limit: 100, status: "active" })
+        .then(setItems)
+        .catch(() => {});
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
   const handleSearch = async () => {
     if (!searchQuery.trim()) {
       setSearchResults(null);
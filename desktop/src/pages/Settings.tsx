@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { useTranslation } from "react-i18next";
+import { listen } from "@tauri-apps/api/event";
 import { Card, CardContent, CardHeader, CardTitle } from "../components/ui/card";
 import { Button } from "../components/ui/button";
 import { Input } from "../components/ui/input";
@@ -7,29 +8,43 @@ import { Label } from "../components/ui/label";
 import { Switch } from "../components/ui/switch";
 import { Settings, Key, Eye, EyeOff, Check, X, Loader2 } from "lucide-react";
 import {
-  hasOpenAIKey,
+  getKeyStatus,
   setOpenAIKey,
   deleteOpenAIKey,
   getSettings,
   saveSettings,
+  getInstallLocation,
+  moveToApplications,
   type AppSettings,
+  type RunningFromDmg,
+  type KeychainStatus,
+  type ServiceStartMode,
 } from "../lib/api";
 
 export function SettingsPage() {
   const { t } = useTranslation();
   const [apiKey, setApiKey] = useState("");
   const [hasKey, setHasKey] = useState(false);
+  const [keyStatus, setKeyStatus] = useState<KeychainStatus>("Absent");
   const [showKey, setShowKey] = useState(false);
   const [isLoading, setIsLoading] = useState(true);
   const [isSaving, setIsSaving] = useState(false);
   const [settings, setSettingsState] = useState<AppSettings | null>(null);
+  const [installIssue, setInstallIssue] = useState<RunningFromDmg | null>(null);
   const [message, setMessage] = useState<{ type: "success" | "error"; text: string } | null>(null);
 
   const loadData = useCallback(async () => {
     try {
-      const [keyExists, appSettings] = await Promise.all([hasOpenAIKey(), getSettings()]);
-      setHasKey(keyExists);
+      // One Keychain read: each can raise its own access prompt
+      const [status, appSettings, location] = await Promise.all([
+        getKeyStatus(),
+        getSettings(),
+        getInstallLocation(),
+      ]);
+      setHasKey(status === "Present");
+      setKeyStatus(status);
       setSettingsState(appSettings);
+      setInstallIssue(location);
     } catch (error) {
       console.error("Failed to load settings:", error);
       setMessage({ type: "error", text: t("settings.messages.loadFailed") });
@@ -42,6 +57,16 @@ export function SettingsPage() {
     loadData();
   }, [loadData]);
 
+  // settings.json was edited outside the app, or reset to defaults
+  useEffect(() => {
+    const unlisten = listen<string[]>("settings-changed", () => {
+      loadData();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadData]);
+
   const handleSaveApiKey = async () => {
     if (!apiKey.trim()) {
       setMessage({ type: "error", text: t("settings.messages.missingApiKey") });
@@ -88,18 +113,30 @@ export function SettingsPage() {
     }
   };
 
-  const handleToggleAutoStart = async (checked: boolean) => {
-    await updateSettings({ auto_start_service: checked });
+  const handleStartModeChange = async (mode: ServiceStartMode) => {
+    await updateSettings({ service_start_mode: mode });
   };
 
   const handleToggleStartHidden = async (checked: boolean) => {
     await updateSettings({ start_hidden: checked });
   };
 
+  const handleToggleAlwaysOnTop = async (checked: boolean) => {
+    await updateSettings({ always_on_top: checked });
+  };
+
   const handleToggleLaunchAtLogin = async (checked: boolean) => {
     await updateSettings({ launch_at_login: checked });
   };
 
+  const handleMoveToApplications = async () => {
+    try {
+      await moveToApplications();
+    } catch (error) {
+      setMessage({ type: "error", text: String(error) });
+    }
+  };
+
   if (isLoading) {
     return (
       <Card>
@@ -137,6 +174,12 @@ export function SettingsPage() {
             {t("settings.apiKeyDescription")}
           </p>
 
+          {(keyStatus === "Locked" || keyStatus === "AccessDenied") && (
+            <p className="text-sm text-destructive">
+              {t(keyStatus === "Locked" ? "settings.keychainLocked" : "settings.keychainAccessDenied")}
+            </p>
+          )}
+
           {hasKey ? (
             <div className="flex items-center gap-3">
               <div className="flex-1 px-3 py-2 bg-muted rounded-md font-mono text-sm">
@@ -185,11 +228,16 @@ export function SettingsPage() {
               <Label htmlFor="auto-start">{t("settings.autoStartTitle")}</Label>
               <p className="text-sm text-muted-foreground">{t("settings.autoStartDescription")}</p>
             </div>
-            <Switch
+            <select
               id="auto-start"
-              checked={settings?.auto_start_service ?? true}
-              onCheckedChange={handleToggleAutoStart}
-            />
+              className="h-9 rounded-md border border-input bg-transparent px-2 text-sm shadow-sm focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring"
+              value={settings?.service_start_mode ?? "OnAppLaunch"}
+              onChange={(e) => handleStartModeChange(e.target.value as ServiceStartMode)}
+            >
+              <option value="OnAppLaunch">{t("settings.startMode.onAppLaunch")}</option>
+              <option value="OnFirstUse">{t("settings.startMode.onFirstUse")}</option>
+              <option value="Manual">{t("settings.startMode.manual")}</option>
+            </select>
           </div>
 
           <div className="flex items-center justify-between">
@@ -204,16 +252,37 @@ export function SettingsPage() {
             />
           </div>
 
+          <div className="flex items-center justify-between">
+            <div className="space-y-0.5">
+              <Label htmlFor="always-on-top">{t("settings.alwaysOnTopTitle")}</Label>
+              <p className="text-sm text-muted-foreground">{t("settings.alwaysOnTopDescription")}</p>
+            </div>
+            <Switch
+              id="always-on-top"
+              checked={settings?.always_on_top ?? false}
+              onCheckedChange={handleToggleAlwaysOnTop}
+            />
+          </div>
+
           <div className="flex items-center justify-between">
             <div className="space-y-0.5">
               <Label htmlFor="launch-at-login">{t("settings.launchAtLoginTitle")}</Label>
               <p className="text-sm text-muted-foreground">
                 {t("settings.launchAtLoginDescription")}
               </p>
+              {installIssue && (
+                <p className="text-sm text-destructive">
+                  {t("settings.launchAtLoginNeedsApplications")}{" "}
+                  <Button variant="link" className="h-auto p-0" onClick={handleMoveToApplications}>
+                    {t("settings.moveToApplications")}
+                  </Button>
+                </p>
+              )}
             </div>
             <Switch
               id="launch-at-login"
               checked={settings?.launch_at_login ?? false}
+              disabled={installIssue !== null}
               onCheckedChange={handleToggleLaunchAtLogin}
             />
           </div>
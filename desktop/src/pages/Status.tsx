@@ -13,6 +13,10 @@ interface Stats {
     archived: number;
   };
   categories: number;
+  // The last fetch only succeeded after retrying
+  retried?: boolean;
+  // The service sent stats in a layout this app doesn't know
+  schemaWarning?: boolean;
 }
 
 interface StatusPageProps {
@@ -122,8 +126,16 @@ export function StatusPage({
           <CardContent className="pt-6">
             <div className="flex items-center justify-between text-sm">
               <span className="text-muted-foreground">{t("status.labels.backend")}</span>
-              <span className="font-medium">{stats.backend}</span>
+              <span className="font-medium">
+                {stats.backend}
+                {stats.retried && (
+                  <span className="ml-1 text-muted-foreground">{t("status.retried")}</span>
+                )}
+              </span>
             </div>
+            {stats.schemaWarning && (
+              <p className="text-xs text-muted-foreground mt-3">{t("status.statsSchemaWarning")}</p>
+            )}
           </CardContent>
         </Card>
       )}
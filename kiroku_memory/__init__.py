@@ -1,3 +1,8 @@
 """AI Agent Memory System - MVP Core"""
 
-__version__ = "0.1.25"
+from .secrets_file import load_secrets_file
+
+__version__ = "0.1.26"
+
+# Before any module reads settings from the environment
+load_secrets_file()
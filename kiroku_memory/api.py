@@ -1,10 +1,13 @@
 """FastAPI endpoints for memory system"""
 
-from datetime import datetime
+import hmac
+import os
+from datetime import date, datetime
 from typing import Optional
 from uuid import UUID
 
-from fastapi import FastAPI, HTTPException, Depends
+from fastapi import FastAPI, HTTPException, Depends, Request
+from fastapi.responses import JSONResponse, StreamingResponse
 from fastapi.middleware.cors import CORSMiddleware
 from pydantic import BaseModel, Field
 from .db.database import init_db, close_db
@@ -17,14 +20,34 @@ from .conflict import auto_resolve_conflicts
 from .summarize import build_all_summaries, get_tiered_context
 from .jobs import run_nightly_consolidation, run_weekly_maintenance, run_monthly_reindex
 from .observability import metrics, get_health_status, logger
+from .changes import changes, stream as change_stream
+from .usage import usage
 
 
 app = FastAPI(
     title="Kiroku Memory API",
     description="Tiered Retrieval Memory System for AI Agents",
-    version="0.1.25",
+    version="0.1.26",
 )
 
+# Bearer-token auth, enabled by the desktop app's require_auth setting.
+# Registered before CORS so CORS stays outermost and 401s still carry its headers.
+API_TOKEN = os.environ.get("KIROKU_API_TOKEN")
+
+if API_TOKEN:
+    @app.middleware("http")
+    async def require_bearer_token(request: Request, call_next):
+        if request.method != "OPTIONS":
+            expected = f"Bearer {API_TOKEN}"
+            provided = request.headers.get("authorization", "")
+            if not hmac.compare_digest(provided.encode(), expected.encode()):
+                return JSONResponse(
+                    {"detail": "Missing or invalid bearer token"},
+                    status_code=401,
+                    headers={"WWW-Authenticate": "Bearer"},
+                )
+        return await call_next(request)
+
 # CORS middleware for Tauri desktop app
 app.add_middleware(
     CORSMiddleware,
@@ -41,6 +64,8 @@ class IngestRequest(BaseModel):
     content: str
     source: str
     metadata: Optional[dict] = None
+    # Client-generated id; a replayed request with the same id is not stored twice
+    client_id: Optional[UUID] = None
 
 
 class IngestResponse(BaseModel):
@@ -280,6 +305,8 @@ async def extract_endpoint(request: ExtractRequest):
             await classify_item(uow, item_id, use_llm=False)
             await auto_resolve_conflicts(uow, item_id)
         await uow.commit()
+        for item_id in item_ids:
+            changes.publish("created", item_id)
         return ExtractResponse(
             resource_id=request.resource_id,
             items_created=len(item_ids),
@@ -421,11 +448,21 @@ async def ingest_v2_endpoint(request: IngestRequest):
     multiple backends (PostgreSQL, SurrealDB).
     """
     async with get_unit_of_work() as uow:
+        if request.client_id:
+            existing = await uow.resources.get(request.client_id)
+            if existing:
+                return IngestResponse(
+                    resource_id=existing.id,
+                    created_at=existing.created_at,
+                )
+
         entity = ResourceEntity(
             content=request.content,
             source=request.source,
             metadata=request.metadata or {},
         )
+        if request.client_id:
+            entity.id = request.client_id
         resource_id = await uow.resources.create(entity)
         resource = await uow.resources.get(resource_id)
         return IngestResponse(
@@ -544,6 +581,7 @@ async def create_item_v2(request: CreateItemRequest):
                 logger.debug("Skipping graph edge creation")
 
         await uow.commit()
+        changes.publish("created", item_id)
 
         return ItemOut(
             id=item_id,
@@ -623,6 +661,69 @@ async def create_item_meta(item_id: UUID, request: CreateMetaFactRequest):
         )
 
 
+class DeleteResponse(BaseModel):
+    """Number of items removed"""
+    deleted: int
+
+
+@app.delete("/v2/items/{item_id}", response_model=DeleteResponse, tags=["v2"])
+async def delete_item_v2(item_id: UUID):
+    """Delete an item together with its meta-facts and embedding"""
+    async with get_unit_of_work() as uow:
+        if not await uow.items.delete(item_id):
+            raise HTTPException(status_code=404, detail="Item not found")
+        await uow.commit()
+        changes.publish("deleted", item_id)
+        return DeleteResponse(deleted=1)
+
+
+@app.delete("/v2/items", response_model=DeleteResponse, tags=["v2"])
+async def delete_all_items_v2():
+    """Delete every item and the graph edges built from them"""
+    async with get_unit_of_work() as uow:
+        deleted = await uow.items.delete_all()
+        await uow.graph.delete_all()
+        await uow.commit()
+        changes.publish_many("deleted", deleted)
+        return DeleteResponse(deleted=deleted)
+
+
+class PruneRequest(BaseModel):
+    """Delete items older than a number of days"""
+    older_than_days: int = Field(..., ge=1, description="Minimum age in days")
+    dry_run: bool = Field(False, description="Only count matching items")
+
+
+class PruneResponse(BaseModel):
+    """Items deleted, or that would be with dry_run"""
+    count: int
+    dry_run: bool
+
+
+@app.post("/v2/items/prune", response_model=PruneResponse, tags=["v2"])
+async def prune_items_v2(request: PruneRequest):
+    """Delete items (with their meta-facts) created more than older_than_days ago"""
+    async with get_unit_of_work() as uow:
+        if request.dry_run:
+            count = await uow.items.count_older_than(request.older_than_days)
+        else:
+            count = await uow.items.delete_older_than(request.older_than_days)
+            await uow.commit()
+            changes.publish_many("deleted", count)
+        return PruneResponse(count=count, dry_run=request.dry_run)
+
+
+@app.get("/v2/events", tags=["v2"])
+async def events_v2(request: Request):
+    """Stream item changes as server-sent `memory-changed` events"""
+    queue = changes.subscribe()
+    return StreamingResponse(
+        change_stream(changes, queue, request.is_disconnected),
+        media_type="text/event-stream",
+        headers={"Cache-Control": "no-cache"},
+    )
+
+
 @app.get("/v2/categories", response_model=list[CategoryOut], tags=["v2"])
 async def list_categories_v2():
     """List all categories with summaries (v2 - derived from items)"""
@@ -640,6 +741,9 @@ async def stats_v2():
         active_items = await uow.items.count(status="active")
         archived_items = await uow.items.count(status="archived")
         cat_names = await uow.items.list_distinct_categories(status="active")
+        category_counts = await uow.categories.count_items_per_category(status="active")
+        # Resources still waiting for extraction, capped since clients only show "999+"
+        pending = len(await uow.resources.list_unextracted(limit=1000))
 
         return {
             "backend": settings.backend,
@@ -649,9 +753,32 @@ async def stats_v2():
                 "archived": archived_items,
             },
             "categories": len(cat_names),
+            "category_counts": category_counts,
+            "pending": pending,
         }
 
 
+class UsageRecordOut(BaseModel):
+    """Tokens one model used on one day"""
+    date: str
+    model: str
+    prompt_tokens: int
+    completion_tokens: int
+
+
+class UsageResponse(BaseModel):
+    records: list[UsageRecordOut]
+
+
+@app.get("/v2/usage", response_model=UsageResponse, tags=["v2"])
+async def usage_v2(
+    since: Optional[date] = None,
+    until: Optional[date] = None,
+):
+    """OpenAI token usage per day and model, from `since` to `until` inclusive"""
+    return UsageResponse(records=usage.records(since, until))
+
+
 # ============ Search & Graph ============
 
 class SearchResultOut(BaseModel):
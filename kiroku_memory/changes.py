@@ -0,0 +1,76 @@
+"""Change feed - Broadcasts item changes to /v2/events subscribers"""
+
+import asyncio
+import json
+from typing import AsyncIterator, Optional
+from uuid import UUID
+
+# SSE event name the desktop app listens for
+CHANGE_EVENT = "memory-changed"
+
+# Comment sent when nothing changed, so proxies keep the connection open
+KEEPALIVE_SECONDS = 15.0
+
+# Changes held per subscriber; a subscriber that falls further behind misses
+# some and catches up from /v2/stats
+QUEUE_SIZE = 1000
+
+
+class ChangeFeed:
+    """Fan-out of item changes to every open event stream"""
+
+    def __init__(self):
+        self._subscribers: set[asyncio.Queue] = set()
+
+    def subscribe(self) -> asyncio.Queue:
+        queue: asyncio.Queue = asyncio.Queue(maxsize=QUEUE_SIZE)
+        self._subscribers.add(queue)
+        return queue
+
+    def unsubscribe(self, queue: asyncio.Queue) -> None:
+        self._subscribers.discard(queue)
+
+    def publish(self, kind: str, item_id: Optional[UUID] = None, source: str = "api") -> None:
+        """Send a change to all subscribers; call only after the change is committed"""
+        self._broadcast({
+            "kind": kind,
+            "id": str(item_id) if item_id else None,
+            "source": source,
+        })
+
+    def _broadcast(self, change: dict) -> None:
+        for queue in self._subscribers:
+            try:
+                queue.put_nowait(change)
+            except asyncio.QueueFull:
+                pass
+
+    def publish_many(self, kind: str, count: int, source: str = "api") -> None:
+        """Send one change without an id covering `count` items, e.g. after a bulk delete"""
+        if count <= 0:
+            return
+        self._broadcast({"kind": kind, "id": None, "source": source, "count": count})
+
+
+def format_event(change: dict) -> str:
+    """Encode a change as one text/event-stream message"""
+    return f"event: {CHANGE_EVENT}\ndata: {json.dumps(change)}\n\n"
+
+
+async def stream(feed: ChangeFeed, queue: asyncio.Queue, is_disconnected) -> AsyncIterator[str]:
+    """Yield SSE messages from `queue` until the client goes away"""
+    try:
+        yield ": connected\n\n"
+        while not await is_disconnected():
+            try:
+                change = await asyncio.wait_for(queue.get(), timeout=KEEPALIVE_SECONDS)
+            except asyncio.TimeoutError:
+                yield ": keep-alive\n\n"
+                continue
+            yield format_event(change)
+    finally:
+        feed.unsubscribe(queue)
+
+
+# Global feed instance
+changes = ChangeFeed()
@@ -9,6 +9,7 @@ from openai import AsyncOpenAI
 from .db.config import settings
 from .db.repositories.base import UnitOfWork
 from .db.entities import ItemEntity, CategoryEntity
+from .usage import usage
 
 
 # Default categories
@@ -87,7 +88,7 @@ async def classify_item(
     categories_text = "\n".join(f"- {name}: {desc}" for name, desc in DEFAULT_CATEGORIES)
 
     response = await client.chat.completions.create(
-        model="gpt-4o-mini",
+        model=settings.chat_model,
         messages=[
             {"role": "user", "content": CLASSIFY_PROMPT.format(
                 categories=categories_text,
@@ -99,6 +100,7 @@ async def classify_item(
         temperature=0,
         max_tokens=20,
     )
+    usage.record_response(settings.chat_model, response)
 
     category = response.choices[0].message.content.strip().lower()
 
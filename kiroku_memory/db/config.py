@@ -21,6 +21,9 @@ class Settings(BaseSettings):
     embedding_dimensions: int = 1536
     embedding_provider: str = "openai"  # openai or local
 
+    # Chat model for extraction, classification and summaries
+    chat_model: str = "gpt-4o-mini"
+
     # Redis
     redis_url: str = "redis://localhost:6379/0"
 
@@ -85,6 +85,27 @@ class ItemRepository(ABC):
         """Update item status (active, archived, deleted)"""
         ...
 
+    @abstractmethod
+    async def delete(self, item_id: UUID) -> bool:
+        """Delete an item and its meta-facts, return False if it did not exist"""
+        ...
+
+    @abstractmethod
+    async def delete_all(self) -> int:
+        """Delete every item, return count deleted"""
+        ...
+
+    @abstractmethod
+    async def count_older_than(self, max_age_days: int) -> int:
+        """Count items (not meta-facts) created more than max_age_days ago"""
+        ...
+
+    @abstractmethod
+    async def delete_older_than(self, max_age_days: int) -> int:
+        """Delete items created more than max_age_days ago with their meta-facts,
+        return count deleted"""
+        ...
+
     @abstractmethod
     async def list(
         self,
@@ -7,7 +7,7 @@ from uuid import UUID
 
 from datetime import datetime, timedelta, timezone
 
-from sqlalchemy import select, func, update, and_
+from sqlalchemy import select, func, update, delete, and_
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from ...models import Item
@@ -105,6 +105,36 @@ class PostgresItemRepository(ItemRepository):
             .values(status=status)
         )
 
+    async def delete(self, item_id: UUID) -> bool:
+        """Delete an item; meta-facts and its embedding cascade"""
+        result = await self._session.execute(
+            delete(Item).where(Item.id == item_id)
+        )
+        return (result.rowcount or 0) > 0
+
+    async def delete_all(self) -> int:
+        """Delete every item, return count deleted"""
+        result = await self._session.execute(delete(Item))
+        return result.rowcount or 0
+
+    def _older_than(self, max_age_days: int):
+        cutoff = datetime.now(timezone.utc).replace(tzinfo=None) - timedelta(days=max_age_days)
+        return and_(Item.created_at < cutoff, Item.meta_about.is_(None))
+
+    async def count_older_than(self, max_age_days: int) -> int:
+        """Count items (not meta-facts) created more than max_age_days ago"""
+        result = await self._session.execute(
+            select(func.count(Item.id)).where(self._older_than(max_age_days))
+        )
+        return result.scalar() or 0
+
+    async def delete_older_than(self, max_age_days: int) -> int:
+        """Delete items created more than max_age_days ago; meta-facts cascade"""
+        result = await self._session.execute(
+            delete(Item).where(self._older_than(max_age_days))
+        )
+        return result.rowcount or 0
+
     async def list(
         self,
         category: Optional[str] = None,
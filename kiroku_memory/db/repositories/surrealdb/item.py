@@ -206,6 +206,65 @@ class SurrealItemRepository(ItemRepository):
             {"id": record_id, "status": status},
         )
 
+    async def delete(self, item_id: UUID) -> bool:
+        """Delete an item and its meta-facts (embeddings live on the record)"""
+        from surrealdb import RecordID
+
+        record_id = RecordID("item", str(item_id))
+
+        existing = await self._client.query("SELECT id FROM $id", {"id": record_id})
+        if not existing:
+            return False
+
+        await self._client.query(
+            "DELETE FROM item WHERE meta_about = $id", {"id": record_id}
+        )
+        await self._client.query("DELETE $id", {"id": record_id})
+        return True
+
+    async def delete_all(self) -> int:
+        """Delete every item, return count deleted"""
+        count_result = await self._client.query(
+            "SELECT count() FROM item GROUP ALL", {}
+        )
+        count = count_result[0].get("count", 0) if count_result else 0
+
+        await self._client.query("DELETE FROM item", {})
+        return count
+
+    async def count_older_than(self, max_age_days: int) -> int:
+        """Count items (not meta-facts) created more than max_age_days ago"""
+        result = await self._client.query(
+            """
+            SELECT count() FROM item
+            WHERE meta_about = NONE
+                AND created_at < time::now() - type::duration($age)
+            GROUP ALL
+            """,
+            {"age": f"{max_age_days}d"},
+        )
+        return result[0].get("count", 0) if result else 0
+
+    async def delete_older_than(self, max_age_days: int) -> int:
+        """Delete items created more than max_age_days ago with their meta-facts"""
+        result = await self._client.query(
+            """
+            SELECT id FROM item
+            WHERE meta_about = NONE
+                AND created_at < time::now() - type::duration($age)
+            """,
+            {"age": f"{max_age_days}d"},
+        )
+        ids = [r.get("id") for r in result] if result else []
+        if not ids:
+            return 0
+
+        await self._client.query(
+            "DELETE FROM item WHERE meta_about IN $ids", {"ids": ids}
+        )
+        await self._client.query("DELETE FROM item WHERE id IN $ids", {"ids": ids})
+        return len(ids)
+
     async def list(
         self,
         category: Optional[str] = None,
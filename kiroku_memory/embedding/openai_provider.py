@@ -6,6 +6,7 @@ from typing import Optional
 
 from openai import AsyncOpenAI
 
+from ..usage import usage
 from .base import EmbeddingProvider, EmbeddingResult
 
 
@@ -56,6 +57,7 @@ class OpenAIEmbeddingProvider(EmbeddingProvider):
             input=text,
             dimensions=self._dimensions,
         )
+        usage.record_response(self._model_name, response)
         vector = response.data[0].embedding
         return EmbeddingResult(
             vector=vector,
@@ -73,6 +75,7 @@ class OpenAIEmbeddingProvider(EmbeddingProvider):
             input=texts,
             dimensions=self._dimensions,
         )
+        usage.record_response(self._model_name, response)
 
         results = []
         for data in response.data:
@@ -10,6 +10,7 @@ from .db.config import settings
 from .db.repositories.base import UnitOfWork
 from .db.entities import ItemEntity, GraphEdgeEntity
 from .entity_resolution import resolve_entity
+from .usage import usage
 
 
 # Initialize OpenAI client (lazy)
@@ -68,7 +69,7 @@ async def extract_facts(text: str) -> list[ExtractedFact]:
     client = get_openai_client()
 
     response = await client.chat.completions.create(
-        model="gpt-4o-mini",
+        model=settings.chat_model,
         messages=[
             {"role": "system", "content": "You extract structured facts from text. Return only valid JSON."},
             {"role": "user", "content": EXTRACTION_PROMPT.format(text=text)},
@@ -76,6 +77,7 @@ async def extract_facts(text: str) -> list[ExtractedFact]:
         response_format={"type": "json_object"},
         temperature=0.1,
     )
+    usage.record_response(settings.chat_model, response)
 
     content = response.choices[0].message.content
     if not content:
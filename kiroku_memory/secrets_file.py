@@ -0,0 +1,30 @@
+"""Secrets handed over by the desktop app through a private file"""
+
+import json
+import os
+
+SECRETS_FILE_ENV = "KIROKU_SECRETS_FILE"
+
+
+def load_secrets_file() -> None:
+    """Copy the secrets from KIROKU_SECRETS_FILE into this process's environment.
+
+    The desktop app writes the file (mode 0600) instead of putting secrets in the
+    child's environment block, where other local processes could read them. It
+    keeps the file for as long as any process may still import the app, so an
+    unreadable file is fatal: starting without it would also drop the bearer
+    token and serve a require_auth service unauthenticated.
+    """
+    path = os.environ.get(SECRETS_FILE_ENV)
+    if not path:
+        return
+    try:
+        with open(path, encoding="utf-8") as f:
+            secrets = json.load(f)
+    except (OSError, ValueError) as e:
+        raise RuntimeError(f"{SECRETS_FILE_ENV} is set but {path} can't be read: {e}") from e
+    if not isinstance(secrets, dict):
+        raise RuntimeError(f"{SECRETS_FILE_ENV} file {path} does not hold an object")
+    for name, value in secrets.items():
+        if isinstance(value, str):
+            os.environ[name] = value
@@ -8,6 +8,7 @@ from openai import AsyncOpenAI
 from .db.config import settings
 from .db.repositories.base import UnitOfWork
 from .db.entities import CategoryEntity
+from .usage import usage
 
 
 _client: Optional[AsyncOpenAI] = None
@@ -63,7 +64,7 @@ async def build_category_summary(
     # Generate summary
     client = get_openai_client()
     response = await client.chat.completions.create(
-        model="gpt-4o-mini",
+        model=settings.chat_model,
         messages=[
             {"role": "user", "content": SUMMARY_PROMPT.format(
                 category=category_name,
@@ -73,6 +74,7 @@ async def build_category_summary(
         temperature=0.3,
         max_tokens=200,
     )
+    usage.record_response(settings.chat_model, response)
 
     summary = response.choices[0].message.content.strip()
 
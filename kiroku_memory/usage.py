@@ -0,0 +1,164 @@
+"""Usage - OpenAI token counts per day and model, served at /v2/usage"""
+
+import glob
+import json
+import os
+import threading
+from datetime import date
+from typing import Any, Iterable, Optional
+
+from .observability import logger
+
+
+def _pid_alive(pid: int) -> bool:
+    try:
+        os.kill(pid, 0)
+    except ProcessLookupError:
+        return False
+    except OSError:
+        # Exists but belongs to someone else
+        return True
+    return True
+
+
+class UsageTracker:
+    """
+    Token counts keyed by (day, model).
+
+    Counts live in memory and are lost on restart unless a file is given
+    (the USAGE_FILE environment variable for the global tracker). Each worker
+    process appends its requests to its own file next to it (`usage.json`
+    gives `usage.<pid>.jsonl`), and reads add up the files of all workers.
+    """
+
+    def __init__(self, path: Optional[str] = None):
+        self._lock = threading.Lock()
+        self._counts: dict[tuple[str, str], list[int]] = {}
+        self._prefix = os.path.splitext(path)[0] if path else None
+        if self._prefix:
+            self._merge_dead_workers()
+
+    def _own_file(self) -> str:
+        # Looked up per write, as forked workers inherit the tracker
+        return f"{self._prefix}.{os.getpid()}.jsonl"
+
+    def _worker_files(self) -> list[str]:
+        return glob.glob(f"{glob.escape(self._prefix)}.*.jsonl")
+
+    def _append(self, path: str, rows: Iterable[dict[str, Any]]) -> None:
+        lines = "".join(json.dumps(row) + "\n" for row in rows)
+        if not lines:
+            return
+        try:
+            with open(path, "a", encoding="utf-8") as f:
+                f.write(lines)
+        except OSError as e:
+            logger.warning(f"Could not save usage to {path}: {e}")
+
+    def _merge_dead_workers(self) -> None:
+        """Fold the files of workers that have exited into this one's, so
+        restarts don't leave one file per process behind"""
+        # Signal 0 only probes for the process on POSIX
+        if os.name != "posix":
+            return
+        own = self._own_file()
+        for path in self._worker_files():
+            pid = path[len(self._prefix) + 1:-len(".jsonl")]
+            if path == own or not pid.isdigit() or _pid_alive(int(pid)):
+                continue
+            # Renaming first means only one starting worker takes each file
+            claimed = f"{path}.{os.getpid()}.merging"
+            try:
+                os.rename(path, claimed)
+            except OSError:
+                continue
+            counts: dict[tuple[str, str], list[int]] = {}
+            self._add_file(counts, claimed)
+            self._append(own, self._rows(counts, None, None))
+            try:
+                os.remove(claimed)
+            except OSError as e:
+                logger.warning(f"Could not remove merged usage file {claimed}: {e}")
+
+    @staticmethod
+    def _add(counts: dict[tuple[str, str], list[int]], key: tuple[str, str], prompt: int, completion: int) -> None:
+        total = counts.setdefault(key, [0, 0])
+        total[0] += prompt
+        total[1] += completion
+
+    def _add_file(self, counts: dict[tuple[str, str], list[int]], path: str) -> None:
+        try:
+            with open(path, encoding="utf-8") as f:
+                lines = f.readlines()
+        except FileNotFoundError:
+            return
+        except OSError as e:
+            logger.warning(f"Ignoring unreadable usage file {path}: {e}")
+            return
+        for line in lines:
+            # A worker killed mid-write leaves a torn last line
+            try:
+                row = json.loads(line)
+                key = (row["date"], row["model"])
+            except (ValueError, KeyError, TypeError):
+                continue
+            self._add(counts, key, row.get("prompt_tokens", 0), row.get("completion_tokens", 0))
+
+    @staticmethod
+    def _rows(
+        counts: dict[tuple[str, str], list[int]], since: Optional[date], until: Optional[date]
+    ) -> list[dict[str, Any]]:
+        rows = []
+        for (day, model), (prompt, completion) in sorted(counts.items()):
+            if since and day < since.isoformat():
+                continue
+            if until and day > until.isoformat():
+                continue
+            rows.append({
+                "date": day,
+                "model": model,
+                "prompt_tokens": prompt,
+                "completion_tokens": completion,
+            })
+        return rows
+
+    def record(self, model: str, prompt_tokens: int, completion_tokens: int = 0, day: Optional[date] = None) -> None:
+        """Add tokens used by one request"""
+        if prompt_tokens <= 0 and completion_tokens <= 0:
+            return
+        key = ((day or date.today()).isoformat(), model)
+        with self._lock:
+            if self._prefix:
+                self._append(self._own_file(), [{
+                    "date": key[0],
+                    "model": model,
+                    "prompt_tokens": prompt_tokens,
+                    "completion_tokens": completion_tokens,
+                }])
+            else:
+                self._add(self._counts, key, prompt_tokens, completion_tokens)
+
+    def record_response(self, model: str, response: Any) -> None:
+        """Add the usage reported on an OpenAI response, if any"""
+        usage = getattr(response, "usage", None)
+        if usage is None:
+            return
+        self.record(
+            model,
+            getattr(usage, "prompt_tokens", 0) or 0,
+            getattr(usage, "completion_tokens", 0) or 0,
+        )
+
+    def records(self, since: Optional[date] = None, until: Optional[date] = None) -> list[dict[str, Any]]:
+        """Rows for the days from `since` to `until`, both inclusive"""
+        with self._lock:
+            if not self._prefix:
+                return self._rows(self._counts, since, until)
+        counts: dict[tuple[str, str], list[int]] = {}
+        for path in self._worker_files():
+            self._add_file(counts, path)
+        return self._rows(counts, since, until)
+
+
+# Global usage tracker
+usage = UsageTracker(os.environ.get("USAGE_FILE") or None)
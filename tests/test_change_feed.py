@@ -0,0 +1,90 @@
+"""Tests for the /v2/events change feed"""
+
+from __future__ import annotations
+
+import json
+import os
+
+import pytest
+
+from kiroku_memory.changes import ChangeFeed, format_event, stream
+
+
+@pytest.fixture
+def api_app():
+    """FastAPI app on an in-memory SurrealDB"""
+    pytest.importorskip("surrealdb")
+    os.environ.setdefault("BACKEND", "surrealdb")
+    os.environ.setdefault("SURREAL_URL", "memory")
+
+    from kiroku_memory.api import app
+
+    return app
+
+
+def test_format_event():
+    """Changes are sent as named SSE messages with a JSON body"""
+    message = format_event({"kind": "created", "id": "abc", "source": "api"})
+    assert message.startswith("event: memory-changed\ndata: ")
+    assert message.endswith("\n\n")
+    data = message.split("data: ", 1)[1].strip()
+    assert json.loads(data) == {"kind": "created", "id": "abc", "source": "api"}
+
+
+def test_bulk_change_is_one_event():
+    """A bulk delete sends a single change with a count, however many items it covered"""
+    feed = ChangeFeed()
+    queue = feed.subscribe()
+    feed.publish_many("deleted", 5000)
+    feed.publish_many("deleted", 0)
+
+    assert queue.get_nowait() == {"kind": "deleted", "id": None, "source": "api", "count": 5000}
+    assert queue.empty()
+
+
+@pytest.mark.asyncio
+async def test_stream_unsubscribes_on_disconnect():
+    """The stream ends once the client is gone and stops receiving changes"""
+    feed = ChangeFeed()
+    queue = feed.subscribe()
+    feed.publish("deleted")
+
+    async def disconnected_after_one():
+        return queue.empty()
+
+    messages = [m async for m in stream(feed, queue, disconnected_after_one)]
+    assert messages[0] == ": connected\n\n"
+    assert messages[1].startswith("event: memory-changed")
+    assert len(messages) == 2
+
+    feed.publish("created")
+    assert queue.empty()
+
+
+@pytest.mark.asyncio
+async def test_api_publishes_item_changes(api_app):
+    """Creating and deleting items through the API reaches subscribers"""
+    from httpx import AsyncClient, ASGITransport
+    from kiroku_memory.changes import changes
+
+    queue = changes.subscribe()
+    try:
+        transport = ASGITransport(app=api_app)
+        async with AsyncClient(transport=transport, base_url="http://test") as client:
+            resp = await client.post("/v2/items", json={
+                "subject": "Dave",
+                "predicate": "drinks",
+                "object": "coffee",
+            })
+            assert resp.status_code == 200
+            item_id = resp.json()["id"]
+
+            resp = await client.delete(f"/v2/items/{item_id}")
+            assert resp.status_code == 200
+
+        created = queue.get_nowait()
+        deleted = queue.get_nowait()
+        assert (created["kind"], created["id"]) == ("created", item_id)
+        assert (deleted["kind"], deleted["id"]) == ("deleted", item_id)
+    finally:
+        changes.unsubscribe(queue)
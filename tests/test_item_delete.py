@@ -0,0 +1,113 @@
+"""Tests for deleting items through the v2 API"""
+
+from __future__ import annotations
+
+import os
+from uuid import uuid4
+
+import pytest
+
+
+@pytest.fixture
+def api_app():
+    """FastAPI app on an in-memory SurrealDB"""
+    pytest.importorskip("surrealdb")
+    os.environ.setdefault("BACKEND", "surrealdb")
+    os.environ.setdefault("SURREAL_URL", "memory")
+
+    from kiroku_memory.api import app
+
+    return app
+
+
+async def _create_item(client, obj: str) -> str:
+    resp = await client.post("/v2/items", json={
+        "subject": "Carol",
+        "predicate": "likes",
+        "object": obj,
+        "category": "preferences",
+    })
+    assert resp.status_code == 200
+    return resp.json()["id"]
+
+
+@pytest.mark.asyncio
+async def test_api_delete_item(api_app):
+    """DELETE /v2/items/{id} removes the item and 404s the second time"""
+    from httpx import AsyncClient, ASGITransport
+
+    transport = ASGITransport(app=api_app)
+    async with AsyncClient(transport=transport, base_url="http://test") as client:
+        item_id = await _create_item(client, "tea")
+
+        resp = await client.delete(f"/v2/items/{item_id}")
+        assert resp.status_code == 200
+        assert resp.json() == {"deleted": 1}
+
+        resp = await client.get(f"/v2/items/{item_id}/meta")
+        assert resp.status_code == 404
+
+        resp = await client.delete(f"/v2/items/{item_id}")
+        assert resp.status_code == 404
+        assert resp.json()["detail"] == "Item not found"
+
+
+@pytest.mark.asyncio
+async def test_api_delete_all_items(api_app):
+    """DELETE /v2/items empties the store and reports the count"""
+    from httpx import AsyncClient, ASGITransport
+
+    transport = ASGITransport(app=api_app)
+    async with AsyncClient(transport=transport, base_url="http://test") as client:
+        await _create_item(client, "coffee")
+        await _create_item(client, "cocoa")
+
+        resp = await client.delete("/v2/items")
+        assert resp.status_code == 200
+        assert resp.json()["deleted"] >= 2
+
+        resp = await client.get("/v2/items")
+        assert resp.json() == []
+
+        resp = await client.delete(f"/v2/items/{uuid4()}")
+        assert resp.status_code == 404
+
+
+async def _backdate(item_id: str, days: int) -> None:
+    from kiroku_memory.db.surrealdb import get_surreal_connection
+
+    async with get_surreal_connection() as db:
+        await db.query(
+            'UPDATE type::thing("item", $uuid) SET created_at = time::now() - type::duration($age)',
+            {"uuid": item_id, "age": f"{days}d"},
+        )
+
+
+@pytest.mark.asyncio
+async def test_api_prune_items(api_app):
+    """POST /v2/items/prune counts with dry_run and deletes without it"""
+    from httpx import AsyncClient, ASGITransport
+
+    transport = ASGITransport(app=api_app)
+    async with AsyncClient(transport=transport, base_url="http://test") as client:
+        await client.delete("/v2/items")
+        old_id = await _create_item(client, "green tea")
+        new_id = await _create_item(client, "oolong")
+        await _backdate(old_id, 30)
+
+        resp = await client.post("/v2/items/prune", json={"older_than_days": 7, "dry_run": True})
+        assert resp.status_code == 200
+        assert resp.json() == {"count": 1, "dry_run": True}
+        resp = await client.get(f"/v2/items/{old_id}/meta")
+        assert resp.status_code == 200
+
+        resp = await client.post("/v2/items/prune", json={"older_than_days": 7})
+        assert resp.status_code == 200
+        assert resp.json() == {"count": 1, "dry_run": False}
+        resp = await client.get(f"/v2/items/{old_id}/meta")
+        assert resp.status_code == 404
+        resp = await client.get(f"/v2/items/{new_id}/meta")
+        assert resp.status_code == 200
+
+        resp = await client.post("/v2/items/prune", json={"older_than_days": 0})
+        assert resp.status_code == 422
@@ -0,0 +1,29 @@
+"""Tests for reading secrets handed over by the desktop app"""
+
+from __future__ import annotations
+
+import json
+import os
+
+import pytest
+
+from kiroku_memory.secrets_file import SECRETS_FILE_ENV, load_secrets_file
+
+
+def test_secrets_are_copied_into_environment(tmp_path, monkeypatch):
+    """Values from the file become environment variables"""
+    path = tmp_path / "secrets.json"
+    path.write_text(json.dumps({"KIROKU_API_TOKEN": "token"}))
+    monkeypatch.setenv(SECRETS_FILE_ENV, str(path))
+    monkeypatch.delenv("KIROKU_API_TOKEN", raising=False)
+
+    load_secrets_file()
+    assert os.environ["KIROKU_API_TOKEN"] == "token"
+
+
+def test_missing_secrets_file_refuses_to_start(tmp_path, monkeypatch):
+    """Starting without the file would serve without the bearer token"""
+    monkeypatch.setenv(SECRETS_FILE_ENV, str(tmp_path / "gone.json"))
+
+    with pytest.raises(RuntimeError):
+        load_secrets_file()
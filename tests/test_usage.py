@@ -0,0 +1,93 @@
+"""Tests for OpenAI token usage tracking"""
+
+from __future__ import annotations
+
+import os
+from datetime import date
+from types import SimpleNamespace
+
+import pytest
+
+from kiroku_memory.usage import UsageTracker
+
+
+def test_records_sum_per_day_and_model():
+    """Requests on the same day and model add up; the range is inclusive"""
+    tracker = UsageTracker()
+    tracker.record("gpt-4o-mini", 100, 20, day=date(2026, 3, 1))
+    tracker.record("gpt-4o-mini", 50, 5, day=date(2026, 3, 1))
+    tracker.record("text-embedding-3-small", 30, day=date(2026, 3, 2))
+    tracker.record("gpt-4o-mini", 10, 1, day=date(2026, 3, 5))
+
+    records = tracker.records(date(2026, 3, 1), date(2026, 3, 2))
+    assert records == [
+        {"date": "2026-03-01", "model": "gpt-4o-mini", "prompt_tokens": 150, "completion_tokens": 25},
+        {"date": "2026-03-02", "model": "text-embedding-3-small", "prompt_tokens": 30, "completion_tokens": 0},
+    ]
+
+
+def test_record_response_reads_openai_usage():
+    """Embedding responses have no completion tokens; missing usage is skipped"""
+    tracker = UsageTracker()
+    tracker.record_response("text-embedding-3-small", SimpleNamespace(usage=SimpleNamespace(prompt_tokens=8, total_tokens=8)))
+    tracker.record_response("gpt-4o-mini", SimpleNamespace(usage=None))
+
+    records = tracker.records()
+    assert len(records) == 1
+    assert records[0]["prompt_tokens"] == 8
+    assert records[0]["completion_tokens"] == 0
+
+
+def test_usage_file_survives_restart(tmp_path):
+    """Counts are reloaded from the usage files"""
+    path = str(tmp_path / "usage.json")
+    UsageTracker(path).record("gpt-4o-mini", 7, 3, day=date(2026, 3, 1))
+
+    records = UsageTracker(path).records()
+    assert records == [
+        {"date": "2026-03-01", "model": "gpt-4o-mini", "prompt_tokens": 7, "completion_tokens": 3},
+    ]
+
+
+def test_usage_files_of_all_workers_add_up(tmp_path):
+    """Each worker appends to its own file; reads sum them and exited workers' files are merged"""
+    path = str(tmp_path / "usage.json")
+    # A worker that has since exited (no process has this pid)
+    (tmp_path / "usage.999999999.jsonl").write_text(
+        '{"date": "2026-03-01", "model": "gpt-4o-mini", "prompt_tokens": 5, "completion_tokens": 1}\n'
+        '{"date": "2026-03-01", "model": "gpt-4o'
+    )
+    tracker = UsageTracker(path)
+    tracker.record("gpt-4o-mini", 7, 3, day=date(2026, 3, 1))
+
+    assert tracker.records() == [
+        {"date": "2026-03-01", "model": "gpt-4o-mini", "prompt_tokens": 12, "completion_tokens": 4},
+    ]
+    if os.name == "posix":
+        assert sorted(p.name for p in tmp_path.iterdir()) == [f"usage.{os.getpid()}.jsonl"]
+
+
+@pytest.mark.asyncio
+async def test_api_usage_filters_by_date():
+    """GET /v2/usage returns the records in the requested range"""
+    pytest.importorskip("surrealdb")
+    os.environ.setdefault("BACKEND", "surrealdb")
+    os.environ.setdefault("SURREAL_URL", "memory")
+
+    from httpx import AsyncClient, ASGITransport
+    from kiroku_memory.api import app
+    from kiroku_memory.usage import usage
+
+    usage.record("gpt-4o-mini", 40, 2, day=date(2020, 1, 1))
+    usage.record("gpt-4o-mini", 60, 6, day=date(2020, 1, 3))
+
+    transport = ASGITransport(app=app)
+    async with AsyncClient(transport=transport, base_url="http://test") as client:
+        resp = await client.get("/v2/usage", params={"since": "2020-01-01", "until": "2020-01-02"})
+        assert resp.status_code == 200
+        assert resp.json() == {"records": [
+            {"date": "2020-01-01", "model": "gpt-4o-mini", "prompt_tokens": 40, "completion_tokens": 2},
+        ]}
+
+        resp = await client.get("/v2/usage", params={"since": "not-a-date"})
+        assert resp.status_code == 422
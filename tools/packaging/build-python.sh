@@ -282,6 +282,39 @@ print(f'Executable: {sys.executable}')
 print(f'Prefix: {sys.prefix}')
 "
 
+# Write the manifest used by the app's runtime integrity check (verify_runtime).
+# Bytecode is skipped: Python rewrites it at runtime.
+echo ""
+echo "=== Writing runtime manifest ==="
+"$PYTHON_BIN" - "$OUTPUT_SUBDIR" <<'PYEOF'
+import hashlib
+import json
+import os
+import sys
+
+root = sys.argv[1]
+files = []
+for dirpath, dirnames, filenames in os.walk(os.path.join(root, "python")):
+    dirnames[:] = sorted(d for d in dirnames if d != "__pycache__")
+    for name in sorted(filenames):
+        path = os.path.join(dirpath, name)
+        if os.path.islink(path) or name.endswith(".pyc"):
+            continue
+        digest = hashlib.sha256()
+        with open(path, "rb") as f:
+            for chunk in iter(lambda: f.read(1 << 16), b""):
+                digest.update(chunk)
+        files.append({
+            "path": os.path.relpath(path, root).replace(os.sep, "/"),
+            "sha256": digest.hexdigest(),
+            "size": os.path.getsize(path),
+        })
+
+with open(os.path.join(root, "runtime-manifest.json"), "w") as f:
+    json.dump({"files": files}, f)
+print(f"Manifest: {len(files)} files")
+PYEOF
+
 # Calculate size
 BUNDLE_SIZE=$(du -sh "$PYTHON_DIR" | cut -f1)
 echo ""